@@ -0,0 +1,60 @@
+//! Compares the time to deserialize a few parameters along with many parameters the type
+//! doesn't know, with values which need decoding or not. The values of the unknown parameters are
+//! never decoded, so both take the same time.
+//!
+//! Run with `cargo bench --bench ignored`.
+use std::time::{Duration, Instant};
+
+use _serde::Deserialize;
+use serde_querystring::{from_str, ParseMode};
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+struct Query<'a> {
+    name: &'a str,
+    page: u32,
+}
+
+const ITERATIONS: u32 = 20_000;
+
+/// Returns the average time to deserialize the query string
+fn measure(input: &str, mode: ParseMode) -> Duration {
+    let start = Instant::now();
+    // Used once done, so the deserialization is not optimized away
+    let mut total = 0;
+    for _ in 0..ITERATIONS {
+        let query: Query = from_str(input, mode).unwrap();
+        total += query.page as usize + query.name.len();
+    }
+    let elapsed = start.elapsed();
+    assert_eq!(total, 6 * ITERATIONS as usize);
+    elapsed / ITERATIONS
+}
+
+/// The known parameters, followed by 50 unknown ones with the value
+fn with_ignored(value: &str) -> String {
+    let mut input = String::from("name=john&page=2");
+    for i in 0..50 {
+        input.push_str(&format!("&param{:02}={}", i, value));
+    }
+    input
+}
+
+fn main() {
+    let plain = with_ignored("a-long-value-with-nothing-to-decode");
+    let encoded = with_ignored("a%20long+value%20to%20decode%E2%9C%93");
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Brackets,
+    ] {
+        println!(
+            "{:?}: {:?} for 2 parameters, {:?} with 50 plain ones, {:?} with 50 encoded ones",
+            mode,
+            measure("name=john&page=2", mode),
+            measure(&plain, mode),
+            measure(&encoded, mode),
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! Compares the time to scan and decode long values, to check the `simd` feature against the
+//! default byte by byte search. Run it with and without the feature:
+//!
+//! `cargo bench --bench scan` and `cargo bench --bench scan --features simd`.
+use std::time::{Duration, Instant};
+
+use _serde::Deserialize;
+use serde_querystring::{from_str, ParseMode};
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+struct Query {
+    value: String,
+}
+
+const ITERATIONS: u32 = 5_000;
+
+/// Returns the average time to deserialize the query string
+fn measure(input: &str, mode: ParseMode) -> Duration {
+    let start = Instant::now();
+    // Used once done, so the deserialization is not optimized away
+    let mut total = 0;
+    for _ in 0..ITERATIONS {
+        let query: Query = from_str(input, mode).unwrap();
+        total += query.value.len();
+    }
+    let elapsed = start.elapsed();
+    assert!(total > 0);
+    elapsed / ITERATIONS
+}
+
+/// A value of 4KiB with an escape every `every` bytes
+fn long_value(every: usize) -> String {
+    let mut value = String::new();
+    while value.len() < 4096 {
+        value.push_str(&"a".repeat(every - 3));
+        value.push_str("%20");
+    }
+    value
+}
+
+/// The value, after 100 unknown parameters with long plain values
+fn with_separators(value: &str) -> String {
+    let mut input = String::new();
+    for i in 0..100 {
+        input.push_str(&format!("param{:03}={}&", i, "b".repeat(100)));
+    }
+    input.push_str("value=");
+    input.push_str(value);
+    input
+}
+
+fn main() {
+    println!(
+        "simd feature: {}",
+        if cfg!(feature = "simd") { "on" } else { "off" }
+    );
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Brackets,
+    ] {
+        println!(
+            "{:?}: {:?} for a value with sparse escapes, {:?} with dense escapes, {:?} after 100 long parameters",
+            mode,
+            measure(&format!("value={}", long_value(256)), mode),
+            measure(&format!("value={}", long_value(8)), mode),
+            measure(&with_separators("plain"), mode),
+        );
+    }
+}
@@ -0,0 +1,18 @@
+//! Parses arbitrary bytes with an arbitrary config, every entry point should return instead of
+//! panicking
+#![no_main]
+
+use std::collections::HashMap;
+
+use libfuzzer_sys::fuzz_target;
+use serde_querystring::json::to_json;
+use serde_querystring::{from_bytes, from_bytes_value, parse_pairs, QsConfig};
+
+fuzz_target!(|input: (QsConfig, &[u8])| {
+    let (config, bytes) = input;
+
+    let _ = from_bytes_value(bytes, config);
+    let _ = parse_pairs(bytes, config);
+    let _ = from_bytes::<HashMap<String, String>>(bytes, config);
+    let _ = to_json(bytes, config.mode());
+});
@@ -0,0 +1,32 @@
+//! Serializes arbitrary values and parses them back, they should be equal to the original ones
+//!
+//! Copy this target and replace `Query` with your own types to fuzz them against the parser.
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use serde::{Deserialize, Serialize};
+use serde_querystring::test_util::roundtrip;
+use serde_querystring::ParseMode;
+
+#[derive(Debug, PartialEq, Arbitrary, Serialize, Deserialize)]
+struct Address {
+    city: String,
+    zip: u32,
+}
+
+#[derive(Debug, PartialEq, Arbitrary, Serialize, Deserialize)]
+struct Query {
+    name: String,
+    page: u32,
+    active: bool,
+    #[serde(default)]
+    tags: Vec<String>,
+    address: Address,
+}
+
+fuzz_target!(|query: Query| {
+    if let Err(error) = roundtrip(&query, ParseMode::Brackets) {
+        panic!("{}", error);
+    }
+});
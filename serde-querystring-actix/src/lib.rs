@@ -10,7 +10,7 @@ use actix_web::{Error, FromRequest, HttpRequest, ResponseError};
 use derive_more::{Display, From};
 use serde::de;
 
-pub use serde_querystring::de::ParseMode;
+pub use serde_querystring::de::{ParseMode, QsConfig, QsConfigBuilder};
 
 /// Actix-web's web::Query modified to work with serde-querystring
 ///
@@ -57,15 +57,13 @@ impl<T> QueryString<T> {
         self.0
     }
 
-    /// Get query parameters from the path
-    pub fn from_query(
-        query_str: &str,
-        parse_mode: serde_querystring::de::ParseMode,
-    ) -> Result<Self, QueryStringPayloadError>
+    /// Get query parameters from the path, with a parse mode or a config
+    pub fn from_query<C>(query_str: &str, config: C) -> Result<Self, QueryStringPayloadError>
     where
         T: de::DeserializeOwned,
+        C: Into<QsConfig>,
     {
-        serde_querystring::de::from_str::<T>(query_str, parse_mode)
+        serde_querystring::de::from_str::<T, _>(query_str, config)
             .map(Self)
             .map_err(QueryStringPayloadError::Deserialize)
     }
@@ -105,7 +103,7 @@ where
             .cloned()
             .unwrap_or_default();
 
-        serde_querystring::de::from_str::<T>(req.query_string(), config.mode)
+        serde_querystring::de::from_str::<T, _>(req.query_string(), config.config)
             .map(|val| ready(Ok(QueryString(val))))
             .unwrap_or_else(move |e| {
                 let e = QueryStringPayloadError::Deserialize(e);
@@ -161,10 +159,12 @@ where
 ///     );
 /// }
 /// ```
+type ErrorHandler = dyn Fn(QueryStringPayloadError, &HttpRequest) -> Error + Send + Sync;
+
 #[derive(Clone)]
 pub struct QueryStringConfig {
-    mode: serde_querystring::de::ParseMode,
-    ehandler: Option<Arc<dyn Fn(QueryStringPayloadError, &HttpRequest) -> Error + Send + Sync>>,
+    config: QsConfig,
+    ehandler: Option<Arc<ErrorHandler>>,
 }
 
 impl QueryStringConfig {
@@ -177,8 +177,22 @@ impl QueryStringConfig {
         self
     }
 
+    /// Set the parse mode, keeping the other options of the config
     pub fn parse_mode(mut self, mode: serde_querystring::de::ParseMode) -> Self {
-        self.mode = mode;
+        self.config = QsConfigBuilder::from(self.config).mode(mode).build();
+        self
+    }
+
+    /// Set the config used to parse the query strings, to choose more options than the mode
+    ///
+    /// ```rust
+    /// use serde_querystring_actix::{ParseMode, QsConfig, QueryStringConfig};
+    ///
+    /// let config = QueryStringConfig::default()
+    ///     .config(QsConfig::builder().mode(ParseMode::Brackets).plus_as_space(false).build());
+    /// ```
+    pub fn config(mut self, config: QsConfig) -> Self {
+        self.config = config;
         self
     }
 }
@@ -186,7 +200,7 @@ impl QueryStringConfig {
 impl Default for QueryStringConfig {
     fn default() -> Self {
         QueryStringConfig {
-            mode: serde_querystring::de::ParseMode::Duplicate,
+            config: QsConfig::new(serde_querystring::de::ParseMode::Duplicate),
             ehandler: None,
         }
     }
@@ -229,14 +243,14 @@ mod tests {
     async fn test_service_request_extract() {
         let req = TestRequest::with_uri("/name/user1/").to_srv_request();
         assert!(QueryString::<Id>::from_query(
-            &req.query_string(),
+            req.query_string(),
             serde_querystring::de::ParseMode::UrlEncoded
         )
         .is_err());
 
         let req = TestRequest::with_uri("/name/user1/?id=test").to_srv_request();
         let mut s = QueryString::<Id>::from_query(
-            &req.query_string(),
+            req.query_string(),
             serde_querystring::de::ParseMode::UrlEncoded,
         )
         .unwrap();
@@ -277,6 +291,27 @@ mod tests {
         assert_eq!(s.id, "test1");
     }
 
+    #[actix_rt::test]
+    async fn test_config() {
+        let config = QsConfig::builder()
+            .pair_separator(serde_querystring::PairSeparator::Semicolon)
+            .plus_as_space(false)
+            .build();
+        let req = TestRequest::with_uri("/name/user1/?id=a;id=b+c")
+            .app_data(
+                QueryStringConfig::default()
+                    .config(config)
+                    .parse_mode(ParseMode::Brackets),
+            )
+            .to_srv_request();
+        let (req, mut pl) = req.into_parts();
+
+        let s = QueryString::<Id>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(s.id, "b+c");
+    }
+
     #[actix_rt::test]
     async fn test_custom_error_responder() {
         let req = TestRequest::with_uri("/name/user1/")
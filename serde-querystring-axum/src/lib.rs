@@ -9,12 +9,17 @@ use http::{request::Parts, StatusCode};
 use serde::de::DeserializeOwned;
 use serde_querystring::de::Error;
 
-pub use serde_querystring::de::ParseMode;
+pub use serde_querystring::de::{ParseMode, QsConfig, QsConfigBuilder};
 
 pub trait QueryStringMode {
     fn get_mode() -> ParseMode {
         ParseMode::UrlEncoded
     }
+
+    /// The config used to parse the query string, override it to set more options than the mode
+    fn get_config() -> QsConfig {
+        Self::get_mode().into()
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -31,7 +36,7 @@ where
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let query = parts.uri.query().unwrap_or_default();
         let value =
-            serde_querystring::from_str(query, T::get_mode()).map_err(QueryStringRejection)?;
+            serde_querystring::from_str(query, T::get_config()).map_err(QueryStringRejection)?;
         Ok(QueryString(value))
     }
 }
@@ -84,7 +89,11 @@ mod tests {
             pages: Option<Vec<u64>>,
         }
 
-        impl QueryStringMode for Pagination {}
+        impl QueryStringMode for Pagination {
+            fn get_mode() -> ParseMode {
+                ParseMode::Duplicate
+            }
+        }
 
         check(
             "http://example.com/test",
@@ -138,7 +147,7 @@ mod tests {
         }
 
         async fn handler(QueryString(params): QueryString<Params>) -> String {
-            format!("{}-{}", params.n.get(0).unwrap(), params.n.get(2).unwrap())
+            format!("{}-{}", params.n.first().unwrap(), params.n.get(2).unwrap())
         }
 
         let app = Router::new().route("/", get(handler));
@@ -158,6 +167,32 @@ mod tests {
         assert_eq!(body.data().await.unwrap().unwrap(), "100-300")
     }
 
+    #[tokio::test]
+    async fn test_config() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Search {
+            q: String,
+        }
+
+        impl QueryStringMode for Search {
+            fn get_config() -> QsConfig {
+                QsConfig::builder()
+                    .mode(ParseMode::Brackets)
+                    .pair_separator(serde_querystring::PairSeparator::Semicolon)
+                    .plus_as_space(false)
+                    .build()
+            }
+        }
+
+        check(
+            "http://example.com/test?q=a;q=c+d",
+            Search {
+                q: String::from("c+d"),
+            },
+        )
+        .await;
+    }
+
     #[tokio::test]
     async fn correct_rejection_default() {
         #[derive(Deserialize)]
@@ -186,7 +221,12 @@ mod tests {
         assert_eq!(parts.status, StatusCode::BAD_REQUEST);
         assert_eq!(
             body.data().await.unwrap().unwrap(),
-            "Failed to deserialize query string"
+            format!(
+                "Failed to deserialize query string: {}",
+                serde_querystring::from_str::<Params, _>("n=string", ParseMode::UrlEncoded)
+                    .err()
+                    .unwrap()
+            )
         );
     }
 
@@ -205,7 +245,7 @@ mod tests {
         ) -> impl IntoResponse {
             match x {
                 Ok(QueryString(_)) => (StatusCode::OK, ""),
-                Err(QueryStringRejection(e)) => (StatusCode::BAD_GATEWAY, "Something went wrong"),
+                Err(QueryStringRejection(_e)) => (StatusCode::BAD_GATEWAY, "Something went wrong"),
             }
         }
 
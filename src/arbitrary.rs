@@ -0,0 +1,157 @@
+//! Implementations of `arbitrary::Arbitrary` for the configs, enabled by the `arbitrary` feature
+//!
+//! They let fuzz targets try the parser with every combination of options, `ParseMode::Custom`
+//! is never generated. The depth and index limits are kept small so the inputs stay fast.
+
+use _arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::config::{
+    DuplicateKeys, EmptyBrackets, InvalidUtf8, KeyConflict, PairSeparator, ParseMode, QsConfig,
+    SparseIndexes,
+};
+
+/// The delimiters of the delimiter modes, and of the `delimiter` option
+const DELIMITERS: &[u8] = b"|,;: +";
+
+/// The multi byte delimiters of the `delimiter` option
+const DELIMITER_STRS: &[&str] = &["|", ",", "||", ", ", "%2C"];
+
+impl<'a> Arbitrary<'a> for PairSeparator {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            PairSeparator::Ampersand,
+            PairSeparator::Semicolon,
+            PairSeparator::Both,
+            PairSeparator::Newline,
+        ])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for DuplicateKeys {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            DuplicateKeys::Last,
+            DuplicateKeys::First,
+            DuplicateKeys::Error,
+        ])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for EmptyBrackets {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            EmptyBrackets::Append,
+            EmptyBrackets::First,
+            EmptyBrackets::Error,
+        ])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for KeyConflict {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            KeyConflict::Container,
+            KeyConflict::Scalar,
+            KeyConflict::Error,
+        ])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for SparseIndexes {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[
+            SparseIndexes::Compact,
+            SparseIndexes::Fill,
+            SparseIndexes::Error,
+        ])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for InvalidUtf8 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(*u.choose(&[InvalidUtf8::Error, InvalidUtf8::Replace, InvalidUtf8::Bytes])?)
+    }
+}
+
+impl<'a> Arbitrary<'a> for ParseMode {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=8u8)? {
+            0 => ParseMode::UrlEncoded,
+            1 => ParseMode::Duplicate,
+            2 => ParseMode::Delimiter(*u.choose(DELIMITERS)?),
+            3 => ParseMode::Brackets,
+            4 => ParseMode::DotNotation,
+            5 => ParseMode::BracketsDelimiter(*u.choose(DELIMITERS)?),
+            6 => ParseMode::Matrix,
+            7 => ParseMode::Rack,
+            _ => ParseMode::Php,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for QsConfig {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut config = QsConfig::new(u.arbitrary()?)
+            .pair_separator(u.arbitrary()?)
+            .plus_as_space(u.arbitrary()?)
+            .unicode_escapes(u.arbitrary()?)
+            .invalid_utf8(u.arbitrary()?)
+            .max_depth(u.int_in_range(0..=8)?)
+            .skip_invalid_pairs(u.arbitrary()?)
+            .duplicate_keys(u.arbitrary()?)
+            .strict_keys(u.arbitrary()?)
+            .present_as_some(u.arbitrary()?)
+            .lenient_bool(u.arbitrary()?)
+            .lenient_numbers(u.arbitrary()?)
+            .trim_whitespace(u.arbitrary()?)
+            .case_insensitive_enums(u.arbitrary()?)
+            .numeric_enums(u.arbitrary()?)
+            .null_as_none(u.arbitrary()?)
+            .empty_brackets(u.arbitrary()?)
+            .key_conflict(u.arbitrary()?)
+            .sparse_indexes(u.arbitrary()?)
+            .max_index(u.int_in_range(0..=64)?)
+            .infer_types(u.arbitrary()?)
+            .decode_before_split(u.arbitrary()?)
+            .empty_as_empty_seq(u.arbitrary()?);
+
+        if u.arbitrary()? {
+            config = config.max_pairs(u.int_in_range(0..=64)?);
+        }
+        if u.arbitrary()? {
+            config = config.max_length(u.int_in_range(0..=4096)?);
+        }
+        if u.arbitrary()? {
+            config = config.delimiter(u.choose(DELIMITER_STRS)?);
+        }
+        if u.arbitrary()? {
+            config = config.delimiter_escape(*u.choose(b"\\^")?);
+        }
+        if u.arbitrary()? {
+            config = config.key_transform(crate::config::camel_to_snake);
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "std"))]
+mod ser {
+    use _arbitrary::{Arbitrary, Result, Unstructured};
+
+    use crate::ser::{NoneStyle, SerializerConfig};
+
+    impl<'a> Arbitrary<'a> for NoneStyle {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(*u.choose(&[NoneStyle::Skip, NoneStyle::Empty, NoneStyle::KeyOnly])?)
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for SerializerConfig {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(SerializerConfig::new(u.arbitrary()?)
+                .empty_brackets(u.arbitrary()?)
+                .none_style(u.arbitrary()?)
+                .sort_keys(u.arbitrary()?))
+        }
+    }
+}
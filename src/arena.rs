@@ -0,0 +1,73 @@
+//! Deserializing into an arena, enabled by the `bumpalo` feature
+//!
+//! The keys and values which need decoding are allocated in a `bumpalo::Bump` instead of a
+//! buffer of the deserializer, so they are borrowed for as long as the arena like the rest of
+//! the input. Borrowed targets like `&str` work for every value, not only those with nothing to
+//! decode.
+
+#[cfg(feature = "bumpalo")]
+use _serde::de;
+
+#[cfg(feature = "bumpalo")]
+use crate::de::{deserialize_input, Error, QsBuffers, QsConfig};
+
+/// The arena decoded slices are allocated in
+#[cfg(feature = "bumpalo")]
+pub use _bumpalo::Bump as Arena;
+
+/// Stands for the arena without the `bumpalo` feature, it can't be created
+#[cfg(not(feature = "bumpalo"))]
+pub enum Arena {}
+
+#[cfg(not(feature = "bumpalo"))]
+impl Arena {
+    pub fn alloc_slice_copy(&self, _: &[u8]) -> &mut [u8] {
+        match *self {}
+    }
+}
+
+/// Deserialize an instance of type `T` from bytes of query string, allocating the decoded keys
+/// and values in the arena
+///
+/// `T` can borrow any of its strings, they are borrowed from the input when they need no
+/// decoding and from the arena otherwise. `Rack` and `Php` modes build a tree of owned values
+/// first, so their strings can't be borrowed.
+///
+/// # Example
+#[cfg_attr(feature = "bumpalo", doc = "```rust")]
+#[cfg_attr(not(feature = "bumpalo"), doc = "```rust,ignore")]
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_str_in, Bump, ParseMode};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Search<'a> {
+///     q: &'a str,
+///     tags: Vec<&'a str>,
+/// }
+///
+/// let arena = Bump::new();
+/// let search: Search = from_str_in(&arena, "q=rust+serde&tags=a%20b&tags=c", ParseMode::Duplicate).unwrap();
+///
+/// assert_eq!(search.q, "rust serde");
+/// assert_eq!(search.tags, ["a b", "c"]);
+/// ```
+#[cfg(feature = "bumpalo")]
+pub fn from_bytes_in<'de, T, C>(arena: &'de Arena, input: &'de [u8], config: C) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+    C: Into<QsConfig>,
+{
+    deserialize_input(&mut QsBuffers::new(), input, config.into(), Some(arena))
+}
+
+/// Deserialize an instance of type `T` from a query string, allocating the decoded keys and
+/// values in the arena. See [`from_bytes_in`]
+#[cfg(feature = "bumpalo")]
+pub fn from_str_in<'de, T, C>(arena: &'de Arena, input: &'de str, config: C) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+    C: Into<QsConfig>,
+{
+    from_bytes_in(arena, input.as_bytes(), config)
+}
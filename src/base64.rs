@@ -0,0 +1,164 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use _base64::alphabet;
+use _base64::engine::{DecodePaddingMode, Engine, GeneralPurpose, GeneralPurposeConfig};
+use _serde::{de, ser};
+
+/// The standard alphabet, written with padding and read with or without it
+const STANDARD: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// The url safe alphabet, written without padding and read with or without it
+const URL_SAFE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+struct Base64Visitor<T> {
+    engine: &'static GeneralPurpose,
+    marker: PhantomData<T>,
+}
+
+impl<T> Base64Visitor<T> {
+    fn decode<E: de::Error>(&self, v: &[u8]) -> Result<T, E>
+    where
+        T: From<Vec<u8>>,
+    {
+        // An unencoded `+` of the standard alphabet is decoded as a space by the parsers
+        let v: Vec<u8> = v
+            .iter()
+            .map(|&b| if b == b' ' { b'+' } else { b })
+            .collect();
+
+        self.engine
+            .decode(v)
+            .map(T::from)
+            .map_err(|e| E::custom(format_args!("invalid base64 value: {}", e)))
+    }
+}
+
+impl<'de, T> de::Visitor<'de> for Base64Visitor<T>
+where
+    T: From<Vec<u8>>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a base64 encoded value")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.decode(v.as_bytes())
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        self.decode(v)
+    }
+}
+
+macro_rules! base64 {
+    ($(#[$doc:meta])* $name:ident, $engine:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name<T = Vec<u8>>(pub T);
+
+        impl<T> $name<T> {
+            /// Returns the decoded value
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+        }
+
+        impl<T> From<T> for $name<T> {
+            fn from(value: T) -> Self {
+                $name(value)
+            }
+        }
+
+        impl<T> Deref for $name<T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $name<T> {
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+
+        impl<T> ser::Serialize for $name<T>
+        where
+            T: AsRef<[u8]>,
+        {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ser::Serializer,
+            {
+                serializer.serialize_str(&$engine.encode(self.0.as_ref()))
+            }
+        }
+
+        impl<'de, T> de::Deserialize<'de> for $name<T>
+        where
+            T: From<Vec<u8>>,
+        {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                deserializer
+                    .deserialize_str(Base64Visitor {
+                        engine: &$engine,
+                        marker: PhantomData,
+                    })
+                    .map($name)
+            }
+        }
+    };
+}
+
+base64!(
+    /// A value encoded in base64 with the standard alphabet, decoded when deserialized
+    ///
+    /// The padding is optional when decoding, and written when encoding. Spaces are read as
+    /// `+`, as the parsers decode an unencoded `+` into a space. The decoded bytes are converted
+    /// into `T`, like `Vec<u8>`, `serde_bytes::ByteBuf` or `BString`.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use _serde::{Deserialize, Serialize};
+    /// use serde_querystring::{from_str, to_string, Base64, ParseMode};
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    ///# #[serde(crate = "_serde")]
+    /// struct Query {
+    ///     token: Base64,
+    /// }
+    ///
+    /// let query: Query = from_str("token=aGk/Pz4+", ParseMode::UrlEncoded).unwrap();
+    ///
+    /// assert_eq!(*query.token, b"hi??>>");
+    /// assert_eq!(to_string(&query, ParseMode::UrlEncoded).unwrap(), "token=aGk%2FPz4%2B");
+    /// ```
+    Base64,
+    STANDARD
+);
+
+base64!(
+    /// A value encoded in base64 with the url safe alphabet(`-` and `_`), decoded when
+    /// deserialized
+    ///
+    /// It works the same as [`Base64`], but the padding is not written when encoding, so the
+    /// value needs no percent encoding. It is the usual encoding of cursors and tokens.
+    Base64Url,
+    URL_SAFE
+);
@@ -0,0 +1,145 @@
+//! Rewriting query strings in a canonical form, for signing requests and building cache keys
+
+use crate::config::PairSeparator;
+use crate::decode::{parse_bytes_with, DecodeOptions};
+use crate::encode::{encode_bytes, EncodeSet};
+use crate::pairs::{split_pair, split_pairs};
+
+/// Options of [`canonicalize`]
+///
+/// The defaults sort the pairs, remove the ones without a value, decode `+` as a space and
+/// re-encode everything but the unreserved bytes of RFC 3986, with upper case hex digits.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::CanonicalizeOptions;
+///
+/// // Keep the pairs without a value, as the canonical requests of AWS SigV4 do
+/// let options = CanonicalizeOptions::new().remove_empty(false);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct CanonicalizeOptions {
+    pair_separator: PairSeparator,
+    decode_options: DecodeOptions,
+    sort: bool,
+    remove_empty: bool,
+    bare_keys: bool,
+    encode_set: EncodeSet,
+}
+
+impl CanonicalizeOptions {
+    /// Creates the default options
+    pub fn new() -> Self {
+        Self {
+            pair_separator: PairSeparator::Ampersand,
+            decode_options: DecodeOptions::default(),
+            sort: true,
+            remove_empty: true,
+            bare_keys: false,
+            encode_set: EncodeSet::RFC3986,
+        }
+    }
+
+    /// Sets which bytes separate the pairs of the input, the output always uses `&`
+    pub fn pair_separator(mut self, pair_separator: PairSeparator) -> Self {
+        self.pair_separator = pair_separator;
+        self
+    }
+
+    /// Decode `+` as a space, which is then encoded as `%20`. Otherwise `+` is a literal plus
+    /// encoded as `%2B`. Defaults to true.
+    pub fn plus_as_space(mut self, plus_as_space: bool) -> Self {
+        self.decode_options.plus_as_space = plus_as_space;
+        self
+    }
+
+    /// Sort the pairs by their encoded keys, and then by their encoded values. Otherwise they
+    /// keep their order. Defaults to true.
+    pub fn sort(mut self, sort: bool) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Remove the pairs without a value(`key=` and `key`), otherwise they are written as
+    /// `key=`. Defaults to true.
+    pub fn remove_empty(mut self, remove_empty: bool) -> Self {
+        self.remove_empty = remove_empty;
+        self
+    }
+
+    /// Write the keys without a value(`key`) as they are instead of `key=`, as the parsers may
+    /// tell them apart from the empty values. Defaults to false.
+    pub fn bare_keys(mut self, bare_keys: bool) -> Self {
+        self.bare_keys = bare_keys;
+        self
+    }
+
+    /// Sets the bytes which are written as is, defaults to [`EncodeSet::RFC3986`]
+    pub fn encode_set(mut self, encode_set: EncodeSet) -> Self {
+        self.encode_set = encode_set;
+        self
+    }
+}
+
+impl Default for CanonicalizeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rewrite a query string in a canonical form, so the queries which parse the same way are
+/// equal
+///
+/// The keys and values are percent decoded and encoded again in a single way, the malformed
+/// escapes included(`%zz` is `%25zz`). Empty pairs(`&&`) are removed, and every pair is written
+/// as `key=value` unless bare keys are kept. Sorting also sorts the values of repeated keys,
+/// as AWS SigV4 does.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{canonicalize, CanonicalizeOptions};
+///
+/// let query = canonicalize("b=a+b&a=%7e%2f&c=&a=1", CanonicalizeOptions::new());
+/// assert_eq!(query, "a=1&a=~%2F&b=a%20b");
+/// ```
+pub fn canonicalize(input: impl AsRef<[u8]>, options: CanonicalizeOptions) -> String {
+    let mut scratch = Vec::new();
+    let mut pairs = Vec::new();
+
+    for pair in split_pairs(input.as_ref(), options.pair_separator) {
+        let (key, value) = split_pair(pair);
+        if options.remove_empty && value.is_empty() {
+            continue;
+        }
+        let bare = options.bare_keys && key.len() == pair.len();
+
+        let mut encoded_key = Vec::new();
+        let key = parse_bytes_with(key, &mut scratch, options.decode_options);
+        encode_bytes(&key, &options.encode_set, &mut encoded_key);
+
+        let mut encoded_value = Vec::new();
+        let value = parse_bytes_with(value, &mut scratch, options.decode_options);
+        encode_bytes(&value, &options.encode_set, &mut encoded_value);
+
+        pairs.push((encoded_key, (!bare).then(|| encoded_value)));
+    }
+
+    if options.sort {
+        pairs.sort();
+    }
+
+    let mut output = Vec::with_capacity(input.as_ref().len());
+    for (index, (key, value)) in pairs.iter().enumerate() {
+        if index > 0 {
+            output.push(b'&');
+        }
+        output.extend_from_slice(key);
+        if let Some(value) = value {
+            output.push(b'=');
+            output.extend_from_slice(value);
+        }
+    }
+
+    // Every byte outside of the ascii range is percent encoded
+    String::from_utf8(output).expect("percent encoded output is always valid utf-8")
+}
@@ -0,0 +1,171 @@
+//! Query strings percent encoded in a legacy charset, like Shift_JIS, Windows-1252 or EUC-KR
+//!
+//! Enabled by the `charset` feature. The escaped bytes are decoded with an [`Encoding`] of
+//! `encoding_rs` into utf-8, before the query string is deserialized.
+//!
+//! # Example
+//! ```rust
+//!# use _serde::Deserialize;
+//! use serde_querystring::charset::{self, encoding_rs::SHIFT_JIS};
+//! use serde_querystring::ParseMode;
+//!
+//! #[derive(Deserialize)]
+//!# #[serde(crate = "_serde")]
+//! struct Form {
+//!     name: String,
+//! }
+//!
+//! // `テスト` in Shift_JIS
+//! let form: Form = charset::from_bytes(b"name=%83e%83X%83g", SHIFT_JIS, ParseMode::UrlEncoded).unwrap();
+//!
+//! assert_eq!(form.name, "テスト");
+//! ```
+
+use std::borrow::Cow;
+
+use _serde::de;
+
+pub use _encoding_rs::{self as encoding_rs, Encoding};
+
+use crate::de::{from_bytes as from_utf8_bytes, Error, ErrorKind, QsConfig};
+use crate::decode::parse_char;
+use crate::pairs::get_value;
+
+/// The name of the parameter html forms fill with the charset they are submitted in
+pub const CHARSET_PARAM: &str = "_charset_";
+
+/// Bytes which are kept as is in the transcoded query string
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~' | b'*')
+}
+
+/// Re-encodes a decoded run of bytes as percent encoded utf-8
+fn transcode_run(run: &[u8], encoding: &'static Encoding, output: &mut Vec<u8>) {
+    let (decoded, _) = encoding.decode_without_bom_handling(run);
+    for &byte in decoded.as_bytes() {
+        if is_unreserved(byte) {
+            output.push(byte);
+        } else {
+            output.extend_from_slice(format!("%{:02X}", byte).as_bytes());
+        }
+    }
+}
+
+/// Transcodes the percent encoded bytes of a query string from the encoding into utf-8
+///
+/// Only the runs of escaped and unreserved bytes containing non ascii bytes are transcoded, so
+/// the separators, brackets and delimiters keep their meaning. Malformed sequences are replaced
+/// by `U+FFFD`. The input is borrowed when there is nothing to transcode.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::charset::{encoding_rs::WINDOWS_1252, transcode};
+///
+/// assert_eq!(&*transcode(b"q=caf%E9&page=1", WINDOWS_1252), b"q=caf%C3%A9&page=1");
+/// ```
+pub fn transcode<'a>(input: &'a [u8], encoding: &'static Encoding) -> Cow<'a, [u8]> {
+    if encoding == encoding_rs::UTF_8 || input.iter().all(|&byte| byte != b'%' && byte < 0x80) {
+        return Cow::Borrowed(input);
+    }
+
+    let mut output = Vec::with_capacity(input.len());
+    let mut run = Vec::new();
+    // Index of the first byte of the current run
+    let mut start = 0;
+    let mut non_ascii = false;
+    let mut transcoded = false;
+    let mut cursor = 0;
+
+    while cursor <= input.len() {
+        let (byte, len) = match input.get(cursor..) {
+            Some([b'%', high, low, ..]) => match parse_char(*high, *low) {
+                Some(byte) => (Some(byte), 3),
+                None => (None, 1),
+            },
+            Some([byte, ..]) if *byte >= 0x80 || is_unreserved(*byte) => (Some(*byte), 1),
+            _ => (None, 1),
+        };
+
+        match byte {
+            Some(byte) => {
+                non_ascii |= byte >= 0x80;
+                run.push(byte);
+            }
+            None => {
+                if non_ascii {
+                    transcode_run(&run, encoding, &mut output);
+                    transcoded = true;
+                } else {
+                    output.extend_from_slice(&input[start..cursor]);
+                }
+                if let Some(byte) = input.get(cursor) {
+                    output.push(*byte);
+                }
+
+                run.clear();
+                non_ascii = false;
+                start = cursor + len;
+            }
+        }
+        cursor += len;
+    }
+
+    if transcoded {
+        Cow::Owned(output)
+    } else {
+        Cow::Borrowed(input)
+    }
+}
+
+/// Returns the encoding named by the `_charset_` parameter of the query string
+///
+/// It fails with `ErrorKind::InvalidEncoding` if the label is not a known encoding.
+pub fn detect_charset<C>(input: &[u8], config: C) -> Result<Option<&'static Encoding>, Error>
+where
+    C: Into<QsConfig>,
+{
+    let label = match get_value(input, CHARSET_PARAM.as_bytes(), config) {
+        Some(label) => label,
+        None => return Ok(None),
+    };
+
+    match Encoding::for_label(&label) {
+        Some(encoding) => Ok(Some(encoding)),
+        None => Err(Error::new(ErrorKind::InvalidEncoding)
+            .message(String::from("unknown charset"))
+            .value(&label)),
+    }
+}
+
+/// Deserialize an instance of type `T` from bytes of query string percent encoded in the
+/// given encoding
+///
+/// The `_charset_` parameter, which html forms fill when they have a hidden field of that name,
+/// takes precedence over the encoding. It is kept in the query string, so it is seen by the
+/// deserialized type like any other parameter.
+pub fn from_bytes<T, C>(input: &[u8], encoding: &'static Encoding, config: C) -> Result<T, Error>
+where
+    T: de::DeserializeOwned,
+    C: Into<QsConfig>,
+{
+    let config = config.into();
+    let encoding = detect_charset(input, config)?.unwrap_or(encoding);
+    let transcoded = transcode(input, encoding);
+    from_utf8_bytes(&transcoded, config).map_err(|mut e| {
+        // The spans are of the transcoded query string
+        if let Cow::Owned(_) = transcoded {
+            e.clear_spans();
+        }
+        e
+    })
+}
+
+/// Deserialize an instance of type `T` from a query string percent encoded in the given
+/// encoding. See [`from_bytes`]
+pub fn from_str<T, C>(input: &str, encoding: &'static Encoding, config: C) -> Result<T, Error>
+where
+    T: de::DeserializeOwned,
+    C: Into<QsConfig>,
+{
+    from_bytes(input.as_bytes(), encoding, config)
+}
@@ -0,0 +1,6 @@
+//! APIs matching other query string crates, to ease migrating from them
+//!
+//! They are thin wrappers over the parsers and the serializer of this crate, so the parsing
+//! follows this crate where the other crates behave differently.
+
+pub mod serde_qs;
@@ -0,0 +1,110 @@
+//! The API of the [`serde_qs`](https://crates.io/crates/serde_qs) crate, backed by the
+//! `Brackets` mode
+//!
+//! Replacing `serde_qs::` by `serde_querystring::compat::serde_qs::` is enough for most
+//! projects. The differences with `serde_qs` are:
+//! - Keys nested deeper than the max depth fail with `ErrorKind::DepthLimitExceeded`, instead
+//!   of keeping the rest of the key as a single sub key.
+//! - Repeated keys without brackets use their last value instead of failing, unless the
+//!   deserialized type is a sequence.
+//! - Percent encoded brackets(`%5B`) are always parsed as brackets, even in strict mode.
+//!
+//! # Example
+//! ```rust
+//!# use _serde::{Deserialize, Serialize};
+//! use serde_querystring::compat::serde_qs;
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//!# #[serde(crate = "_serde")]
+//! struct Query {
+//!     name: String,
+//!     ids: Vec<u32>,
+//! }
+//!
+//! let query: Query = serde_qs::from_str("name=John&ids[0]=1&ids[1]=2").unwrap();
+//! assert_eq!(query.ids, vec![1, 2]);
+//!
+//! assert_eq!(serde_qs::to_string(&query).unwrap(), "name=John&ids[0]=1&ids[1]=2");
+//!
+//! let config = serde_qs::Config::new(2, false);
+//! let query: Query = config.deserialize_str("name=John&ids[]=1").unwrap();
+//! assert_eq!(query.ids, vec![1]);
+//! ```
+
+use std::io;
+
+use _serde::{Deserialize, Serialize};
+
+use crate::config::{ParseMode, QsConfig};
+
+pub use crate::de::Error;
+
+/// The result of the functions of this module
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// The options of the deserializer, like `serde_qs::Config`
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    max_depth: usize,
+    strict: bool,
+}
+
+impl Config {
+    /// Creates a config with the maximum number of sub keys in a key, and the strict mode
+    /// which is only kept for compatibility
+    pub fn new(max_depth: usize, strict: bool) -> Self {
+        Self { max_depth, strict }
+    }
+
+    /// Returns the maximum number of sub keys in a key
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Returns whether the strict mode is set
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Deserialize an instance of type `T` from bytes of query string
+    pub fn deserialize_bytes<'de, T: Deserialize<'de>>(&self, input: &'de [u8]) -> Result<T> {
+        crate::de::from_bytes(input, QsConfig::from(*self))
+    }
+
+    /// Deserialize an instance of type `T` from a query string
+    pub fn deserialize_str<'de, T: Deserialize<'de>>(&self, input: &'de str) -> Result<T> {
+        self.deserialize_bytes(input.as_bytes())
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::new(5, true)
+    }
+}
+
+impl From<Config> for QsConfig {
+    fn from(config: Config) -> Self {
+        QsConfig::new(ParseMode::Brackets).max_depth(config.max_depth)
+    }
+}
+
+/// Deserialize an instance of type `T` from bytes of query string, with the default config
+pub fn from_bytes<'de, T: Deserialize<'de>>(input: &'de [u8]) -> Result<T> {
+    Config::default().deserialize_bytes(input)
+}
+
+/// Deserialize an instance of type `T` from a query string, with the default config
+pub fn from_str<'de, T: Deserialize<'de>>(input: &'de str) -> Result<T> {
+    Config::default().deserialize_str(input)
+}
+
+/// Serialize a value into a query string, with indexed brackets for the sequences
+pub fn to_string<T: Serialize>(input: &T) -> Result<String> {
+    crate::ser::to_string(input, ParseMode::Brackets)
+}
+
+/// Serialize a value as a query string into a writer
+pub fn to_writer<T: Serialize, W: io::Write>(input: &T, writer: &mut W) -> Result<()> {
+    crate::ser::to_writer(writer, input, ParseMode::Brackets)
+}
@@ -0,0 +1,1069 @@
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+
+use crate::decode::DecodeOptions;
+use crate::parsers::QsParser;
+#[cfg(any(
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+use crate::scan::memchr3;
+#[cfg(any(
+    feature = "serde",
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+use crate::scan::{memchr, memchr2};
+
+/// Which bytes separate the pairs of a query string
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PairSeparator {
+    /// Pairs are separated by `&`, which is the default
+    Ampersand,
+    /// Pairs are separated by `;`, as in older W3C recommendations
+    Semicolon,
+    /// Both `&` and `;` separate pairs
+    Both,
+    /// Pairs are on separate lines(`\n` or `\r\n`), as in dotenv files and some webhook payloads
+    ///
+    /// A lone `\r` separates the pairs too, and empty lines are skipped.
+    Newline,
+}
+
+impl PairSeparator {
+    #[cfg(any(
+        feature = "serde",
+        feature = "urlencoded",
+        feature = "duplicate",
+        feature = "delimiter",
+        feature = "brackets"
+    ))]
+    #[inline]
+    pub(crate) fn matches(self, b: u8) -> bool {
+        match self {
+            PairSeparator::Ampersand => b == b'&',
+            PairSeparator::Semicolon => b == b';',
+            PairSeparator::Both => b == b'&' || b == b';',
+            PairSeparator::Newline => b == b'\n' || b == b'\r',
+        }
+    }
+
+    /// Returns the index of the first separator in the slice, or its length
+    #[cfg(any(
+        feature = "serde",
+        feature = "urlencoded",
+        feature = "duplicate",
+        feature = "delimiter",
+        feature = "brackets"
+    ))]
+    #[inline]
+    pub(crate) fn find(self, slice: &[u8]) -> usize {
+        let index = match self {
+            PairSeparator::Ampersand => memchr(b'&', slice),
+            PairSeparator::Semicolon => memchr(b';', slice),
+            PairSeparator::Both => memchr2(b'&', b';', slice),
+            PairSeparator::Newline => memchr2(b'\n', b'\r', slice),
+        };
+        index.unwrap_or(slice.len())
+    }
+
+    /// Returns the index of the first separator or `byte` in the slice, or its length
+    #[cfg(any(
+        feature = "urlencoded",
+        feature = "duplicate",
+        feature = "delimiter",
+        feature = "brackets"
+    ))]
+    #[inline]
+    pub(crate) fn find_or(self, byte: u8, slice: &[u8]) -> usize {
+        let index = match self {
+            PairSeparator::Ampersand => memchr2(b'&', byte, slice),
+            PairSeparator::Semicolon => memchr2(b';', byte, slice),
+            PairSeparator::Both => memchr3(b'&', b';', byte, slice),
+            PairSeparator::Newline => memchr3(b'\n', b'\r', byte, slice),
+        };
+        index.unwrap_or(slice.len())
+    }
+}
+
+impl Default for PairSeparator {
+    fn default() -> Self {
+        PairSeparator::Ampersand
+    }
+}
+
+/// What to do when a key is repeated, but a single value is expected
+///
+/// In `UrlEncoded` and `Delimiter` modes every key has a single value, so it applies to all of
+/// the repeated keys. In the other modes it only applies when a repeated key is deserialized
+/// into a single value, sequences still get all the values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// The last value is used, which is the default
+    Last,
+    /// The first value is used
+    First,
+    /// Deserializing fails with `ErrorKind::DuplicateKey`
+    Error,
+}
+
+impl Default for DuplicateKeys {
+    fn default() -> Self {
+        DuplicateKeys::Last
+    }
+}
+
+/// How the empty brackets(`key[]`) of a sequence are ordered with the explicit indexes
+/// (`key[2]`) in the brackets modes
+///
+/// Values with the same index keep the order they appear in the query string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmptyBrackets {
+    /// Each `key[]` gets the index after the highest one before it, like PHP, which is the
+    /// default. (`a[]=x&a[2]=y&a[]=z` is `[x, y, z]`)
+    Append,
+    /// The `key[]` values come first, before the indexed ones. (`a[2]=y&a[]=x` is `[x, y]`)
+    First,
+    /// Deserializing fails with `ErrorKind::InvalidIndex` when a sequence has both
+    Error,
+}
+
+impl Default for EmptyBrackets {
+    fn default() -> Self {
+        EmptyBrackets::Append
+    }
+}
+
+/// What is used when a key has both a value and sub keys(`a=1&a[b]=2`) in the brackets modes
+///
+/// Sub keys which are indexes(`a=1&a[0]=2`) are not a conflict, the value is part of the
+/// sequence.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyConflict {
+    /// The sub keys are used and the values are ignored, which is the default
+    Container,
+    /// The values are used and the sub keys are ignored
+    Scalar,
+    /// Deserializing fails with `ErrorKind::KeyConflict`
+    Error,
+}
+
+impl Default for KeyConflict {
+    fn default() -> Self {
+        KeyConflict::Container
+    }
+}
+
+/// How the missing indexes of a sequence(`a[0]=x&a[5]=y`) are handled in the brackets modes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SparseIndexes {
+    /// The values are kept in the order of their indexes without the gaps, which is the
+    /// default. (`a[0]=x&a[5]=y` is `[x, y]`)
+    Compact,
+    /// The gaps are filled with the default values of the items, an empty string for strings,
+    /// `0` for numbers, `None` for options and so on. (`a[0]=x&a[2]=y` is `[x, "", y]`)
+    Fill,
+    /// Deserializing fails with `ErrorKind::InvalidIndex` when an index is missing
+    Error,
+}
+
+impl Default for SparseIndexes {
+    fn default() -> Self {
+        SparseIndexes::Compact
+    }
+}
+
+/// What to do when a percent decoded key or value is not valid utf-8, but a string is expected
+///
+/// Types deserialized from bytes(`serde_bytes::ByteBuf`) get the decoded bytes either way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidUtf8 {
+    /// Deserializing fails with `ErrorKind::InvalidEncoding`, which is the default
+    Error,
+    /// The invalid sequences are replaced by `U+FFFD`, as `String::from_utf8_lossy` does
+    Replace,
+    /// The bytes are given to the deserialized type instead of a string, so self describing
+    /// types(untagged enums) can fall back to bytes. The untyped `Value` has no bytes, so it
+    /// still fails with `ErrorKind::InvalidEncoding`.
+    Bytes,
+}
+
+impl Default for InvalidUtf8 {
+    fn default() -> Self {
+        InvalidUtf8::Error
+    }
+}
+
+/// An enum used to choose the parsing method for deserialization
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum ParseMode {
+    /// The simplest parser for querystring.
+    /// It parses the whole querystring, and overwrites each repeated key’s value.
+    /// It does not support vectors, maps nor tuples, but provides the best performance.
+    ///
+    /// More description at ([UrlEncodedQs](crate::UrlEncodedQS))
+    UrlEncoded,
+
+    /// A querystring parser with support for vectors/lists of values by repeating keys.
+    /// (ex. `"key=value1&key=value2"`)
+    ///
+    /// More description at ([DuplicateQs](crate::DuplicateQS))
+    Duplicate,
+
+    /// A querystring parser with support for vectors/lists of values by the use of a delimiter byte.
+    /// (ex. `"key=value1|value2|value3"`). Holds the delimiter as a single byte `Delimiter(b'|')`
+    ///
+    /// More description at ([DelimiterQs](crate::DelimiterQS))
+    Delimiter(u8),
+
+    /// A querystring parser with support for vectors/lists, maps and enums
+    /// by the use of brackets(like qs or PHP).(ex. `key[2]=value2&key[1]=value1"`)
+    ///
+    /// More description at ([BracketsQs](crate::BracketsQS))
+    Brackets,
+
+    /// Same as `Brackets`, but dots can also be used to separate the sub keys, like the `allowDots`
+    /// option of qs. (ex. `user.address.city=Oslo&user.tags[0]=admin`)
+    ///
+    /// Percent encoded dots(`%2E`) are kept inside keys.
+    /// More description at ([BracketsQs::parse_dots](crate::BracketsQS::parse_dots))
+    DotNotation,
+
+    /// Same as `Brackets`, but the values are also split by a delimiter byte into sequences.
+    /// (ex. `filter[ids]=1,2,3`). Holds the delimiter as a single byte `BracketsDelimiter(b',')`
+    ///
+    /// More description at ([BracketsQs::parse_delimited](crate::BracketsQS::parse_delimited))
+    BracketsDelimiter(u8),
+
+    /// Matrix parameters of a path segment, like the `@MatrixParam` of JAX-RS.
+    /// (ex. `;color=red;size=10,20`)
+    ///
+    /// The pairs are separated by `;` and the values by commas, otherwise it is the same as
+    /// `Delimiter(b',')`. The leading `;` is optional, and the name of the segment(`cars` of
+    /// `cars;color=red`) should be removed before parsing.
+    Matrix,
+
+    /// A dialect implemented outside of this crate, whose pairs are split by a [`QsParser`].
+    /// They are deserialized the same way as the `Duplicate` mode.
+    ///
+    /// `from_bytes_scoped` doesn't support it, and values are serialized as in `Duplicate` mode.
+    Custom(&'static dyn QsParser),
+
+    /// The nested params of Rails and Rack, parsed the same way as Rack's `parse_nested_query`.
+    /// (ex. `user[name]=John&user[roles][]=admin&items[][id]=1&items[][id]=2`)
+    ///
+    /// Unlike `Brackets`, `[]` is the only way to build arrays, a new hash is started in an array
+    /// of hashes(`items[][id]`) when its key is repeated, repeated plain keys keep their last
+    /// value and `key[0]` is a hash with the `"0"` key. Mixing a value with sub keys, or a hash
+    /// with an array, fails with `ErrorKind::KeyConflict`, like the `ParameterTypeError` of Rack.
+    ///
+    /// The values are decoded before deserializing, so borrowed `&str` fields are not supported.
+    Rack,
+
+    /// The variables of PHP, parsed the same way as `parse_str` of PHP 8.
+    /// (ex. `user.name=John&tags[]=a&tags[]=b`)
+    ///
+    /// Spaces and dots in the names are underscores(`user_name`), an unterminated bracket is an
+    /// underscore(`a[b=1` is `a_b`), and `[]` appends after the largest integer index. Values
+    /// replace the keys they conflict with instead of failing, and keys nested deeper than
+    /// `max_depth` remove their whole variable, the same as `max_input_nesting_level`.
+    ///
+    /// The values are decoded before deserializing, so borrowed `&str` fields are not supported.
+    Php,
+}
+
+impl ParseMode {
+    /// Guesses the parse mode of a query string, for servers accepting clients with different
+    /// conventions
+    ///
+    /// - `Brackets` if a key has brackets(`key[0]` or `key%5B0%5D`)
+    /// - `Duplicate` if a key is repeated
+    /// - `Delimiter` if a value has a `|` or `,`, whichever is seen first
+    /// - `Duplicate` otherwise, which parses single values the same way as `UrlEncoded`
+    ///
+    /// Pairs are expected to be separated by `&`. Single values are not split in delimiter mode,
+    /// so a comma inside a text value is kept.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_querystring::ParseMode;
+    ///
+    /// assert!(matches!(ParseMode::detect(b"ids[]=1&ids[]=2"), ParseMode::Brackets));
+    /// assert!(matches!(ParseMode::detect(b"ids=1&ids=2"), ParseMode::Duplicate));
+    /// assert!(matches!(ParseMode::detect(b"ids=1|2"), ParseMode::Delimiter(b'|')));
+    /// ```
+    pub fn detect(input: &[u8]) -> ParseMode {
+        let mut keys = BTreeSet::new();
+        let mut repeated = false;
+        let mut delimiter = None;
+
+        for pair in input.split(|b| *b == b'&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = match pair.iter().position(|b| *b == b'=') {
+                Some(index) => (&pair[..index], &pair[index + 1..]),
+                None => (pair, &pair[pair.len()..]),
+            };
+
+            let encoded_bracket = key
+                .windows(3)
+                .any(|window| window.eq_ignore_ascii_case(b"%5B"));
+            if key.contains(&b'[') || encoded_bracket {
+                return ParseMode::Brackets;
+            }
+
+            repeated |= !keys.insert(key);
+            if delimiter.is_none() {
+                delimiter = value.iter().copied().find(|b| *b == b'|' || *b == b',');
+            }
+        }
+
+        match delimiter {
+            Some(delimiter) if !repeated => ParseMode::Delimiter(delimiter),
+            _ => ParseMode::Duplicate,
+        }
+    }
+
+    /// Returns the byte splitting the values, in the delimited modes
+    #[cfg(any(feature = "serde", feature = "brackets"))]
+    pub(crate) fn delimiter(self) -> Option<u8> {
+        match self {
+            ParseMode::Delimiter(delimiter) | ParseMode::BracketsDelimiter(delimiter) => {
+                Some(delimiter)
+            }
+            ParseMode::Matrix => Some(b','),
+            _ => None,
+        }
+    }
+}
+
+/// The default for [`QsConfig::max_depth`]
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+/// The default for [`QsConfig::max_index`]
+pub const DEFAULT_MAX_INDEX: usize = 1000;
+
+/// A function normalizing the decoded keys before they are matched with the fields,
+/// see [`QsConfig::key_transform`]
+pub type KeyTransform = fn(&str) -> Cow<'_, str>;
+
+/// Converts a camelCase or PascalCase key to snake_case(`userId` to `user_id`), it can be used
+/// as a [`KeyTransform`]
+///
+/// Runs of capitals are kept together, so `HTTPServer` becomes `http_server`.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::camel_to_snake;
+///
+/// assert_eq!(camel_to_snake("createdAfter"), "created_after");
+/// assert_eq!(camel_to_snake("page"), "page");
+/// ```
+pub fn camel_to_snake(key: &str) -> Cow<'_, str> {
+    if !key.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Cow::Borrowed(key);
+    }
+
+    let mut snake = String::with_capacity(key.len() + 4);
+    let mut chars = key.chars().peekable();
+    let mut previous: Option<char> = None;
+    while let Some(c) = chars.next() {
+        if c.is_ascii_uppercase() {
+            let after_lower =
+                previous.map_or(false, |p| p.is_ascii_lowercase() || p.is_ascii_digit());
+            let ends_capitals = previous.map_or(false, |p| p.is_ascii_uppercase())
+                && chars.peek().map_or(false, |n| n.is_ascii_lowercase());
+            if after_lower || ends_capitals {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+        previous = Some(c);
+    }
+    Cow::Owned(snake)
+}
+
+/// Configures how query strings are parsed
+///
+/// It can be created from a `ParseMode`, and further options can be set by chaining methods
+/// or by using a [`QsConfigBuilder`]. A `ParseMode` can be used in its place as a shorthand
+/// for the default options.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{PairSeparator, ParseMode, QsConfig};
+///
+/// let config = QsConfig::new(ParseMode::Duplicate).pair_separator(PairSeparator::Both);
+///
+/// let config = QsConfig::builder()
+///     .mode(ParseMode::Brackets)
+///     .pair_separator(PairSeparator::Semicolon)
+///     .build();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct QsConfig {
+    pub(crate) mode: ParseMode,
+    pub(crate) pair_separator: PairSeparator,
+    pub(crate) decode_options: DecodeOptions,
+    pub(crate) max_depth: usize,
+    pub(crate) max_pairs: Option<usize>,
+    pub(crate) max_length: Option<usize>,
+    pub(crate) skip_invalid_pairs: bool,
+    pub(crate) duplicate_keys: DuplicateKeys,
+    pub(crate) strict_keys: bool,
+    pub(crate) present_as_some: bool,
+    pub(crate) lenient_bool: bool,
+    pub(crate) lenient_numbers: bool,
+    pub(crate) trim_whitespace: bool,
+    pub(crate) case_insensitive_enums: bool,
+    pub(crate) numeric_enums: bool,
+    pub(crate) null_as_none: bool,
+    pub(crate) key_transform: Option<KeyTransform>,
+    pub(crate) empty_brackets: EmptyBrackets,
+    pub(crate) key_conflict: KeyConflict,
+    pub(crate) sparse_indexes: SparseIndexes,
+    pub(crate) max_index: usize,
+    pub(crate) infer_types: bool,
+    pub(crate) delimiter: Option<&'static str>,
+    pub(crate) decode_before_split: bool,
+    pub(crate) delimiter_escape: Option<u8>,
+    pub(crate) empty_as_empty_seq: bool,
+    pub(crate) invalid_utf8: InvalidUtf8,
+}
+
+impl QsConfig {
+    /// Creates a config which parses query strings in the given `ParseMode`
+    pub fn new(mode: ParseMode) -> Self {
+        Self {
+            mode,
+            pair_separator: match mode {
+                ParseMode::Matrix => PairSeparator::Semicolon,
+                _ => PairSeparator::Ampersand,
+            },
+            decode_options: DecodeOptions::default(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_pairs: None,
+            max_length: None,
+            skip_invalid_pairs: false,
+            duplicate_keys: DuplicateKeys::Last,
+            strict_keys: false,
+            present_as_some: false,
+            lenient_bool: false,
+            lenient_numbers: false,
+            trim_whitespace: false,
+            case_insensitive_enums: false,
+            numeric_enums: false,
+            null_as_none: false,
+            key_transform: None,
+            empty_brackets: EmptyBrackets::Append,
+            key_conflict: KeyConflict::Container,
+            sparse_indexes: SparseIndexes::Compact,
+            max_index: DEFAULT_MAX_INDEX,
+            infer_types: false,
+            delimiter: None,
+            decode_before_split: false,
+            delimiter_escape: None,
+            empty_as_empty_seq: false,
+            invalid_utf8: InvalidUtf8::Error,
+        }
+    }
+
+    /// Creates a builder with the default options, in `UrlEncoded` mode
+    pub fn builder() -> QsConfigBuilder {
+        QsConfigBuilder::default()
+    }
+
+    /// Sets the bytes separating the pairs, defaults to `&`(`;` in `Matrix` mode)
+    pub fn pair_separator(mut self, pair_separator: PairSeparator) -> Self {
+        self.pair_separator = pair_separator;
+        self
+    }
+
+    /// Decode `+` as a space(html form semantics), or keep it as is(RFC 3986 semantics).
+    /// It is decoded as a space by default.
+    ///
+    /// Percent encoded spaces(`%20`) are decoded as spaces either way.
+    pub fn plus_as_space(mut self, plus_as_space: bool) -> Self {
+        self.decode_options.plus_as_space = plus_as_space;
+        self
+    }
+
+    /// Decode the legacy `%uXXXX` escapes(`%u00e9` is `é`), sent by some very old clients.
+    /// They are kept as is by default.
+    ///
+    /// A high surrogate must be followed by a low one(`%uD83D%uDE00`), deserializing fails with
+    /// `ErrorKind::InvalidUnicodeEscape` on a malformed escape.
+    pub fn unicode_escapes(mut self, unicode_escapes: bool) -> Self {
+        self.decode_options.unicode_escapes = unicode_escapes;
+        self
+    }
+
+    /// Sets what to do when a percent decoded key or value is not valid utf-8, defaults to
+    /// `InvalidUtf8::Error`
+    pub fn invalid_utf8(mut self, invalid_utf8: InvalidUtf8) -> Self {
+        self.invalid_utf8 = invalid_utf8;
+        self
+    }
+
+    /// Sets the maximum number of sub keys in a key(`a[b][c]` has 2) for the brackets modes,
+    /// defaults to [`DEFAULT_MAX_DEPTH`].
+    ///
+    /// Deserializing fails with `ErrorKind::DepthLimitExceeded` if any key of the query string
+    /// is nested deeper, even when the key is not used by the deserialized type.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the maximum number of pairs in the query string, there is no limit by default.
+    ///
+    /// Deserializing fails with `ErrorKind::TooManyParams` if there are more pairs, empty
+    /// pairs(`&&`) are not counted.
+    pub fn max_pairs(mut self, max_pairs: usize) -> Self {
+        self.max_pairs = Some(max_pairs);
+        self
+    }
+
+    /// Sets the maximum length of each key and each value in bytes once percent decoded,
+    /// there is no limit by default.
+    ///
+    /// Deserializing fails with `ErrorKind::LimitExceeded` if any key or value is longer.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Drop the pairs which can't be parsed instead of failing, they are returned by
+    /// [`invalid_pairs`](crate::invalid_pairs). It is disabled by default.
+    ///
+    /// The pairs with malformed `%u` escapes, keys or values longer than the max length, keys
+    /// nested deeper than the max depth in the brackets modes, or keys or values which are not
+    /// valid utf-8 with `InvalidUtf8::Error` are dropped. They are not counted in the max pairs.
+    pub fn skip_invalid_pairs(mut self, skip_invalid_pairs: bool) -> Self {
+        self.skip_invalid_pairs = skip_invalid_pairs;
+        self
+    }
+
+    /// Sets which value is used when a key is repeated but a single value is expected,
+    /// defaults to the last one
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    /// Fail with `ErrorKind::UnknownKey` when a key is not used by the deserialized type,
+    /// whether or not it uses `#[serde(deny_unknown_fields)]`. It is disabled by default.
+    ///
+    /// The key of the error is the unknown key, with its parent keys in the brackets modes.
+    pub fn strict_keys(mut self, strict_keys: bool) -> Self {
+        self.strict_keys = strict_keys;
+        self
+    }
+
+    /// Deserialize an `Option` as `Some` whenever its key is present, so `None` only means
+    /// the key is absent. It is disabled by default.
+    ///
+    /// A bare key(`debug`) is then deserialized from an empty value, which is `true` for
+    /// booleans and an empty string for strings, the same as `debug=`. When disabled, a bare
+    /// key or an empty value may be deserialized as `None` depending on the parse mode.
+    ///
+    /// It doesn't apply to `Value`, which has no way to tell an empty value
+    /// from an absent one. Use [`Presence`](crate::Presence) to tell a bare key from an
+    /// empty value too.
+    pub fn present_as_some(mut self, present_as_some: bool) -> Self {
+        self.present_as_some = present_as_some;
+        self
+    }
+
+    /// Accept `yes` and `no` as booleans too, and match the booleans in any case(`On`, `TRUE`).
+    /// It is disabled by default.
+    ///
+    /// `1`, `on` and `true` are parsed as true, and `0`, `off` and `false` as false either way.
+    pub fn lenient_bool(mut self, lenient_bool: bool) -> Self {
+        self.lenient_bool = lenient_bool;
+        self
+    }
+
+    /// Accept integers with a leading `+`(`+42`, or `%2B42` as `+` is decoded as a space),
+    /// underscores between the digits(`1_000`) and in hexadecimal(`0x1F`). The underscores are
+    /// accepted in floats too. It is disabled by default.
+    ///
+    /// Floats accept `inf`, `nan` and the scientific notation(`1.5e3`) either way.
+    pub fn lenient_numbers(mut self, lenient_numbers: bool) -> Self {
+        self.lenient_numbers = lenient_numbers;
+        self
+    }
+
+    /// Trim the ascii whitespace around the decoded keys and values(`page=%202` or `page=+2`),
+    /// before they are deserialized. It is disabled by default.
+    pub fn trim_whitespace(mut self, trim_whitespace: bool) -> Self {
+        self.trim_whitespace = trim_whitespace;
+        self
+    }
+
+    /// Match the unit variants of enums in any case(`side=left` for `Side::Left`), against their
+    /// serialized names. It is disabled by default.
+    ///
+    /// It doesn't apply to the aliases of the variants, or to the variants used as keys.
+    pub fn case_insensitive_enums(mut self, case_insensitive_enums: bool) -> Self {
+        self.case_insensitive_enums = case_insensitive_enums;
+        self
+    }
+
+    /// Match the unit variants of enums by their index too(`status=2` for the third variant),
+    /// which is their discriminant unless it is set explicitly. It is disabled by default.
+    ///
+    /// Enums with explicit discriminants can derive `Deserialize_repr` of the `serde_repr` crate
+    /// instead, which works without this.
+    pub fn numeric_enums(mut self, numeric_enums: bool) -> Self {
+        self.numeric_enums = numeric_enums;
+        self
+    }
+
+    /// Deserialize empty values(`key=`) and literal `null`s(`key=null`) as `None` for an `Option`,
+    /// instead of failing to parse them as numbers for example. It is disabled by default.
+    ///
+    /// It takes precedence over [`present_as_some`](Self::present_as_some), and doesn't apply
+    /// to `Value`.
+    pub fn null_as_none(mut self, null_as_none: bool) -> Self {
+        self.null_as_none = null_as_none;
+        self
+    }
+
+    /// Normalize the decoded keys before they are matched with the fields, like
+    /// [`camel_to_snake`] to map `userId` to a `user_id` field. Keys are kept as they are by
+    /// default.
+    ///
+    /// It applies to the sub keys of the brackets modes too, and keys which are not valid
+    /// utf-8 are not transformed.
+    pub fn key_transform(mut self, key_transform: KeyTransform) -> Self {
+        self.key_transform = Some(key_transform);
+        self
+    }
+
+    /// Sets how the empty brackets(`key[]`) of a sequence are ordered with the explicit indexes,
+    /// defaults to `EmptyBrackets::Append`
+    pub fn empty_brackets(mut self, empty_brackets: EmptyBrackets) -> Self {
+        self.empty_brackets = empty_brackets;
+        self
+    }
+
+    /// Sets what is used when a key has both a value and sub keys(`a=1&a[b]=2`) in the
+    /// brackets modes, defaults to `KeyConflict::Container`
+    pub fn key_conflict(mut self, key_conflict: KeyConflict) -> Self {
+        self.key_conflict = key_conflict;
+        self
+    }
+
+    /// Sets how the missing indexes of a sequence(`a[0]=x&a[5]=y`) are handled in the brackets
+    /// modes, defaults to `SparseIndexes::Compact`
+    ///
+    /// It applies to the sequences of the deserialized types, a `Value` keeps
+    /// sparse indexes as the keys of a map.
+    pub fn sparse_indexes(mut self, sparse_indexes: SparseIndexes) -> Self {
+        self.sparse_indexes = sparse_indexes;
+        self
+    }
+
+    /// Sets the largest index of a sequence(`a[1000]`) for the brackets modes, defaults to
+    /// [`DEFAULT_MAX_INDEX`].
+    ///
+    /// Deserializing a sequence fails with `ErrorKind::LimitExceeded` if any of its indexes is
+    /// larger, so filling the gaps can't allocate more items than this.
+    pub fn max_index(mut self, max_index: usize) -> Self {
+        self.max_index = max_index;
+        self
+    }
+
+    /// Give self describing types the values which look like booleans(`true`, `false`) and
+    /// numbers as such, instead of strings. It is disabled by default.
+    ///
+    /// Serde buffers the values of internally tagged(`#[serde(tag = "type")]`), adjacently
+    /// tagged and untagged enums as self describing values, so their number and boolean fields
+    /// need it. String fields of those enums can't get such values anymore, and it doesn't
+    /// apply to `Value` or the keys.
+    pub fn infer_types(mut self, infer_types: bool) -> Self {
+        self.infer_types = infer_types;
+        self
+    }
+
+    /// Sets the delimiter of the `Delimiter` and `BracketsDelimiter` modes, instead of the byte
+    /// of the mode, so non ASCII(`、`) and multi byte(`::`) delimiters can be used.
+    ///
+    /// The delimiter is matched with the raw values, before they are percent decoded. An empty
+    /// delimiter is ignored, and the serializer still uses the byte of the mode.
+    pub fn delimiter(mut self, delimiter: &'static str) -> Self {
+        self.delimiter = if delimiter.is_empty() {
+            None
+        } else {
+            Some(delimiter)
+        };
+        self
+    }
+
+    /// Percent decode the values of the `Delimiter` and `BracketsDelimiter` modes before they are
+    /// split, so encoded delimiters(`a%2Cb,c` is `[a, b, c]`) split the values too.
+    ///
+    /// The values are split before they are decoded by default, which keeps the encoded
+    /// delimiters inside the values(`a%2Cb,c` is `["a,b", "c"]`). Clients usually percent encode
+    /// non ASCII delimiters, so they need it.
+    pub fn decode_before_split(mut self, decode_before_split: bool) -> Self {
+        self.decode_before_split = decode_before_split;
+        self
+    }
+
+    /// Sets a byte escaping the delimiter(`a\,b,c` is `["a,b", "c"]`) in the `Delimiter` and
+    /// `BracketsDelimiter` modes, there is no escape by default.
+    ///
+    /// The escape is removed from the values before the delimiters and before itself(`\\`),
+    /// and is kept as it is anywhere else.
+    pub fn delimiter_escape(mut self, escape: u8) -> Self {
+        self.delimiter_escape = Some(escape);
+        self
+    }
+
+    /// Deserialize a single empty value(`tags=` or `tags`) into an empty sequence, instead of a
+    /// sequence with an empty item. It is disabled by default, except for the `Delimiter` mode
+    /// which always does so.
+    ///
+    /// It doesn't apply to tuples or to `Value`.
+    pub fn empty_as_empty_seq(mut self, empty_as_empty_seq: bool) -> Self {
+        self.empty_as_empty_seq = empty_as_empty_seq;
+        self
+    }
+
+    /// Returns the parse mode
+    pub fn mode(&self) -> ParseMode {
+        self.mode
+    }
+}
+
+impl From<ParseMode> for QsConfig {
+    fn from(mode: ParseMode) -> Self {
+        Self::new(mode)
+    }
+}
+
+/// Builds a [`QsConfig`] option by option
+///
+/// It is created by [`QsConfig::builder`], or from an existing config to change some of its options.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{ParseMode, QsConfig, QsConfigBuilder};
+///
+/// let config = QsConfig::builder().mode(ParseMode::Brackets).plus_as_space(false).build();
+///
+/// let config = QsConfigBuilder::from(config).mode(ParseMode::DotNotation).build();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct QsConfigBuilder {
+    config: QsConfig,
+}
+
+impl QsConfigBuilder {
+    /// Sets the parse mode, defaults to `UrlEncoded`
+    ///
+    /// The `Matrix` mode also sets the pair separator to `;`.
+    pub fn mode(mut self, mode: ParseMode) -> Self {
+        if let ParseMode::Matrix = mode {
+            self.config.pair_separator = PairSeparator::Semicolon;
+        }
+        self.config.mode = mode;
+        self
+    }
+
+    /// Sets the bytes separating the pairs, defaults to `&`(`;` in `Matrix` mode)
+    pub fn pair_separator(mut self, pair_separator: PairSeparator) -> Self {
+        self.config = self.config.pair_separator(pair_separator);
+        self
+    }
+
+    /// Decode `+` as a space, or keep it as is. See [`QsConfig::plus_as_space`]
+    pub fn plus_as_space(mut self, plus_as_space: bool) -> Self {
+        self.config = self.config.plus_as_space(plus_as_space);
+        self
+    }
+
+    /// Decode the legacy `%uXXXX` escapes. See [`QsConfig::unicode_escapes`]
+    pub fn unicode_escapes(mut self, unicode_escapes: bool) -> Self {
+        self.config = self.config.unicode_escapes(unicode_escapes);
+        self
+    }
+
+    /// Sets what to do with invalid utf-8. See [`QsConfig::invalid_utf8`]
+    pub fn invalid_utf8(mut self, invalid_utf8: InvalidUtf8) -> Self {
+        self.config = self.config.invalid_utf8(invalid_utf8);
+        self
+    }
+
+    /// Sets the maximum nesting of keys. See [`QsConfig::max_depth`]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.config = self.config.max_depth(max_depth);
+        self
+    }
+
+    /// Sets the maximum number of pairs. See [`QsConfig::max_pairs`]
+    pub fn max_pairs(mut self, max_pairs: usize) -> Self {
+        self.config = self.config.max_pairs(max_pairs);
+        self
+    }
+
+    /// Sets the maximum length of keys and values. See [`QsConfig::max_length`]
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.config = self.config.max_length(max_length);
+        self
+    }
+
+    /// Drop the pairs which can't be parsed. See [`QsConfig::skip_invalid_pairs`]
+    pub fn skip_invalid_pairs(mut self, skip_invalid_pairs: bool) -> Self {
+        self.config = self.config.skip_invalid_pairs(skip_invalid_pairs);
+        self
+    }
+
+    /// Sets the policy for repeated keys. See [`QsConfig::duplicate_keys`]
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.config = self.config.duplicate_keys(duplicate_keys);
+        self
+    }
+
+    /// Fail on unknown keys. See [`QsConfig::strict_keys`]
+    pub fn strict_keys(mut self, strict_keys: bool) -> Self {
+        self.config = self.config.strict_keys(strict_keys);
+        self
+    }
+
+    /// Deserialize present keys as `Some`. See [`QsConfig::present_as_some`]
+    pub fn present_as_some(mut self, present_as_some: bool) -> Self {
+        self.config = self.config.present_as_some(present_as_some);
+        self
+    }
+
+    /// Accept more booleans. See [`QsConfig::lenient_bool`]
+    pub fn lenient_bool(mut self, lenient_bool: bool) -> Self {
+        self.config = self.config.lenient_bool(lenient_bool);
+        self
+    }
+
+    /// Accept more forms of numbers. See [`QsConfig::lenient_numbers`]
+    pub fn lenient_numbers(mut self, lenient_numbers: bool) -> Self {
+        self.config = self.config.lenient_numbers(lenient_numbers);
+        self
+    }
+
+    /// Trim the whitespace around keys and values. See [`QsConfig::trim_whitespace`]
+    pub fn trim_whitespace(mut self, trim_whitespace: bool) -> Self {
+        self.config = self.config.trim_whitespace(trim_whitespace);
+        self
+    }
+
+    /// Match the enum variants in any case. See [`QsConfig::case_insensitive_enums`]
+    pub fn case_insensitive_enums(mut self, case_insensitive_enums: bool) -> Self {
+        self.config = self.config.case_insensitive_enums(case_insensitive_enums);
+        self
+    }
+
+    /// Match the enum variants by index. See [`QsConfig::numeric_enums`]
+    pub fn numeric_enums(mut self, numeric_enums: bool) -> Self {
+        self.config = self.config.numeric_enums(numeric_enums);
+        self
+    }
+
+    /// Deserialize null values as `None`. See [`QsConfig::null_as_none`]
+    pub fn null_as_none(mut self, null_as_none: bool) -> Self {
+        self.config = self.config.null_as_none(null_as_none);
+        self
+    }
+
+    /// Normalize the keys. See [`QsConfig::key_transform`]
+    pub fn key_transform(mut self, key_transform: KeyTransform) -> Self {
+        self.config = self.config.key_transform(key_transform);
+        self
+    }
+
+    /// Sets how empty brackets are ordered. See [`QsConfig::empty_brackets`]
+    pub fn empty_brackets(mut self, empty_brackets: EmptyBrackets) -> Self {
+        self.config = self.config.empty_brackets(empty_brackets);
+        self
+    }
+
+    /// Sets how conflicting keys are resolved. See [`QsConfig::key_conflict`]
+    pub fn key_conflict(mut self, key_conflict: KeyConflict) -> Self {
+        self.config = self.config.key_conflict(key_conflict);
+        self
+    }
+
+    /// Sets how missing indexes are handled. See [`QsConfig::sparse_indexes`]
+    pub fn sparse_indexes(mut self, sparse_indexes: SparseIndexes) -> Self {
+        self.config = self.config.sparse_indexes(sparse_indexes);
+        self
+    }
+
+    /// Sets the largest index of a sequence. See [`QsConfig::max_index`]
+    pub fn max_index(mut self, max_index: usize) -> Self {
+        self.config = self.config.max_index(max_index);
+        self
+    }
+
+    /// Infer the types of the values. See [`QsConfig::infer_types`]
+    pub fn infer_types(mut self, infer_types: bool) -> Self {
+        self.config = self.config.infer_types(infer_types);
+        self
+    }
+
+    /// Sets a delimiter of any length. See [`QsConfig::delimiter`]
+    pub fn delimiter(mut self, delimiter: &'static str) -> Self {
+        self.config = self.config.delimiter(delimiter);
+        self
+    }
+
+    /// Split the values once decoded. See [`QsConfig::decode_before_split`]
+    pub fn decode_before_split(mut self, decode_before_split: bool) -> Self {
+        self.config = self.config.decode_before_split(decode_before_split);
+        self
+    }
+
+    /// Sets a byte escaping the delimiter. See [`QsConfig::delimiter_escape`]
+    pub fn delimiter_escape(mut self, escape: u8) -> Self {
+        self.config = self.config.delimiter_escape(escape);
+        self
+    }
+
+    /// Deserialize empty values as empty sequences. See [`QsConfig::empty_as_empty_seq`]
+    pub fn empty_as_empty_seq(mut self, empty_as_empty_seq: bool) -> Self {
+        self.config = self.config.empty_as_empty_seq(empty_as_empty_seq);
+        self
+    }
+
+    /// Returns the config
+    pub fn build(self) -> QsConfig {
+        self.config
+    }
+}
+
+impl Default for QsConfigBuilder {
+    fn default() -> Self {
+        Self {
+            config: QsConfig::new(ParseMode::UrlEncoded),
+        }
+    }
+}
+
+impl From<QsConfig> for QsConfigBuilder {
+    fn from(config: QsConfig) -> Self {
+        Self { config }
+    }
+}
+
+/// The parse options of the [`qs`](https://www.npmjs.com/package/qs) package of JavaScript,
+/// mapped onto a [`QsConfig`] in the brackets modes
+///
+/// The defaults are the same as `qs`, and it can be used in place of a `QsConfig`.
+///
+/// - `allowDots` is the `DotNotation` mode, and `comma` is `BracketsDelimiter(b',')`. They
+///   can't be used together, `allowDots` is used then.
+/// - `depth` is [`QsConfig::max_depth`], `arrayLimit` is [`QsConfig::max_index`] and
+///   `parameterLimit` is [`QsConfig::max_pairs`].
+///
+/// # Note
+/// `qs` silently handles the query strings above its limits, while deserializing fails with
+/// `ErrorKind::DepthLimitExceeded` or `ErrorKind::LimitExceeded` here. The extra pairs are
+/// ignored by `qs`, deeper keys are kept as a single sub key(`[f][g]`), and sequences with
+/// larger indexes are objects.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_str, JsQsOptions};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     ids: Vec<u32>,
+/// }
+///
+/// let options = JsQsOptions::default().comma(true).parameter_limit(100);
+/// let query: Query = from_str("ids=1,2&ids[]=3", options).unwrap();
+///
+/// assert_eq!(query.ids, vec![1, 2, 3]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JsQsOptions {
+    allow_dots: bool,
+    comma: bool,
+    array_limit: usize,
+    parameter_limit: usize,
+    depth: usize,
+}
+
+impl JsQsOptions {
+    /// Parse dots as sub keys(`a.b=c`), the `allowDots` option. It is disabled by default.
+    pub fn allow_dots(mut self, allow_dots: bool) -> Self {
+        self.allow_dots = allow_dots;
+        self
+    }
+
+    /// Split the values by commas(`a=b,c`), the `comma` option. It is disabled by default.
+    pub fn comma(mut self, comma: bool) -> Self {
+        self.comma = comma;
+        self
+    }
+
+    /// Sets the largest index of a sequence, the `arrayLimit` option. It defaults to 20.
+    pub fn array_limit(mut self, array_limit: usize) -> Self {
+        self.array_limit = array_limit;
+        self
+    }
+
+    /// Sets the maximum number of pairs, the `parameterLimit` option. It defaults to 1000.
+    pub fn parameter_limit(mut self, parameter_limit: usize) -> Self {
+        self.parameter_limit = parameter_limit;
+        self
+    }
+
+    /// Sets the maximum number of sub keys in a key, the `depth` option. It defaults to 5.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Returns the config with the same options
+    pub fn config(self) -> QsConfig {
+        let mode = if self.allow_dots {
+            ParseMode::DotNotation
+        } else if self.comma {
+            ParseMode::BracketsDelimiter(b',')
+        } else {
+            ParseMode::Brackets
+        };
+
+        QsConfig::new(mode)
+            .max_depth(self.depth)
+            .max_index(self.array_limit)
+            .max_pairs(self.parameter_limit)
+    }
+}
+
+impl Default for JsQsOptions {
+    fn default() -> Self {
+        Self {
+            allow_dots: false,
+            comma: false,
+            array_limit: 20,
+            parameter_limit: 1000,
+            depth: 5,
+        }
+    }
+}
+
+impl From<JsQsOptions> for QsConfig {
+    fn from(options: JsQsOptions) -> Self {
+        options.config()
+    }
+}
@@ -0,0 +1,118 @@
+use alloc::borrow::{Borrow, Cow};
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::ops::Deref;
+
+use _serde::{de, ser};
+
+/// A string borrowed from the input when it needs no percent decoding, and owned otherwise
+///
+/// Serde deserializes `Cow<str>` as owned, unless it is the direct field of a struct with
+/// `#[serde(borrow)]`. `CowStr` borrows anywhere, like the keys and values of maps, the items of
+/// sequences or an `Option`.
+///
+/// # Example
+/// ```rust
+/// use std::borrow::Cow;
+/// use std::collections::HashMap;
+///
+/// use serde_querystring::{from_str, CowStr, ParseMode};
+///
+/// let map: HashMap<CowStr, CowStr> = from_str("q=rust&lang=en+us", ParseMode::UrlEncoded).unwrap();
+///
+/// assert!(matches!(map["q"].0, Cow::Borrowed("rust")));
+/// assert!(matches!(map["lang"].0, Cow::Owned(_)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CowStr<'a>(pub Cow<'a, str>);
+
+impl<'a> CowStr<'a> {
+    /// Returns the wrapped value
+    pub fn into_inner(self) -> Cow<'a, str> {
+        self.0
+    }
+
+    /// Returns true if the string is borrowed from the input
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self.0, Cow::Borrowed(_))
+    }
+}
+
+impl<'a> From<&'a str> for CowStr<'a> {
+    fn from(value: &'a str) -> Self {
+        CowStr(Cow::Borrowed(value))
+    }
+}
+
+impl From<String> for CowStr<'_> {
+    fn from(value: String) -> Self {
+        CowStr(Cow::Owned(value))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for CowStr<'a> {
+    fn from(value: Cow<'a, str>) -> Self {
+        CowStr(value)
+    }
+}
+
+impl Deref for CowStr<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// So maps with `CowStr` keys can be indexed by `&str`
+impl Borrow<str> for CowStr<'_> {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CowStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl ser::Serialize for CowStr<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+struct CowStrVisitor;
+
+impl<'de> de::Visitor<'de> for CowStrVisitor {
+    type Value = CowStr<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(CowStr(Cow::Borrowed(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(CowStr(Cow::Owned(v.to_string())))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(CowStr(Cow::Owned(v)))
+    }
+}
+
+impl<'de: 'a, 'a> de::Deserialize<'de> for CowStr<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(CowStrVisitor)
+    }
+}
@@ -0,0 +1,384 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+use _serde::{de, ser};
+
+/// A date and time type the wrappers of this module can parse and write
+///
+/// It is implemented for `chrono::DateTime<Utc>` and `chrono::DateTime<FixedOffset>` with the
+/// `chrono` feature, and `time::OffsetDateTime` with the `time` feature.
+pub trait DateTimeValue: Sized {
+    /// Creates the value from milliseconds since the unix epoch
+    fn from_unix_millis(millis: i64) -> Option<Self>;
+
+    /// Returns the milliseconds since the unix epoch
+    fn to_unix_millis(&self) -> i64;
+
+    /// Parses a RFC 3339 date and time(`2024-01-02T03:04:05Z`)
+    fn parse_rfc3339(s: &str) -> Option<Self>;
+
+    /// Writes the value as a RFC 3339 date and time, `None` if it is out of the range of
+    /// RFC 3339(like a negative year)
+    fn to_rfc3339(&self) -> Option<String>;
+
+    /// Creates the value from a date, at midnight UTC
+    fn from_date(year: i32, month: u8, day: u8) -> Option<Self>;
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use _chrono::{DateTime, FixedOffset, NaiveDate, SecondsFormat, Utc};
+
+    use super::DateTimeValue;
+
+    impl DateTimeValue for DateTime<Utc> {
+        fn from_unix_millis(millis: i64) -> Option<Self> {
+            DateTime::from_timestamp_millis(millis)
+        }
+
+        fn to_unix_millis(&self) -> i64 {
+            self.timestamp_millis()
+        }
+
+        fn parse_rfc3339(s: &str) -> Option<Self> {
+            DateTime::parse_from_rfc3339(s)
+                .ok()
+                .map(|dt| dt.with_timezone(&Utc))
+        }
+
+        fn to_rfc3339(&self) -> Option<String> {
+            Some(self.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+        }
+
+        fn from_date(year: i32, month: u8, day: u8) -> Option<Self> {
+            NaiveDate::from_ymd_opt(year, month.into(), day.into())?
+                .and_hms_opt(0, 0, 0)
+                .map(|dt| dt.and_utc())
+        }
+    }
+
+    impl DateTimeValue for DateTime<FixedOffset> {
+        fn from_unix_millis(millis: i64) -> Option<Self> {
+            DateTime::<Utc>::from_unix_millis(millis).map(|dt| dt.fixed_offset())
+        }
+
+        fn to_unix_millis(&self) -> i64 {
+            self.timestamp_millis()
+        }
+
+        fn parse_rfc3339(s: &str) -> Option<Self> {
+            DateTime::parse_from_rfc3339(s).ok()
+        }
+
+        fn to_rfc3339(&self) -> Option<String> {
+            Some(self.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+        }
+
+        fn from_date(year: i32, month: u8, day: u8) -> Option<Self> {
+            DateTime::<Utc>::from_date(year, month, day).map(|dt| dt.fixed_offset())
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_impls {
+    use _time::format_description::well_known::Rfc3339;
+    use _time::{Date, Month, OffsetDateTime};
+
+    use super::DateTimeValue;
+
+    impl DateTimeValue for OffsetDateTime {
+        fn from_unix_millis(millis: i64) -> Option<Self> {
+            OffsetDateTime::from_unix_timestamp_nanos(i128::from(millis) * 1_000_000).ok()
+        }
+
+        fn to_unix_millis(&self) -> i64 {
+            self.unix_timestamp_nanos().div_euclid(1_000_000) as i64
+        }
+
+        fn parse_rfc3339(s: &str) -> Option<Self> {
+            OffsetDateTime::parse(s, &Rfc3339).ok()
+        }
+
+        fn to_rfc3339(&self) -> Option<String> {
+            self.format(&Rfc3339).ok()
+        }
+
+        fn from_date(year: i32, month: u8, day: u8) -> Option<Self> {
+            let month = Month::try_from(month).ok()?;
+            Date::from_calendar_date(year, month, day)
+                .ok()
+                .map(|date| date.midnight().assume_utc())
+        }
+    }
+}
+
+/// Parses a date(`2024-01-02`)
+fn parse_date<T: DateTimeValue>(s: &str) -> Option<T> {
+    let bytes = s.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    T::from_date(
+        s[..4].parse().ok()?,
+        s[5..7].parse().ok()?,
+        s[8..].parse().ok()?,
+    )
+}
+
+/// Parses milliseconds since the unix epoch, as digits with an optional sign
+fn parse_millis<T: DateTimeValue>(s: &str) -> Option<T> {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    T::from_unix_millis(s.parse().ok()?)
+}
+
+/// Parses a RFC 3339 date and time, or one without an offset(`2024-01-02T03:04:05`) as UTC.
+/// A space can separate the date and the time too.
+fn parse_lenient_rfc3339<T: DateTimeValue>(s: &str) -> Option<T> {
+    let s = match s.as_bytes().get(10) {
+        Some(b' ') => format!("{}T{}", &s[..10], &s[11..]),
+        _ => s.to_string(),
+    };
+    T::parse_rfc3339(&s).or_else(|| T::parse_rfc3339(&format!("{}Z", s)))
+}
+
+/// Which of the formats a wrapper reads
+#[derive(Clone, Copy)]
+enum Format {
+    UnixSeconds,
+    UnixMillis,
+    Rfc3339,
+    Lenient,
+}
+
+impl Format {
+    fn expecting(self) -> &'static str {
+        match self {
+            Format::UnixSeconds => "seconds since the unix epoch",
+            Format::UnixMillis => "milliseconds since the unix epoch",
+            Format::Rfc3339 => "a RFC 3339 date and time",
+            Format::Lenient => {
+                "a date, a RFC 3339 date and time or milliseconds since the unix epoch"
+            }
+        }
+    }
+
+    fn with_millis<T: DateTimeValue>(self, millis: i64) -> Option<T> {
+        match self {
+            Format::UnixSeconds => T::from_unix_millis(millis.checked_mul(1000)?),
+            _ => T::from_unix_millis(millis),
+        }
+    }
+
+    fn parse<T: DateTimeValue>(self, s: &str) -> Option<T> {
+        match self {
+            Format::UnixSeconds | Format::UnixMillis => self.with_millis(s.parse().ok()?),
+            Format::Rfc3339 => T::parse_rfc3339(s),
+            Format::Lenient => parse_millis(s)
+                .or_else(|| parse_date(s))
+                .or_else(|| parse_lenient_rfc3339(s)),
+        }
+    }
+}
+
+struct DateTimeVisitor<T> {
+    format: Format,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T> de::Visitor<'de> for DateTimeVisitor<T>
+where
+    T: DateTimeValue,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.format.expecting())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        match self.format {
+            Format::Rfc3339 => Err(E::invalid_type(de::Unexpected::Signed(v), &self)),
+            format => format
+                .with_millis(v)
+                .ok_or_else(|| E::invalid_value(de::Unexpected::Signed(v), &self)),
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        match i64::try_from(v) {
+            Ok(v) => self.visit_i64(v),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.format
+            .parse(v)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+macro_rules! datetime {
+    ($(#[$doc:meta])* $name:ident, $format:expr, $serialize:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name<T>(pub T);
+
+        impl<T> $name<T> {
+            /// Returns the wrapped value
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+        }
+
+        impl<T> From<T> for $name<T> {
+            fn from(value: T) -> Self {
+                $name(value)
+            }
+        }
+
+        impl<T> Deref for $name<T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $name<T> {
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+
+        impl<T> ser::Serialize for $name<T>
+        where
+            T: DateTimeValue,
+        {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ser::Serializer,
+            {
+                let serialize: fn(&T, S) -> Result<S::Ok, S::Error> = $serialize;
+                serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de, T> de::Deserialize<'de> for $name<T>
+        where
+            T: DateTimeValue,
+        {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                let visitor = DateTimeVisitor {
+                    format: $format,
+                    marker: PhantomData,
+                };
+                match $format {
+                    Format::UnixSeconds | Format::UnixMillis => deserializer.deserialize_i64(visitor),
+                    _ => deserializer.deserialize_str(visitor),
+                }
+                .map($name)
+            }
+        }
+    };
+}
+
+fn serialize_rfc3339<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: DateTimeValue,
+    S: ser::Serializer,
+{
+    match value.to_rfc3339() {
+        Some(value) => serializer.serialize_str(&value),
+        None => Err(ser::Error::custom(
+            "the date and time is out of the range of RFC 3339",
+        )),
+    }
+}
+
+datetime!(
+    /// A date and time written as seconds since the unix epoch(`since=1704164645`)
+    ///
+    /// The milliseconds are truncated when it is serialized.
+    ///
+    /// # Example
+    #[cfg_attr(feature = "chrono", doc = "```rust")]
+    #[cfg_attr(not(feature = "chrono"), doc = "```rust,ignore")]
+    ///# use _serde::Deserialize;
+    ///# use _chrono as chrono;
+    /// use chrono::{DateTime, Utc};
+    /// use serde_querystring::{from_str, ParseMode, UnixSeconds};
+    ///
+    /// #[derive(Deserialize)]
+    ///# #[serde(crate = "_serde")]
+    /// struct Query {
+    ///     since: UnixSeconds<DateTime<Utc>>,
+    /// }
+    ///
+    /// let query: Query = from_str("since=1704164645", ParseMode::UrlEncoded).unwrap();
+    ///
+    /// assert_eq!(query.since.to_rfc3339(), "2024-01-02T03:04:05+00:00");
+    /// ```
+    UnixSeconds,
+    Format::UnixSeconds,
+    |value, serializer| serializer.serialize_i64(value.to_unix_millis().div_euclid(1000))
+);
+
+datetime!(
+    /// A date and time written as milliseconds since the unix epoch(`since=1704164645000`)
+    UnixMillis,
+    Format::UnixMillis,
+    |value, serializer| serializer.serialize_i64(value.to_unix_millis())
+);
+
+datetime!(
+    /// A date and time written as RFC 3339(`since=2024-01-02T03:04:05Z`)
+    ///
+    /// The offset is required, `+` of a positive offset must be percent encoded(`%2B02:00`) as
+    /// it is decoded as a space otherwise.
+    Rfc3339,
+    Format::Rfc3339,
+    serialize_rfc3339
+);
+
+datetime!(
+    /// A date and time written in any of the usual formats of date range parameters
+    ///
+    /// - A date(`2024-01-02`), at midnight UTC
+    /// - A RFC 3339 date and time(`2024-01-02T03:04:05Z`), where the offset is optional and
+    ///   defaults to UTC, and the `T` can be a space
+    /// - Milliseconds since the unix epoch(`1704164645000`)
+    ///
+    /// It is serialized as RFC 3339.
+    ///
+    /// # Example
+    #[cfg_attr(feature = "time", doc = "```rust")]
+    #[cfg_attr(not(feature = "time"), doc = "```rust,ignore")]
+    ///# use _serde::Deserialize;
+    ///# use _time as time;
+    /// use serde_querystring::{from_str, LenientDateTime, ParseMode};
+    /// use time::OffsetDateTime;
+    ///
+    /// #[derive(Deserialize)]
+    ///# #[serde(crate = "_serde")]
+    /// struct Query {
+    ///     from: LenientDateTime<OffsetDateTime>,
+    ///     to: LenientDateTime<OffsetDateTime>,
+    /// }
+    ///
+    /// let query: Query = from_str("from=2024-01-02&to=1704164645000", ParseMode::UrlEncoded).unwrap();
+    ///
+    /// assert_eq!(query.to.unix_timestamp() - query.from.unix_timestamp(), 3 * 3600 + 4 * 60 + 5);
+    /// ```
+    LenientDateTime,
+    Format::Lenient,
+    serialize_rfc3339
+);
@@ -1,24 +1,75 @@
-use std::fmt;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::ops::Range;
 
-#[derive(Debug, Eq, PartialEq)]
+/// The kind of an error, to tell apart the invalid values from the query strings going over
+/// the limits of the config
+///
+/// More kinds may be added, so matching on it needs a wildcard arm.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
 pub enum ErrorKind {
     InvalidType,
     InvalidLength,
     InvalidEncoding,
     InvalidNumber,
     InvalidBoolean,
+    /// A `%u` escape is not followed by 4 hex digits, or is a lone surrogate, with unicode
+    /// escapes decoded
+    InvalidUnicodeEscape,
+    /// A key is nested deeper than the max depth of the config
+    DepthLimitExceeded,
+    /// There are more pairs than the max pairs of the config
+    TooManyParams,
+    /// The keys or values are longer, or the indexes of a sequence larger than the limits of
+    /// the config
+    LimitExceeded,
+    /// A key is repeated, but a single value is expected
+    DuplicateKey,
+    /// A key is not used by the deserialized type, with strict keys
+    UnknownKey,
+    /// An index of a sequence conflicts with the other indexes in the brackets modes
+    InvalidIndex,
+    /// A key has both a value and sub keys in the brackets modes, with `KeyConflict::Error`,
+    /// or the types of a key don't match in the `Rack` mode
+    KeyConflict,
+    /// The signature of a signed query string is missing or doesn't match, with the `signing`
+    /// feature
+    InvalidSignature,
+    /// The signature of a signed query string has expired, with the `signing` feature
+    SignatureExpired,
     Other,
 }
 
 #[derive(Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub struct Error {
     pub kind: ErrorKind,
     pub message: String,
 
+    // The path of the key of the value causing the error, like `filter[age][gte]`
+    pub key: String,
     // The slice causing the error
     pub value: String,
     // Index of the byte in the value slice, causing the error
     pub index: Option<usize>,
+    // The spans of the key and the value, boxed to keep the error small
+    spans: Option<Box<Spans>>,
+}
+
+/// The byte ranges of the key and the value causing an error, in the input
+#[derive(Debug, Default, Eq, PartialEq)]
+struct Spans {
+    key: Option<Range<usize>>,
+    value: Option<Range<usize>>,
+}
+
+/// The range of the addresses of a slice, made relative to the input once the error is returned
+pub(crate) fn address_range(slice: &[u8]) -> Range<usize> {
+    let start = slice.as_ptr() as usize;
+    start..start + slice.len()
 }
 
 impl Error {
@@ -26,8 +77,10 @@ impl Error {
         Error {
             kind,
             message: String::new(),
+            key: String::new(),
             value: String::new(),
             index: None,
+            spans: None,
         }
     }
 
@@ -45,6 +98,130 @@ impl Error {
         self.index = Some(index);
         self
     }
+
+    /// Returns the path of the key causing the error, with its keys and the indexes of its
+    /// sequences separated by dots(`filter.range.max` for `filter[range][max]`)
+    ///
+    /// # Example
+    /// ```rust
+    ///# use _serde::Deserialize;
+    /// use serde_querystring::{from_str, ParseMode};
+    ///
+    /// #[derive(Debug, Deserialize)]
+    ///# #[serde(crate = "_serde")]
+    /// struct Range {
+    ///     max: u32,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize)]
+    ///# #[serde(crate = "_serde")]
+    /// struct Filter {
+    ///     range: Range,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize)]
+    ///# #[serde(crate = "_serde")]
+    /// struct Query {
+    ///     filter: Filter,
+    /// }
+    ///
+    /// let error = from_str::<Query, _>("filter[range][max]=1x", ParseMode::Brackets).unwrap_err();
+    ///
+    /// assert_eq!(error.path(), "filter.range.max");
+    /// assert_eq!(error.value, "1x");
+    /// ```
+    pub fn path(&self) -> String {
+        let mut path = String::with_capacity(self.key.len());
+        for (index, segment) in self.key.split('[').enumerate() {
+            if index > 0 {
+                path.push('.');
+            }
+            path.push_str(segment.strip_suffix(']').unwrap_or(segment));
+        }
+        path
+    }
+
+    /// Returns the byte range of the key causing the error in the input, from its first parent
+    /// key in the brackets modes(`filter[age][gte]`)
+    pub fn key_span(&self) -> Option<Range<usize>> {
+        self.spans.as_ref()?.key.clone()
+    }
+
+    /// Returns the byte range of the value causing the error in the input
+    pub fn value_span(&self) -> Option<Range<usize>> {
+        self.spans.as_ref()?.value.clone()
+    }
+
+    /// Returns the byte offset of the error in the input, in the value if it is known or at the
+    /// start of the key otherwise
+    pub fn position(&self) -> Option<usize> {
+        match (self.value_span(), self.key_span()) {
+            (Some(value), _) => Some(value.start + self.index.unwrap_or(0)),
+            (None, Some(key)) => Some(key.start),
+            (None, None) => None,
+        }
+    }
+
+    fn spans_mut(&mut self) -> &mut Spans {
+        self.spans.get_or_insert_with(Default::default)
+    }
+
+    /// Sets the value and its span to the raw slice if the error has none yet
+    pub(crate) fn or_value(mut self, slice: Option<&[u8]>) -> Self {
+        let slice = match slice {
+            Some(slice) => slice,
+            None => return self,
+        };
+        let spans = self.spans_mut();
+        if spans.value.is_none() {
+            spans.value = Some(address_range(slice));
+        }
+        if self.value.is_empty() {
+            self = self.value(slice);
+        }
+        self
+    }
+
+    /// Extends the span of the key to a parent key
+    pub(crate) fn parent_key_span(mut self, span: Option<Range<usize>>) -> Self {
+        let span = match span {
+            Some(span) => span,
+            None => return self,
+        };
+        let spans = self.spans_mut();
+        spans.key = Some(match spans.key.take() {
+            Some(inner) => inner.start.min(span.start)..inner.end.max(span.end),
+            None => span,
+        });
+        self
+    }
+
+    /// Removes the spans, when the input is not the one given by the user
+    pub(crate) fn clear_spans(&mut self) {
+        self.spans = None;
+    }
+
+    /// Makes the spans relative to the input, the spans of slices which are not part of the
+    /// input are removed
+    pub(crate) fn relative_to(mut self, input: &[u8]) -> Self {
+        let bytes = input;
+        let input = address_range(input);
+        let relative = |span: Option<Range<usize>>| {
+            span.filter(|span| input.start <= span.start && span.end <= input.end)
+                .map(|span| span.start - input.start..span.end - input.start)
+        };
+        if let Some(spans) = &mut self.spans {
+            spans.key = relative(spans.key.take()).map(|mut span| {
+                // The closing bracket of the last sub key is part of the key
+                if bytes.get(span.end) == Some(&b']') {
+                    span.end += 1;
+                }
+                span
+            });
+            spans.value = relative(spans.value.take());
+        }
+        self
+    }
 }
 
 impl _serde::de::Error for Error {
@@ -59,15 +236,150 @@ impl _serde::de::Error for Error {
         Error::new(ErrorKind::InvalidType)
             .message(format_args!("invalid type: {}, expected {}", unexp, exp).to_string())
     }
+
+    /// The missing field is the key of the error, under the keys of its parents
+    fn missing_field(field: &'static str) -> Self {
+        let mut error = Error::new(ErrorKind::Other).message(format!("missing field `{}`", field));
+        error.key = field.to_string();
+        error
+    }
+}
+
+impl ErrorKind {
+    fn name(self) -> &'static str {
+        match self {
+            ErrorKind::InvalidType => "InvalidType",
+            ErrorKind::InvalidLength => "InvalidLength",
+            ErrorKind::InvalidEncoding => "InvalidEncoding",
+            ErrorKind::InvalidNumber => "InvalidNumber",
+            ErrorKind::InvalidBoolean => "InvalidBoolean",
+            ErrorKind::InvalidUnicodeEscape => "InvalidUnicodeEscape",
+            ErrorKind::DepthLimitExceeded => "DepthLimitExceeded",
+            ErrorKind::TooManyParams => "TooManyParams",
+            ErrorKind::LimitExceeded => "LimitExceeded",
+            ErrorKind::DuplicateKey => "DuplicateKey",
+            ErrorKind::UnknownKey => "UnknownKey",
+            ErrorKind::InvalidIndex => "InvalidIndex",
+            ErrorKind::KeyConflict => "KeyConflict",
+            ErrorKind::InvalidSignature => "InvalidSignature",
+            ErrorKind::SignatureExpired => "SignatureExpired",
+            ErrorKind::Other => "Other",
+        }
+    }
+}
+
+/// Serialized as the name of the kind(`"InvalidNumber"`)
+impl _serde::Serialize for ErrorKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: _serde::Serializer,
+    {
+        serializer.serialize_unit_variant("ErrorKind", *self as u32, self.name())
+    }
 }
 
-impl std::error::Error for Error {}
+/// Serialized as a struct of its `kind`, `key`, `message` and `position`, the byte offset of
+/// the error in the input or `None` when it is unknown. The value is left out, as it is part of
+/// the input sent by the client.
+///
+/// # Example
+#[cfg_attr(feature = "json", doc = "```rust")]
+#[cfg_attr(not(feature = "json"), doc = "```rust,ignore")]
+///# use _serde::Deserialize;
+///# use _serde_json as serde_json;
+/// use serde_querystring::{from_str, ParseMode};
+///
+/// #[derive(Debug, Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     page: u32,
+/// }
+///
+/// let error = from_str::<Query, _>("page=1x", ParseMode::UrlEncoded).unwrap_err();
+///
+/// assert_eq!(
+///     serde_json::to_string(&error).unwrap(),
+///     r#"{"kind":"InvalidNumber","key":"page","message":"lexical parse error: 'invalid digit found' at index 1","position":5}"#
+/// );
+/// ```
+impl _serde::Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: _serde::Serializer,
+    {
+        use _serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Error", 4)?;
+        state.serialize_field("kind", &self.kind)?;
+        state.serialize_field("key", &self.key)?;
+        state.serialize_field("message", &self.message)?;
+        state.serialize_field("position", &self.position())?;
+        state.end()
+    }
+}
+
+/// Labels the key and the value causing the error, the query string is given to the report
+/// with `miette::Report::with_source_code`. The code is the kind of the error.
+///
+/// # Example
+#[cfg_attr(feature = "miette", doc = "```rust")]
+#[cfg_attr(not(feature = "miette"), doc = "```rust,ignore")]
+///# use _serde::Deserialize;
+///# use _miette as miette;
+/// use serde_querystring::{from_str, ParseMode};
+///
+/// #[derive(Debug, Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     page: u32,
+/// }
+///
+/// let query = "q=rust&page=1x";
+/// let error = from_str::<Query, _>(query, ParseMode::UrlEncoded).unwrap_err();
+/// let report = miette::Report::new(error).with_source_code(query.to_string());
+///
+/// assert_eq!(report.code().unwrap().to_string(), "serde_querystring::InvalidNumber");
+/// ```
+#[cfg(feature = "miette")]
+impl _miette::Diagnostic for Error {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(format!("serde_querystring::{}", self.kind.name())))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = _miette::LabeledSpan> + '_>> {
+        let key = self
+            .key_span()
+            .map(|span| _miette::LabeledSpan::at(span, format!("in `{}`", self.key)));
+        let value = self.value_span().map(|span| {
+            _miette::LabeledSpan::new_primary_with_span(Some(self.message.clone()), span)
+        });
+        if key.is_none() && value.is_none() {
+            return None;
+        }
+        Some(Box::new(key.into_iter().chain(value)))
+    }
+}
+
+impl _serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Error::new(ErrorKind::Other).message(msg.to_string())
+    }
+}
+
+impl _serde::de::StdError for Error {}
 
 impl fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> fmt::Result {
         f.write_fmt(format_args!(
             "Error {:?}: {} in `{}`",
             self.kind, self.message, self.value
-        ))
+        ))?;
+        if !self.key.is_empty() {
+            f.write_fmt(format_args!(" of `{}`", self.key))?;
+        }
+        Ok(())
     }
 }
@@ -2,37 +2,205 @@ mod error;
 mod slices;
 mod traits;
 
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
 use _serde::{de, forward_to_deserialize_any};
 
 pub use error::{Error, ErrorKind};
 
+pub(crate) use error::address_range;
+
+pub use crate::config::{
+    DuplicateKeys, EmptyBrackets, InvalidUtf8, KeyConflict, ParseMode, QsConfig, QsConfigBuilder,
+    SparseIndexes,
+};
+
 pub(crate) mod __implementors {
-    pub(crate) use super::slices::{DecodedSlice, RawSlice};
+    pub(crate) use super::slices::{DecodedSlice, RawSlice, Scratch, Value};
     pub(crate) use super::traits::{IntoDeserializer, IntoRawSlices};
 }
 
-use crate::parsers::{BracketsQS, DelimiterQS, DuplicateQS, UrlEncodedQS};
+use crate::arena::Arena;
+use crate::config::PairSeparator;
+use crate::decode::{check_pair, parse_bytes, InvalidPair};
+use crate::pairs::{split_pair, split_pairs};
+#[cfg(feature = "brackets")]
+use crate::parsers::BracketsQS;
+#[cfg(feature = "delimiter")]
+use crate::parsers::DelimiterQS;
+#[cfg(feature = "duplicate")]
+use crate::parsers::DuplicateQS;
+#[cfg(feature = "urlencoded")]
+use crate::parsers::UrlEncodedQS;
+use crate::value::Value;
+use crate::{php, rack};
+
+use slices::{trim_whitespace, DecodedSlice, Scratch, Value as _};
+use traits::IntoDeserializer;
 
 pub(crate) struct QSDeserializer<I, T> {
     iter: I,
     value: Option<T>,
-    scratch: Vec<u8>,
+    // The key of the value, to report the key of the errors
+    key: Vec<u8>,
+    key_span: Option<Range<usize>>,
+    scratch: Scratch,
 }
 
 impl<I, T> QSDeserializer<I, T> {
-    pub fn new(iter: I) -> Self {
+    fn with_buffers(iter: I, scratch: Scratch, key: Vec<u8>) -> Self {
         Self {
             iter,
             value: None,
-            scratch: Vec::new(),
+            key,
+            key_span: None,
+            scratch,
         }
     }
+
+    fn into_buffers(self) -> QsBuffers {
+        QsBuffers {
+            scratch: self.scratch.into_buffer(),
+            key: self.key,
+        }
+    }
+
+    /// Deserializes `T` from the pairs, using the buffers and giving them back once done. The
+    /// decoded keys and values are moved into the arena if there is one.
+    pub(crate) fn deserialize<'de, V>(
+        iter: I,
+        config: &QsConfig,
+        buffers: &mut QsBuffers,
+        arena: Option<&'de Arena>,
+    ) -> Result<V, Error>
+    where
+        I: Iterator<Item = (DecodedSlice<'de>, T)>,
+        for<'s> T: __implementors::IntoDeserializer<'de, 's>,
+        V: de::Deserialize<'de>,
+    {
+        let buffers_scratch = core::mem::take(&mut buffers.scratch);
+        let scratch = Scratch::with_buffer(buffers_scratch, *config).arena(arena);
+        let mut deserializer = Self::with_buffers(iter, scratch, core::mem::take(&mut buffers.key));
+        let result = V::deserialize(&mut deserializer);
+        *buffers = deserializer.into_buffers();
+        result
+    }
+}
+
+/// The buffers the values and keys are decoded into while deserializing, which can be reused
+/// by many calls of [`from_bytes_with`]
+///
+/// They keep the capacity of the longest decoded value, so a worker handling many requests
+/// allocates them once instead of for every request. The maps of the parsers are still
+/// allocated by each call.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_str_with, ParseMode, QsBuffers};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Search {
+///     q: String,
+/// }
+///
+/// let mut buffers = QsBuffers::new();
+/// for query in ["q=rust+serde", "q=query%20string"] {
+///     let search: Search = from_str_with(&mut buffers, query, ParseMode::UrlEncoded).unwrap();
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct QsBuffers {
+    scratch: Vec<u8>,
+    key: Vec<u8>,
+}
+
+impl QsBuffers {
+    /// Creates empty buffers, they allocate once something is decoded into them
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates buffers with room for values and keys of the given length
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            scratch: Vec::with_capacity(capacity),
+            key: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Frees the memory of the buffers above the given capacity, after an unusually long query
+    /// string
+    pub fn shrink_to(&mut self, capacity: usize) {
+        self.scratch.shrink_to(capacity);
+        self.key.shrink_to(capacity);
+    }
+}
+
+/// Applies the key transform of the config to a decoded key, keys which are not valid utf-8
+/// are kept as they are. The whitespace around the key is trimmed first if the config asks for it.
+pub(crate) fn transform_key<'de>(key: DecodedSlice<'de>, config: &QsConfig) -> DecodedSlice<'de> {
+    let key = match key.0 {
+        Cow::Borrowed(b) if config.trim_whitespace => {
+            DecodedSlice(Cow::Borrowed(trim_whitespace(b)))
+        }
+        Cow::Owned(o) if config.trim_whitespace => {
+            DecodedSlice(Cow::Owned(trim_whitespace(&o).to_vec()))
+        }
+        key => DecodedSlice(key),
+    };
+
+    let transform = match config.key_transform {
+        Some(transform) => transform,
+        None => return key,
+    };
+
+    let transformed = match core::str::from_utf8(&key.0) {
+        Ok(k) => match transform(k) {
+            Cow::Borrowed(b) if b == k => return key,
+            Cow::Borrowed(b) => b.as_bytes().to_vec(),
+            Cow::Owned(o) => o.into_bytes(),
+        },
+        Err(_) => return key,
+    };
+    DecodedSlice(Cow::Owned(transformed))
+}
+
+/// The error returned when a value is ignored with strict keys, the key is added by the map
+/// holding the value
+pub(crate) fn unknown_key_error() -> Error {
+    Error::new(ErrorKind::UnknownKey)
+        .message(String::from("the key is not used by the deserialized type"))
 }
 
-impl<'de, I, E, A> de::Deserializer<'de> for QSDeserializer<I, A>
+/// The error returned instead of panicking when the deserializer is in an unexpected state, like
+/// a `Deserialize` implementation calling `next_value` before `next_key`
+pub(crate) fn invalid_state_error(message: &str) -> Error {
+    Error::new(ErrorKind::Other).message(String::from(message))
+}
+
+/// Adds the key to the path of the key of an error, so nested keys are reported as `key[sub]`.
+/// The span of the key is extended to the key too, if it is part of the input.
+pub(crate) fn with_key(mut error: Error, key: &[u8], span: Option<Range<usize>>) -> Error {
+    error = error.parent_key_span(span);
+
+    let key = String::from_utf8_lossy(key);
+    error.key = match error.key.find('[') {
+        _ if error.key.is_empty() => key.into_owned(),
+        Some(index) => format!("{}[{}]{}", key, &error.key[..index], &error.key[index..]),
+        None => format!("{}[{}]", key, error.key),
+    };
+    error
+}
+
+impl<'de, I, A> de::Deserializer<'de> for &mut QSDeserializer<I, A>
 where
-    I: Iterator<Item = (E, A)>,
-    for<'s> E: __implementors::IntoDeserializer<'de, 's>,
+    I: Iterator<Item = (DecodedSlice<'de>, A)>,
     for<'s> A: __implementors::IntoDeserializer<'de, 's>,
 {
     type Error = Error;
@@ -44,30 +212,159 @@ where
         visitor.visit_map(self)
     }
 
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        // A sequence at the root holds the keys, for flags like `?a&b&c`
+        visitor.visit_seq(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        // An enum at the root is a single key, the variant, and its value
+        match (self.iter.next(), self.iter.next()) {
+            (Some((key, value)), None) => visitor.visit_enum(RootEnum {
+                key: Some(key),
+                value,
+                scratch: &mut self.scratch,
+            }),
+            _ => Err(Error::new(ErrorKind::InvalidType).message(String::from(
+                "an enum should be the only key of the query string",
+            ))),
+        }
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        bytes byte_buf option unit unit_struct newtype_struct tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// The variant of an enum at the root of the query string, with the value of its key
+struct RootEnum<'de, 'a, A> {
+    key: Option<DecodedSlice<'de>>,
+    value: A,
+    scratch: &'a mut Scratch,
+}
+
+impl<'de, A> de::EnumAccess<'de> for RootEnum<'de, '_, A>
+where
+    for<'s> A: __implementors::IntoDeserializer<'de, 's>,
+{
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| invalid_state_error("the variant is taken twice"))?;
+        let key = transform_key(key, &self.scratch.config);
+        let variant = seed.deserialize(key.into_deserializer(self.scratch))?;
+        Ok((variant, self))
     }
 }
 
-impl<'de, I, E, A> de::MapAccess<'de> for QSDeserializer<I, A>
+impl<'de, A> de::VariantAccess<'de> for RootEnum<'de, '_, A>
 where
-    I: Iterator<Item = (E, A)>,
-    for<'s> E: __implementors::IntoDeserializer<'de, 's>,
     for<'s> A: __implementors::IntoDeserializer<'de, 's>,
 {
     type Error = Error;
 
-    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.value.into_deserializer(self.scratch))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_tuple(
+            self.value.into_deserializer(self.scratch),
+            len,
+            visitor,
+        )
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_struct(
+            self.value.into_deserializer(self.scratch),
+            "",
+            fields,
+            visitor,
+        )
+    }
+}
+
+impl<'de, I, A> de::SeqAccess<'de> for QSDeserializer<I, A>
+where
+    I: Iterator<Item = (DecodedSlice<'de>, A)>,
+{
+    type Error = Error;
+
+    fn next_element_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
         K: de::DeserializeSeed<'de>,
     {
-        let mut scratch = Vec::new();
+        match self.iter.next() {
+            Some((k, _)) => seed
+                .deserialize(
+                    transform_key(k, &self.scratch.config).into_deserializer(&mut self.scratch),
+                )
+                .map(Some),
+            None => Ok(None),
+        }
+    }
 
+    fn size_hint(&self) -> Option<usize> {
+        self.iter.size_hint().1
+    }
+}
+
+impl<'de, I, A> de::MapAccess<'de> for QSDeserializer<I, A>
+where
+    I: Iterator<Item = (DecodedSlice<'de>, A)>,
+    for<'s> A: __implementors::IntoDeserializer<'de, 's>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
         if let Some((k, v)) = self.iter.next() {
             self.value = Some(v);
-            seed.deserialize(k.into_deserializer(&mut scratch))
+            self.key.clear();
+            self.key.extend_from_slice(&k.0);
+            self.key_span = k.raw_slice().map(address_range);
+            let k = transform_key(k, &self.scratch.config);
+            seed.deserialize(k.into_deserializer(&mut self.scratch))
                 .map(Some)
         } else {
             Ok(None)
@@ -81,8 +378,9 @@ where
         let value = self
             .value
             .take()
-            .expect("Method next_value called before next_key");
+            .ok_or_else(|| invalid_state_error("next_value is called before next_key"))?;
         seed.deserialize(value.into_deserializer(&mut self.scratch))
+            .map_err(|e| with_key(e, &self.key, self.key_span.clone()))
     }
 
     fn size_hint(&self) -> Option<usize> {
@@ -90,66 +388,595 @@ where
     }
 }
 
-/// An enum used to choose the parsing method for deserialization
-#[derive(Clone, Copy)]
-pub enum ParseMode {
-    /// The simplest parser for querystring.
-    /// It parses the whole querystring, and overwrites each repeated key’s value.
-    /// It does not support vectors, maps nor tuples, but provides the best performance.
-    ///
-    /// More description at ([UrlEncodedQs](crate::UrlEncodedQS))
-    UrlEncoded,
+/// Returns the error of a pair which can't be parsed
+fn invalid_pair_error(pair: &[u8], invalid: InvalidPair) -> Error {
+    match invalid {
+        InvalidPair::UnicodeEscape(index) => Error::new(ErrorKind::InvalidUnicodeEscape)
+            .message(String::from("malformed %u escape"))
+            .value(pair)
+            .index(index),
+        InvalidPair::TooLong(max_length) => {
+            let key = split_pair(pair).0;
+            Error::new(ErrorKind::LimitExceeded)
+                .message(format!(
+                    "key or value longer than the max length of {}",
+                    max_length
+                ))
+                // The key is truncated, as it may be too long to be part of the error
+                .value(&key[..key.len().min(max_length)])
+        }
+        InvalidPair::InvalidUtf8 => Error::new(ErrorKind::InvalidEncoding)
+            .message(String::from("key or value is not valid utf-8"))
+            .value(pair),
+    }
+}
 
-    /// A querystring parser with support for vectors/lists of values by repeating keys.
-    /// (ex. `"key=value1&key=value2"`)
-    ///
-    /// More description at ([DuplicateQs](crate::DuplicateQS))
-    Duplicate,
+/// Returns an error if the query string has more pairs, or longer keys or values than the
+/// limits of the config, or malformed `%u` escapes when they are decoded
+///
+/// With `skip_invalid_pairs`, the pairs which can't be parsed are not checked nor counted.
+pub(crate) fn check_limits(input: &[u8], config: &QsConfig) -> Result<(), Error> {
+    let unicode_escapes = config.decode_options.unicode_escapes;
+    if config.max_pairs.is_none() && config.max_length.is_none() && !unicode_escapes {
+        return Ok(());
+    }
 
-    /// A querystring parser with support for vectors/lists of values by the use of a delimiter byte.
-    /// (ex. `"key=value1|value2|value3"`). Holds the delimiter as a single byte `Delimiter(b'|')`
-    ///
-    /// More description at ([DelimiterQs](crate::DelimiterQS))
-    Delimiter(u8),
+    let mut count = 0;
+    for pair in split_pairs(input, config.pair_separator) {
+        if let Err(invalid) = check_pair(pair, config) {
+            if config.skip_invalid_pairs {
+                continue;
+            }
+            return Err(invalid_pair_error(pair, invalid));
+        }
 
-    /// A querystring parser with support for vectors/lists, maps and enums
-    /// by the use of brackets(like qs or PHP).(ex. `key[2]=value2&key[1]=value1"`)
-    ///
-    /// More description at ([BracketsQs](crate::BracketsQS))
-    Brackets,
+        if let Some(max_pairs) = config.max_pairs {
+            if count == max_pairs {
+                return Err(Error::new(ErrorKind::TooManyParams)
+                    .message(format!("more than {} pairs in the query string", max_pairs)));
+            }
+        }
+        count += 1;
+    }
+
+    Ok(())
+}
+
+/// Returns the errors of the pairs of the query string which are dropped with
+/// [`QsConfig::skip_invalid_pairs`], in their order in the input
+///
+/// The value of an error is the raw pair, and its spans are of the input.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_str, invalid_pairs, ErrorKind, ParseMode, QsConfig};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Hit {
+///     page: String,
+///     referrer: Option<String>,
+/// }
+///
+/// let input = "page=/home&referrer=%u12&utm=abcdefghijklmnopqrstuvwxyz";
+/// let config = QsConfig::new(ParseMode::UrlEncoded)
+///     .unicode_escapes(true)
+///     .max_length(16)
+///     .skip_invalid_pairs(true);
+///
+/// let hit: Hit = from_str(input, config).unwrap();
+/// assert_eq!(hit.page, "/home");
+/// assert_eq!(hit.referrer, None);
+///
+/// let kinds: Vec<ErrorKind> = invalid_pairs(input.as_bytes(), config)
+///     .into_iter()
+///     .map(|e| e.kind)
+///     .collect();
+/// assert_eq!(kinds, [ErrorKind::InvalidUnicodeEscape, ErrorKind::LimitExceeded]);
+/// ```
+pub fn invalid_pairs<C: Into<QsConfig>>(input: &[u8], config: C) -> Vec<Error> {
+    let config = config.into().skip_invalid_pairs(true);
+    #[cfg(feature = "brackets")]
+    let brackets = matches!(
+        config.mode,
+        ParseMode::Brackets | ParseMode::DotNotation | ParseMode::BracketsDelimiter(_)
+    );
+
+    split_pairs(input, config.pair_separator)
+        .filter_map(|pair| {
+            let error = match check_pair(pair, &config) {
+                Err(invalid) => invalid_pair_error(pair, invalid),
+                #[cfg(feature = "brackets")]
+                Ok(()) if brackets => {
+                    // The pair is parsed alone, so it is not dropped by the parser
+                    BracketsQS::parse_with_config(pair, &config.skip_invalid_pairs(false))
+                        .check_depth()
+                        .err()?
+                }
+                Ok(()) => return None,
+            };
+            let mut error = error.value(pair);
+            error.clear_spans();
+            Some(error.or_value(Some(pair)).relative_to(input))
+        })
+        .collect()
 }
 
 /// Deserialize an instance of type `T` from bytes of query string.
-pub fn from_bytes<'de, T>(input: &'de [u8], config: ParseMode) -> Result<T, Error>
+///
+/// The config can be a `ParseMode`, or a `QsConfig` for more options. Its type is the second
+/// generic parameter, so a turbofish call names it with `_`, like `from_bytes::<T, _>(..)`.
+pub fn from_bytes<'de, T, C>(input: &'de [u8], config: C) -> Result<T, Error>
 where
     T: de::Deserialize<'de>,
+    C: Into<QsConfig>,
 {
-    match config {
+    from_bytes_with(&mut QsBuffers::new(), input, config)
+}
+
+/// Deserialize an instance of type `T` from bytes of query string, decoding the keys and values
+/// into the buffers. See [`QsBuffers`]
+pub fn from_bytes_with<'de, T, C>(
+    buffers: &mut QsBuffers,
+    input: &'de [u8],
+    config: C,
+) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+    C: Into<QsConfig>,
+{
+    deserialize_input(buffers, input, config.into(), None)
+}
+
+/// Deserializes `T` with the parser of the mode of the config, decoding into the buffers, or the
+/// arena if there is one
+pub(crate) fn deserialize_input<'de, T>(
+    buffers: &mut QsBuffers,
+    input: &'de [u8],
+    config: QsConfig,
+    arena: Option<&'de Arena>,
+) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    check_limits(input, &config)?;
+    // Only the parsers behind the features decode into them
+    #[cfg(not(any(
+        feature = "urlencoded",
+        feature = "duplicate",
+        feature = "delimiter",
+        feature = "brackets"
+    )))]
+    let _ = (buffers, arena);
+
+    let result = match config.mode {
+        // A simple key=value parser
+        #[cfg(feature = "urlencoded")]
         ParseMode::UrlEncoded => {
-            // A simple key=value parser
-            T::deserialize(QSDeserializer::new(UrlEncodedQS::parse(input).into_iter()))
+            UrlEncodedQS::parse_with_config(input, &config).deserialize_with(buffers, arena)
         }
+        // A parser with duplicated keys interpreted as sequence
+        #[cfg(feature = "duplicate")]
         ParseMode::Duplicate => {
-            // A parser with duplicated keys interpreted as sequence
-            T::deserialize(QSDeserializer::new(DuplicateQS::parse(input).into_iter()))
+            DuplicateQS::parse_with_config(input, &config).deserialize_with(buffers, arena)
         }
+        // A parser with sequences of values seperated by one character
+        #[cfg(feature = "delimiter")]
         ParseMode::Delimiter(s) => {
-            // A parser with sequences of values seperated by one character
-            T::deserialize(QSDeserializer::new(
-                DelimiterQS::parse(input, s).into_iter(),
-            ))
+            DelimiterQS::parse_with_config(input, s, &config).deserialize_with(buffers, arena)
         }
-        ParseMode::Brackets => {
-            // A PHP like interpretation of querystrings
-            T::deserialize(QSDeserializer::new(BracketsQS::parse(input).into_iter()))
+        // Matrix parameters, with values seperated by commas
+        #[cfg(feature = "delimiter")]
+        ParseMode::Matrix => DelimiterQS::parse_with_config(strip_matrix(input), b',', &config)
+            .deserialize_with(buffers, arena),
+        // A PHP like interpretation of querystrings
+        #[cfg(feature = "brackets")]
+        ParseMode::Brackets | ParseMode::DotNotation | ParseMode::BracketsDelimiter(_) => {
+            BracketsQS::parse_with_config(input, &config).deserialize_with(buffers, arena)
         }
-    }
+        // Pairs split by a parser outside of this crate
+        #[cfg(feature = "duplicate")]
+        ParseMode::Custom(parser) => {
+            DuplicateQS::parse_custom(input, parser, &config).deserialize_with(buffers, arena)
+        }
+        // Rails like nested params, built as a tree before deserializing
+        ParseMode::Rack => T::deserialize(Value::Map(rack::parse(input, &config)?)),
+        // PHP variables, including the mangling of their names
+        ParseMode::Php => T::deserialize(Value::Map(php::parse(input, &config)?)),
+        // The parser of the mode is left out by its feature
+        #[cfg(not(all(
+            feature = "urlencoded",
+            feature = "duplicate",
+            feature = "delimiter",
+            feature = "brackets"
+        )))]
+        mode => Err(disabled_mode_error(mode)),
+    };
+    result.map_err(|e| e.relative_to(input))
+}
+
+/// The error of a mode whose parser is left out, naming the feature which enables it
+#[cfg(not(all(
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+)))]
+pub(crate) fn disabled_mode_error(mode: ParseMode) -> Error {
+    let feature = match mode {
+        ParseMode::UrlEncoded => "urlencoded",
+        ParseMode::Duplicate | ParseMode::Custom(_) => "duplicate",
+        ParseMode::Delimiter(_) | ParseMode::Matrix => "delimiter",
+        _ => "brackets",
+    };
+    Error::new(ErrorKind::Other).message(format!("the `{}` feature is not enabled", feature))
+}
+
+/// Removes the `;` starting the matrix parameters, so it isn't parsed as an empty key
+#[cfg(feature = "delimiter")]
+pub(crate) fn strip_matrix(input: &[u8]) -> &[u8] {
+    input.strip_prefix(b";").unwrap_or(input)
 }
 
 /// Deserialize an instance of type `T` from a query string.
-pub fn from_str<'de, T>(input: &'de str, config: ParseMode) -> Result<T, Error>
+///
+/// The config can be a `ParseMode`, or a `QsConfig` for more options.
+pub fn from_str<'de, T, C>(input: &'de str, config: C) -> Result<T, Error>
 where
     T: de::Deserialize<'de>,
+    C: Into<QsConfig>,
 {
     from_bytes(input.as_bytes(), config)
 }
+
+/// Deserialize an instance of type `T` from a query string, decoding the keys and values into
+/// the buffers. See [`QsBuffers`]
+pub fn from_str_with<'de, T, C>(
+    buffers: &mut QsBuffers,
+    input: &'de str,
+    config: C,
+) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+    C: Into<QsConfig>,
+{
+    from_bytes_with(buffers, input.as_bytes(), config)
+}
+
+/// Deserialize an instance of type `T` owning its data from bytes of query string, so the input
+/// can be dropped or reused once it returns, like a buffer read from a socket.
+///
+/// The input is still parsed in a single pass, the values are only copied by the deserialized
+/// type. `T` can't borrow from the input, so its strings are `String`s instead of `&str`s.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_bytes_owned, ParseMode};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     q: String,
+/// }
+///
+/// let mut buffer = b"q=rust".to_vec();
+/// let query: Query = from_bytes_owned(&buffer, ParseMode::UrlEncoded).unwrap();
+/// buffer.clear();
+///
+/// assert_eq!(query.q, "rust");
+/// ```
+pub fn from_bytes_owned<T, C>(input: &[u8], config: C) -> Result<T, Error>
+where
+    T: de::DeserializeOwned,
+    C: Into<QsConfig>,
+{
+    from_bytes(input, config)
+}
+
+/// Deserialize an instance of type `T` owning its data from a query string. See
+/// [`from_bytes_owned`]
+pub fn from_str_owned<T, C>(input: &str, config: C) -> Result<T, Error>
+where
+    T: de::DeserializeOwned,
+    C: Into<QsConfig>,
+{
+    from_bytes(input.as_bytes(), config)
+}
+
+/// Deserialize an instance of type `T` from a query string, in the mode guessed by
+/// [`ParseMode::detect`].
+///
+/// Use `QsConfig::new(ParseMode::detect(input))` to set more options.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::from_bytes_auto;
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     ids: Vec<u32>,
+/// }
+///
+/// for input in [&b"ids[]=1&ids[]=2"[..], b"ids=1&ids=2", b"ids=1,2"] {
+///     let query: Query = from_bytes_auto(input).unwrap();
+///     assert_eq!(query.ids, vec![1, 2]);
+/// }
+/// ```
+pub fn from_bytes_auto<'de, T>(input: &'de [u8]) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    from_bytes(input, ParseMode::detect(input))
+}
+
+/// Deserialize an instance of type `T` from a query string, in the mode guessed by
+/// [`ParseMode::detect`].
+pub fn from_str_auto<'de, T>(input: &'de str) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    from_bytes_auto(input.as_bytes())
+}
+
+/// Returns the first key of a path(`filter` for `filter[age]`, or `filter.age` in
+/// `DotNotation` mode). Keys are not split in the modes without sub keys.
+fn parent_key(key: &str, mode: ParseMode) -> &str {
+    let end = match mode {
+        ParseMode::DotNotation => key.find(['[', '.']),
+        ParseMode::Brackets
+        | ParseMode::BracketsDelimiter(_)
+        | ParseMode::Rack
+        | ParseMode::Php => key.find('['),
+        _ => None,
+    };
+    end.map_or(key, |end| &key[..end])
+}
+
+/// The most keys skipped by `deserialize_skipping` when `max_pairs` is not set
+const MAX_SKIPPED_KEYS: usize = 64;
+
+/// Replaces the pairs of a key, or of its sub keys, by pair separators so they are skipped and
+/// the spans of the other pairs stay the same. Returns false if there was no such pair.
+fn remove_pairs(input: &mut [u8], key: &str, config: &QsConfig) -> bool {
+    let parent = parent_key(key, config.mode);
+    let mut scratch = Vec::new();
+    let ranges: Vec<Range<usize>> = split_pairs(input, config.pair_separator)
+        .filter(|pair| {
+            let decoded = parse_bytes(split_pair(pair).0, &mut scratch);
+            let decoded = String::from_utf8_lossy(&decoded);
+            decoded == key || parent_key(&decoded, config.mode) == parent
+        })
+        .map(|pair| {
+            let start = pair.as_ptr() as usize - input.as_ptr() as usize;
+            start..start + pair.len()
+        })
+        .collect();
+
+    let separator = match config.pair_separator {
+        PairSeparator::Semicolon => b';',
+        PairSeparator::Newline => b'\n',
+        PairSeparator::Ampersand | PairSeparator::Both => b'&',
+    };
+    for range in &ranges {
+        input[range.clone()].iter_mut().for_each(|b| *b = separator);
+    }
+    !ranges.is_empty()
+}
+
+/// Deserialize an instance of type `T` from bytes of query string, failing with the errors of
+/// all the keys instead of the first one.
+///
+/// When a key fails, its pairs are skipped and the input is deserialized again, until it
+/// succeeds or fails without a new key to skip, like a required field which was skipped. At
+/// most `max_pairs` keys are skipped, or 64 if it is not set. The errors are in the order of their keys in the input, followed by the ones without a key span
+/// like missing fields.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_bytes_collect_errors, ParseMode};
+///
+/// #[derive(Debug, Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Form {
+///     age: u32,
+///     email: String,
+///     newsletter: Option<bool>,
+/// }
+///
+/// let errors = from_bytes_collect_errors::<Form, _>(
+///     b"age=ten&email=a%40example.com&newsletter=maybe",
+///     ParseMode::UrlEncoded,
+/// )
+/// .unwrap_err();
+///
+/// let keys: Vec<&str> = errors.iter().map(|e| e.key.as_str()).collect();
+/// assert_eq!(keys, ["age", "newsletter"]);
+/// ```
+pub fn from_bytes_collect_errors<T, C>(input: &[u8], config: C) -> Result<T, Vec<Error>>
+where
+    T: de::DeserializeOwned,
+    C: Into<QsConfig>,
+{
+    match deserialize_skipping(input, config.into()) {
+        (Some(value), errors) if errors.is_empty() => Ok(value),
+        (_, errors) => Err(errors),
+    }
+}
+
+/// Deserializes the input, skipping the pairs of the keys which fail until it succeeds
+///
+/// Returns the value if it succeeds, and the errors of the skipped keys sorted by their
+/// position in the input. The error failing the deserialization is part of them otherwise,
+/// unless it is caused by a skipped key, like a required field.
+fn deserialize_skipping<T>(input: &[u8], config: QsConfig) -> (Option<T>, Vec<Error>)
+where
+    T: de::DeserializeOwned,
+{
+    let mut input = Cow::Borrowed(input);
+    let mut errors = Vec::new();
+    // The parent keys of the errors, which are skipped
+    let mut skipped: Vec<String> = Vec::new();
+
+    let value = loop {
+        let error = match from_bytes(&input, config) {
+            Ok(value) => break Some(value),
+            Err(error) => error,
+        };
+
+        let parent = parent_key(&error.key, config.mode).to_string();
+        if !parent.is_empty() && skipped.contains(&parent) {
+            // Caused by a skipped key
+            break None;
+        }
+        // Each skipped key deserializes the input again, so they are limited
+        let limit = config.max_pairs.unwrap_or(MAX_SKIPPED_KEYS);
+        let removed = !parent.is_empty()
+            && skipped.len() < limit
+            && remove_pairs(input.to_mut(), &error.key, &config);
+        errors.push(error);
+        if !removed {
+            break None;
+        }
+        skipped.push(parent);
+    };
+
+    errors.sort_by_key(|e| e.key_span().map_or(usize::MAX, |span| span.start));
+    (value, errors)
+}
+
+/// Deserialize an instance of type `T` from a query string, failing with the errors of all the
+/// keys. See [`from_bytes_collect_errors`]
+pub fn from_str_collect_errors<T, C>(input: &str, config: C) -> Result<T, Vec<Error>>
+where
+    T: de::DeserializeOwned,
+    C: Into<QsConfig>,
+{
+    from_bytes_collect_errors(input.as_bytes(), config)
+}
+
+/// Deserialize an instance of type `T` from bytes of query string at best, skipping the keys
+/// which fail. Returns the value with the errors of the skipped keys.
+///
+/// The skipped fields get their default value, so they must be an `Option` or have
+/// `#[serde(default)]`. It fails with the errors of all the keys if the value can't be built
+/// without them, the same as [`from_bytes_collect_errors`].
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_bytes_partial, ParseMode};
+///
+/// #[derive(Debug, Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Search {
+///     q: String,
+///     #[serde(default)]
+///     page: u32,
+///     sort: Option<String>,
+/// }
+///
+/// let (search, errors) =
+///     from_bytes_partial::<Search, _>(b"q=rust&page=two&sort=date", ParseMode::UrlEncoded).unwrap();
+///
+/// assert_eq!(search.q, "rust");
+/// assert_eq!(search.page, 0);
+/// assert_eq!(search.sort.as_deref(), Some("date"));
+/// assert_eq!(errors[0].key, "page");
+/// ```
+pub fn from_bytes_partial<T, C>(input: &[u8], config: C) -> Result<(T, Vec<Error>), Vec<Error>>
+where
+    T: de::DeserializeOwned,
+    C: Into<QsConfig>,
+{
+    match deserialize_skipping(input, config.into()) {
+        (Some(value), errors) => Ok((value, errors)),
+        (None, errors) => Err(errors),
+    }
+}
+
+/// Deserialize an instance of type `T` from a query string at best, skipping the keys which
+/// fail. See [`from_bytes_partial`]
+pub fn from_str_partial<T, C>(input: &str, config: C) -> Result<(T, Vec<Error>), Vec<Error>>
+where
+    T: de::DeserializeOwned,
+    C: Into<QsConfig>,
+{
+    from_bytes_partial(input.as_bytes(), config)
+}
+
+/// Deserialize an instance of type `T` from the pairs under a prefix(`filter[age]` or
+/// `filter.age` for the `filter` prefix), other pairs are ignored.
+///
+/// The keys are parsed the same way as the brackets modes, so sequences and maps can be nested
+/// in the scope. Dots only separate the sub keys in the `DotNotation`, `UrlEncoded` and
+/// `Duplicate` modes, and the values are split by the delimiter in the delimiter modes.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_bytes_scoped, ParseMode};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Filter {
+///     age: u32,
+///     name: Option<String>,
+/// }
+///
+/// let filter: Filter =
+///     from_bytes_scoped(b"page=2&filter[age]=30&filter.name=John", "filter", ParseMode::UrlEncoded)
+///         .unwrap();
+///
+/// assert_eq!(filter.age, 30);
+/// assert_eq!(filter.name.unwrap(), "John");
+/// ```
+#[cfg(feature = "brackets")]
+pub fn from_bytes_scoped<'de, T, C>(input: &'de [u8], prefix: &str, config: C) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+    C: Into<QsConfig>,
+{
+    let mut config = config.into();
+    check_limits(input, &config)?;
+
+    config.mode = match config.mode {
+        ParseMode::UrlEncoded | ParseMode::Duplicate => ParseMode::DotNotation,
+        ParseMode::Delimiter(s) => ParseMode::BracketsDelimiter(s),
+        ParseMode::Matrix => ParseMode::BracketsDelimiter(b','),
+        ParseMode::Custom(_) => {
+            return Err(Error::new(ErrorKind::Other)
+                .message(String::from("custom parse modes can't be scoped")))
+        }
+        ParseMode::Rack | ParseMode::Php => {
+            let mut map = match config.mode {
+                ParseMode::Rack => rack::parse(input, &config)?,
+                _ => php::parse(input, &config)?,
+            };
+            let scope = map
+                .remove(prefix)
+                .unwrap_or_else(|| Value::Map(Default::default()));
+            return T::deserialize(scope);
+        }
+        mode => mode,
+    };
+    BracketsQS::parse_with_config(input, &config)
+        .deserialize_scoped(prefix.as_bytes())
+        .map_err(|e| e.relative_to(input))
+}
+
+/// Deserialize an instance of type `T` from the pairs of a query string under a prefix.
+///
+/// See [`from_bytes_scoped`] for more details.
+#[cfg(feature = "brackets")]
+pub fn from_str_scoped<'de, T, C>(input: &'de str, prefix: &str, config: C) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+    C: Into<QsConfig>,
+{
+    from_bytes_scoped(input.as_bytes(), prefix, config)
+}
@@ -1,34 +1,265 @@
-use std::borrow::Cow;
-use std::fmt;
-use std::str;
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ptr::NonNull;
+use core::str;
 
 use lexical::FromLexical;
 
-use crate::decode::parse_bytes;
-use crate::decode::Reference;
+use crate::arena::Arena;
+use crate::config::{InvalidUtf8, ParseMode, QsConfig};
+use crate::decode::{parse_bytes_with, Reference};
+use crate::parsers::unescape;
 
 use super::{Error, ErrorKind};
 
+/// The buffer used to decode slices, along with the config to decode and deserialize them with
+pub struct Scratch {
+    buf: Vec<u8>,
+    pub(crate) config: QsConfig,
+    // The arena the decoded slices are moved into, without the `'de` lifetime it is borrowed for
+    arena: Option<NonNull<Arena>>,
+}
+
+impl Scratch {
+    pub fn new(config: QsConfig) -> Self {
+        Self::with_buffer(Vec::new(), config)
+    }
+
+    /// Uses the buffer to decode the slices, it is cleared before each use
+    pub(crate) fn with_buffer(buf: Vec<u8>, config: QsConfig) -> Self {
+        Self {
+            buf,
+            config,
+            arena: None,
+        }
+    }
+
+    /// Moves the decoded slices into the arena, so they are borrowed for `'de` like the input.
+    ///
+    /// The scratch must only decode slices borrowed for the same `'de` as the arena.
+    pub(crate) fn arena(mut self, arena: Option<&Arena>) -> Self {
+        self.arena = arena.map(NonNull::from);
+        self
+    }
+
+    pub(crate) fn into_buffer(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+impl Default for Scratch {
+    fn default() -> Self {
+        Self::new(QsConfig::new(ParseMode::UrlEncoded))
+    }
+}
+
 pub trait Value<'de> {
-    fn parse_number<'s, T>(&self, scratch: &'s mut Vec<u8>) -> Result<T, Error>
+    fn parse_number<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: FromLexical;
 
-    fn parse_bool<'s>(&self, scratch: &'s mut Vec<u8>) -> Result<bool, Error>;
+    fn parse_bool(&self, scratch: &mut Scratch) -> Result<bool, Error>;
 
-    fn parse_bytes<'s>(self, scratch: &'s mut Vec<u8>) -> Reference<'de, 's, [u8]>;
-    fn parse_str<'s>(self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>, Error>;
+    fn parse_bytes<'s>(self, scratch: &'s mut Scratch) -> Reference<'de, 's, [u8]>;
+    fn parse_str<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, str>, Error>;
 
     fn is_none(&self) -> bool;
+
+    /// Returns true for empty values and literal `null`s
+    fn is_null(&self) -> bool;
+
+    /// Returns true for a key without a value(`key`), but not an empty one(`key=`)
+    fn is_flag(&self) -> bool {
+        false
+    }
+
+    /// Returns the slice as it appeared in the input, if it is still available
+    fn raw_slice(&self) -> Option<&'de [u8]>;
 }
 
 #[inline]
 fn invalid_boolean_error(slice: &[u8]) -> Error {
-    Error::new(ErrorKind::InvalidBoolean).value(slice).message(
-        "invalid boolean {}, supported values are 1, on and true for true \
-        and 0, off and false for false"
-            .to_string(),
-    )
+    Error::new(ErrorKind::InvalidBoolean)
+        .value(slice)
+        .message(format!(
+            "invalid boolean {}, supported values are 1, on and true for true \
+            and 0, off and false for false",
+            String::from_utf8_lossy(slice)
+        ))
+}
+
+/// Parses a decoded boolean, lenient booleans also accept `yes` and `no`, in any case
+fn parse_bool(slice: &[u8], lenient: bool) -> Result<bool, Error> {
+    let matches = |value: &[u8]| {
+        if lenient {
+            slice.eq_ignore_ascii_case(value)
+        } else {
+            slice == value
+        }
+    };
+
+    match slice.len() {
+        0 => Ok(true),
+        1 => match slice[0] {
+            b'1' => Ok(true),
+            b'0' => Ok(false),
+            _ => Err(invalid_boolean_error(slice)),
+        },
+        2 if matches(b"on") => Ok(true),
+        2 if lenient && matches(b"no") => Ok(false),
+        3 if matches(b"off") => Ok(false),
+        3 if lenient && matches(b"yes") => Ok(true),
+        4 if matches(b"true") => Ok(true),
+        5 if matches(b"false") => Ok(false),
+        _ => Err(invalid_boolean_error(slice)),
+    }
+}
+
+/// Rewrites a number written in one of the lenient forms to a plain one: a leading `+`, which is
+/// a space once decoded, `_` between digits(`1_000`) and hexadecimal integers(`0x1F`)
+fn normalize_number(slice: &[u8]) -> Option<Vec<u8>> {
+    let (negative, digits) = match slice {
+        [b'-', rest @ ..] => (true, rest),
+        [b'+' | b' ', rest @ ..] => (false, rest),
+        _ => (false, slice),
+    };
+
+    // Underscores must sit between two digits
+    for (i, _) in digits.iter().enumerate().filter(|(_, &b)| b == b'_') {
+        let is_digit = |i: Option<usize>| {
+            i.and_then(|i| digits.get(i))
+                .map_or(false, u8::is_ascii_alphanumeric)
+        };
+        if !is_digit(i.checked_sub(1)) || !is_digit(Some(i + 1)) {
+            return None;
+        }
+    }
+    let digits: Vec<u8> = digits.iter().copied().filter(|&b| b != b'_').collect();
+
+    let mut normalized = Vec::with_capacity(digits.len() + 1);
+    if negative {
+        normalized.push(b'-');
+    }
+    match digits.as_slice() {
+        [b'0', b'x' | b'X', hex @ ..] => {
+            // `from_str_radix` takes a sign of its own, which would accept `0x+1F`
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            let value = u128::from_str_radix(str::from_utf8(hex).ok()?, 16).ok()?;
+            normalized.extend_from_slice(value.to_string().as_bytes());
+        }
+        _ => normalized.extend_from_slice(&digits),
+    }
+    Some(normalized)
+}
+
+/// Parses a number, trying the lenient forms when it fails and lenient numbers are enabled
+fn parse_number<T>(slice: &[u8], lenient: bool) -> Result<T, Error>
+where
+    T: FromLexical,
+{
+    lexical::parse(slice)
+        .or_else(|e| match normalize_number(slice) {
+            Some(normalized) if lenient => lexical::parse(&normalized).map_err(|_| e),
+            _ => Err(e),
+        })
+        .map_err(|e| {
+            Error::new(ErrorKind::InvalidNumber)
+                .value(slice)
+                .message(e.to_string())
+        })
+}
+
+/// Removes the ascii whitespace around a slice
+pub(crate) fn trim_whitespace(slice: &[u8]) -> &[u8] {
+    let start = slice
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(slice.len());
+    let end = slice
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(start, |i| i + 1);
+    &slice[start..end]
+}
+
+/// Moves the decoded bytes into the arena of the scratch if there is one, borrowed bytes are
+/// kept as is
+#[inline]
+fn into_arena<'de, 's>(
+    bytes: Reference<'de, 's, [u8]>,
+    arena: Option<NonNull<Arena>>,
+) -> Reference<'de, 's, [u8]> {
+    let arena = match arena {
+        // Safety: the scratch only has an arena while deserializing values borrowed for the same
+        // `'de` as the arena, see `Scratch::arena`
+        Some(arena) => unsafe { arena.as_ref() },
+        None => return bytes,
+    };
+    match bytes {
+        Reference::Borrowed(b) => Reference::Borrowed(b),
+        Reference::Copied(c) => Reference::Borrowed(arena.alloc_slice_copy(c)),
+        Reference::Owned(o) => Reference::Borrowed(arena.alloc_slice_copy(&o)),
+    }
+}
+
+/// Trims the decoded bytes if the config asks for it
+fn trim_decoded<'de, 's>(
+    bytes: Reference<'de, 's, [u8]>,
+    config: &QsConfig,
+) -> Reference<'de, 's, [u8]> {
+    if !config.trim_whitespace {
+        return bytes;
+    }
+    match bytes {
+        Reference::Borrowed(b) => Reference::Borrowed(trim_whitespace(b)),
+        Reference::Copied(c) => Reference::Copied(trim_whitespace(c)),
+        Reference::Owned(o) if trim_whitespace(&o).len() == o.len() => Reference::Owned(o),
+        Reference::Owned(o) => Reference::Owned(trim_whitespace(&o).to_vec()),
+    }
+}
+
+#[inline]
+fn is_null_slice(slice: &[u8]) -> bool {
+    slice.is_empty() || slice == b"null"
+}
+
+/// Converts the decoded bytes into a str, following the `InvalidUtf8` policy of the config.
+/// Errors hold the raw slice when there is one, or the decoded bytes.
+fn bytes_to_str<'de, 's>(
+    bytes: Reference<'de, 's, [u8]>,
+    config: &QsConfig,
+    raw: Option<&[u8]>,
+) -> Result<Reference<'de, 's, str>, Error> {
+    let (error, bytes) = match bytes {
+        Reference::Borrowed(b) => match str::from_utf8(b) {
+            Ok(s) => return Ok(Reference::Borrowed(s)),
+            Err(e) => (e, Cow::Borrowed(b)),
+        },
+        Reference::Copied(c) => match str::from_utf8(c) {
+            Ok(s) => return Ok(Reference::Copied(s)),
+            Err(e) => (e, Cow::Borrowed(c)),
+        },
+        Reference::Owned(o) => match String::from_utf8(o) {
+            Ok(s) => return Ok(Reference::Owned(s)),
+            Err(e) => (e.utf8_error(), Cow::Owned(e.into_bytes())),
+        },
+    };
+
+    if config.invalid_utf8 == InvalidUtf8::Replace {
+        return Ok(Reference::Owned(
+            String::from_utf8_lossy(&bytes).into_owned(),
+        ));
+    }
+
+    Err(Error::new(ErrorKind::InvalidEncoding)
+        .message("invalid utf-8 sequence found in the percent decoded value".to_string())
+        .value(raw.unwrap_or(&bytes))
+        .index(error.valid_up_to()))
 }
 
 /// Holds a slice of bytes that is already percent decoded
@@ -42,61 +273,57 @@ impl<'de> fmt::Display for DecodedSlice<'de> {
 }
 
 impl<'de> Value<'de> for DecodedSlice<'de> {
-    fn parse_number<'s, T>(&self, _: &'s mut Vec<u8>) -> Result<T, Error>
+    fn parse_number<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: FromLexical,
     {
-        lexical::parse(&self.0).map_err(|e| {
-            Error::new(ErrorKind::InvalidNumber)
-                .value(&self.0)
-                .message(e.to_string())
-        })
+        let config = &scratch.config;
+        let slice = if config.trim_whitespace {
+            trim_whitespace(&self.0)
+        } else {
+            &self.0
+        };
+        parse_number(slice, config.lenient_numbers)
     }
 
-    fn parse_bool<'s>(&self, _: &'s mut Vec<u8>) -> Result<bool, Error> {
-        match self.0.len() {
-            0 => Ok(true),
-            1 => match self.0[0] {
-                b'1' => Ok(true),
-                b'0' => Ok(false),
-                _ => Err(invalid_boolean_error(&self.0)),
-            },
-            2 if self.0.as_ref() == b"on" => Ok(true),
-            3 if self.0.as_ref() == b"off" => Ok(false),
-            4 if self.0.as_ref() == b"true" => Ok(true),
-            5 if self.0.as_ref() == b"false" => Ok(false),
-            _ => Err(invalid_boolean_error(&self.0)),
-        }
+    fn parse_bool(&self, scratch: &mut Scratch) -> Result<bool, Error> {
+        let config = &scratch.config;
+        let slice = if config.trim_whitespace {
+            trim_whitespace(&self.0)
+        } else {
+            &self.0
+        };
+        parse_bool(slice, config.lenient_bool)
     }
 
-    fn parse_bytes<'s>(self, _: &'s mut Vec<u8>) -> Reference<'de, 's, [u8]> {
-        match self.0 {
+    fn parse_bytes<'s>(self, scratch: &'s mut Scratch) -> Reference<'de, 's, [u8]> {
+        let bytes = match self.0 {
             Cow::Borrowed(b) => Reference::Borrowed(b),
             Cow::Owned(o) => Reference::Owned(o),
-        }
-    }
-
-    fn parse_str<'s>(self, _: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>, Error> {
-        let res = match self.0 {
-            Cow::Borrowed(b) => str::from_utf8(b)
-                .map(Reference::Borrowed)
-                .map_err(|e| (e, Reference::Borrowed(b))),
-            Cow::Owned(o) => String::from_utf8(o)
-                .map(Reference::Owned)
-                .map_err(|e| (e.utf8_error(), Reference::Owned(e.into_bytes()))),
         };
+        into_arena(trim_decoded(bytes, &scratch.config), scratch.arena)
+    }
 
-        res.map_err(|(error, slice)| {
-            Error::new(ErrorKind::InvalidEncoding)
-                .message("invalid utf-8 sequence found in the percent decoded value".to_string())
-                .value(&slice)
-                .index(error.valid_up_to())
-        })
+    fn parse_str<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, str>, Error> {
+        let config = scratch.config;
+        bytes_to_str(self.parse_bytes(scratch), &config, None)
     }
 
     fn is_none(&self) -> bool {
         self.0.is_empty()
     }
+
+    fn is_null(&self) -> bool {
+        is_null_slice(&self.0)
+    }
+
+    fn raw_slice(&self) -> Option<&'de [u8]> {
+        // Borrowed slices didn't need any decoding
+        match self.0 {
+            Cow::Borrowed(b) => Some(b),
+            Cow::Owned(_) => None,
+        }
+    }
 }
 
 /// Holds a slice of bytes that is not percent decoded yet
@@ -110,78 +337,94 @@ impl<'de> fmt::Display for RawSlice<'de> {
 }
 
 impl<'de> Value<'de> for RawSlice<'de> {
-    fn parse_number<'s, T>(&self, _: &'s mut Vec<u8>) -> Result<T, Error>
+    fn parse_number<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: FromLexical,
     {
-        lexical::parse(self.0).map_err(|e| {
-            Error::new(ErrorKind::InvalidNumber)
-                .value(self.0)
-                .message(e.to_string())
-        })
+        let config = scratch.config;
+        match parse_number(self.0, config.lenient_numbers) {
+            // The value may be percent encoded(`%2B42`), or surrounded by whitespace
+            Err(e)
+                if config.trim_whitespace || (config.lenient_numbers && self.0.contains(&b'%')) =>
+            {
+                parse_number(&self.parse_bytes(scratch), config.lenient_numbers).map_err(|_| e)
+            }
+            res => res,
+        }
     }
 
-    fn parse_bool<'s>(&self, _: &'s mut Vec<u8>) -> Result<bool, Error> {
-        match self.0.len() {
-            0 => Ok(true),
-            1 => match self.0[0] {
-                b'1' => Ok(true),
-                b'0' => Ok(false),
-                _ => Err(invalid_boolean_error(self.0)),
-            },
-            2 if self.0 == b"on" => Ok(true),
-            3 if self.0 == b"off" => Ok(false),
-            4 if self.0 == b"true" => Ok(true),
-            5 if self.0 == b"false" => Ok(false),
-            _ => Err(invalid_boolean_error(self.0)),
+    fn parse_bool(&self, scratch: &mut Scratch) -> Result<bool, Error> {
+        let config = scratch.config;
+        match parse_bool(self.0, config.lenient_bool) {
+            Err(e) if config.trim_whitespace => {
+                parse_bool(&self.parse_bytes(scratch), config.lenient_bool).map_err(|_| e)
+            }
+            res => res,
         }
     }
 
-    fn parse_bytes<'s>(self, scratch: &'s mut Vec<u8>) -> Reference<'de, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn parse_bytes<'s>(self, scratch: &'s mut Scratch) -> Reference<'de, 's, [u8]> {
+        let config = scratch.config;
+        let arena = scratch.arena;
+        let bytes = unescape(
+            parse_bytes_with(self.0, &mut scratch.buf, config.decode_options),
+            &config,
+        );
+        into_arena(trim_decoded(bytes, &config), arena)
     }
 
-    fn parse_str<'s>(self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>, Error> {
+    fn parse_str<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, str>, Error> {
         let slice = self.0;
-
-        parse_bytes(slice, scratch)
-            .try_map(str::from_utf8)
-            .map_err(|error| {
-                Error::new(ErrorKind::InvalidEncoding)
-                    .message(
-                        "invalid utf-8 sequence found in the percent decoded value".to_string(),
-                    )
-                    .value(slice)
-                    .index(error.valid_up_to())
-            })
+        let config = scratch.config;
+        bytes_to_str(self.parse_bytes(scratch), &config, Some(slice))
     }
 
     fn is_none(&self) -> bool {
         self.0.is_empty()
     }
+
+    fn is_null(&self) -> bool {
+        is_null_slice(self.0)
+    }
+
+    fn raw_slice(&self) -> Option<&'de [u8]> {
+        Some(self.0)
+    }
 }
 
 impl<'de> Value<'de> for Option<RawSlice<'de>> {
-    fn parse_number<'s, T>(&self, scratch: &'s mut Vec<u8>) -> Result<T, Error>
+    fn parse_number<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: FromLexical,
     {
         self.unwrap_or_default().parse_number(scratch)
     }
 
-    fn parse_bool<'s>(&self, scratch: &'s mut Vec<u8>) -> Result<bool, Error> {
+    fn parse_bool(&self, scratch: &mut Scratch) -> Result<bool, Error> {
         self.unwrap_or_default().parse_bool(scratch)
     }
 
-    fn parse_bytes<'s>(self, scratch: &'s mut Vec<u8>) -> Reference<'de, 's, [u8]> {
+    fn parse_bytes<'s>(self, scratch: &'s mut Scratch) -> Reference<'de, 's, [u8]> {
         self.unwrap_or_default().parse_bytes(scratch)
     }
 
-    fn parse_str<'s>(self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>, Error> {
+    fn parse_str<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, str>, Error> {
         self.unwrap_or_default().parse_str(scratch)
     }
 
     fn is_none(&self) -> bool {
         self.is_none()
     }
+
+    fn is_null(&self) -> bool {
+        self.as_ref().map_or(true, Value::is_null)
+    }
+
+    fn is_flag(&self) -> bool {
+        self.is_none()
+    }
+
+    fn raw_slice(&self) -> Option<&'de [u8]> {
+        Some(self.unwrap_or_default().0)
+    }
 }
@@ -1,13 +1,18 @@
-use std::str;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str;
 
 use _serde::{de, forward_to_deserialize_any};
-use lexical::{self, FromLexical};
 
+use crate::config::{DuplicateKeys, InvalidUtf8};
 use crate::decode::Reference;
+use crate::parsers::{Delimiter, SeparatorValues};
+use crate::{presence, raw, separated};
 
 use super::{
     error::{Error, ErrorKind},
-    slices::{DecodedSlice, RawSlice, Value},
+    slices::{DecodedSlice, RawSlice, Scratch, Value},
+    unknown_key_error, with_key,
 };
 
 pub trait IntoDeserializer<'de, 's> {
@@ -15,7 +20,7 @@ pub trait IntoDeserializer<'de, 's> {
     type Deserializer: de::Deserializer<'de, Error = Error>;
 
     /// Convert this value into a deserializer.
-    fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer;
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer;
 }
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
@@ -23,7 +28,7 @@ pub trait IntoDeserializer<'de, 's> {
 impl<'de, 's> IntoDeserializer<'de, 's> for DecodedSlice<'de> {
     type Deserializer = ValueDeserializer<'s, Self>;
 
-    fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer {
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
         ValueDeserializer(self, scratch)
     }
 }
@@ -31,7 +36,7 @@ impl<'de, 's> IntoDeserializer<'de, 's> for DecodedSlice<'de> {
 impl<'de, 's> IntoDeserializer<'de, 's> for RawSlice<'de> {
     type Deserializer = ValueDeserializer<'s, Self>;
 
-    fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer {
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
         ValueDeserializer(self, scratch)
     }
 }
@@ -39,14 +44,92 @@ impl<'de, 's> IntoDeserializer<'de, 's> for RawSlice<'de> {
 impl<'de, 's> IntoDeserializer<'de, 's> for Option<RawSlice<'de>> {
     type Deserializer = ValueDeserializer<'s, Self>;
 
-    fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer {
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
         ValueDeserializer(self, scratch)
     }
 }
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 
-pub struct ValueDeserializer<'s, T>(T, &'s mut Vec<u8>);
+pub struct ValueDeserializer<'s, T>(T, &'s mut Scratch);
+
+enum Inferred {
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+}
+
+/// Returns the boolean or the number a value looks like, if any
+fn infer_type(value: &str) -> Option<Inferred> {
+    match value {
+        "true" => return Some(Inferred::Bool(true)),
+        "false" => return Some(Inferred::Bool(false)),
+        _ => {}
+    }
+
+    let numeric = value
+        .bytes()
+        .all(|b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E'));
+    let starts_with_digit = match value.as_bytes() {
+        [b'-', first, ..] | [first, ..] => first.is_ascii_digit(),
+        [] => false,
+    };
+    if !numeric || !starts_with_digit {
+        return None;
+    }
+
+    if let Ok(number) = value.parse() {
+        Some(Inferred::Unsigned(number))
+    } else if let Ok(number) = value.parse() {
+        Some(Inferred::Signed(number))
+    } else {
+        value.parse().ok().map(Inferred::Float)
+    }
+}
+
+impl<'de, 's, T> ValueDeserializer<'s, T>
+where
+    T: Value<'de>,
+{
+    fn visit_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Keys don't have a raw slice, and are always strings
+        let infer_types = self.1.config.infer_types && self.0.raw_slice().is_some();
+
+        let value = if self.1.config.invalid_utf8 == InvalidUtf8::Bytes {
+            let bytes = self.0.parse_bytes(self.1);
+            if str::from_utf8(&bytes).is_err() {
+                return match bytes {
+                    Reference::Borrowed(b) => visitor.visit_borrowed_bytes(b),
+                    Reference::Copied(c) => visitor.visit_bytes(c),
+                    Reference::Owned(o) => visitor.visit_byte_buf(o),
+                };
+            }
+            bytes.try_map(str::from_utf8).map_err(de::Error::custom)?
+        } else {
+            self.0.parse_str(self.1)?
+        };
+
+        if infer_types {
+            match infer_type(&value) {
+                Some(Inferred::Bool(b)) => return visitor.visit_bool(b),
+                Some(Inferred::Unsigned(n)) => return visitor.visit_u64(n),
+                Some(Inferred::Signed(n)) => return visitor.visit_i64(n),
+                Some(Inferred::Float(n)) => return visitor.visit_f64(n),
+                None => {}
+            }
+        }
+
+        match value {
+            Reference::Borrowed(b) => visitor.visit_borrowed_str(b),
+            Reference::Copied(o) => visitor.visit_str(o),
+            Reference::Owned(o) => visitor.visit_string(o),
+        }
+    }
+}
 
 macro_rules! deserialize_number {
     ($($method:ident => $visit:ident) *) => {
@@ -56,7 +139,11 @@ macro_rules! deserialize_number {
             where
                 V: de::Visitor<'de>,
             {
-                visitor.$visit(self.0.parse_number(self.1)?)
+                let raw = self.0.raw_slice();
+                self.0
+                    .parse_number(self.1)
+                    .and_then(|number| visitor.$visit(number))
+                    .map_err(|e| e.or_value(raw))
             }
         )*
     };
@@ -73,18 +160,36 @@ where
     where
         V: de::Visitor<'de>,
     {
-        match self.0.parse_str(self.1)? {
-            Reference::Borrowed(b) => visitor.visit_borrowed_str(b),
-            Reference::Copied(o) => visitor.visit_str(o),
-            Reference::Owned(o) => visitor.visit_string(o),
-        }
+        let raw = self.0.raw_slice();
+        self.visit_any(visitor).map_err(|e: Error| e.or_value(raw))
     }
 
     #[inline]
-    fn deserialize_newtype_struct<V>(self, _: &str, visitor: V) -> Result<V::Value, Error>
+    fn deserialize_newtype_struct<V>(self, name: &str, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
+        if name == presence::TOKEN && self.0.is_flag() {
+            return visitor.visit_unit();
+        }
+        if name == raw::TOKEN {
+            return match self.0.raw_slice() {
+                Some(slice) => visitor.visit_borrowed_bytes(slice),
+                None => Err(Error::new(ErrorKind::Other)
+                    .message(String::from("raw value is not available for decoded keys"))),
+            };
+        }
+        if let Some(delimiter) = separated::delimiter(name) {
+            return match self.0.raw_slice() {
+                Some(slice) => visitor.visit_newtype_struct(IterDeserializer(
+                    SeparatorValues::from_slice(slice, Delimiter::byte(delimiter)),
+                    self.1,
+                )),
+                None => Err(Error::new(ErrorKind::Other).message(String::from(
+                    "separated values are not supported for decoded keys",
+                ))),
+            };
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -93,20 +198,48 @@ where
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_bool(self.0.parse_bool(self.1)?)
+        let raw = self.0.raw_slice();
+        self.0
+            .parse_bool(self.1)
+            .and_then(|b| visitor.visit_bool(b))
+            .map_err(|e| e.or_value(raw))
     }
 
     #[inline]
     fn deserialize_enum<V>(
         self,
         _: &'static str,
-        _: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_enum(self)
+        let raw = self.0.raw_slice();
+        let config = self.1.config;
+        if !config.case_insensitive_enums && !config.numeric_enums {
+            return visitor.visit_enum(self).map_err(|e: Error| e.or_value(raw));
+        }
+
+        let value = self.0.parse_str(self.1).map_err(|e| e.or_value(raw))?;
+        if config.numeric_enums && !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+            if let Ok(index) = value.parse::<u32>() {
+                return visitor
+                    .visit_enum(de::IntoDeserializer::<Error>::into_deserializer(index))
+                    .map_err(|e: Error| e.or_value(raw));
+            }
+        }
+
+        let variant = match variants
+            .iter()
+            .find(|variant| variant.eq_ignore_ascii_case(&value))
+        {
+            Some(variant) if config.case_insensitive_enums => variant,
+            _ => &*value,
+        };
+        visitor
+            .visit_enum(de::IntoDeserializer::<Error>::into_deserializer(variant))
+            .map_err(|e: Error| e.or_value(raw))
     }
 
     #[inline]
@@ -114,7 +247,9 @@ where
     where
         V: de::Visitor<'de>,
     {
-        if self.0.is_none() {
+        if (self.0.is_none() && !self.1.config.present_as_some)
+            || (self.1.config.null_as_none && self.0.is_null())
+        {
             visitor.visit_none()
         } else {
             visitor.visit_some(self)
@@ -126,20 +261,55 @@ where
     where
         V: de::Visitor<'de>,
     {
+        if self.1.config.strict_keys {
+            return Err(unknown_key_error());
+        }
         visitor.visit_unit()
     }
 
+    /// Strings skip the type inference, and are borrowed from the input when there is nothing to
+    /// decode, so types parsed from strings(like `Uuid`) don't allocate
+    #[inline]
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.1.config.invalid_utf8 == InvalidUtf8::Bytes {
+            return self.deserialize_any(visitor);
+        }
+
+        let raw = self.0.raw_slice();
+        self.0
+            .parse_str(self.1)
+            .and_then(|value| match value {
+                Reference::Borrowed(b) => visitor.visit_borrowed_str(b),
+                Reference::Copied(c) => visitor.visit_str(c),
+                Reference::Owned(o) => visitor.visit_string(o),
+            })
+            .map_err(|e| e.or_value(raw))
+    }
+
+    #[inline]
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
     /// We don't check the bytes to be valid utf8
     #[inline]
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
+        let raw = self.0.raw_slice();
         match self.0.parse_bytes(self.1) {
             Reference::Borrowed(b) => visitor.visit_borrowed_bytes(b),
             Reference::Copied(c) => visitor.visit_bytes(c),
             Reference::Owned(o) => visitor.visit_byte_buf(o),
         }
+        .map_err(|e: Error| e.or_value(raw))
     }
 
     #[inline]
@@ -159,7 +329,7 @@ where
 
     forward_to_deserialize_any! {
         <W: Visitor<'de>>
-        char str string unit unit_struct map struct
+        char unit unit_struct map struct
         tuple seq tuple_struct
     }
 
@@ -202,7 +372,12 @@ pub trait IntoRawSlices<'de> {
 
     fn into_sized_iterator(self, size: usize) -> Result<Self::SizedIterator, Error>;
     fn into_unsized_iterator(self) -> Self::UnSizedIterator;
-    fn into_single_slice(self) -> RawSlice<'de>;
+    /// Returns the value to deserialize a single value from, based on the duplicate keys policy
+    fn into_single_slice(self, duplicate_keys: DuplicateKeys) -> Result<RawSlice<'de>, Error>;
+    /// Returns true if the single value is empty or a literal `null`
+    fn is_null(&self, duplicate_keys: DuplicateKeys) -> bool;
+    /// Returns true if the single value is a key without a value(`key`)
+    fn is_flag(&self, duplicate_keys: DuplicateKeys) -> bool;
 }
 
 impl<'de, 's, I> IntoDeserializer<'de, 's> for I
@@ -211,39 +386,35 @@ where
 {
     type Deserializer = IterDeserializer<'s, I>;
 
-    fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer {
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
         IterDeserializer(self, scratch)
     }
 }
 
-pub struct IterDeserializer<'s, I>(I, &'s mut Vec<u8>);
+pub struct IterDeserializer<'s, I>(I, &'s mut Scratch);
 
 impl<'de, 's, I> IterDeserializer<'s, I>
 where
     I: 'de + IntoRawSlices<'de>,
 {
-    fn parse_number<T>(self) -> Result<T, Error>
-    where
-        T: FromLexical,
-    {
-        self.0.into_single_slice().parse_number(self.1)
-    }
-
     #[inline]
-    fn into_slice_deserializer(self) -> ValueDeserializer<'s, RawSlice<'de>> {
-        ValueDeserializer(self.0.into_single_slice(), self.1)
+    fn into_slice_deserializer(self) -> Result<ValueDeserializer<'s, RawSlice<'de>>, Error> {
+        Ok(ValueDeserializer(
+            self.0.into_single_slice(self.1.config.duplicate_keys)?,
+            self.1,
+        ))
     }
 }
 
-macro_rules! deserialize_number {
-    ($($method:ident => $visit:ident) *) => {
+macro_rules! forward_to_slice_deserializer {
+    ($($method:ident ,)*) => {
         $(
             #[inline]
-            fn $method<V>(self, visitor: V) -> Result<V::Value,Error>
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
             where
                 V: de::Visitor<'de>,
             {
-                visitor.$visit(self.parse_number()?)
+                self.into_slice_deserializer()?.$method(visitor)
             }
         )*
     };
@@ -255,19 +426,38 @@ where
 {
     type Error = Error;
 
+    /// Self describing types(like flattened maps) get a sequence for multiple values, the same
+    /// as a [`Value`](crate::Value)
     #[inline]
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
-        self.into_slice_deserializer().deserialize_any(visitor)
+        let mut values: Vec<RawSlice<'de>> = self.0.into_unsized_iterator().collect();
+        if values.len() > 1 {
+            visitor.visit_seq(SizedIterDeserializer(values.into_iter(), self.1, 0))
+        } else {
+            ValueDeserializer(values.pop().unwrap_or_default(), self.1).deserialize_any(visitor)
+        }
     }
 
     #[inline]
-    fn deserialize_newtype_struct<V>(self, _: &str, visitor: V) -> Result<V::Value, Error>
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
     where
         V: de::Visitor<'de>,
     {
+        if name == presence::TOKEN && self.0.is_flag(self.1.config.duplicate_keys) {
+            return visitor.visit_unit();
+        }
+        if name == raw::TOKEN || separated::delimiter(name).is_some() {
+            return self
+                .into_slice_deserializer()?
+                .deserialize_newtype_struct(name, visitor);
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -276,7 +466,7 @@ where
     where
         V: de::Visitor<'de>,
     {
-        self.into_slice_deserializer().deserialize_bool(visitor)
+        self.into_slice_deserializer()?.deserialize_bool(visitor)
     }
 
     #[inline]
@@ -289,7 +479,7 @@ where
     where
         V: de::Visitor<'de>,
     {
-        self.into_slice_deserializer()
+        self.into_slice_deserializer()?
             .deserialize_enum(name, variants, visitor)
     }
 
@@ -298,7 +488,11 @@ where
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_some(self)
+        if self.1.config.null_as_none && self.0.is_null(self.1.config.duplicate_keys) {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
     }
 
     #[inline]
@@ -306,6 +500,9 @@ where
     where
         V: de::Visitor<'de>,
     {
+        if self.1.config.strict_keys {
+            return Err(unknown_key_error());
+        }
         visitor.visit_unit()
     }
 
@@ -315,7 +512,7 @@ where
     where
         V: de::Visitor<'de>,
     {
-        self.into_slice_deserializer().deserialize_bytes(visitor)
+        self.into_slice_deserializer()?.deserialize_bytes(visitor)
     }
 
     #[inline]
@@ -330,9 +527,24 @@ where
     where
         V: de::Visitor<'de>,
     {
+        if self.1.config.empty_as_empty_seq {
+            let values: Vec<RawSlice<'de>> = self.0.into_unsized_iterator().collect();
+            if let [value] = values.as_slice() {
+                if value.0.is_empty() {
+                    return visitor.visit_seq(SizedIterDeserializer(
+                        core::iter::empty(),
+                        self.1,
+                        0,
+                    ));
+                }
+            }
+            return visitor.visit_seq(SizedIterDeserializer(values.into_iter(), self.1, 0));
+        }
+
         visitor.visit_seq(SizedIterDeserializer(
             self.0.into_unsized_iterator(),
             self.1,
+            0,
         ))
     }
 
@@ -343,6 +555,7 @@ where
         visitor.visit_seq(SizedIterDeserializer(
             self.0.into_sized_iterator(len)?,
             self.1,
+            0,
         ))
     }
 
@@ -358,31 +571,47 @@ where
         visitor.visit_seq(SizedIterDeserializer(
             self.0.into_sized_iterator(len)?,
             self.1,
+            0,
         ))
     }
 
-    forward_to_deserialize_any! {
-        <W: Visitor<'de>>
-        char str string unit unit_struct map struct identifier
+    forward_to_slice_deserializer! {
+        deserialize_char, deserialize_str, deserialize_string, deserialize_unit,
+        deserialize_identifier, deserialize_map,
     }
 
-    deserialize_number!(
-        deserialize_i8 => visit_i8
-        deserialize_i16 => visit_i16
-        deserialize_i32 => visit_i32
-        deserialize_i64 => visit_i64
+    #[inline]
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.into_slice_deserializer()?
+            .deserialize_unit_struct(name, visitor)
+    }
 
-        deserialize_u8 => visit_u8
-        deserialize_u16 => visit_u16
-        deserialize_u32 => visit_u32
-        deserialize_u64 => visit_u64
+    #[inline]
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.into_slice_deserializer()?
+            .deserialize_struct(name, fields, visitor)
+    }
 
-        deserialize_f32 => visit_f32
-        deserialize_f64 => visit_f64
-    );
+    forward_to_slice_deserializer! {
+        deserialize_i8, deserialize_i16, deserialize_i32, deserialize_i64,
+        deserialize_u8, deserialize_u16, deserialize_u32, deserialize_u64,
+        deserialize_f32, deserialize_f64,
+    }
 }
 
-struct SizedIterDeserializer<'s, I>(I, &'s mut Vec<u8>);
+/// The values of a sequence, with the index of the next one to report the index of the errors
+struct SizedIterDeserializer<'s, I>(I, &'s mut Scratch, usize);
 
 impl<'de, 's, I> de::SeqAccess<'de> for SizedIterDeserializer<'s, I>
 where
@@ -394,9 +623,14 @@ where
     where
         T: de::DeserializeSeed<'de>,
     {
+        let index = self.2;
+        self.2 += 1;
         self.0
             .next()
-            .map(|v| seed.deserialize(v.into_deserializer(self.1)))
+            .map(|v| {
+                seed.deserialize(v.into_deserializer(self.1))
+                    .map_err(|e| with_key(e, index.to_string().as_bytes(), None))
+            })
             .transpose()
     }
 }
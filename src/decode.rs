@@ -1,4 +1,15 @@
-use std::borrow::{Borrow, Cow};
+use alloc::borrow::{Borrow, Cow, ToOwned};
+use alloc::vec::Vec;
+
+#[cfg(any(
+    feature = "serde",
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+use crate::config::{InvalidUtf8, QsConfig};
+use crate::scan::{memchr, memchr2};
 
 /// Parses a single percent encoded char
 #[inline]
@@ -6,10 +17,92 @@ pub fn parse_char(h: u8, l: u8) -> Option<u8> {
     Some(char::from(h).to_digit(16)? as u8 * 0x10 + char::from(l).to_digit(16)? as u8)
 }
 
+/// Parses the 4 hex digits of a `%uXXXX` escape
+fn parse_code_unit(slice: &[u8]) -> Option<u32> {
+    match slice {
+        [b'%', b'u' | b'U', a, b, c, d, ..] => {
+            Some(((parse_char(*a, *b)? as u32) << 8) | parse_char(*c, *d)? as u32)
+        }
+        _ => None,
+    }
+}
+
+/// Parses a legacy `%uXXXX` escape at the start of the slice, returning the char and the
+/// length of the escape
+///
+/// A high surrogate must be followed by the escape of a low surrogate(`%uD83D%uDE00`), they
+/// are decoded as a single char.
+pub fn parse_unicode_escape(slice: &[u8]) -> Option<(char, usize)> {
+    let unit = parse_code_unit(slice)?;
+    match unit {
+        0xD800..=0xDBFF => {
+            let low = parse_code_unit(&slice[6..])?;
+            if !(0xDC00..=0xDFFF).contains(&low) {
+                return None;
+            }
+            let c = char::from_u32(0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00))?;
+            Some((c, 12))
+        }
+        _ => char::from_u32(unit).map(|c| (c, 6)),
+    }
+}
+
+/// Returns the index of the first `%u` escape in the slice which is not a valid
+/// `%uXXXX` escape
+#[cfg(any(
+    feature = "serde",
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+pub fn find_invalid_unicode_escape(slice: &[u8]) -> Option<usize> {
+    let mut cursor = 0;
+    while cursor < slice.len() {
+        if slice[cursor] == b'%' && matches!(slice.get(cursor + 1), Some(b'u' | b'U')) {
+            match parse_unicode_escape(&slice[cursor..]) {
+                Some((_, len)) => cursor += len,
+                None => return Some(cursor),
+            }
+        } else {
+            cursor += 1;
+        }
+    }
+    None
+}
+
+/// Options changing how the slices are percent decoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodeOptions {
+    /// Decode `+` as a space, as in html forms
+    pub plus_as_space: bool,
+    /// Decode the legacy `%uXXXX` escapes as the utf-8 bytes of the char
+    pub unicode_escapes: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            plus_as_space: true,
+            unicode_escapes: false,
+        }
+    }
+}
+
 /// Decodes a slice and return a Reference pointer
+#[cfg(feature = "serde")]
 pub fn parse_bytes<'de, 's>(
     slice: &'de [u8],
     scratch: &'s mut Vec<u8>,
+) -> Reference<'de, 's, [u8]> {
+    parse_bytes_with(slice, scratch, DecodeOptions::default())
+}
+
+/// Decodes a slice using the options and return a Reference pointer
+pub fn parse_bytes_with<'de, 's>(
+    slice: &'de [u8],
+    scratch: &'s mut Vec<u8>,
+    options: DecodeOptions,
 ) -> Reference<'de, 's, [u8]> {
     scratch.clear();
 
@@ -19,33 +112,48 @@ pub fn parse_bytes<'de, 's>(
     // Index of the first byte not yet copied into the scratch space.
     let mut cursor = 0;
 
-    while let Some(v) = slice.get(cursor) {
-        match v {
-            b'+' => {
+    // The bytes between the escapes are copied as whole runs
+    while let Some(offset) = find_escape(&slice[cursor..], options) {
+        cursor += offset;
+        match slice[cursor] {
+            b'+' if options.plus_as_space => {
                 scratch.extend_from_slice(&slice[index..cursor]);
                 scratch.push(b' ');
 
                 cursor += 1;
                 index = cursor;
             }
-            b'%' => {
-                // we saw percentage
-                if slice.len() > cursor + 2 {
-                    match parse_char(slice[cursor + 1], slice[cursor + 2]) {
-                        Some(b) => {
-                            scratch.extend_from_slice(&slice[index..cursor]);
-                            scratch.push(b);
-
-                            cursor += 3;
-                            index = cursor;
-                        }
-                        None => {
-                            // If it wasn't valid, go to the next byte
-                            cursor += 1;
-                        }
+            b'%' if options.unicode_escapes
+                && matches!(slice.get(cursor + 1), Some(b'u' | b'U')) =>
+            {
+                match parse_unicode_escape(&slice[cursor..]) {
+                    Some((c, len)) => {
+                        scratch.extend_from_slice(&slice[index..cursor]);
+                        scratch.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+
+                        cursor += len;
+                        index = cursor;
+                    }
+                    None => {
+                        // Malformed escapes are kept as is
+                        cursor += 1;
+                    }
+                }
+            }
+            // we saw percentage
+            b'%' if slice.len() > cursor + 2 => {
+                match parse_char(slice[cursor + 1], slice[cursor + 2]) {
+                    Some(b) => {
+                        scratch.extend_from_slice(&slice[index..cursor]);
+                        scratch.push(b);
+
+                        cursor += 3;
+                        index = cursor;
+                    }
+                    None => {
+                        // If it wasn't valid, go to the next byte
+                        cursor += 1;
                     }
-                } else {
-                    cursor += 1;
                 }
             }
             _ => {
@@ -55,13 +163,171 @@ pub fn parse_bytes<'de, 's>(
     }
 
     if scratch.is_empty() {
-        Reference::Borrowed(&slice[index..cursor])
+        Reference::Borrowed(&slice[index..])
     } else {
-        scratch.extend_from_slice(&slice[index..cursor]);
+        scratch.extend_from_slice(&slice[index..]);
         Reference::Copied(scratch)
     }
 }
 
+/// Decodes the keys of the pairs, remembering the last one so a key repeated in a row
+/// (`v=1&v=2&v=3`) is only decoded once
+#[cfg(any(
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+#[derive(Default)]
+pub(crate) struct KeyDecoder<'a> {
+    scratch: Vec<u8>,
+    // The last raw key, and whether its decoded bytes are in the scratch
+    last: Option<(&'a [u8], bool)>,
+}
+
+#[cfg(any(
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+impl<'a> KeyDecoder<'a> {
+    pub(crate) fn decode<'s>(
+        &'s mut self,
+        key: &'a [u8],
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        match self.last {
+            Some((last, true)) if last == key => return Reference::Copied(&self.scratch),
+            Some((last, false)) if last == key => return Reference::Borrowed(key),
+            _ => {}
+        }
+
+        let decoded = parse_bytes_with(key, &mut self.scratch, options);
+        self.last = Some((key, matches!(decoded, Reference::Copied(_))));
+        decoded
+    }
+}
+
+/// Returns the index of the first byte which may need decoding, a `%` or a `+`
+#[inline]
+fn find_escape(slice: &[u8], options: DecodeOptions) -> Option<usize> {
+    if options.plus_as_space {
+        memchr2(b'%', b'+', slice)
+    } else {
+        memchr(b'%', slice)
+    }
+}
+
+/// Returns the length of the slice once percent decoded, without decoding it
+#[cfg(any(
+    feature = "serde",
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+pub fn decoded_len(slice: &[u8], options: DecodeOptions) -> usize {
+    let mut len = slice.len();
+    let mut cursor = 0;
+
+    while let Some(offset) = memchr(b'%', &slice[cursor..]) {
+        cursor += offset;
+
+        if options.unicode_escapes {
+            if let Some((c, escape_len)) = parse_unicode_escape(&slice[cursor..]) {
+                cursor += escape_len;
+                len -= escape_len - c.len_utf8();
+                continue;
+            }
+        }
+
+        if slice.len() > cursor + 2 && parse_char(slice[cursor + 1], slice[cursor + 2]).is_some() {
+            cursor += 3;
+            len -= 2;
+        } else {
+            cursor += 1;
+        }
+    }
+
+    len
+}
+
+/// Why a raw pair can't be parsed with the options of a config
+#[cfg(any(
+    feature = "serde",
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+pub(crate) enum InvalidPair {
+    /// A malformed `%u` escape, at the index in the pair
+    UnicodeEscape(usize),
+    /// The key or the value is longer than the max length
+    TooLong(usize),
+    /// The key or the value is not valid utf-8, only checked with `skip_invalid_pairs`
+    InvalidUtf8,
+}
+
+/// Checks a raw pair against the limits of the config, before it is parsed
+#[cfg(any(
+    feature = "serde",
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+pub(crate) fn check_pair(pair: &[u8], config: &QsConfig) -> Result<(), InvalidPair> {
+    let options = config.decode_options;
+    if options.unicode_escapes {
+        if let Some(index) = find_invalid_unicode_escape(pair) {
+            return Err(InvalidPair::UnicodeEscape(index));
+        }
+    }
+
+    let (key, value) = match memchr(b'=', pair) {
+        Some(index) => (&pair[..index], &pair[index + 1..]),
+        None => (pair, &pair[pair.len()..]),
+    };
+    if let Some(max_length) = config.max_length {
+        if decoded_len(key, options) > max_length || decoded_len(value, options) > max_length {
+            return Err(InvalidPair::TooLong(max_length));
+        }
+    }
+
+    if config.skip_invalid_pairs && config.invalid_utf8 == InvalidUtf8::Error {
+        let mut scratch = Vec::new();
+        for slice in [key, value] {
+            if core::str::from_utf8(&parse_bytes_with(slice, &mut scratch, options)).is_err() {
+                return Err(InvalidPair::InvalidUtf8);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the length of the pair at the start of the slice if it is dropped, with
+/// `skip_invalid_pairs`
+#[cfg(any(
+    feature = "serde",
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+#[inline]
+pub(crate) fn invalid_pair_len(slice: &[u8], config: &QsConfig) -> Option<usize> {
+    if !config.skip_invalid_pairs {
+        return None;
+    }
+
+    let len = config.pair_separator.find(slice);
+    check_pair(&slice[..len], config).err().map(|_| len)
+}
+
 /// A struct that can hold an owned or borrowed value
 ///
 /// The difference between `Reference` and `Cow` is that it can contain a reference
@@ -72,6 +338,8 @@ where
 {
     Borrowed(&'b T),
     Copied(&'c T),
+    // Only built by the unescaping of the delimited modes, and the deserializers
+    #[cfg_attr(not(any(feature = "serde", feature = "brackets")), allow(dead_code))]
     Owned(<T as ToOwned>::Owned),
 }
 
@@ -87,6 +355,7 @@ where
         }
     }
 
+    #[cfg(feature = "serde")]
     pub fn try_map<F, B, E>(self, f: F) -> Result<Reference<'b, 'c, B>, E>
     where
         F: FnOnce(&T) -> Result<&B, E>,
@@ -100,7 +369,7 @@ where
     }
 }
 
-impl<'b, 'c, T> std::ops::Deref for Reference<'b, 'c, T>
+impl<'b, 'c, T> core::ops::Deref for Reference<'b, 'c, T>
 where
     T: ?Sized + 'static + ToOwned,
 {
@@ -0,0 +1,130 @@
+/// Upper case hex digits used for percent encoding
+const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Returns the mask of the ascii bytes in the given list
+const fn mask_of(bytes: &[u8]) -> u128 {
+    let mut mask = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        mask |= 1 << bytes[index];
+        index += 1;
+    }
+    mask
+}
+
+/// Alphanumeric ascii bytes, which are never percent encoded by the presets
+const ALPHANUMERIC: u128 =
+    mask_of(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789");
+
+/// Bytes which are always percent encoded, as they would change the meaning of the query
+const ALWAYS_ENCODED: u128 = mask_of(b" %&=+#");
+
+/// The set of ascii bytes which are written as is by the serializer, every other byte is
+/// percent encoded.
+///
+/// `%`, `&`, `=`, `+`, `#` and space are always encoded, whatever the set is, as they would
+/// change the meaning of the query string. Bytes outside of the ascii range are always encoded too.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{EncodeSet, ParseMode, SerializerConfig};
+///
+/// // Keep the slashes of paths readable, and encode spaces as `%20`
+/// let set = EncodeSet::RFC3986.add(b'/');
+/// let config = SerializerConfig::new(ParseMode::Duplicate).encode_set(set);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncodeSet {
+    // Bit `n` is set if the ascii byte `n` is written as is
+    unreserved: u128,
+    space_as_plus: bool,
+}
+
+impl EncodeSet {
+    /// The `application/x-www-form-urlencoded` set of HTML5, which is the default.
+    ///
+    /// Alphanumerics and `*-._` are written as is, and spaces are encoded as `+`.
+    pub const FORM: EncodeSet = EncodeSet {
+        unreserved: ALPHANUMERIC | mask_of(b"*-._"),
+        space_as_plus: true,
+    };
+
+    /// The unreserved set of RFC 3986.
+    ///
+    /// Alphanumerics and `-._~` are written as is, and spaces are encoded as `%20`.
+    pub const RFC3986: EncodeSet = EncodeSet {
+        unreserved: ALPHANUMERIC | mask_of(b"-._~"),
+        space_as_plus: false,
+    };
+
+    /// Write the ascii byte as is, unless it is one of the bytes which are always encoded
+    pub const fn add(mut self, byte: u8) -> Self {
+        if byte < 128 {
+            self.unreserved |= 1 << byte;
+        }
+        self
+    }
+
+    /// Percent encode the byte
+    pub const fn remove(mut self, byte: u8) -> Self {
+        if byte < 128 {
+            self.unreserved &= !(1 << byte);
+        }
+        self
+    }
+
+    /// Encode spaces as `+` instead of `%20`
+    pub const fn space_as_plus(mut self, space_as_plus: bool) -> Self {
+        self.space_as_plus = space_as_plus;
+        self
+    }
+
+    /// Returns true if the byte can be written as is
+    #[inline]
+    fn is_unreserved(&self, b: u8) -> bool {
+        b < 128 && (self.unreserved & !ALWAYS_ENCODED) & (1 << b) != 0
+    }
+}
+
+impl Default for EncodeSet {
+    fn default() -> Self {
+        EncodeSet::FORM
+    }
+}
+
+/// Percent encodes a slice and appends the result to the output
+///
+/// Every byte outside of the set is percent encoded, so the output can be decoded back by
+/// any of the parsers.
+pub fn encode_bytes(slice: &[u8], set: &EncodeSet, output: &mut Vec<u8>) {
+    encode(slice, set, None, output)
+}
+
+/// Percent encodes a slice like `encode_bytes`, but makes sure the `reserved` byte
+/// never appears in the output, even if it is normally written as is.
+///
+/// It is used to encode the values joined by a delimiter.
+pub fn encode_bytes_reserving(slice: &[u8], set: &EncodeSet, reserved: u8, output: &mut Vec<u8>) {
+    encode(slice, set, Some(reserved), output)
+}
+
+fn encode(slice: &[u8], set: &EncodeSet, reserved: Option<u8>, output: &mut Vec<u8>) {
+    // Index of the first byte not yet copied into the output
+    let mut index = 0;
+
+    for (cursor, b) in slice.iter().enumerate() {
+        if set.is_unreserved(*b) && Some(*b) != reserved {
+            continue;
+        }
+
+        output.extend_from_slice(&slice[index..cursor]);
+        if *b == b' ' && set.space_as_plus && reserved != Some(b'+') {
+            output.push(b'+');
+        } else {
+            output.extend_from_slice(&[b'%', HEX[(b >> 4) as usize], HEX[(b & 0x0F) as usize]]);
+        }
+        index = cursor + 1;
+    }
+
+    output.extend_from_slice(&slice[index..]);
+}
@@ -0,0 +1,156 @@
+//! A C interface parsing query strings into json, enabled by the `ffi` feature
+//!
+//! It lets servers written in other languages(NGINX or Envoy modules) share the parser of the
+//! Rust services. Build the crate as a C library with
+//! `cargo rustc --release --features ffi --crate-type cdylib`(or `staticlib`), and declare:
+//!
+//! ```c
+//! typedef struct {
+//!     int32_t status;
+//!     uint8_t *data;
+//!     size_t len;
+//! } QsResult;
+//!
+//! QsResult qs_parse_to_json(const uint8_t *input, size_t len, uint32_t mode);
+//! void qs_result_free(QsResult result);
+//! ```
+//!
+//! The mode is one of the `QS_MODE_*` constants, the delimiter modes take their delimiter in
+//! the second byte(`QS_MODE_DELIMITER | ('|' << 8)`).
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::{ptr, slice};
+
+use crate::json::to_json;
+use crate::ParseMode;
+
+/// Simple mode, see [`ParseMode::UrlEncoded`]
+pub const QS_MODE_URLENCODED: u32 = 0;
+/// Repeated key mode, see [`ParseMode::Duplicate`]
+pub const QS_MODE_DUPLICATE: u32 = 1;
+/// Delimiter mode, with the delimiter in the second byte. See [`ParseMode::Delimiter`]
+pub const QS_MODE_DELIMITER: u32 = 2;
+/// Brackets mode, see [`ParseMode::Brackets`]
+pub const QS_MODE_BRACKETS: u32 = 3;
+/// Dot notation mode, see [`ParseMode::DotNotation`]
+pub const QS_MODE_DOT_NOTATION: u32 = 4;
+/// Brackets with delimiter mode, with the delimiter in the second byte. See
+/// [`ParseMode::BracketsDelimiter`]
+pub const QS_MODE_BRACKETS_DELIMITER: u32 = 5;
+/// Matrix mode, see [`ParseMode::Matrix`]
+pub const QS_MODE_MATRIX: u32 = 6;
+/// Rack mode, see [`ParseMode::Rack`]
+pub const QS_MODE_RACK: u32 = 7;
+/// PHP mode, see [`ParseMode::Php`]
+pub const QS_MODE_PHP: u32 = 8;
+
+/// The query string was parsed, the data is its json
+pub const QS_OK: i32 = 0;
+/// The input is null
+pub const QS_ERROR_NULL_INPUT: i32 = 1;
+/// The mode is not one of the `QS_MODE_*` constants
+pub const QS_ERROR_INVALID_MODE: i32 = 2;
+/// The query string can't be parsed, the data is the error message
+pub const QS_ERROR_PARSE: i32 = 3;
+/// The parser panicked
+pub const QS_ERROR_PANIC: i32 = 4;
+
+/// The status of a call, and the buffer it returns
+///
+/// The buffer is owned by the caller, who frees it with [`qs_result_free`]. It is null when
+/// there is nothing to return.
+#[repr(C)]
+pub struct QsResult {
+    pub status: i32,
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl QsResult {
+    fn new(status: i32, data: Vec<u8>) -> Self {
+        let len = data.len();
+        let data = Box::into_raw(data.into_boxed_slice());
+        Self {
+            status,
+            data: data as *mut u8,
+            len,
+        }
+    }
+
+    fn empty(status: i32) -> Self {
+        Self {
+            status,
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+/// Returns the parse mode of a `QS_MODE_*` constant
+fn parse_mode(mode: u32) -> Option<ParseMode> {
+    let delimiter = (mode >> 8) as u8;
+    if mode >> 16 != 0 {
+        return None;
+    }
+
+    match mode & 0xFF {
+        QS_MODE_URLENCODED => Some(ParseMode::UrlEncoded),
+        QS_MODE_DUPLICATE => Some(ParseMode::Duplicate),
+        QS_MODE_DELIMITER if delimiter != 0 => Some(ParseMode::Delimiter(delimiter)),
+        QS_MODE_BRACKETS => Some(ParseMode::Brackets),
+        QS_MODE_DOT_NOTATION => Some(ParseMode::DotNotation),
+        QS_MODE_BRACKETS_DELIMITER if delimiter != 0 => {
+            Some(ParseMode::BracketsDelimiter(delimiter))
+        }
+        QS_MODE_MATRIX => Some(ParseMode::Matrix),
+        QS_MODE_RACK => Some(ParseMode::Rack),
+        QS_MODE_PHP => Some(ParseMode::Php),
+        _ => None,
+    }
+}
+
+/// Parse `len` bytes of query string into a json object, in the mode of a `QS_MODE_*` constant
+///
+/// Every value is a json string, and keys with multiple values are arrays or nested objects.
+/// On success the status is `QS_OK` and the data is the json, when the query string can't be
+/// parsed the status is `QS_ERROR_PARSE` and the data is the error message.
+///
+/// # Safety
+/// `input` must point to `len` readable bytes, it can be null when `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn qs_parse_to_json(input: *const u8, len: usize, mode: u32) -> QsResult {
+    let input = if input.is_null() {
+        if len != 0 {
+            return QsResult::empty(QS_ERROR_NULL_INPUT);
+        }
+        &[]
+    } else {
+        slice::from_raw_parts(input, len)
+    };
+    let mode = match parse_mode(mode) {
+        Some(mode) => mode,
+        None => return QsResult::empty(QS_ERROR_INVALID_MODE),
+    };
+
+    // Unwinding into the caller is undefined behavior
+    let result = catch_unwind(AssertUnwindSafe(|| to_json(input, mode)));
+    match result {
+        Ok(Ok(json)) => QsResult::new(QS_OK, json.to_string().into_bytes()),
+        Ok(Err(error)) => QsResult::new(QS_ERROR_PARSE, error.to_string().into_bytes()),
+        Err(_) => QsResult::empty(QS_ERROR_PANIC),
+    }
+}
+
+/// Frees the buffer of a result
+///
+/// # Safety
+/// The result must be returned by this library, and freed only once.
+#[no_mangle]
+pub unsafe extern "C" fn qs_result_free(result: QsResult) {
+    if !result.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            result.data,
+            result.len,
+        )));
+    }
+}
@@ -0,0 +1,199 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use _serde::de::{self, Deserialize};
+use _serde::ser::{self, SerializeMap};
+
+use crate::de::{Error, ErrorKind};
+use crate::separated::CommaSeparated;
+use crate::value::Value;
+
+/// A comparison of a [`Filter`], named by its operator(`filter[price][gte]=10`)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Comparison<T> {
+    /// `eq`, or a value without an operator(`filter[price]=10`)
+    Eq(T),
+    /// `ne`
+    Ne(T),
+    /// `gt`
+    Gt(T),
+    /// `gte`
+    Gte(T),
+    /// `lt`
+    Lt(T),
+    /// `lte`
+    Lte(T),
+    /// `in`, the values are separated by commas(`filter[id][in]=1,2`) or repeated
+    In(Vec<T>),
+    /// `like`, the pattern is kept as is
+    Like(String),
+}
+
+impl<T> Comparison<T> {
+    /// Returns the name of the operator
+    pub fn operator(&self) -> &'static str {
+        match self {
+            Comparison::Eq(_) => "eq",
+            Comparison::Ne(_) => "ne",
+            Comparison::Gt(_) => "gt",
+            Comparison::Gte(_) => "gte",
+            Comparison::Lt(_) => "lt",
+            Comparison::Lte(_) => "lte",
+            Comparison::In(_) => "in",
+            Comparison::Like(_) => "like",
+        }
+    }
+}
+
+impl<T> Comparison<T>
+where
+    T: de::DeserializeOwned,
+{
+    fn from_value(operator: &str, value: Value) -> Result<Self, Error> {
+        Ok(match operator {
+            "eq" => Comparison::Eq(value.deserialize_into()?),
+            "ne" => Comparison::Ne(value.deserialize_into()?),
+            "gt" => Comparison::Gt(value.deserialize_into()?),
+            "gte" => Comparison::Gte(value.deserialize_into()?),
+            "lt" => Comparison::Lt(value.deserialize_into()?),
+            "lte" => Comparison::Lte(value.deserialize_into()?),
+            "in" => Comparison::In(
+                value
+                    .deserialize_into::<CommaSeparated<Vec<T>>>()?
+                    .into_inner(),
+            ),
+            "like" => Comparison::Like(value.deserialize_into()?),
+            _ => {
+                return Err(Error::new(ErrorKind::UnknownKey)
+                    .message(format!("`{}` is not a filter operator", operator))
+                    .value(operator.as_bytes()))
+            }
+        })
+    }
+}
+
+/// The comparisons of a field, from a map of operators(`filter[price][gte]=10&filter[price][lte]=99`)
+///
+/// The operators are `eq`, `ne`, `gt`, `gte`, `lt`, `lte`, `in` and `like`, and a value without
+/// an operator(`filter[price]=10`) is `eq`. The maps come from the brackets mode, the comparisons
+/// are ordered by their operators and an unknown operator is an error.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_str, Comparison, Filter, ParseMode};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Filters {
+///     price: Filter<u32>,
+///     #[serde(default)]
+///     id: Filter<u32>,
+/// }
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     filter: Filters,
+/// }
+///
+/// let query: Query = from_str(
+///     "filter[price][gte]=10&filter[price][lte]=99&filter[id][in]=1,2",
+///     ParseMode::Brackets,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(*query.filter.price, [Comparison::Gte(10), Comparison::Lte(99)]);
+/// assert_eq!(*query.filter.id, [Comparison::In(vec![1, 2])]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Filter<T> {
+    comparisons: Vec<Comparison<T>>,
+}
+
+impl<T> Filter<T> {
+    /// Creates a filter from its comparisons
+    pub fn new(comparisons: Vec<Comparison<T>>) -> Self {
+        Self { comparisons }
+    }
+
+    /// Returns the comparisons
+    pub fn into_comparisons(self) -> Vec<Comparison<T>> {
+        self.comparisons
+    }
+}
+
+impl<T> Filter<T>
+where
+    T: de::DeserializeOwned,
+{
+    /// Takes the comparisons out of a parsed value
+    pub fn from_value(value: Value) -> Result<Self, Error> {
+        let comparisons = match value {
+            Value::Map(map) => map
+                .into_iter()
+                .map(|(operator, value)| Comparison::from_value(&operator, value))
+                .collect::<Result<_, _>>()?,
+            value => vec![Comparison::Eq(value.deserialize_into()?)],
+        };
+        Ok(Self { comparisons })
+    }
+}
+
+impl<T> Deref for Filter<T> {
+    type Target = [Comparison<T>];
+
+    fn deref(&self) -> &[Comparison<T>] {
+        &self.comparisons
+    }
+}
+
+impl<T> Default for Filter<T> {
+    fn default() -> Self {
+        Self {
+            comparisons: Vec::new(),
+        }
+    }
+}
+
+impl<T> ser::Serialize for Filter<T>
+where
+    T: ser::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.comparisons.len()))?;
+        for comparison in &self.comparisons {
+            let operator = comparison.operator();
+            match comparison {
+                Comparison::Eq(value)
+                | Comparison::Ne(value)
+                | Comparison::Gt(value)
+                | Comparison::Gte(value)
+                | Comparison::Lt(value)
+                | Comparison::Lte(value) => map.serialize_entry(operator, value)?,
+                Comparison::In(values) => map.serialize_entry(operator, &CommaSeparated(values))?,
+                Comparison::Like(pattern) => map.serialize_entry(operator, pattern)?,
+            }
+        }
+        map.end()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Filter<T>
+where
+    T: de::DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Self::from_value(Value::deserialize(deserializer)?)
+            .map_err(|e| de::Error::custom(e.message))
+    }
+}
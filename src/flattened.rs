@@ -0,0 +1,99 @@
+use core::ops::{Deref, DerefMut};
+
+use _serde::{de, ser};
+
+use crate::value::{Value, ValueVisitor};
+
+/// A struct flattened into its parent with `#[serde(flatten)]`, whose fields are parsed like
+/// the ones of the parent
+///
+/// Serde buffers the values of flattened fields, so numbers and booleans can't be parsed from
+/// the query string anymore, and a flattened struct with such fields fails to deserialize.
+/// Wrapping it keeps the buffered pairs as a [`Value`], which parses them the same way the
+/// query string does, in every mode.
+///
+/// Only the last value of a repeated key is used for a single value, and the options of
+/// the config(like `lenient_bool`) don't apply to the wrapped struct.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_str, Flattened, ParseMode};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Pagination {
+///     page: u32,
+///     per_page: Option<u32>,
+/// }
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Request {
+///     sort: String,
+///     #[serde(flatten)]
+///     pagination: Flattened<Pagination>,
+/// }
+///
+/// let request: Request = from_str("page=2&sort=name&per_page=10", ParseMode::UrlEncoded).unwrap();
+///
+/// assert_eq!(request.pagination.page, 2);
+/// assert_eq!(request.pagination.per_page, Some(10));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Flattened<T>(pub T);
+
+impl<T> Flattened<T> {
+    /// Returns the wrapped value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Flattened<T> {
+    fn from(value: T) -> Self {
+        Flattened(value)
+    }
+}
+
+impl<T> Deref for Flattened<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Flattened<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> ser::Serialize for Flattened<T>
+where
+    T: ser::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T> de::Deserialize<'de> for Flattened<T>
+where
+    T: de::DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let value: Value = deserializer.deserialize_map(ValueVisitor)?;
+        value
+            .deserialize_into()
+            .map(Flattened)
+            .map_err(de::Error::custom)
+    }
+}
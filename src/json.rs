@@ -0,0 +1,99 @@
+//! Conversion between query strings and `serde_json::Value`
+//!
+//! Enabled by the `json` feature.
+
+use _serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use _serde_json::{Map, Value as JsonValue};
+
+use crate::de::{Error, QsConfig};
+use crate::ser::{to_string, SerializerConfig};
+use crate::value::{from_bytes_value, Value};
+
+impl From<Value> for JsonValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::String(s) => JsonValue::String(s),
+            Value::Sequence(values) => {
+                JsonValue::Array(values.into_iter().map(JsonValue::from).collect())
+            }
+            Value::Map(map) => JsonValue::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, JsonValue::from(value)))
+                    .collect::<Map<String, JsonValue>>(),
+            ),
+        }
+    }
+}
+
+/// Parse bytes of query string into a json object
+///
+/// Query strings have no types, so every value is a json string. Use [`from_bytes_value`]
+/// and deserialize into a typed value instead when the shape is known.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{json::to_json, ParseMode};
+///
+/// let json = to_json(b"user[name]=John&tags[]=a&tags[]=b", ParseMode::Brackets).unwrap();
+///
+/// assert_eq!(json["user"]["name"], "John");
+/// assert_eq!(json["tags"][1], "b");
+/// ```
+pub fn to_json<C: Into<QsConfig>>(input: &[u8], config: C) -> Result<JsonValue, Error> {
+    from_bytes_value(input, config).map(JsonValue::from)
+}
+
+/// Serialize a json object into a query string
+///
+/// Numbers and booleans are written as is and `null` values are skipped(or written based on
+/// the `none_style` of the config). The value should be an object, and nested arrays and objects
+/// are only supported in brackets mode.
+///
+/// # Example
+/// ```rust
+///# use _serde_json as serde_json;
+/// use serde_json::json;
+/// use serde_querystring::{json::from_json, ParseMode};
+///
+/// let json = json!({ "page": 2, "tags": ["a", "b"], "filter": null });
+///
+/// assert_eq!(
+///     from_json(&json, ParseMode::Duplicate).unwrap(),
+///     "page=2&tags=a&tags=b"
+/// );
+/// ```
+pub fn from_json<C>(value: &JsonValue, config: C) -> Result<String, Error>
+where
+    C: Into<SerializerConfig>,
+{
+    to_string(&NullAsNone(value), config)
+}
+
+/// Serializes json values, with `null` values serialized as `None` instead of unit
+struct NullAsNone<'a>(&'a JsonValue);
+
+impl<'a> Serialize for NullAsNone<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0 {
+            JsonValue::Null => serializer.serialize_none(),
+            JsonValue::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(&NullAsNone(value))?;
+                }
+                seq.end()
+            }
+            JsonValue::Object(map) => {
+                let mut ser = serializer.serialize_map(Some(map.len()))?;
+                for (key, value) in map {
+                    ser.serialize_entry(key, &NullAsNone(value))?;
+                }
+                ser.end()
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
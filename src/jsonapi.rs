@@ -0,0 +1,154 @@
+//! The query parameters of [JSON:API](https://jsonapi.org/format/#fetching), as typed values
+//!
+//! Enabled by the `jsonapi` feature.
+//!
+//! # Example
+//! ```rust
+//! use serde_querystring::jsonapi::JsonApiQuery;
+//!
+//! let query = JsonApiQuery::from_bytes(
+//!     b"include=author,comments.author&fields[articles]=title,body&sort=-created,title&page[number]=2",
+//! )
+//! .unwrap();
+//!
+//! assert!(query.includes("comments"));
+//! assert_eq!(query.fields("articles").unwrap(), ["title", "body"]);
+//! assert!(query.sort[0].is_descending());
+//! assert_eq!(query.sort[1].field, "title");
+//! assert_eq!(query.page.number, Some(2));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use _serde::de::{self, Deserialize};
+
+use crate::de::{Error, ErrorKind, ParseMode};
+use crate::sort::SortField;
+use crate::value::{from_bytes_value, Value};
+
+/// The `page[number]` and `page[size]` parameters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Page {
+    pub number: Option<u64>,
+    pub size: Option<u64>,
+}
+
+/// The query parameters defined by JSON:API
+///
+/// - `fields[TYPE]=a,b` is the sparse fieldset of a type, an empty value selects no fields
+/// - `include=a,b.c` are the relationship paths to include
+/// - `page[number]` and `page[size]` are the page
+/// - `sort=-a,b` are the sort fields, in order
+///
+/// Other parameters, like `filter`, are ignored. It implements `Deserialize` too, so it can be
+/// extracted by the web frameworks, or flattened into a type with the other parameters.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JsonApiQuery {
+    pub fields: BTreeMap<String, Vec<String>>,
+    pub include: Vec<String>,
+    pub page: Page,
+    pub sort: Vec<SortField>,
+}
+
+/// The comma separated items of a parameter, the empty ones are skipped
+fn items(value: Value) -> Result<Vec<String>, Error> {
+    Ok(value
+        .deserialize_into::<Vec<String>>()?
+        .iter()
+        .flat_map(|value| value.split(','))
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+impl JsonApiQuery {
+    /// Parse bytes of query string into the JSON:API parameters
+    pub fn from_bytes(input: &[u8]) -> Result<Self, Error> {
+        Self::from_value(from_bytes_value(input, ParseMode::BracketsDelimiter(b','))?)
+    }
+
+    /// Takes the JSON:API parameters out of a parsed query string
+    pub fn from_value(value: Value) -> Result<Self, Error> {
+        let mut map = match value {
+            Value::Map(map) => map,
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidType)
+                    .message(String::from("expected a map of parameters")))
+            }
+        };
+        let mut query = Self::default();
+
+        if let Some(fields) = map.remove("fields") {
+            let fields = match fields {
+                Value::Map(fields) => fields,
+                _ => {
+                    return Err(Error::new(ErrorKind::InvalidType)
+                        .message(String::from(
+                            "expected the fields of each type(`fields[TYPE]`)",
+                        ))
+                        .value(b"fields"))
+                }
+            };
+            for (kind, value) in fields {
+                query.fields.insert(kind, items(value)?);
+            }
+        }
+
+        if let Some(include) = map.remove("include") {
+            query.include = items(include)?;
+        }
+
+        if let Some(Value::Map(mut page)) = map.remove("page") {
+            if let Some(number) = page.remove("number") {
+                query.page.number = number.deserialize_into()?;
+            }
+            if let Some(size) = page.remove("size") {
+                query.page.size = size.deserialize_into()?;
+            }
+        }
+
+        if let Some(sort) = map.remove("sort") {
+            query.sort = items(sort)?
+                .iter()
+                .map(|field| field.parse())
+                .collect::<Result<_, _>>()?;
+        }
+
+        Ok(query)
+    }
+
+    /// Returns the sparse fieldset of a type, `None` when all of its fields are requested
+    pub fn fields(&self, kind: &str) -> Option<&[String]> {
+        self.fields.get(kind).map(Vec::as_slice)
+    }
+
+    /// Returns true if a relationship path is included, directly or as the start of a longer
+    /// path(`comments` is included by `comments.author`)
+    pub fn includes(&self, path: &str) -> bool {
+        self.include.iter().any(|include| {
+            include
+                .strip_prefix(path)
+                .map_or(false, |rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+}
+
+impl FromStr for JsonApiQuery {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Self::from_bytes(s.as_bytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonApiQuery {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Self::from_value(Value::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
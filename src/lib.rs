@@ -1,16 +1,204 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+mod config;
 mod decode;
+#[cfg(all(feature = "serde", feature = "std"))]
+mod encode;
+mod scan;
 
-#[doc(hidden)]
 pub mod parsers;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+
+#[cfg(feature = "serde")]
+mod arena;
+
+#[cfg(feature = "base64")]
+mod base64;
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod datetime;
+
 #[cfg(feature = "serde")]
 #[doc(hidden)]
 pub mod de;
 
-pub use parsers::{BracketsQS, DelimiterQS, DuplicateQS, UrlEncodedQS};
+#[cfg(all(feature = "serde", feature = "std"))]
+#[doc(hidden)]
+pub mod ser;
+
+#[cfg(all(feature = "serde", feature = "std"))]
+mod canonical;
+
+#[cfg(all(feature = "serde", feature = "std"))]
+pub mod compat;
+
+#[cfg(all(feature = "serde", feature = "std"))]
+mod merge;
+
+#[cfg(feature = "serde")]
+mod cow;
+
+#[cfg(feature = "serde")]
+mod filter;
+
+#[cfg(feature = "serde")]
+mod flattened;
+
+#[cfg(feature = "serde")]
+mod pairs;
+
+#[cfg(feature = "serde")]
+mod php;
 
 #[cfg(feature = "serde")]
+mod presence;
+
+#[cfg(feature = "serde")]
+mod rack;
+
+#[cfg(feature = "serde")]
+mod raw;
+
+#[cfg(feature = "serde")]
+mod separated;
+
+#[cfg(feature = "bytes")]
+mod shared;
+
+#[cfg(feature = "serde")]
+mod sort;
+
+#[cfg(feature = "serde")]
+mod value;
+
+pub use config::{
+    camel_to_snake, DuplicateKeys, EmptyBrackets, InvalidUtf8, JsQsOptions, KeyConflict,
+    KeyTransform, PairSeparator, ParseMode, QsConfig, QsConfigBuilder, SparseIndexes,
+    DEFAULT_MAX_DEPTH, DEFAULT_MAX_INDEX,
+};
+pub use parsers::{CookieQS, QsParser};
+
+#[cfg(feature = "brackets")]
+pub use parsers::BracketsQS;
+
+#[cfg(feature = "delimiter")]
+pub use parsers::DelimiterQS;
+
+#[cfg(feature = "duplicate")]
+pub use parsers::DuplicateQS;
+
+#[cfg(feature = "urlencoded")]
+pub use parsers::UrlEncodedQS;
+
+#[cfg(feature = "serde")]
+#[doc(inline)]
+pub use de::{
+    from_bytes, from_bytes_auto, from_bytes_collect_errors, from_bytes_owned, from_bytes_partial,
+    from_bytes_with, from_str, from_str_auto, from_str_collect_errors, from_str_owned,
+    from_str_partial, from_str_with, invalid_pairs, Error, ErrorKind, QsBuffers,
+};
+
+#[cfg(all(feature = "serde", feature = "brackets"))]
 #[doc(inline)]
-pub use de::{from_bytes, from_str, Error, ErrorKind, ParseMode};
+pub use de::{from_bytes_scoped, from_str_scoped};
+
+#[cfg(all(feature = "serde", feature = "std"))]
+#[doc(inline)]
+pub use ser::{
+    to_bytes, to_string, to_writer, EncodeSet, NoneStyle, QueryStringBuilder, SerializerConfig,
+};
+
+#[cfg(all(feature = "serde", feature = "std"))]
+pub use canonical::{canonicalize, CanonicalizeOptions};
+
+#[cfg(all(feature = "serde", feature = "std"))]
+pub use merge::{diff, merge, MergePolicy, QueryChange};
+
+#[cfg(feature = "serde")]
+pub use cow::CowStr;
+
+#[cfg(feature = "serde")]
+pub use filter::{Comparison, Filter};
+
+#[cfg(feature = "serde")]
+pub use flattened::Flattened;
+
+#[cfg(feature = "serde")]
+pub use pairs::{get_value, parse_pairs, PairStream};
+
+#[cfg(all(feature = "serde", feature = "brackets"))]
+pub use pairs::get_path;
+
+#[cfg(all(feature = "serde", feature = "std"))]
+pub use pairs::parse_multimap;
+
+#[cfg(feature = "serde")]
+pub use presence::Presence;
+
+#[cfg(feature = "serde")]
+pub use raw::RawQsValue;
+
+#[cfg(feature = "serde")]
+pub use separated::{CommaSeparated, PipeSeparated};
+
+#[cfg(feature = "serde")]
+pub use sort::{SortDirection, SortField, SortRules, SortSpec, Unrestricted};
+
+#[cfg(feature = "serde")]
+pub use value::{from_bytes_value, from_str_value, Map, Value};
+
+#[cfg(feature = "base64")]
+pub use base64::{Base64, Base64Url};
+
+#[cfg(feature = "bstr")]
+pub use _bstr::{BStr, BString};
+
+#[cfg(feature = "bumpalo")]
+pub use arena::{from_bytes_in, from_str_in, Arena as Bump};
+
+#[cfg(feature = "bytes")]
+pub use shared::{from_bytes_shared, SharedBytes};
+
+#[cfg(feature = "uuid")]
+pub use _uuid::{Error as UuidError, Uuid};
+
+#[cfg(feature = "indexmap")]
+pub use _indexmap::IndexMap;
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub use datetime::{DateTimeValue, LenientDateTime, Rfc3339, UnixMillis, UnixSeconds};
+
+#[cfg(feature = "charset")]
+pub mod charset;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "json")]
+pub mod json;
+
+#[cfg(feature = "jsonapi")]
+pub mod jsonapi;
+
+#[cfg(feature = "odata")]
+pub mod odata;
+
+#[cfg(feature = "proptest")]
+pub mod proptest;
+
+#[cfg(feature = "rsql")]
+pub mod rsql;
+
+#[cfg(feature = "signing")]
+pub mod signing;
+
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
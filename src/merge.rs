@@ -0,0 +1,412 @@
+//! Merging and comparing query strings by their keys, for links changing a few parameters of
+//! the current query
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::config::{DuplicateKeys, PairSeparator, ParseMode, QsConfig};
+use crate::decode::parse_bytes_with;
+use crate::pairs::{decode_lossy, lossy, split_pair, split_pairs};
+#[cfg(feature = "brackets")]
+use crate::parsers::key_segments;
+use crate::parsers::{Delimited, Delimiter};
+
+/// How [`merge`] merges the values of the patch with the values of the same keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// The keys of the patch replace the values of the query, and in the brackets modes the
+    /// keys they conflict with too(`filter` and `filter[age]`)
+    Replace,
+    /// The values of the patch are added after the values of the same keys. In the delimited
+    /// modes they are joined to the last value, and the keys which have a single value
+    /// (`UrlEncoded`, or a key without `[]` in `Rack` and `Php`) are replaced.
+    Append,
+    /// The keys of the patch are only added if the query doesn't have them, like defaults
+    KeepExisting,
+}
+
+/// A key whose values differ between two query strings, returned by [`diff`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryChange {
+    /// The key is only in the second query string
+    Added { key: String, values: Vec<String> },
+    /// The key is only in the first query string
+    Removed { key: String, values: Vec<String> },
+    /// The key has different values
+    Changed {
+        key: String,
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+}
+
+/// A raw pair, and the decoded segments of its key
+struct Pair<'a> {
+    key: &'a [u8],
+    value: Option<Cow<'a, [u8]>>,
+    path: Vec<Vec<u8>>,
+    // The key ends with `[]`
+    sequence: bool,
+    patched: bool,
+}
+
+fn parse<'a>(input: &'a [u8], config: &QsConfig, patched: bool) -> Vec<Pair<'a>> {
+    let raw = match config.mode {
+        ParseMode::Custom(parser) => parser.parse(input, config),
+        _ => split_pairs(input, config.pair_separator)
+            .map(|pair| {
+                let (key, value) = split_pair(pair);
+                (key, (key.len() < pair.len()).then(|| value))
+            })
+            .collect(),
+    };
+
+    let mut scratch = Vec::new();
+    raw.into_iter()
+        .map(|(key, value)| {
+            let (path, sequence) = key_path(key, &mut scratch, config);
+            Pair {
+                key,
+                value: value.map(Cow::Borrowed),
+                path,
+                sequence,
+                patched,
+            }
+        })
+        .collect()
+}
+
+/// Returns the decoded segments of a key, the trailing `[]` removed, and whether it had one
+fn key_path(key: &[u8], scratch: &mut Vec<u8>, config: &QsConfig) -> (Vec<Vec<u8>>, bool) {
+    #[cfg(feature = "brackets")]
+    {
+        let dots = match config.mode {
+            ParseMode::Brackets
+            | ParseMode::BracketsDelimiter(_)
+            | ParseMode::Rack
+            | ParseMode::Php => Some(false),
+            ParseMode::DotNotation => Some(true),
+            _ => None,
+        };
+        if let Some(dots) = dots {
+            let mut path: Vec<Vec<u8>> = key_segments(key, dots)
+                .map(|segment| parse_bytes_with(segment, scratch, config.decode_options).to_vec())
+                .collect();
+            let sequence = path.len() > 1 && path[path.len() - 1].is_empty();
+            while path.len() > 1 && path[path.len() - 1].is_empty() {
+                path.pop();
+            }
+            return (path, sequence);
+        }
+    }
+
+    let key = parse_bytes_with(key, scratch, config.decode_options).to_vec();
+    (vec![key], false)
+}
+
+/// Whether the repeated values of a key are all kept, instead of a single one
+fn collects(pair: &Pair, mode: ParseMode) -> bool {
+    match mode {
+        ParseMode::UrlEncoded | ParseMode::Delimiter(_) | ParseMode::Matrix => false,
+        ParseMode::Rack | ParseMode::Php => pair.sequence,
+        _ => true,
+    }
+}
+
+/// Whether two keys are the same, or one of them is a sub key of the other
+fn conflicts(a: &[Vec<u8>], b: &[Vec<u8>]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a == b)
+}
+
+/// Merge the keys of a patch into a query string, keeping its other pairs as they are
+///
+/// Keys are compared once decoded, by their segments in the brackets modes, so `a[b]` is the
+/// same key as `a%5Bb%5D`, and `a.b` in `DotNotation` mode. A replaced key takes the place of
+/// the first pair it replaces, and the new keys are added at the end. The pairs are not
+/// encoded again, and they are written as `key=value` in `Custom` mode. In `Matrix` mode each
+/// pair starts with the separator, like `;a=1;b=2`.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{merge, MergePolicy, ParseMode};
+///
+/// let query = "filter[age]=30&filter[name]=John&page=3";
+///
+/// assert_eq!(
+///     merge(query, "filter[age]=40&page=1", MergePolicy::Replace, ParseMode::Brackets),
+///     "filter[age]=40&filter[name]=John&page=1"
+/// );
+/// assert_eq!(
+///     merge("ids=1|2", "ids=3", MergePolicy::Append, ParseMode::Delimiter(b'|')),
+///     "ids=1|2|3"
+/// );
+/// ```
+pub fn merge<C>(base: &str, patch: &str, policy: MergePolicy, config: C) -> String
+where
+    C: Into<QsConfig>,
+{
+    let config = config.into();
+    let mut pairs = parse(base.as_bytes(), &config, false);
+
+    for pair in parse(patch.as_bytes(), &config, true) {
+        match policy {
+            MergePolicy::Replace => replace(&mut pairs, pair),
+            MergePolicy::Append => append(&mut pairs, pair, &config),
+            MergePolicy::KeepExisting => {
+                if !pairs
+                    .iter()
+                    .any(|p| !p.patched && conflicts(&p.path, &pair.path))
+                {
+                    pairs.push(pair);
+                }
+            }
+        }
+    }
+
+    let separator = match config.pair_separator {
+        PairSeparator::Semicolon => b';',
+        PairSeparator::Newline => b'\n',
+        PairSeparator::Ampersand | PairSeparator::Both => b'&',
+    };
+    // Matrix parameters all start with the separator, including the first one
+    let leading = matches!(config.mode, ParseMode::Matrix);
+    let mut output = Vec::with_capacity(base.len() + patch.len() + 2);
+    for (index, pair) in pairs.iter().enumerate() {
+        if index > 0 || leading {
+            output.push(separator);
+        }
+        output.extend_from_slice(pair.key);
+        if let Some(value) = &pair.value {
+            output.push(b'=');
+            output.extend_from_slice(value);
+        }
+    }
+
+    // The pairs are split at ascii bytes, unless a custom parser splits them elsewhere
+    match String::from_utf8(output) {
+        Ok(output) => output,
+        Err(error) => String::from_utf8_lossy(error.as_bytes()).into_owned(),
+    }
+}
+
+fn replace<'a>(pairs: &mut Vec<Pair<'a>>, pair: Pair<'a>) {
+    // The other values of the patch stay after its first one
+    if let Some(index) = pairs.iter().rposition(|p| p.patched && p.path == pair.path) {
+        pairs.insert(index + 1, pair);
+        return;
+    }
+
+    let index = pairs
+        .iter()
+        .position(|p| !p.patched && conflicts(&p.path, &pair.path));
+    pairs.retain(|p| p.patched || !conflicts(&p.path, &pair.path));
+    match index {
+        Some(index) => pairs.insert(index, pair),
+        None => pairs.push(pair),
+    }
+}
+
+fn append<'a>(pairs: &mut Vec<Pair<'a>>, pair: Pair<'a>, config: &QsConfig) {
+    let last = match pairs.iter().rposition(|p| p.path == pair.path) {
+        Some(last) => last,
+        None => return pairs.push(pair),
+    };
+
+    if collects(&pair, config.mode) {
+        pairs.insert(last + 1, pair);
+    } else if let Some(delimiter) = config.mode.delimiter() {
+        let delimiter = [delimiter];
+        let delimiter = config.delimiter.map_or(&delimiter[..], str::as_bytes);
+        let target = &mut pairs[last];
+        match (&target.value, pair.value) {
+            (Some(old), Some(new)) if !old.is_empty() => {
+                let mut joined = old.to_vec();
+                joined.extend_from_slice(delimiter);
+                joined.extend_from_slice(&new);
+                target.value = Some(Cow::Owned(joined));
+            }
+            (_, Some(new)) => target.value = Some(new),
+            (_, None) => {}
+        }
+    } else {
+        replace(pairs, pair);
+    }
+}
+
+/// The values of a key, as they are deserialized
+struct Field {
+    key: String,
+    path: Vec<Vec<u8>>,
+    values: Vec<String>,
+    // The position of each value in the sequence, the index of `a[0]`, and the highest index
+    // before a value without one(`a[]`), as they are ordered when deserialized
+    slots: Vec<(Option<usize>, bool)>,
+    max_index: Option<usize>,
+}
+
+/// Whether the mode deserializes sequences from their indexes(`a[0]`) as well as `a[]`
+fn indexes_sequences(mode: ParseMode) -> bool {
+    matches!(
+        mode,
+        ParseMode::Brackets | ParseMode::BracketsDelimiter(_) | ParseMode::DotNotation
+    )
+}
+
+/// Removes the index of a sequence item(`a[0]`) from its path, and returns it
+fn sequence_index(path: &mut Vec<Vec<u8>>) -> Option<usize> {
+    if path.len() < 2 {
+        return None;
+    }
+
+    let last = &path[path.len() - 1];
+    if last.is_empty() || !last.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let index = core::str::from_utf8(last).ok()?.parse().ok()?;
+    path.pop();
+    Some(index)
+}
+
+/// Removes the last segment of a decoded key, `a[0]` or `a.0` becoming `a`
+fn parent_key(mut key: String) -> String {
+    let index = match key.ends_with(']') {
+        true => key.rfind('['),
+        false => key.rfind('.'),
+    };
+    if let Some(index) = index {
+        key.truncate(index);
+    }
+    key
+}
+
+fn fields(input: &[u8], config: &QsConfig) -> Vec<Field> {
+    let mut scratch = Vec::new();
+    let mut fields: Vec<Field> = Vec::new();
+    let mut positions: BTreeMap<Vec<Vec<u8>>, usize> = BTreeMap::new();
+
+    for mut pair in parse(input, config, false) {
+        let (index, item) = match indexes_sequences(config.mode) {
+            true => {
+                let index = sequence_index(&mut pair.path);
+                (index, index.is_some() || pair.sequence)
+            }
+            false => (None, false),
+        };
+        let value = pair.value.as_deref().unwrap_or_default();
+        let values: Vec<String> = match config.mode.delimiter() {
+            Some(delimiter) => {
+                let delimiter = Delimiter::from_config(delimiter, config);
+                Delimited::new(value, Some(delimiter))
+                    .map(|value| {
+                        let value = parse_bytes_with(value, &mut scratch, config.decode_options);
+                        lossy(delimiter.unescape(value)).into_owned()
+                    })
+                    .collect()
+            }
+            None => vec![decode_lossy(value, &mut scratch, config).into_owned()],
+        };
+
+        let position = match positions.get(&pair.path) {
+            Some(&position) => position,
+            None => {
+                let key = decode_lossy(pair.key, &mut scratch, config).into_owned();
+                positions.insert(pair.path.clone(), fields.len());
+                fields.push(Field {
+                    key: if item { parent_key(key) } else { key },
+                    path: pair.path.clone(),
+                    values: Vec::new(),
+                    slots: Vec::new(),
+                    max_index: None,
+                });
+                fields.len() - 1
+            }
+        };
+
+        let field = &mut fields[position];
+        let slot = match index {
+            Some(index) => {
+                field.max_index = field.max_index.max(Some(index));
+                (Some(index), false)
+            }
+            None => (field.max_index, true),
+        };
+        if field.values.is_empty() || item || collects(&pair, config.mode) {
+            field.slots.extend(values.iter().map(|_| slot));
+            field.values.extend(values);
+        } else if config.duplicate_keys != DuplicateKeys::First {
+            field.slots = values.iter().map(|_| slot).collect();
+            field.values = values;
+        }
+    }
+
+    for field in &mut fields {
+        if field.max_index.is_some() {
+            let mut values: Vec<_> = field.slots.drain(..).zip(field.values.drain(..)).collect();
+            values.sort_by_key(|(slot, _)| *slot);
+            field.values = values.into_iter().map(|(_, value)| value).collect();
+        }
+    }
+    fields
+}
+
+/// Compare the keys of two query strings, and return the ones whose values differ
+///
+/// The values are compared once decoded, as they are deserialized: a key keeps all of its
+/// values in the modes collecting repeated keys, and only its last one(or first, with
+/// `DuplicateKeys::First`) otherwise. Keys are compared the same way as [`merge`] does, and
+/// the items of a sequence by their order once deserialized, so `a[]=1&a[]=2` is the same
+/// as `a[0]=1&a[1]=2` in the brackets modes.
+/// The removed and changed keys come first, in the order of the first query string, and the
+/// added ones after them.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{diff, ParseMode, QueryChange};
+///
+/// let changes = diff("page=1&q=rust&q=serde", "q=rust&q=serde&page=2", ParseMode::Duplicate);
+///
+/// assert_eq!(
+///     changes,
+///     vec![QueryChange::Changed {
+///         key: "page".to_string(),
+///         old: vec!["1".to_string()],
+///         new: vec!["2".to_string()],
+///     }]
+/// );
+/// ```
+pub fn diff<C>(a: &str, b: &str, config: C) -> Vec<QueryChange>
+where
+    C: Into<QsConfig>,
+{
+    let config = config.into();
+    let old = fields(a.as_bytes(), &config);
+    let mut new = fields(b.as_bytes(), &config);
+    let mut changes = Vec::new();
+
+    for field in old {
+        match new.iter().position(|new| new.path == field.path) {
+            Some(index) => {
+                let new = new.remove(index);
+                if new.values != field.values {
+                    changes.push(QueryChange::Changed {
+                        key: field.key,
+                        old: field.values,
+                        new: new.values,
+                    });
+                }
+            }
+            None => changes.push(QueryChange::Removed {
+                key: field.key,
+                values: field.values,
+            }),
+        }
+    }
+
+    changes.extend(new.into_iter().map(|field| QueryChange::Added {
+        key: field.key,
+        values: field.values,
+    }));
+    changes
+}
@@ -0,0 +1,506 @@
+//! The system query options of [OData v4](https://docs.oasis-open.org/odata/odata/v4.01/odata-v4.01-part2-url-conventions.html),
+//! as typed values
+//!
+//! Enabled by the `odata` feature.
+//!
+//! # Example
+//! ```rust
+//! use serde_querystring::odata::{CompareOp, FilterExpr, Literal, ODataQuery, Operand};
+//!
+//! let query = ODataQuery::from_bytes(
+//!     b"$top=10&$skip=20&$orderby=Price desc,Name&$filter=Price gt 5 and not Discontinued",
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(query.top, Some(10));
+//! assert_eq!(query.skip, Some(20));
+//! assert!(query.orderby[0].is_descending());
+//! assert_eq!(query.orderby[1].field, "Name");
+//! assert_eq!(
+//!     query.filter,
+//!     Some(FilterExpr::And(
+//!         Box::new(FilterExpr::Compare(
+//!             Operand::Property(String::from("Price")),
+//!             CompareOp::Gt,
+//!             Operand::Literal(Literal::Number(5.0)),
+//!         )),
+//!         Box::new(FilterExpr::Not(Box::new(FilterExpr::Property(String::from(
+//!             "Discontinued"
+//!         ))))),
+//!     ))
+//! );
+//! ```
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use _serde::de::{self, Deserialize};
+
+use crate::de::{Error, ErrorKind, ParseMode};
+use crate::sort::{SortDirection, SortField};
+use crate::value::{from_bytes_value, Value};
+
+/// The system query options defined by OData
+///
+/// - `$top` and `$skip` are the page
+/// - `$count` is `true` or `false`
+/// - `$orderby=a desc,b` are the sort fields, in order, ascending unless followed by `desc`
+/// - `$select=a,b` and `$expand=a,b` are the selected properties and the expanded navigation
+///   properties, split by the top level commas
+/// - `$search` is kept as is
+/// - `$filter` is parsed into a [`FilterExpr`]
+///
+/// Other parameters, like the custom ones without a `$`, are ignored. It implements
+/// `Deserialize` too, so it can be flattened into a type with the other parameters.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ODataQuery {
+    pub top: Option<u64>,
+    pub skip: Option<u64>,
+    pub count: Option<bool>,
+    pub orderby: Vec<SortField>,
+    pub select: Vec<String>,
+    pub expand: Vec<String>,
+    pub search: Option<String>,
+    pub filter: Option<FilterExpr>,
+}
+
+impl ODataQuery {
+    /// Parse bytes of query string into the OData options
+    pub fn from_bytes(input: &[u8]) -> Result<Self, Error> {
+        Self::from_value(from_bytes_value(input, ParseMode::UrlEncoded)?)
+    }
+
+    /// Takes the OData options out of a parsed query string
+    pub fn from_value(value: Value) -> Result<Self, Error> {
+        let mut map = match value {
+            Value::Map(map) => map,
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidType)
+                    .message(String::from("expected a map of parameters")))
+            }
+        };
+        let mut query = Self::default();
+
+        if let Some(top) = map.remove("$top") {
+            query.top = Some(top.deserialize_into()?);
+        }
+
+        if let Some(skip) = map.remove("$skip") {
+            query.skip = Some(skip.deserialize_into()?);
+        }
+
+        if let Some(count) = map.remove("$count") {
+            query.count = Some(count.deserialize_into()?);
+        }
+
+        if let Some(orderby) = map.remove("$orderby") {
+            query.orderby = split_items(&orderby.deserialize_into::<String>()?)
+                .map(parse_orderby)
+                .collect::<Result<_, _>>()?;
+        }
+
+        if let Some(select) = map.remove("$select") {
+            query.select = split_items(&select.deserialize_into::<String>()?)
+                .map(String::from)
+                .collect();
+        }
+
+        if let Some(expand) = map.remove("$expand") {
+            query.expand = split_items(&expand.deserialize_into::<String>()?)
+                .map(String::from)
+                .collect();
+        }
+
+        if let Some(search) = map.remove("$search") {
+            query.search = Some(search.deserialize_into()?);
+        }
+
+        if let Some(filter) = map.remove("$filter") {
+            query.filter = Some(filter.deserialize_into::<String>()?.parse()?);
+        }
+
+        Ok(query)
+    }
+}
+
+impl FromStr for ODataQuery {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Self::from_bytes(s.as_bytes())
+    }
+}
+
+impl<'de> Deserialize<'de> for ODataQuery {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Self::from_value(Value::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+/// Split by the commas outside of parentheses(`$expand=a($select=b,c),d`), trimmed and without
+/// the empty items
+fn split_items(s: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0usize;
+    s.split(move |c| match c {
+        '(' => {
+            depth += 1;
+            false
+        }
+        ')' => {
+            depth = depth.saturating_sub(1);
+            false
+        }
+        ',' => depth == 0,
+        _ => false,
+    })
+    .map(str::trim)
+    .filter(|item| !item.is_empty())
+}
+
+fn parse_orderby(item: &str) -> Result<SortField, Error> {
+    let mut words = item.split_whitespace();
+    let field = words.next().unwrap_or_default();
+    let direction = match words.next() {
+        None | Some("asc") => SortDirection::Ascending,
+        Some("desc") => SortDirection::Descending,
+        Some(_) => {
+            return Err(Error::new(ErrorKind::InvalidType)
+                .message(String::from("expected `asc` or `desc` after the property"))
+                .value(item.as_bytes()))
+        }
+    };
+
+    if words.next().is_some() {
+        return Err(Error::new(ErrorKind::InvalidType)
+            .message(String::from("unexpected words after the direction"))
+            .value(item.as_bytes()));
+    }
+
+    Ok(SortField {
+        field: field.to_string(),
+        direction,
+    })
+}
+
+/// A token of a `$filter` expression
+///
+/// The keywords, like `eq`, `and` or `true`, are identifiers, they are told apart by the parser.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    /// A name, which may be a path of properties(`Address/City`)
+    Identifier(String),
+    /// A quoted string, with the doubled quotes unescaped(`'O''Neil'`)
+    String(String),
+    Number(f64),
+    OpenParen,
+    CloseParen,
+    Comma,
+}
+
+/// Split a `$filter` expression into tokens, with the byte offset each token starts at
+///
+/// It fails with `ErrorKind::InvalidType` and the index of the byte on an unexpected char or an
+/// unterminated string.
+pub fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, Error> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let start = index;
+        let token = match bytes[index] {
+            b' ' | b'\t' => {
+                index += 1;
+                continue;
+            }
+            b'(' => {
+                index += 1;
+                Token::OpenParen
+            }
+            b')' => {
+                index += 1;
+                Token::CloseParen
+            }
+            b',' => {
+                index += 1;
+                Token::Comma
+            }
+            b'\'' => {
+                let mut value = String::new();
+                index += 1;
+                loop {
+                    match input[index..].find('\'') {
+                        Some(end) => {
+                            value.push_str(&input[index..index + end]);
+                            index += end + 1;
+                            if bytes.get(index) == Some(&b'\'') {
+                                value.push('\'');
+                                index += 1;
+                            } else {
+                                break;
+                            }
+                        }
+                        None => {
+                            return Err(Error::new(ErrorKind::InvalidType)
+                                .message(String::from("unterminated string"))
+                                .value(bytes)
+                                .index(start))
+                        }
+                    }
+                }
+                Token::String(value)
+            }
+            b'-' | b'0'..=b'9' => {
+                index += 1;
+                while index < bytes.len()
+                    && matches!(bytes[index], b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-')
+                {
+                    index += 1;
+                }
+                let number = input[start..index].parse().map_err(|_| {
+                    Error::new(ErrorKind::InvalidNumber)
+                        .message(format!("`{}` is not a number", &input[start..index]))
+                        .value(bytes)
+                        .index(start)
+                })?;
+                Token::Number(number)
+            }
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'$' => {
+                while index < bytes.len()
+                    && matches!(bytes[index], b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'/' | b'.' | b'$')
+                {
+                    index += 1;
+                }
+                Token::Identifier(input[start..index].to_string())
+            }
+            _ => {
+                return Err(Error::new(ErrorKind::InvalidType)
+                    .message(String::from("unexpected char in the filter"))
+                    .value(bytes)
+                    .index(start))
+            }
+        };
+        tokens.push((start, token));
+    }
+
+    Ok(tokens)
+}
+
+/// A literal of a `$filter` expression
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+/// A side of a comparison, or an argument of a function
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operand {
+    Property(String),
+    Literal(Literal),
+    /// A function call, the name is kept as is(`tolower(Name)`)
+    Call(String, Vec<Operand>),
+}
+
+/// The comparison operators of a `$filter` expression
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl CompareOp {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        Some(match keyword {
+            "eq" => CompareOp::Eq,
+            "ne" => CompareOp::Ne,
+            "gt" => CompareOp::Gt,
+            "ge" => CompareOp::Ge,
+            "lt" => CompareOp::Lt,
+            "le" => CompareOp::Le,
+            _ => return None,
+        })
+    }
+}
+
+/// A basic `$filter` expression
+///
+/// It covers the comparisons, `and`, `or`, `not`, the parentheses and the function calls like
+/// `contains(Name,'x')` or `tolower(Name) eq 'x'`. `not` binds tighter than the comparisons, which bind tighter than `and`,
+/// which binds tighter than `or`. The arithmetic operators and lambdas are not supported.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterExpr {
+    Compare(Operand, CompareOp, Operand),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    /// A function call on its own(`$filter=contains(Name,'x')`)
+    Call(String, Vec<Operand>),
+    /// A boolean property on its own(`$filter=Discontinued`)
+    Property(String),
+    /// A boolean literal on its own(`$filter=true`)
+    Literal(Literal),
+}
+
+impl FromStr for FilterExpr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            input: s,
+            tokens: &tokens,
+            position: 0,
+        };
+
+        let expr = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(_) => Err(parser.error("unexpected token after the expression")),
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: &'a [(usize, Token)],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Identifier(ident)) if ident == keyword => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn error(&self, message: &str) -> Error {
+        let index = self
+            .tokens
+            .get(self.position)
+            .map_or(self.input.len(), |(index, _)| *index);
+        Error::new(ErrorKind::InvalidType)
+            .message(String::from(message))
+            .value(self.input.as_bytes())
+            .index(index)
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, Error> {
+        let mut expr = self.parse_and()?;
+        while self.eat_keyword("or") {
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, Error> {
+        let mut expr = self.parse_comparison()?;
+        while self.eat_keyword("and") {
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.parse_comparison()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_comparison(&mut self) -> Result<FilterExpr, Error> {
+        if self.eat_keyword("not") {
+            return Ok(FilterExpr::Not(Box::new(self.parse_comparison()?)));
+        }
+
+        if self.peek() == Some(&Token::OpenParen) {
+            self.position += 1;
+            let expr = self.parse_or()?;
+            if self.next() != Some(&Token::CloseParen) {
+                self.position -= 1;
+                return Err(self.error("expected a closing parenthesis"));
+            }
+            return Ok(expr);
+        }
+
+        let left = self.parse_operand()?;
+        let op = match self.peek() {
+            Some(Token::Identifier(ident)) => CompareOp::from_keyword(ident),
+            _ => None,
+        };
+
+        match (op, left) {
+            (Some(op), left) => {
+                self.position += 1;
+                let right = self.parse_operand()?;
+                Ok(FilterExpr::Compare(left, op, right))
+            }
+            (None, Operand::Property(property)) => Ok(FilterExpr::Property(property)),
+            (None, Operand::Literal(literal)) => Ok(FilterExpr::Literal(literal)),
+            (None, Operand::Call(name, arguments)) => Ok(FilterExpr::Call(name, arguments)),
+        }
+    }
+
+    fn parse_arguments(&mut self) -> Result<Vec<Operand>, Error> {
+        let mut arguments = Vec::new();
+        if self.peek() == Some(&Token::CloseParen) {
+            self.position += 1;
+            return Ok(arguments);
+        }
+
+        loop {
+            arguments.push(self.parse_operand()?);
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::CloseParen) => return Ok(arguments),
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error("expected a comma or a closing parenthesis"));
+                }
+            }
+        }
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, Error> {
+        let operand = match self.peek() {
+            Some(Token::Identifier(ident)) => match ident.as_str() {
+                "null" => Operand::Literal(Literal::Null),
+                "true" => Operand::Literal(Literal::Bool(true)),
+                "false" => Operand::Literal(Literal::Bool(false)),
+                "and" | "or" | "not" => return Err(self.error("expected a property or a literal")),
+                _ => Operand::Property(ident.clone()),
+            },
+            Some(Token::String(s)) => Operand::Literal(Literal::String(s.clone())),
+            Some(Token::Number(number)) => Operand::Literal(Literal::Number(*number)),
+            _ => return Err(self.error("expected a property or a literal")),
+        };
+        self.position += 1;
+
+        match operand {
+            Operand::Property(name) if self.peek() == Some(&Token::OpenParen) => {
+                self.position += 1;
+                Ok(Operand::Call(name, self.parse_arguments()?))
+            }
+            operand => Ok(operand),
+        }
+    }
+}
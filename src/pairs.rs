@@ -0,0 +1,307 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::config::{PairSeparator, ParseMode, QsConfig};
+use crate::decode::{parse_bytes_with, Reference};
+#[cfg(feature = "brackets")]
+use crate::parsers::key_segments;
+use crate::parsers::{Delimited, Delimiter};
+use crate::scan::memchr;
+
+/// Decodes a slice into a str, invalid utf-8 sequences are replaced by `U+FFFD`
+pub(crate) fn decode_lossy<'a>(
+    slice: &'a [u8],
+    scratch: &mut Vec<u8>,
+    config: &QsConfig,
+) -> Cow<'a, str> {
+    lossy(parse_bytes_with(slice, scratch, config.decode_options))
+}
+
+/// Converts a decoded value into a str, the same way as `decode_lossy`
+pub(crate) fn lossy<'a>(value: Reference<'a, '_, [u8]>) -> Cow<'a, str> {
+    match value {
+        Reference::Borrowed(b) => String::from_utf8_lossy(b),
+        Reference::Copied(c) => Cow::Owned(String::from_utf8_lossy(c).into_owned()),
+        Reference::Owned(o) => Cow::Owned(String::from_utf8_lossy(&o).into_owned()),
+    }
+}
+
+/// Parse a query string into its decoded key/value pairs, in the order they appear
+///
+/// Unlike the deserializers, repeated keys are all kept. Keys without a value (`key`) get an
+/// empty value, and keys are returned as they are, so `key[0]` is not split in brackets mode.
+/// In delimiter mode, each of the delimited values is returned as a separate pair with the same key.
+///
+/// Invalid utf-8 sequences are replaced by `U+FFFD`, and the pairs borrow from the input when
+/// they don't need to be decoded.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{parse_pairs, ParseMode};
+///
+/// let pairs = parse_pairs(b"b=1&a=x+y&b=2|3", ParseMode::Delimiter(b'|'));
+///
+/// assert_eq!(pairs[1].0, "a");
+/// assert_eq!(pairs[1].1, "x y");
+/// assert_eq!(pairs.len(), 4);
+/// ```
+pub fn parse_pairs<'a, C>(input: &'a [u8], config: C) -> Vec<(Cow<'a, str>, Cow<'a, str>)>
+where
+    C: Into<QsConfig>,
+{
+    let config = config.into();
+    let mut scratch = Vec::new();
+    let mut pairs = Vec::new();
+
+    for (key, value) in raw_pairs(input, &config) {
+        let key = decode_lossy(key, &mut scratch, &config);
+
+        match config.mode.delimiter() {
+            Some(delimiter) => {
+                let delimiter = Delimiter::from_config(delimiter, &config);
+                for value in Delimited::new(value, Some(delimiter)) {
+                    let value = parse_bytes_with(value, &mut scratch, config.decode_options);
+                    pairs.push((key.clone(), lossy(delimiter.unescape(value))));
+                }
+            }
+            None => pairs.push((key, decode_lossy(value, &mut scratch, &config))),
+        }
+    }
+
+    pairs
+}
+
+/// Parse a query string into a map of each key to all of its values, in the order they appear
+///
+/// It is the same as [`parse_pairs`], with the pairs grouped by their keys.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{parse_multimap, ParseMode};
+///
+/// let map = parse_multimap(b"a=1&b=2&a=3", ParseMode::Duplicate);
+///
+/// assert_eq!(map["a"], vec!["1", "3"]);
+/// assert_eq!(map["b"], vec!["2"]);
+/// ```
+#[cfg(feature = "std")]
+pub fn parse_multimap<C>(input: &[u8], config: C) -> HashMap<String, Vec<String>>
+where
+    C: Into<QsConfig>,
+{
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, value) in parse_pairs(input, config) {
+        map.entry(key.into_owned())
+            .or_default()
+            .push(value.into_owned());
+    }
+    map
+}
+
+/// A push parser, parsing a query string fed in chunks into its decoded pairs
+///
+/// Each call to [`feed`](PairStream::feed) returns the pairs completed by the chunk, the same
+/// way as [`parse_pairs`], and keeps the last pair until its separator is fed. Only the pair
+/// being read is buffered, so bodies larger than the memory can be parsed as they are read.
+/// [`finish`](PairStream::finish) returns the last pair at the end of the input.
+///
+/// The custom parsers split the pairs themselves, so in `Custom` mode the whole input is kept
+/// until `finish`.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{PairStream, ParseMode};
+///
+/// let mut stream = PairStream::new(ParseMode::Duplicate);
+///
+/// let pairs = stream.feed(b"a=1&b=x");
+/// assert_eq!(pairs.len(), 1);
+/// assert_eq!(pairs[0].0, "a");
+///
+/// let pairs = stream.feed(b"+y&c=3");
+/// assert_eq!(pairs[0].1, "x y");
+///
+/// let pairs = stream.finish();
+/// assert_eq!(pairs[0].1, "3");
+/// ```
+pub struct PairStream {
+    config: QsConfig,
+    buffer: Vec<u8>,
+    // The length of the start of the buffer whose pairs were returned
+    consumed: usize,
+}
+
+impl PairStream {
+    /// Creates a parser with the config or the parse mode
+    pub fn new<C: Into<QsConfig>>(config: C) -> Self {
+        Self {
+            config: config.into(),
+            buffer: Vec::new(),
+            consumed: 0,
+        }
+    }
+
+    /// Feeds the next chunk of the input, and returns the pairs it completes
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        self.buffer.drain(..self.consumed);
+        self.buffer.extend_from_slice(chunk);
+
+        self.consumed = match self.config.mode {
+            ParseMode::Custom(_) => 0,
+            _ => {
+                let separator = self.config.pair_separator;
+                // The bytes after the last separator may be the start of a pair
+                let start = self.buffer.len() - chunk.len();
+                self.buffer[start..]
+                    .iter()
+                    .rposition(|b| separator.matches(*b))
+                    .map_or(0, |index| start + index + 1)
+            }
+        };
+
+        parse_pairs(&self.buffer[..self.consumed], self.config)
+    }
+
+    /// Ends the input, and returns the pairs which were not returned yet
+    pub fn finish(&mut self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        self.buffer.drain(..self.consumed);
+        self.consumed = self.buffer.len();
+
+        parse_pairs(&self.buffer, self.config)
+    }
+}
+
+/// Iterates over the non empty raw pairs of a query string
+#[inline]
+pub(crate) fn split_pairs(input: &[u8], separator: PairSeparator) -> impl Iterator<Item = &[u8]> {
+    input
+        .split(move |b| separator.matches(*b))
+        .filter(|pair| !pair.is_empty())
+}
+
+/// Splits a pair into its raw key and value, pairs without `=` have an empty value
+#[inline]
+pub(crate) fn split_pair(pair: &[u8]) -> (&[u8], &[u8]) {
+    match memchr(b'=', pair) {
+        Some(index) => (&pair[..index], &pair[index + 1..]),
+        None => (pair, &pair[pair.len()..]),
+    }
+}
+
+/// Iterates over the raw keys and values of the pairs, split by the custom parser if there is one
+fn raw_pairs<'a>(
+    input: &'a [u8],
+    config: &QsConfig,
+) -> Box<dyn Iterator<Item = (&'a [u8], &'a [u8])> + 'a> {
+    match config.mode {
+        ParseMode::Custom(parser) => Box::new(
+            parser
+                .parse(input, config)
+                .into_iter()
+                .map(|(key, value)| (key, value.unwrap_or_default())),
+        ),
+        _ => Box::new(split_pairs(input, config.pair_separator).map(split_pair)),
+    }
+}
+
+/// Returns the decoded value of the last pair matching the raw key
+fn find_last<'a, F>(input: &'a [u8], config: &QsConfig, mut matches: F) -> Option<Cow<'a, [u8]>>
+where
+    F: FnMut(&[u8]) -> bool,
+{
+    let value = raw_pairs(input, config)
+        .filter(|(key, _)| matches(key))
+        .last()?
+        .1;
+
+    Some(parse_bytes_with(value, &mut Vec::new(), config.decode_options).into_cow())
+}
+
+/// Returns the decoded value of the last pair whose key segments match the path
+#[cfg(feature = "brackets")]
+fn find_path<'a, P>(
+    input: &'a [u8],
+    path: &[P],
+    dots: bool,
+    config: &QsConfig,
+) -> Option<Cow<'a, [u8]>>
+where
+    P: AsRef<[u8]>,
+{
+    let mut scratch = Vec::new();
+
+    find_last(input, config, |key| {
+        let mut segments = key_segments(key, dots);
+        path.iter().all(|part| match segments.next() {
+            Some(segment) => {
+                &*parse_bytes_with(segment, &mut scratch, config.decode_options) == part.as_ref()
+            }
+            None => false,
+        }) && segments.next().is_none()
+    })
+}
+
+/// Returns the decoded value of a single key, without parsing the whole query string
+///
+/// The last value is returned when the key is repeated, the same way the deserializers do
+/// for single values in every mode. In the brackets modes only the pairs without
+/// sub keys match, so `key` doesn't match `key[sub]` or `key.sub`; use [`get_path`] for those.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{get_value, ParseMode};
+///
+/// let query = b"page=1&api_key=abc%20def&page=2";
+///
+/// assert_eq!(get_value(query, b"api_key", ParseMode::Duplicate).unwrap(), &b"abc def"[..]);
+/// assert_eq!(get_value(query, b"page", ParseMode::Duplicate).unwrap(), &b"2"[..]);
+/// assert!(get_value(query, b"missing", ParseMode::Duplicate).is_none());
+/// ```
+pub fn get_value<'a, C>(input: &'a [u8], key: &[u8], config: C) -> Option<Cow<'a, [u8]>>
+where
+    C: Into<QsConfig>,
+{
+    let config = config.into();
+    match config.mode {
+        #[cfg(feature = "brackets")]
+        ParseMode::Brackets
+        | ParseMode::BracketsDelimiter(_)
+        | ParseMode::Rack
+        | ParseMode::Php => find_path(input, &[key], false, &config),
+        #[cfg(feature = "brackets")]
+        ParseMode::DotNotation => find_path(input, &[key], true, &config),
+        _ => {
+            let mut scratch = Vec::new();
+            find_last(input, &config, |k| {
+                &*parse_bytes_with(k, &mut scratch, config.decode_options) == key
+            })
+        }
+    }
+}
+
+/// Returns the decoded value of a bracketed key(`filter[age]`) given its path (`["filter", "age"]`)
+///
+/// Like [`get_value`], the last value is returned when the key is repeated. Dots are not
+/// treated as separators, so `filter.age` is a single segment, and pairs are separated by `&`.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::get_path;
+///
+/// let query = b"filter[age]=30&filter[name]=John&sort=asc";
+///
+/// assert_eq!(get_path(query, &["filter", "age"]).unwrap(), &b"30"[..]);
+/// assert_eq!(get_path(query, &["sort"]).unwrap(), &b"asc"[..]);
+/// assert!(get_path(query, &["filter"]).is_none());
+/// ```
+#[cfg(feature = "brackets")]
+pub fn get_path<'a, P>(input: &'a [u8], path: &[P]) -> Option<Cow<'a, [u8]>>
+where
+    P: AsRef<[u8]>,
+{
+    find_path(input, path, false, &QsConfig::new(ParseMode::Brackets))
+}
@@ -1,6 +1,59 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use alloc::{borrow::Cow, vec, vec::Vec};
+
+use crate::config::{PairSeparator, ParseMode, QsConfig};
+use crate::decode::{
+    invalid_pair_len, parse_bytes_with, parse_char, DecodeOptions, KeyDecoder, Reference,
+};
+use crate::scan::{memchr2, memchr3};
+
+use super::ordered::OrderedMap;
+use super::{unescape, Delimited, Delimiter};
+
+/// How the remaining part of a `Key` is split into sub keys
+#[derive(Clone, Copy, PartialEq)]
+enum Notation {
+    /// Only brackets are used, `key[key1][key2]`
+    Brackets,
+    /// Both dots and brackets are used, and the remains start after an opening bracket
+    DotsAfterBracket,
+    /// Both dots and brackets are used, and the remains start after a dot, `key.key1[key2]`
+    DotsAfterDot,
+}
+
+impl Notation {
+    fn after_bracket(dots: bool) -> Self {
+        if dots {
+            Notation::DotsAfterBracket
+        } else {
+            Notation::Brackets
+        }
+    }
+
+    fn dots(self) -> bool {
+        self != Notation::Brackets
+    }
+}
+
+/// Returns the length of the opening bracket at the start of the slice, raw or percent encoded
+#[inline]
+fn opening_bracket(slice: &[u8]) -> Option<usize> {
+    match slice {
+        [b'[', ..] => Some(1),
+        [b'%', a, b, ..] if parse_char(*a, *b) == Some(b'[') => Some(3),
+        _ => None,
+    }
+}
 
-use crate::decode::{parse_bytes, parse_char, Reference};
+/// Returns the index of the first byte which may start the remains of a key, a dot or an
+/// opening bracket, raw or percent encoded
+#[inline]
+fn find_remains(slice: &[u8], dots: bool) -> Option<usize> {
+    if dots {
+        memchr3(b'.', b'[', b'%', slice)
+    } else {
+        memchr2(b'[', b'%', slice)
+    }
+}
 
 /// A `Key` in brackets mode represents some state of a parsed key
 ///
@@ -13,51 +66,64 @@ use crate::decode::{parse_bytes, parse_char, Reference};
 /// For this pair `key[key1][key2]=value`, the first Key would be (`key`, `key1][key2]`).
 /// the first time we call the `sub_key` method we get (`key1`, `key2]`).
 /// and by calling `sub_key` again on the result we get (`key2`, None)
+///
+/// With dots enabled, `key.key1[key2]` gives the same keys, raw dots separate the sub keys
+/// while percent encoded dots are kept in them.
 #[derive(Clone, Copy)]
-struct Key<'a>(&'a [u8], Option<&'a [u8]>);
+struct Key<'a>(&'a [u8], Option<&'a [u8]>, Notation);
 
 impl<'a> Key<'a> {
-    fn parse(slice: &'a [u8]) -> (Self, usize) {
+    fn parse(slice: &'a [u8], dots: bool, separator: PairSeparator) -> (Self, usize) {
+        let end = separator.find_or(b'=', slice);
+
+        // Only a dot, a bracket or an escape may start the remains
         let mut index = 0;
-        while index < slice.len() {
-            match slice[index] {
-                b'[' => {
-                    let res = Key::parse_remains(&slice[..index], &slice[(index + 1)..]);
-                    return (res.0, res.1 + index + 1);
-                }
-                b'%' => {
-                    // Percent encoded opening bracket
-                    if index + 2 < slice.len()
-                        && parse_char(slice[index + 1], slice[index + 2]) == Some(b'[')
-                    {
-                        let res = Key::parse_remains(&slice[..index], &slice[(index + 3)..]);
-                        return (res.0, res.1 + index + 3);
-                    };
-                    index += 1;
-                }
-                b'&' | b'=' => break,
-                _ => index += 1,
+        while let Some(offset) = find_remains(&slice[index..end], dots) {
+            index += offset;
+            if slice[index] == b'.' {
+                let res = Key::parse_remains(
+                    &slice[..index],
+                    &slice[(index + 1)..],
+                    Notation::DotsAfterDot,
+                    separator,
+                );
+                return (res.0, res.1 + index + 1);
+            }
+            if let Some(len) = opening_bracket(&slice[index..]) {
+                let res = Key::parse_remains(
+                    &slice[..index],
+                    &slice[(index + len)..],
+                    Notation::after_bracket(dots),
+                    separator,
+                );
+                return (res.0, res.1 + index + len);
             }
+            index += 1;
         }
 
-        (Self(&slice[..index], None), index)
+        (
+            Self(&slice[..end], None, Notation::after_bracket(dots)),
+            end,
+        )
     }
 
-    fn parse_remains(key: &'a [u8], slice: &'a [u8]) -> (Self, usize) {
-        let mut index = 0;
-        while index < slice.len() {
-            match slice[index] {
-                b'&' | b'=' => break,
-                _ => index += 1,
-            }
-        }
-
-        (Self(key, Some(&slice[..index])), index)
+    fn parse_remains(
+        key: &'a [u8],
+        slice: &'a [u8],
+        notation: Notation,
+        separator: PairSeparator,
+    ) -> (Self, usize) {
+        let index = separator.find_or(b'=', slice);
+        (Self(key, Some(&slice[..index]), notation), index)
     }
 
     fn subkey(self) -> Option<Self> {
         let remains = self.1?;
 
+        if self.2 == Notation::DotsAfterDot {
+            return Some(Self::dot_subkey(remains));
+        }
+
         let mut key_end_index = 0;
         let mut index = 0;
         while index < remains.len() {
@@ -82,20 +148,58 @@ impl<'a> Key<'a> {
             key_end_index = index;
         }
 
-        if index + 1 < remains.len() && remains[index + 1] == b'[' {
-            Some(Self(&remains[..key_end_index], Some(&remains[index + 2..])))
-        } else if index + 3 < remains.len()
-            && remains[index + 1] == b'%'
-            && parse_char(remains[index + 2], remains[index + 3]) == Some(b'[')
-        {
-            Some(Self(&remains[..key_end_index], Some(&remains[index + 4..])))
+        let rest = remains.get(index + 1..).unwrap_or_default();
+        if let Some(len) = opening_bracket(rest) {
+            Some(Self(&remains[..key_end_index], Some(&rest[len..]), self.2))
+        } else if self.2.dots() && rest.first() == Some(&b'.') {
+            Some(Self(
+                &remains[..key_end_index],
+                Some(&rest[1..]),
+                Notation::DotsAfterDot,
+            ))
         } else {
-            Some(Self(&remains[..key_end_index], None))
+            Some(Self(&remains[..key_end_index], None, self.2))
+        }
+    }
+
+    /// Returns the number of sub keys
+    fn depth(self) -> usize {
+        let mut depth = 0;
+        let mut subkey = self.subkey();
+        while let Some(sub) = subkey {
+            depth += 1;
+            subkey = sub.subkey();
         }
+        depth
+    }
+
+    /// A sub key after a dot ends at the next dot or opening bracket
+    fn dot_subkey(remains: &'a [u8]) -> Self {
+        let mut index = 0;
+        while index < remains.len() {
+            if remains[index] == b'.' {
+                return Self(
+                    &remains[..index],
+                    Some(&remains[index + 1..]),
+                    Notation::DotsAfterDot,
+                );
+            }
+            if let Some(len) = opening_bracket(&remains[index..]) {
+                return Self(
+                    &remains[..index],
+                    Some(&remains[index + len..]),
+                    Notation::DotsAfterBracket,
+                );
+            }
+            index += 1;
+        }
+
+        Self(remains, None, Notation::DotsAfterDot)
     }
 
     fn has_subkey(&self) -> bool {
         match self.1 {
+            Some(_) if self.2 == Notation::DotsAfterDot => true,
             Some(remains) => {
                 let mut index = 0;
                 while index < remains.len() {
@@ -113,12 +217,13 @@ impl<'a> Key<'a> {
                         _ => index += 1,
                     }
                 }
-                return false;
+                false
             }
             None => false,
         }
     }
 
+    #[cfg(feature = "serde")]
     fn is_empty(&self) -> bool {
         match self.1 {
             Some(r) => self.0.is_empty() && r.is_empty(),
@@ -126,41 +231,91 @@ impl<'a> Key<'a> {
         }
     }
 
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    /// The decoded key with all its sub keys in brackets, `a.b[c]` gives `a[b][c]`
+    #[cfg(feature = "serde")]
+    fn path(self, options: DecodeOptions) -> alloc::string::String {
+        let mut scratch = Vec::new();
+        let mut path = alloc::string::String::from_utf8_lossy(&parse_bytes_with(
+            self.0,
+            &mut scratch,
+            options,
+        ))
+        .into_owned();
+        let mut subkey = self.subkey();
+        while let Some(sub) = subkey {
+            path.push('[');
+            path.push_str(&alloc::string::String::from_utf8_lossy(&parse_bytes_with(
+                sub.0,
+                &mut scratch,
+                options,
+            )));
+            path.push(']');
+            subkey = sub.subkey();
+        }
+        path
+    }
+
+    fn decode<'s>(
+        &self,
+        keys: &'s mut KeyDecoder<'a>,
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        keys.decode(self.0, options)
     }
 }
 
+/// Iterates over the raw segments of a key, `a[b][c]` gives `a`, `b` and `c`
+///
+/// The segments are split the same way the parser does, anything after a segment which is not
+/// followed by an opening bracket(or a dot) is ignored.
+#[cfg(feature = "serde")]
+pub(crate) fn key_segments(key: &[u8], dots: bool) -> impl Iterator<Item = &[u8]> {
+    let mut key = Some(Key::parse(key, dots, PairSeparator::Ampersand).0);
+    core::iter::from_fn(move || {
+        let current = key?;
+        key = current.subkey();
+        Some(current.0)
+    })
+}
+
+/// A raw value, and the delimiter splitting it into multiple values if there is one
 #[derive(Default, Clone, Copy)]
-struct Value<'a>(&'a [u8]);
+struct Value<'a>(&'a [u8], Option<Delimiter>);
 
 impl<'a> Value<'a> {
-    fn parse(slice: &'a [u8]) -> (Option<Self>, usize) {
-        match slice.get(0) {
-            Some(b'&') | None => {
+    fn parse(
+        slice: &'a [u8],
+        delimiter: Option<Delimiter>,
+        separator: PairSeparator,
+    ) -> (Option<Self>, usize) {
+        match slice.first() {
+            Some(b) if !separator.matches(*b) => {}
+            _ => {
                 return (None, 0);
             }
-            _ => {}
         }
 
-        let mut index = 1;
-        while index < slice.len() {
-            match slice[index] {
-                b'&' => break,
-                _ => index += 1,
-            }
-        }
-
-        (Some(Self(&slice[1..index])), index)
+        let index = 1 + separator.find(&slice[1..]);
+        (Some(Self(&slice[1..index], delimiter)), index)
     }
 
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        parse_bytes_with(self.0, scratch, options)
     }
 
+    #[cfg(feature = "serde")]
     fn slice(&self) -> &'a [u8] {
         self.0
     }
+
+    /// Iterates over the raw values split by the delimiter, or the whole value if there is none
+    fn split(&self) -> impl Iterator<Item = &'a [u8]> {
+        Delimited::new(self.0, self.1)
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -172,9 +327,14 @@ impl<'a> Pair<'a> {
     /// Unlike other parser methods, we directly return the skip_len here
     /// since there are many exceptions to take into account in this method
     /// and it helps avoid some recalculations.
-    fn parse(slice: &'a [u8]) -> (Self, usize) {
-        let (key, key_len) = Key::parse(slice);
-        let (value, value_len) = Value::parse(&slice[key_len..]);
+    fn parse(
+        slice: &'a [u8],
+        dots: bool,
+        delimiter: Option<Delimiter>,
+        separator: PairSeparator,
+    ) -> (Self, usize) {
+        let (key, key_len) = Key::parse(slice, dots, separator);
+        let (value, value_len) = Value::parse(&slice[key_len..], delimiter, separator);
 
         (Self(key, value), key_len + value_len + 1)
     }
@@ -226,64 +386,139 @@ impl<'a> Pair<'a> {
 /// )
 /// ```
 pub struct BracketsQS<'a> {
-    pairs: BTreeMap<Cow<'a, [u8]>, Vec<Pair<'a>>>,
+    pairs: OrderedMap<Cow<'a, [u8]>, Vec<Pair<'a>>>,
+    // The max depth is reduced for the sub values
+    config: QsConfig,
 }
 
 impl<'a> BracketsQS<'a> {
     /// Parse a slice of bytes into a `BracketsQS`
     pub fn parse(slice: &'a [u8]) -> Self {
-        let mut pairs: BTreeMap<_, Vec<Pair<'a>>> = BTreeMap::new();
-        let mut scratch = Vec::new();
+        Self::parse_keys(slice, false, None, &QsConfig::new(ParseMode::Brackets))
+    }
+
+    /// Parse a slice of bytes into a `BracketsQS`, with dots also separating the sub keys
+    /// (like the `allowDots` option of qs). (ex. `user.address.city=Oslo` is the same as
+    /// `user[address][city]=Oslo`)
+    ///
+    /// Percent encoded dots(`%2E`) don't separate sub keys, and brackets can still be used.
+    pub fn parse_dots(slice: &'a [u8]) -> Self {
+        Self::parse_keys(slice, true, None, &QsConfig::new(ParseMode::DotNotation))
+    }
+
+    /// Parse a slice of bytes into a `BracketsQS`, with the values also split by a delimiter byte
+    /// into sequences. (ex. `filter[ids]=1,2,3` is the same as `filter[ids][]=1&filter[ids][]=2&filter[ids][]=3`)
+    ///
+    /// The `values` method returns the split values, while `value` returns the last value as a whole.
+    pub fn parse_delimited(slice: &'a [u8], delimiter: u8) -> Self {
+        Self::parse_keys(
+            slice,
+            false,
+            Some(Delimiter::byte(delimiter)),
+            &QsConfig::new(ParseMode::BracketsDelimiter(delimiter)),
+        )
+    }
+
+    /// Parse a slice of bytes into a `BracketsQS`, using the options of the config
+    ///
+    /// The parse mode of the config chooses between `parse_dots`(`DotNotation`),
+    /// `parse_delimited`(`BracketsDelimiter`) and `parse`(any other mode).
+    pub fn parse_with_config(slice: &'a [u8], config: &QsConfig) -> Self {
+        match config.mode {
+            ParseMode::DotNotation => Self::parse_keys(slice, true, None, config),
+            ParseMode::BracketsDelimiter(delimiter) => Self::parse_keys(
+                slice,
+                false,
+                Some(Delimiter::from_config(delimiter, config)),
+                config,
+            ),
+            _ => Self::parse_keys(slice, false, None, config),
+        }
+    }
+
+    fn parse_keys(
+        slice: &'a [u8],
+        dots: bool,
+        delimiter: Option<Delimiter>,
+        config: &QsConfig,
+    ) -> Self {
+        let mut pairs: OrderedMap<Cow<'a, [u8]>, Vec<Pair<'a>>> = OrderedMap::new();
+        let mut keys = KeyDecoder::default();
 
         let mut index = 0;
 
         while index < slice.len() {
-            let (pair, pair_len) = Pair::parse(&slice[index..]);
+            // Empty pairs(`&&` or empty lines) are skipped
+            if config.pair_separator.matches(slice[index]) {
+                index += 1;
+                continue;
+            }
+            // Pairs which can't be parsed are skipped, with `skip_invalid_pairs`
+            if let Some(len) = invalid_pair_len(&slice[index..], config) {
+                index += len;
+                continue;
+            }
+
+            let (pair, pair_len) =
+                Pair::parse(&slice[index..], dots, delimiter, config.pair_separator);
             index += pair_len;
 
-            let decoded_key = pair.0.decode(&mut scratch);
+            if config.skip_invalid_pairs && pair.0.depth() > config.max_depth {
+                continue;
+            }
 
-            if let Some(values) = pairs.get_mut(decoded_key.as_ref()) {
+            let decoded_key = pair.0.decode(&mut keys, config.decode_options);
+
+            if let Some(values) = pairs.get_mut(&decoded_key[..]) {
                 values.push(pair);
             } else {
                 pairs.insert(decoded_key.into_cow(), vec![pair]);
             }
         }
 
-        Self { pairs }
+        Self {
+            pairs,
+            config: *config,
+        }
     }
 
-    fn from_pairs<I>(iter: I) -> Self
+    fn from_pairs<I>(iter: I, config: QsConfig) -> Self
     where
         I: Iterator<Item = Pair<'a>>,
     {
-        let mut pairs: BTreeMap<_, Vec<Pair<'a>>> = BTreeMap::new();
+        let mut pairs: OrderedMap<Cow<'a, [u8]>, Vec<Pair<'a>>> = OrderedMap::new();
 
-        let mut scratch = Vec::new();
+        let mut keys = KeyDecoder::default();
         let subpairs = iter.filter_map(|p| Some((p.0.subkey()?, p.1)));
 
         for (k, v) in subpairs {
-            let decoded_key = k.decode(&mut scratch);
+            let decoded_key = k.decode(&mut keys, config.decode_options);
             let pair = Pair::new(k, v);
 
-            if let Some(values) = pairs.get_mut(decoded_key.as_ref()) {
+            if let Some(values) = pairs.get_mut(&decoded_key[..]) {
                 values.push(pair);
             } else {
                 pairs.insert(decoded_key.into_cow(), vec![pair]);
             }
         }
 
-        Self { pairs }
+        Self { pairs, config }
     }
 
-    /// Returns a vector containing all the keys in querystring.
+    /// Returns a vector containing all the keys in querystring, in the order they first appear.
     pub fn keys(&self) -> Vec<&Cow<'a, [u8]>> {
         self.pairs.keys().collect()
     }
 
     /// Parses all the subkeys for this key and optionally returns a new 'BracketsQS' if the key exists
-    pub fn sub_values(&self, key: &'a [u8]) -> Option<BracketsQS> {
-        Some(Self::from_pairs(self.pairs.get(key)?.iter().copied()))
+    pub fn sub_values(&self, key: &[u8]) -> Option<BracketsQS<'a>> {
+        Some(Self::from_pairs(
+            self.pairs.get(key)?.iter().copied(),
+            QsConfig {
+                max_depth: self.config.max_depth.saturating_sub(1),
+                ..self.config
+            },
+        ))
     }
 
     /// Returns a vector containing all the values assigned to a key.
@@ -293,17 +528,21 @@ impl<'a> BracketsQS<'a> {
     ///
     /// # Note
     /// Percent decoding the value is done on-the-fly **every time** this function is called.
-    pub fn values(&self, key: &'a [u8]) -> Option<Vec<Option<Cow<'a, [u8]>>>> {
+    pub fn values(&self, key: &[u8]) -> Option<Vec<Option<Cow<'a, [u8]>>>> {
         let mut scratch = Vec::new();
+        let mut values = Vec::new();
+
+        for pair in self.pairs.get(key)?.iter().filter(|p| !p.0.has_subkey()) {
+            match &pair.1 {
+                Some(value) => values.extend(value.split().map(|slice| {
+                    let value = parse_bytes_with(slice, &mut scratch, self.config.decode_options);
+                    Some(unescape(value, &self.config).into_cow())
+                })),
+                None => values.push(None),
+            }
+        }
 
-        Some(
-            self.pairs
-                .get(key)?
-                .iter()
-                .filter(|p| !p.0.has_subkey())
-                .map(|p| p.1.as_ref().map(|v| v.decode(&mut scratch).into_cow()))
-                .collect(),
-        )
+        Some(values)
     }
 
     /// Returns the last direct value assigned to a key.
@@ -313,26 +552,41 @@ impl<'a> BracketsQS<'a> {
     ///
     /// # Note
     /// Percent decoding the value is done on-the-fly **every time** this function is called.
-    pub fn value(&self, key: &'a [u8]) -> Option<Option<Cow<'a, [u8]>>> {
+    pub fn value(&self, key: &[u8]) -> Option<Option<Cow<'a, [u8]>>> {
         let mut scratch = Vec::new();
 
         self.pairs
             .get(key)?
             .iter()
-            .filter(|p| !p.0.has_subkey())
-            .last()
-            .map(|p| p.1.as_ref().map(|v| v.decode(&mut scratch).into_cow()))
+            .rfind(|p| !p.0.has_subkey())
+            .map(|p| {
+                p.1.as_ref().map(|v| {
+                    v.decode(&mut scratch, self.config.decode_options)
+                        .into_cow()
+                })
+            })
     }
 }
 
 #[cfg(feature = "serde")]
 mod de {
+    use alloc::format;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use core::ops::Range;
+
     use _serde::{de, forward_to_deserialize_any, Deserialize, Deserializer};
 
+    use crate::arena::Arena;
     use crate::de::{
-        Error, ErrorKind, QSDeserializer,
-        __implementors::{DecodedSlice, IntoDeserializer, RawSlice},
+        __implementors::{DecodedSlice, IntoDeserializer, RawSlice, Scratch, Value},
+        address_range, invalid_state_error, transform_key, unknown_key_error, with_key,
+        DuplicateKeys, EmptyBrackets, Error, ErrorKind, KeyConflict, QSDeserializer, QsBuffers,
+        SparseIndexes,
     };
+    use crate::decode::KeyDecoder;
+
+    use crate::{presence, raw, separated};
 
     use super::{BracketsQS, Pair};
 
@@ -340,8 +594,115 @@ mod de {
 
     impl<'a> BracketsQS<'a> {
         /// Deserialize the parsed slice into T
+        ///
+        /// It fails if any of the keys is nested deeper than the max depth of the config, or
+        /// has both a value and sub keys with `KeyConflict::Error`.
         pub fn deserialize<T: Deserialize<'a>>(self) -> Result<T, Error> {
-            T::deserialize(QSDeserializer::new(self.into_iter()))
+            self.deserialize_with(&mut QsBuffers::new(), None)
+        }
+
+        /// Deserialize the parsed slice into T, decoding into the buffers, or the arena if
+        /// there is one
+        pub(crate) fn deserialize_with<T: Deserialize<'a>>(
+            self,
+            buffers: &mut QsBuffers,
+            arena: Option<&'a Arena>,
+        ) -> Result<T, Error> {
+            self.check_depth()?;
+            self.check_conflicts()?;
+
+            let config = self.config;
+            QSDeserializer::deserialize(self.into_iter(), &config, buffers, arena)
+        }
+
+        /// Deserialize the sub values of a key into T, as if they were at the root
+        pub(crate) fn deserialize_scoped<T: Deserialize<'a>>(self, key: &[u8]) -> Result<T, Error> {
+            self.check_depth()?;
+
+            match self.sub_values(key) {
+                Some(scoped) => scoped.deserialize(),
+                None => BracketsQS::from_pairs(core::iter::empty(), self.config).deserialize(),
+            }
+        }
+
+        /// Returns an error if any of the keys has more sub keys than the max depth
+        pub(crate) fn check_depth(&self) -> Result<(), Error> {
+            for (_, pairs) in self.pairs.iter() {
+                if let Some(pair) = pairs
+                    .iter()
+                    .find(|pair| pair.0.depth() > self.config.max_depth)
+                {
+                    let mut error = Error::new(ErrorKind::DepthLimitExceeded).message(format!(
+                        "key is nested deeper than the max depth of {}",
+                        self.config.max_depth
+                    ));
+                    error.key = pair.0.path(self.config.decode_options);
+                    return Err(error);
+                }
+            }
+            Ok(())
+        }
+
+        /// Returns an error if any of the keys has both a value and sub keys, when the config
+        /// doesn't resolve the conflicts
+        pub(crate) fn check_conflicts(&self) -> Result<(), Error> {
+            if self.config.key_conflict != KeyConflict::Error {
+                return Ok(());
+            }
+
+            for (key, pairs) in self.pairs.iter() {
+                if has_conflict(pairs) {
+                    return Err(Error::new(ErrorKind::KeyConflict)
+                        .message(String::from("the key has both a value and sub keys"))
+                        .value(key));
+                }
+                if pairs.iter().any(|p| p.0.subkey().is_some()) {
+                    if let Some(sub_values) = self.sub_values(key) {
+                        sub_values.check_conflicts()?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Returns true if the values of a key are used instead of its conflicting sub keys
+        pub(crate) fn scalar_wins(&self, key: &[u8]) -> bool {
+            self.config.key_conflict == KeyConflict::Scalar
+                && self
+                    .pairs
+                    .get(key)
+                    .map_or(false, |pairs| has_conflict(pairs))
+        }
+
+        /// Returns the resolved indexes of the values with empty brackets under a key, each
+        /// repeated for the delimited values
+        pub(crate) fn empty_indexes(&self, key: &[u8]) -> Result<Vec<usize>, Error> {
+            let pairs = match self.pairs.get(key) {
+                Some(pairs) => pairs,
+                None => return Ok(Vec::new()),
+            };
+
+            let indexes = pairs
+                .iter()
+                .map(|pair| match pair.0.subkey() {
+                    Some(subkey) if subkey.is_empty() => None,
+                    Some(subkey) => Some(lexical::parse::<usize, _>(subkey.0).unwrap_or_default()),
+                    None => Some(0),
+                })
+                .collect::<Vec<_>>();
+            let resolved = resolve_indexes(&indexes, self.config.empty_brackets)?;
+
+            let mut empty = Vec::new();
+            for (pair, index) in pairs.iter().zip(resolved) {
+                match pair.0.subkey() {
+                    Some(subkey) if subkey.is_empty() && !subkey.has_subkey() => {
+                        let count = pair.1.unwrap_or_default().split().count();
+                        empty.extend(core::iter::repeat(index).take(count));
+                    }
+                    _ => {}
+                }
+            }
+            Ok(empty)
         }
 
         pub(crate) fn into_iter(self) -> impl Iterator<Item = (DecodedSlice<'a>, Pairs<'a>)> {
@@ -354,33 +715,141 @@ mod de {
     impl<'a, 's> IntoDeserializer<'a, 's> for Pairs<'a> {
         type Deserializer = PairsDeserializer<'a, 's>;
 
-        fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer {
+        fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
             PairsDeserializer(self.0, scratch)
         }
     }
 
-    pub struct PairsDeserializer<'a, 's>(Vec<Pair<'a>>, &'s mut Vec<u8>);
+    /// Returns true if some of the pairs have no sub keys(`a=1`) and some have sub keys which
+    /// are not indexes(`a[b]=2`)
+    fn has_conflict(pairs: &[Pair]) -> bool {
+        pairs.iter().any(|p| p.0.subkey().is_none())
+            && pairs.iter().any(|p| match p.0.subkey() {
+                Some(subkey) => !subkey.0.iter().all(u8::is_ascii_digit),
+                None => false,
+            })
+    }
+
+    pub struct PairsDeserializer<'a, 's>(Vec<Pair<'a>>, &'s mut Scratch);
 
     impl<'a, 's> PairsDeserializer<'a, 's> {
+        /// Drops the values or the sub keys of a key which has both, based on the key conflict
+        /// policy. Enums get all the pairs, so the last one still picks the variant.
         #[inline]
-        fn to_seq_values(&mut self) -> Result<Vec<(usize, RawSlice<'a>)>, Error> {
-            let mut values = std::mem::take(&mut self.0)
-                .into_iter()
-                .map(|pair| {
-                    let index = match pair.0.subkey() {
-                        Some(subkey) if !subkey.is_empty() => lexical::parse::<usize, _>(subkey.0)
-                            .map_err(|e| {
-                                Error::new(ErrorKind::InvalidNumber)
-                                    .message(format!("invalid index: {}", e))
-                            })?,
-                        _ => 0,
-                    };
-                    Ok((index, RawSlice(pair.1.unwrap_or_default().slice())))
+        fn resolve_conflict(&mut self) {
+            if has_conflict(&self.0) {
+                match self.1.config.key_conflict {
+                    KeyConflict::Scalar => self.0.retain(|p| p.0.subkey().is_none()),
+                    _ => self.0.retain(|p| p.0.subkey().is_some()),
+                }
+            }
+        }
+
+        /// Returns the raw value to deserialize a single value from, based on the duplicate
+        /// keys policy
+        #[inline]
+        fn single_value(&self) -> Result<&'a [u8], Error> {
+            let pair = match self.1.config.duplicate_keys {
+                DuplicateKeys::Last => self.0.last(),
+                DuplicateKeys::First => self.0.first(),
+                DuplicateKeys::Error if self.0.len() > 1 => {
+                    return Err(Error::new(ErrorKind::DuplicateKey).message(String::from(
+                        "the key is repeated, but a single value is expected",
+                    )))
+                }
+                DuplicateKeys::Error => self.0.first(),
+            };
+            Ok(pair
+                .ok_or_else(|| invalid_state_error("a key without any value"))?
+                .1
+                .unwrap_or_default()
+                .slice())
+        }
+
+        /// Returns the values of a sequence sorted by their indexes, delimited values are
+        /// split into multiple items with the same index. The missing indexes are `None`
+        /// when they are filled.
+        #[inline]
+        fn take_seq_values(&mut self) -> Result<Vec<Option<RawSlice<'a>>>, Error> {
+            let pairs = core::mem::take(&mut self.0);
+            let indexes = pairs
+                .iter()
+                .map(|pair| match pair.0.subkey() {
+                    Some(subkey) if !subkey.is_empty() => {
+                        lexical::parse::<usize, _>(subkey.0).map(Some).map_err(|e| {
+                            Error::new(ErrorKind::InvalidNumber)
+                                .message(format!("invalid index: {}", e))
+                        })
+                    }
+                    Some(_) => Ok(None),
+                    // Values without brackets are at the first index
+                    None => Ok(Some(0)),
                 })
-                .collect::<Result<Vec<(usize, RawSlice)>, Error>>()?;
+                .collect::<Result<Vec<_>, Error>>()?;
+            let indexes = resolve_indexes(&indexes, self.1.config.empty_brackets)?;
+
+            let max_index = self.1.config.max_index;
+            if indexes.iter().any(|index| *index > max_index) {
+                return Err(Error::new(ErrorKind::LimitExceeded)
+                    .message(format!("index larger than the max index of {}", max_index)));
+            }
 
+            let mut values = Vec::new();
+            for (pair, index) in pairs.into_iter().zip(indexes) {
+                let value = pair.1.unwrap_or_default();
+                values.extend(value.split().map(|slice| (index, RawSlice(slice))));
+            }
             values.sort_by_key(|item| item.0);
-            Ok(values)
+
+            let sparse_indexes = self.1.config.sparse_indexes;
+            let mut items = Vec::with_capacity(values.len());
+            let mut next = 0;
+            for (index, value) in values {
+                if index > next {
+                    match sparse_indexes {
+                        SparseIndexes::Compact => {}
+                        SparseIndexes::Fill => items.resize(items.len() + index - next, None),
+                        SparseIndexes::Error => {
+                            return Err(Error::new(ErrorKind::InvalidIndex)
+                                .message(format!("the index {} of a sequence is missing", next)))
+                        }
+                    }
+                }
+                items.push(Some(value));
+                next = index + 1;
+            }
+            Ok(items)
+        }
+    }
+
+    /// Gives the empty indexes(`key[]`, as `None`) their place among the explicit ones, the
+    /// indexes are in the order they appear in the query string
+    pub(crate) fn resolve_indexes(
+        indexes: &[Option<usize>],
+        empty_brackets: EmptyBrackets,
+    ) -> Result<Vec<usize>, Error> {
+        match empty_brackets {
+            EmptyBrackets::Append => {
+                let mut next = 0;
+                Ok(indexes
+                    .iter()
+                    .map(|index| {
+                        let index = index.unwrap_or(next);
+                        next = next.max(index.saturating_add(1));
+                        index
+                    })
+                    .collect())
+            }
+            EmptyBrackets::Error
+                if indexes.iter().any(Option::is_none) && indexes.iter().any(Option::is_some) =>
+            {
+                Err(Error::new(ErrorKind::InvalidIndex).message(String::from(
+                    "a sequence has both empty brackets and explicit indexes",
+                )))
+            }
+            EmptyBrackets::First | EmptyBrackets::Error => {
+                Ok(indexes.iter().map(|index| index.unwrap_or(0)).collect())
+            }
         }
     }
 
@@ -388,13 +857,13 @@ mod de {
         ($($method:ident ,)*) => {
             $(
                 #[inline]
-                fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+                fn $method<V>(mut self, visitor: V) -> Result<V::Value, Error>
                 where
                     V: de::Visitor<'de>,
                 {
-                    let scratch = self.1;
-                    let value = self.0.last().unwrap().1.unwrap_or_default().slice();
-                    RawSlice(value).into_deserializer(scratch).$method(visitor)
+                    self.resolve_conflict();
+                    let value = self.single_value()?;
+                    RawSlice(value).into_deserializer(self.1).$method(visitor)
                 }
             )*
         };
@@ -407,23 +876,28 @@ mod de {
         where
             V: de::Visitor<'de>,
         {
-            visitor.visit_seq(PairsSeqDeserializer(
-                self.to_seq_values()?.into_iter().map(|v| v.1),
-                self.1,
-            ))
+            self.resolve_conflict();
+            let mut values = self.take_seq_values()?;
+
+            if self.1.config.empty_as_empty_seq {
+                if let [Some(value)] = values.as_slice() {
+                    if value.0.is_empty() {
+                        values.clear();
+                    }
+                }
+            }
+            visitor.visit_seq(PairsSeqDeserializer(values.into_iter(), self.1, 0))
         }
 
         fn deserialize_tuple<V>(mut self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
         where
             V: de::Visitor<'de>,
         {
-            let values = self.to_seq_values()?;
+            self.resolve_conflict();
+            let values = self.take_seq_values()?;
 
             if values.len() == len {
-                visitor.visit_seq(PairsSeqDeserializer(
-                    values.into_iter().map(|v| v.1),
-                    self.1,
-                ))
+                visitor.visit_seq(PairsSeqDeserializer(values.into_iter(), self.1, 0))
             } else {
                 Err(Error::new(ErrorKind::InvalidLength))
             }
@@ -442,24 +916,40 @@ mod de {
         }
 
         fn deserialize_newtype_struct<V>(
-            self,
-            _: &'static str,
+            mut self,
+            name: &'static str,
             visitor: V,
         ) -> Result<V::Value, Self::Error>
         where
             V: de::Visitor<'de>,
         {
+            if name == presence::TOKEN {
+                self.resolve_conflict();
+                if matches!(self.0.last(), Some(pair) if !pair.0.has_subkey() && pair.1.is_none()) {
+                    return visitor.visit_unit();
+                }
+            }
+            if name == raw::TOKEN || separated::delimiter(name).is_some() {
+                self.resolve_conflict();
+                let value = self.single_value()?;
+                return RawSlice(value)
+                    .into_deserializer(self.1)
+                    .deserialize_newtype_struct(name, visitor);
+            }
             visitor.visit_newtype_struct(self)
         }
 
-        fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        fn deserialize_map<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
         where
             V: de::Visitor<'de>,
         {
+            self.resolve_conflict();
             visitor.visit_map(PairsMapDeserializer {
-                iter: BracketsQS::from_pairs(self.0.into_iter()).into_iter(),
+                iter: BracketsQS::from_pairs(self.0.into_iter(), self.1.config).into_iter(),
                 scratch: self.1,
                 value: None,
+                key: Vec::new(),
+                key_span: None,
             })
         }
 
@@ -477,23 +967,42 @@ mod de {
 
         fn deserialize_enum<V>(
             self,
-            _: &'static str,
-            _: &'static [&'static str],
+            name: &'static str,
+            variants: &'static [&'static str],
             visitor: V,
         ) -> Result<V::Value, Self::Error>
         where
             V: de::Visitor<'de>,
         {
-            visitor.visit_enum(self)
+            // Unit variants are matched in any case or by index by the value deserializer
+            let config = self.1.config;
+            match self.0.last() {
+                Some(pair)
+                    if (config.case_insensitive_enums || config.numeric_enums)
+                        && pair.0.subkey().is_none() =>
+                {
+                    RawSlice(pair.1.unwrap_or_default().0)
+                        .into_deserializer(self.1)
+                        .deserialize_enum(name, variants, visitor)
+                }
+                _ => visitor.visit_enum(self),
+            }
         }
 
-        fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
         where
             V: de::Visitor<'de>,
         {
-            if self.0.is_empty() {
-                visitor.visit_none()
-            } else if self.0.len() == 1 && !self.0[0].0.has_subkey() && self.0[0].1.is_none() {
+            self.resolve_conflict();
+            if self.0.is_empty()
+                || (self.0.len() == 1
+                    && !self.0[0].0.has_subkey()
+                    && self.0[0].1.is_none()
+                    && !self.1.config.present_as_some)
+                || (self.1.config.null_as_none
+                    && self.0.iter().all(|pair| !pair.0.has_subkey())
+                    && matches!(self.single_value(), Ok(value) if RawSlice(value).is_null()))
+            {
                 visitor.visit_none()
             } else {
                 visitor.visit_some(self)
@@ -506,7 +1015,58 @@ mod de {
             deserialize_f32, deserialize_f64,
             deserialize_char, deserialize_str, deserialize_string, deserialize_identifier,
             deserialize_bool, deserialize_bytes, deserialize_byte_buf, deserialize_unit,
-            deserialize_any, deserialize_ignored_any,
+        }
+
+        /// Self describing types(like flattened maps) get the same shape as a [`Value`](crate::Value)
+        fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            self.resolve_conflict();
+
+            // Sparse indexes(`key[3]`) are kept as the keys of a map
+            let mut indexes = Vec::new();
+            for pair in &self.0 {
+                match pair.0.subkey() {
+                    Some(subkey) if subkey.has_subkey() => return self.deserialize_map(visitor),
+                    Some(subkey) if !subkey.is_empty() => {
+                        match lexical::parse::<usize, _>(subkey.0) {
+                            Ok(index) => indexes.push(index),
+                            Err(_) => return self.deserialize_map(visitor),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            indexes.sort_unstable();
+            indexes.dedup();
+            if indexes.iter().enumerate().any(|(i, index)| i != *index) {
+                return self.deserialize_map(visitor);
+            }
+
+            let count: usize = self
+                .0
+                .iter()
+                .map(|pair| pair.1.unwrap_or_default().split().count())
+                .sum();
+            if count == 1 && !self.0[0].0.has_subkey() {
+                let value = self.single_value()?;
+                RawSlice(value)
+                    .into_deserializer(self.1)
+                    .deserialize_any(visitor)
+            } else {
+                self.deserialize_seq(visitor)
+            }
+        }
+
+        fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            if self.1.config.strict_keys {
+                return Err(unknown_key_error());
+            }
+            visitor.visit_unit()
         }
 
         forward_to_deserialize_any! {
@@ -523,21 +1083,29 @@ mod de {
         where
             V: de::DeserializeSeed<'de>,
         {
-            let last_pair = self.0.last().expect("Values iterator can't be empty");
+            let last_pair = self
+                .0
+                .last()
+                .ok_or_else(|| invalid_state_error("a key without any value"))?;
             match last_pair.0.subkey() {
                 Some(subkey) => {
-                    let mut scratch = self.1;
-                    let pairs = BracketsQS::from_pairs(self.0.into_iter())
+                    let scratch = self.1;
+                    // The pairs are grouped by their decoded sub keys
+                    let decoded = subkey
+                        .decode(&mut KeyDecoder::default(), scratch.config.decode_options)
+                        .into_cow()
+                        .into_owned();
+                    let pairs = BracketsQS::from_pairs(self.0.into_iter(), scratch.config)
                         .pairs
-                        .remove(subkey.0)
-                        .unwrap();
-                    seed.deserialize(RawSlice(subkey.0).into_deserializer(&mut scratch))
+                        .remove(&decoded[..])
+                        .ok_or_else(|| invalid_state_error("a sub key without any value"))?;
+                    seed.deserialize(RawSlice(subkey.0).into_deserializer(scratch))
                         .map(move |v| (v, Self(pairs, scratch)))
                 }
                 None => {
-                    let mut scratch = self.1;
+                    let scratch = self.1;
                     seed.deserialize(
-                        RawSlice(last_pair.1.unwrap_or_default().0).into_deserializer(&mut scratch),
+                        RawSlice(last_pair.1.unwrap_or_default().0).into_deserializer(scratch),
                     )
                     .map(move |v| (v, PairsDeserializer(Vec::new(), scratch)))
                 }
@@ -549,7 +1117,7 @@ mod de {
         type Error = Error;
 
         fn unit_variant(self) -> Result<(), Self::Error> {
-            if self.0.len() == 0 {
+            if self.0.is_empty() {
                 Ok(())
             } else {
                 Err(Error::new(ErrorKind::Other)
@@ -583,11 +1151,13 @@ mod de {
         }
     }
 
-    struct PairsSeqDeserializer<'s, I>(I, &'s mut Vec<u8>);
+    /// The values of a sequence by their index, with the index of the next one to report the
+    /// index of the errors
+    struct PairsSeqDeserializer<'s, I>(I, &'s mut Scratch, usize);
 
     impl<'de, 's, I> de::SeqAccess<'de> for PairsSeqDeserializer<'s, I>
     where
-        I: Iterator<Item = RawSlice<'de>>,
+        I: Iterator<Item = Option<RawSlice<'de>>>,
     {
         type Error = Error;
 
@@ -595,11 +1165,112 @@ mod de {
         where
             T: de::DeserializeSeed<'de>,
         {
-            if let Some(v) = self.0.next() {
-                seed.deserialize(v.into_deserializer(self.1)).map(Some)
-            } else {
-                Ok(None)
+            let index = self.2;
+            self.2 += 1;
+            match self.0.next() {
+                Some(Some(v)) => seed.deserialize(v.into_deserializer(self.1)),
+                Some(None) => seed.deserialize(DefaultDeserializer(self.1)),
+                None => return Ok(None),
             }
+            .map(Some)
+            .map_err(|e| with_key(e, index.to_string().as_bytes(), None))
+        }
+    }
+
+    /// Deserializes the default value of a type, for the missing indexes of a sequence
+    struct DefaultDeserializer<'s>(&'s mut Scratch);
+
+    macro_rules! default_value {
+        ($($method:ident => $visit:ident($value:expr),)*) => {
+            $(
+                #[inline]
+                fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+                where
+                    V: de::Visitor<'de>,
+                {
+                    visitor.$visit($value)
+                }
+            )*
+        };
+    }
+
+    impl<'de, 's> de::Deserializer<'de> for DefaultDeserializer<'s> {
+        type Error = Error;
+
+        default_value! {
+            deserialize_bool => visit_bool(false),
+            deserialize_i8 => visit_i8(0), deserialize_i16 => visit_i16(0),
+            deserialize_i32 => visit_i32(0), deserialize_i64 => visit_i64(0),
+            deserialize_i128 => visit_i128(0),
+            deserialize_u8 => visit_u8(0), deserialize_u16 => visit_u16(0),
+            deserialize_u32 => visit_u32(0), deserialize_u64 => visit_u64(0),
+            deserialize_u128 => visit_u128(0),
+            deserialize_f32 => visit_f32(0.0), deserialize_f64 => visit_f64(0.0),
+            deserialize_char => visit_char('\0'),
+            deserialize_str => visit_borrowed_str(""), deserialize_string => visit_borrowed_str(""),
+            deserialize_bytes => visit_borrowed_bytes(&[]),
+            deserialize_byte_buf => visit_borrowed_bytes(&[]),
+        }
+
+        fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.visit_unit()
+        }
+
+        fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.visit_none()
+        }
+
+        fn deserialize_newtype_struct<V>(
+            self,
+            _: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.visit_newtype_struct(self)
+        }
+
+        fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.visit_seq(PairsSeqDeserializer(core::iter::empty(), self.0, 0))
+        }
+
+        fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.visit_map(PairsMapDeserializer {
+                iter: core::iter::empty(),
+                scratch: self.0,
+                value: None,
+                key: Vec::new(),
+                key_span: None,
+            })
+        }
+
+        fn deserialize_struct<V>(
+            self,
+            _: &'static str,
+            _: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            self.deserialize_map(visitor)
+        }
+
+        forward_to_deserialize_any! {
+            unit unit_struct tuple tuple_struct enum identifier ignored_any
         }
     }
 
@@ -608,8 +1279,11 @@ mod de {
         I: Iterator<Item = (DecodedSlice<'de>, Pairs<'de>)>,
     {
         iter: I,
-        scratch: &'s mut Vec<u8>,
+        scratch: &'s mut Scratch,
         value: Option<Pairs<'de>>,
+        // The key of the value, to report the key of the errors
+        key: Vec<u8>,
+        key_span: Option<Range<usize>>,
     }
 
     impl<'de, 's, I> de::MapAccess<'de> for PairsMapDeserializer<'de, 's, I>
@@ -624,7 +1298,11 @@ mod de {
         {
             if let Some((k, v)) = self.iter.next() {
                 self.value = Some(v);
+                self.key.clear();
+                self.key.extend_from_slice(&k.0);
+                self.key_span = k.raw_slice().map(address_range);
 
+                let k = transform_key(k, &self.scratch.config);
                 seed.deserialize(k.into_deserializer(self.scratch))
                     .map(Some)
             } else {
@@ -636,12 +1314,12 @@ mod de {
         where
             V: de::DeserializeSeed<'de>,
         {
-            seed.deserialize(
-                self.value
-                    .take()
-                    .expect("next_value is called before next_key")
-                    .into_deserializer(self.scratch),
-            )
+            let value = self
+                .value
+                .take()
+                .ok_or_else(|| invalid_state_error("next_value is called before next_key"))?;
+            seed.deserialize(value.into_deserializer(self.scratch))
+                .map_err(|e| with_key(e, &self.key, self.key_span.clone()))
         }
 
         fn size_hint(&self) -> Option<usize> {
@@ -652,7 +1330,7 @@ mod de {
 
 #[cfg(test)]
 mod tests {
-    use std::borrow::Cow;
+    use alloc::borrow::Cow;
 
     use super::BracketsQS;
 
@@ -789,4 +1467,84 @@ mod tests {
             Some(vec![Some("qux".as_bytes().into())])
         )
     }
+
+    #[test]
+    fn parse_dots() {
+        let slice = b"foo.bar=baz&foo[bar].qux=buzz&foo.foo%2Ebar=qux&foo.=empty";
+
+        let parser = BracketsQS::parse_dots(slice);
+        assert_eq!(parser.keys(), vec![&Cow::Borrowed(b"foo")]);
+        assert_eq!(parser.values(b"foo"), Some(vec![]));
+
+        let foo_values = parser.sub_values(b"foo").unwrap();
+        assert_eq!(
+            foo_values.keys(),
+            vec![
+                &Cow::Borrowed(&b"bar"[..]),
+                &Cow::Borrowed(&b"foo.bar"[..]),
+                &Cow::Borrowed(&b""[..])
+            ]
+        );
+        assert_eq!(
+            foo_values.values(b"bar"),
+            Some(vec![Some("baz".as_bytes().into())])
+        );
+        assert_eq!(
+            foo_values.values(b"foo.bar"),
+            Some(vec![Some("qux".as_bytes().into())])
+        );
+        assert_eq!(
+            foo_values.values(b""),
+            Some(vec![Some("empty".as_bytes().into())])
+        );
+
+        let bar_values = foo_values.sub_values(b"bar").unwrap();
+        assert_eq!(
+            bar_values.values(b"qux"),
+            Some(vec![Some("buzz".as_bytes().into())])
+        );
+    }
+
+    #[test]
+    fn parse_delimited() {
+        let slice = b"foo[bar]=a,b&foo[bar]=c&foo[bar]&foo=x,y";
+
+        let parser = BracketsQS::parse_delimited(slice, b',');
+        assert_eq!(
+            parser.values(b"foo"),
+            Some(vec![
+                Some("x".as_bytes().into()),
+                Some("y".as_bytes().into())
+            ])
+        );
+        assert_eq!(parser.value(b"foo"), Some(Some("x,y".as_bytes().into())));
+
+        let foo_values = parser.sub_values(b"foo").unwrap();
+        assert_eq!(
+            foo_values.values(b"bar"),
+            Some(vec![
+                Some("a".as_bytes().into()),
+                Some("b".as_bytes().into()),
+                Some("c".as_bytes().into()),
+                None
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_plus_literal() {
+        use crate::{ParseMode, QsConfig};
+
+        let slice = b"foo+bar[baz+qux]=a+b";
+        let config = QsConfig::new(ParseMode::Brackets).plus_as_space(false);
+
+        let parser = BracketsQS::parse_with_config(slice, &config);
+        assert_eq!(parser.keys(), vec![&Cow::Borrowed(&b"foo+bar"[..])]);
+
+        let foo_values = parser.sub_values(b"foo+bar").unwrap();
+        assert_eq!(
+            foo_values.value(b"baz+qux"),
+            Some(Some("a+b".as_bytes().into()))
+        );
+    }
 }
@@ -0,0 +1,217 @@
+use alloc::{borrow::Cow, vec::Vec};
+
+use crate::config::{DuplicateKeys, ParseMode, QsConfig};
+use crate::decode::{parse_bytes_with, DecodeOptions, Reference};
+
+use super::ordered::OrderedMap;
+
+/// Removes the spaces and tabs around a part of the header
+fn trim(mut slice: &[u8]) -> &[u8] {
+    while let [b' ' | b'\t', rest @ ..] = slice {
+        slice = rest;
+    }
+    while let [rest @ .., b' ' | b'\t'] = slice {
+        slice = rest;
+    }
+    slice
+}
+
+struct Pair<'a>(&'a [u8], Option<&'a [u8]>);
+
+impl<'a> Pair<'a> {
+    fn parse(slice: &'a [u8]) -> Self {
+        match slice.iter().position(|b| *b == b'=') {
+            Some(index) => {
+                let value = match trim(&slice[index + 1..]) {
+                    // Values can be wrapped in double quotes
+                    [b'"', value @ .., b'"'] => value,
+                    value => value,
+                };
+                Self(trim(&slice[..index]), Some(value))
+            }
+            None => Self(trim(slice), None),
+        }
+    }
+
+    fn decode_key<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        parse_bytes_with(self.0, scratch, options)
+    }
+}
+
+/// A parser for the `Cookie` header(`a=1; b=hello%20world; flag`)
+/// The pairs are separated by `;` and optional spaces, values can be quoted and keys without
+/// a value are flags, the same as in query strings. Like `UrlEncodedQS`, a single value is kept
+/// for each key, but it is the first one by default, as browsers send the most specific cookie
+/// first.
+///
+/// # Note
+/// Keys are decoded when calling the `parse` method, but values are lazily decoded when you
+/// call the `value` method for their keys.
+///
+/// # Example
+/// ```rust
+///# use std::borrow::Cow;
+/// use serde_querystring::CookieQS;
+///
+/// let parser = CookieQS::parse(b"session=abc; theme=\"dark%20blue\"; session=def; flag");
+///
+/// assert_eq!(parser.keys().len(), 3);
+/// assert_eq!(parser.value(b"session"), Some(Some(Cow::Borrowed(&b"abc"[..]))));
+/// assert_eq!(parser.value(b"theme"), Some(Some(Cow::Borrowed(&b"dark blue"[..]))));
+/// assert_eq!(parser.value(b"flag"), Some(None));
+/// ```
+pub struct CookieQS<'a> {
+    pairs: OrderedMap<Cow<'a, [u8]>, Pair<'a>>,
+    config: QsConfig,
+    // The first repeated key, when repeated keys are errors
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    duplicate: Option<Cow<'a, [u8]>>,
+}
+
+impl<'a> CookieQS<'a> {
+    /// Parse the value of a `Cookie` header into a `CookieQS`
+    pub fn parse(slice: &'a [u8]) -> Self {
+        Self::parse_with_config(
+            slice,
+            &QsConfig::new(ParseMode::UrlEncoded).duplicate_keys(DuplicateKeys::First),
+        )
+    }
+
+    /// Parse the value of a `Cookie` header into a `CookieQS`, using the options of the config
+    ///
+    /// The parse mode and the pair separator of the config are ignored.
+    pub fn parse_with_config(slice: &'a [u8], config: &QsConfig) -> Self {
+        let mut pairs = OrderedMap::new();
+        let mut scratch = Vec::new();
+        let mut duplicate = None;
+
+        for pair in slice.split(|b| *b == b';').map(trim) {
+            if pair.is_empty() {
+                continue;
+            }
+
+            let pair = Pair::parse(pair);
+            let decoded_key = pair.decode_key(&mut scratch, config.decode_options);
+
+            if let Some(old_pair) = pairs.get_mut(&decoded_key[..]) {
+                match config.duplicate_keys {
+                    DuplicateKeys::Last => *old_pair = pair,
+                    DuplicateKeys::First => {}
+                    DuplicateKeys::Error => {
+                        if duplicate.is_none() {
+                            duplicate = Some(decoded_key.into_cow());
+                        }
+                        *old_pair = pair;
+                    }
+                }
+            } else {
+                pairs.insert(decoded_key.into_cow(), pair);
+            }
+        }
+
+        Self {
+            pairs,
+            config: *config,
+            duplicate,
+        }
+    }
+
+    /// Returns a vector containing all the cookie names, in the order they first appear.
+    pub fn keys(&self) -> Vec<&Cow<'a, [u8]>> {
+        self.pairs.keys().collect()
+    }
+
+    /// Returns the first value assigned to a key, or the last one with `DuplicateKeys::Last`.
+    ///
+    /// It returns `None` if the **key doesn't exist** in the header,
+    /// and returns `Some(None)` if the **key doesn't have a value**, ex `"; key;"`
+    ///
+    /// # Note
+    /// Percent decoding the value is done on-the-fly **every time** this function is called.
+    pub fn value(&self, key: &[u8]) -> Option<Option<Cow<'a, [u8]>>> {
+        let mut scratch = Vec::new();
+        self.pairs.get(key).map(|p| {
+            p.1.map(|v| parse_bytes_with(v, &mut scratch, self.config.decode_options).into_cow())
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+mod de {
+    use alloc::string::String;
+
+    use _serde::Deserialize;
+
+    use crate::de::{
+        __implementors::{DecodedSlice, RawSlice},
+        Error, ErrorKind, QSDeserializer, QsBuffers,
+    };
+
+    use super::CookieQS;
+
+    impl<'a> CookieQS<'a> {
+        /// Deserialize the parsed header into T
+        pub fn deserialize<T: Deserialize<'a>>(self) -> Result<T, Error> {
+            if let Some(key) = &self.duplicate {
+                return Err(Error::new(ErrorKind::DuplicateKey)
+                    .message(String::from("the key is repeated"))
+                    .value(key));
+            }
+
+            let config = self.config;
+            QSDeserializer::deserialize(self.into_iter(), &config, &mut QsBuffers::new(), None)
+        }
+
+        pub(crate) fn into_iter(
+            self,
+        ) -> impl Iterator<Item = (DecodedSlice<'a>, Option<RawSlice<'a>>)> {
+            self.pairs
+                .into_iter()
+                .map(|(key, pair)| (DecodedSlice(key), pair.1.map(RawSlice)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::Cow;
+
+    use super::CookieQS;
+
+    #[test]
+    fn parse_pairs() {
+        let parser = CookieQS::parse(b"a=1; b=hello%20world;c=x+y ;  d = \"quoted\" ");
+
+        assert_eq!(parser.keys().len(), 4);
+        assert_eq!(parser.value(b"a"), Some(Some(Cow::Borrowed(&b"1"[..]))));
+        assert_eq!(
+            parser.value(b"b"),
+            Some(Some("hello world".as_bytes().into()))
+        );
+        assert_eq!(parser.value(b"c"), Some(Some("x y".as_bytes().into())));
+        assert_eq!(
+            parser.value(b"d"),
+            Some(Some(Cow::Borrowed(&b"quoted"[..])))
+        );
+    }
+
+    #[test]
+    fn parse_no_value() {
+        let parser = CookieQS::parse(b";flag; empty=;; \"=\"");
+
+        assert_eq!(parser.value(b"flag"), Some(None));
+        assert_eq!(parser.value(b"empty"), Some(Some(Cow::Borrowed(&b""[..]))));
+        assert_eq!(parser.value(b"\""), Some(Some(Cow::Borrowed(&b"\""[..]))));
+        assert_eq!(parser.value(b"missing"), None);
+    }
+
+    #[test]
+    fn parse_repeated_keys() {
+        let parser = CookieQS::parse(b"id=1; id=2");
+        assert_eq!(parser.value(b"id"), Some(Some(Cow::Borrowed(&b"1"[..]))));
+    }
+}
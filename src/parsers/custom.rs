@@ -0,0 +1,57 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::panic::RefUnwindSafe;
+
+use crate::config::QsConfig;
+
+/// A parser for query strings in a dialect this crate doesn't support, used with
+/// [`ParseMode::Custom`](crate::ParseMode::Custom)
+///
+/// It only splits the input into its raw keys and values, which are then percent decoded and
+/// deserialized the same way as the `Duplicate` mode: repeated keys give sequences, and a
+/// single value is chosen by `DuplicateKeys` otherwise.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_str, ParseMode, QsConfig, QsParser};
+///
+/// /// Pairs like `key:value`, separated by commas
+/// struct Colons;
+///
+/// impl QsParser for Colons {
+///     fn parse<'a>(&self, input: &'a [u8], _: &QsConfig) -> Vec<(&'a [u8], Option<&'a [u8]>)> {
+///         input
+///             .split(|b| *b == b',')
+///             .filter(|pair| !pair.is_empty())
+///             .map(|pair| match pair.iter().position(|b| *b == b':') {
+///                 Some(index) => (&pair[..index], Some(&pair[index + 1..])),
+///                 None => (pair, None),
+///             })
+///             .collect()
+///     }
+/// }
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     name: String,
+///     ids: Vec<u32>,
+/// }
+///
+/// let query: Query = from_str("name:foo+bar,ids:1,ids:2", ParseMode::Custom(&Colons)).unwrap();
+///
+/// assert_eq!(query.name, "foo bar");
+/// assert_eq!(query.ids, vec![1, 2]);
+/// ```
+pub trait QsParser: Sync + RefUnwindSafe {
+    /// Splits the input into its raw(percent encoded) keys and values, in the order they
+    /// appear. Keys without a value have `None` as their value.
+    fn parse<'a>(&self, input: &'a [u8], config: &QsConfig) -> Vec<(&'a [u8], Option<&'a [u8]>)>;
+}
+
+impl fmt::Debug for dyn QsParser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("QsParser")
+    }
+}
@@ -1,36 +1,40 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use alloc::{borrow::Cow, vec::Vec};
 
-use crate::decode::{parse_bytes, Reference};
+use crate::config::{DuplicateKeys, PairSeparator, ParseMode, QsConfig};
+use crate::decode::{invalid_pair_len, parse_bytes_with, DecodeOptions, KeyDecoder, Reference};
+
+use super::ordered::OrderedMap;
+use super::{Delimited, Delimiter};
 
 struct Key<'a>(&'a [u8]);
 
 impl<'a> Key<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
-        let mut index = 0;
-        while index < slice.len() {
-            match slice[index] {
-                b'&' | b'=' => break,
-                _ => index += 1,
-            }
-        }
-
-        Self(&slice[..index])
+    fn parse(slice: &'a [u8], separator: PairSeparator) -> Self {
+        Self(&slice[..separator.find_or(b'=', slice)])
     }
 
     fn len(&self) -> usize {
         self.0.len()
     }
 
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(
+        &self,
+        keys: &'s mut KeyDecoder<'a>,
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        keys.decode(self.0, options)
     }
 }
 
 struct Value<'a>(&'a [u8]);
 
 impl<'a> Value<'a> {
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        parse_bytes_with(self.0, scratch, options)
     }
 }
 
@@ -38,19 +42,12 @@ impl<'a> Value<'a> {
 struct Values<'a>(&'a [u8]);
 
 impl<'a> Values<'a> {
-    fn parse(slice: &'a [u8]) -> Option<Self> {
-        if *slice.get(0)? == b'&' {
+    fn parse(slice: &'a [u8], separator: PairSeparator) -> Option<Self> {
+        if separator.matches(*slice.first()?) {
             return None;
         }
 
-        let mut index = 1;
-        while index < slice.len() {
-            match slice[index] {
-                b'&' => break,
-                _ => index += 1,
-            }
-        }
-
+        let index = 1 + separator.find(&slice[1..]);
         Some(Self(&slice[1..index]))
     }
 
@@ -58,23 +55,25 @@ impl<'a> Values<'a> {
         self.0.len()
     }
 
-    fn values(&self, delimiter: u8) -> impl Iterator<Item = Value<'a>> {
-        self.0
-            .split(move |c| *c == delimiter)
-            .map(|slice| Value(slice))
+    fn values(&self, delimiter: Delimiter) -> impl Iterator<Item = Value<'a>> {
+        Delimited::new(self.0, Some(delimiter)).map(Value)
     }
 
-    fn decode_to<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode_to<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        parse_bytes_with(self.0, scratch, options)
     }
 }
 
 struct Pair<'a>(Key<'a>, Option<Values<'a>>);
 
 impl<'a> Pair<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
-        let key = Key::parse(slice);
-        let value = Values::parse(&slice[key.len()..]);
+    fn parse(slice: &'a [u8], separator: PairSeparator) -> Self {
+        let key = Key::parse(slice, separator);
+        let value = Values::parse(&slice[key.len()..], separator);
 
         Self(key, value)
     }
@@ -119,35 +118,78 @@ impl<'a> Pair<'a> {
 /// assert_eq!(parser.value(b"foo"), Some(Some("bar|baz||".as_bytes().into())));
 /// ```
 pub struct DelimiterQS<'a> {
-    pairs: BTreeMap<Cow<'a, [u8]>, Pair<'a>>,
-    delimiter: u8,
+    pairs: OrderedMap<Cow<'a, [u8]>, Pair<'a>>,
+    delimiter: Delimiter,
+    config: QsConfig,
+    // The first repeated key, when repeated keys are errors
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    duplicate: Option<Cow<'a, [u8]>>,
 }
 
 impl<'a> DelimiterQS<'a> {
     /// Parse a slice of bytes into a `DelimiterQS`
     pub fn parse(slice: &'a [u8], delimiter: u8) -> Self {
-        let mut pairs: BTreeMap<Cow<'a, [u8]>, Pair<'a>> = BTreeMap::new();
-        let mut scratch = Vec::new();
+        Self::parse_with_config(
+            slice,
+            delimiter,
+            &QsConfig::new(ParseMode::Delimiter(delimiter)),
+        )
+    }
+
+    /// Parse a slice of bytes into a `DelimiterQS`, using the options of the config
+    ///
+    /// The parse mode of the config is ignored, and the delimiter of the config is used instead
+    /// of the given byte when it is set.
+    pub fn parse_with_config(slice: &'a [u8], delimiter: u8, config: &QsConfig) -> Self {
+        let delimiter = Delimiter::from_config(delimiter, config);
+        let mut pairs: OrderedMap<Cow<'a, [u8]>, Pair<'a>> = OrderedMap::new();
+        let mut keys = KeyDecoder::default();
+        let mut duplicate = None;
 
         let mut index = 0;
 
         while index < slice.len() {
-            let pair = Pair::parse(&slice[index..]);
-            index += pair.skip_len();
+            // Empty pairs(`&&` or empty lines) are skipped
+            if config.pair_separator.matches(slice[index]) {
+                index += 1;
+                continue;
+            }
+            // Pairs which can't be parsed are skipped, with `skip_invalid_pairs`
+            if let Some(len) = invalid_pair_len(&slice[index..], config) {
+                index += len;
+                continue;
+            }
 
-            let decoded_key = pair.0.decode(&mut scratch);
+            let pair = Pair::parse(&slice[index..], config.pair_separator);
+            index += pair.skip_len();
 
-            if let Some(old_pair) = pairs.get_mut(decoded_key.as_ref()) {
-                *old_pair = pair;
+            let decoded_key = pair.0.decode(&mut keys, config.decode_options);
+
+            if let Some(old_pair) = pairs.get_mut(&decoded_key[..]) {
+                match config.duplicate_keys {
+                    DuplicateKeys::Last => *old_pair = pair,
+                    DuplicateKeys::First => {}
+                    DuplicateKeys::Error => {
+                        if duplicate.is_none() {
+                            duplicate = Some(decoded_key.into_cow());
+                        }
+                        *old_pair = pair;
+                    }
+                }
             } else {
                 pairs.insert(decoded_key.into_cow(), pair);
             }
         }
 
-        Self { pairs, delimiter }
+        Self {
+            pairs,
+            delimiter,
+            config: *config,
+            duplicate,
+        }
     }
 
-    /// Returns a vector containing all the keys in querystring.
+    /// Returns a vector containing all the keys in querystring, in the order they first appear.
     pub fn keys(&self) -> Vec<&Cow<'a, [u8]>> {
         self.pairs.keys().collect()
     }
@@ -159,53 +201,75 @@ impl<'a> DelimiterQS<'a> {
     ///
     /// # Note
     /// Percent decoding the value is done on-the-fly **every time** this function is called.
-    pub fn values(&self, key: &'a [u8]) -> Option<Option<Vec<Cow<'a, [u8]>>>> {
+    pub fn values(&self, key: &[u8]) -> Option<Option<Vec<Cow<'a, [u8]>>>> {
         let delimiter = self.delimiter;
         let mut scratch = Vec::new();
 
         Some(self.pairs.get(key)?.1.as_ref().map(|values| {
             values
                 .values(delimiter)
-                .map(|v| v.decode(&mut scratch).into_cow())
+                .map(|v| {
+                    delimiter
+                        .unescape(v.decode(&mut scratch, self.config.decode_options))
+                        .into_cow()
+                })
                 .collect()
         }))
     }
 
-    /// Returns the last value assigned to a key without taking delimiters into account
+    /// Returns the last value assigned to a key without taking delimiters into account, or the
+    /// first one with `DuplicateKeys::First`.
     ///
     /// It returns `None` if the **key doesn't exist** in the querystring,
     /// and returns `Some(None)` if the last assignment to a **key doesn't have a value**, ex `"&key&"`
     ///
     /// # Note
     /// Percent decoding the value is done on-the-fly **every time** this function is called.
-    pub fn value(&self, key: &'a [u8]) -> Option<Option<Cow<'a, [u8]>>> {
+    pub fn value(&self, key: &[u8]) -> Option<Option<Cow<'a, [u8]>>> {
         let mut scratch = Vec::new();
 
-        Some(
-            self.pairs
-                .get(key)?
-                .1
-                .as_ref()
-                .map(|values| values.decode_to(&mut scratch).into_cow()),
-        )
+        Some(self.pairs.get(key)?.1.as_ref().map(|values| {
+            values
+                .decode_to(&mut scratch, self.config.decode_options)
+                .into_cow()
+        }))
     }
 }
 
 #[cfg(feature = "serde")]
 mod de {
+    use alloc::string::String;
+
     use _serde::Deserialize;
 
-    use crate::de::{
-        Error, QSDeserializer,
-        __implementors::{DecodedSlice, IntoRawSlices, RawSlice},
-    };
+    use crate::arena::Arena;
+    use crate::de::{__implementors::DecodedSlice, Error, ErrorKind, QSDeserializer, QsBuffers};
+
+    use crate::parsers::SeparatorValues;
 
     use super::DelimiterQS;
 
     impl<'a> DelimiterQS<'a> {
         /// Deserialize the parsed slice into T
         pub fn deserialize<T: Deserialize<'a>>(self) -> Result<T, Error> {
-            T::deserialize(QSDeserializer::new(self.into_iter()))
+            self.deserialize_with(&mut QsBuffers::new(), None)
+        }
+
+        /// Deserialize the parsed slice into T, decoding into the buffers, or the arena if
+        /// there is one
+        pub(crate) fn deserialize_with<T: Deserialize<'a>>(
+            self,
+            buffers: &mut QsBuffers,
+            arena: Option<&'a Arena>,
+        ) -> Result<T, Error> {
+            if let Some(key) = &self.duplicate {
+                return Err(Error::new(ErrorKind::DuplicateKey)
+                    .message(String::from("the key is repeated"))
+                    .value(key));
+            }
+
+            let config = self.config;
+            QSDeserializer::deserialize(self.into_iter(), &config, buffers, arena)
         }
 
         pub(crate) fn into_iter(
@@ -215,115 +279,16 @@ mod de {
             self.pairs.into_iter().map(move |(key, pair)| {
                 (
                     DecodedSlice(key),
-                    SeparatorValues::from_slice(pair.1.map(|v| v.0).unwrap_or_default(), delimiter),
+                    SeparatorValues::from_value(pair.1.map(|v| v.0), delimiter),
                 )
             })
         }
     }
-
-    pub(crate) struct SeparatorValues<'a> {
-        slice: &'a [u8],
-        delimiter: u8,
-    }
-
-    impl<'a> SeparatorValues<'a> {
-        fn from_slice(slice: &'a [u8], delimiter: u8) -> Self {
-            Self { slice, delimiter }
-        }
-    }
-
-    impl<'a> IntoRawSlices<'a> for SeparatorValues<'a> {
-        type SizedIterator = SizedValuesIterator<'a>;
-
-        type UnSizedIterator = SizedValuesIterator<'a>;
-
-        #[inline]
-        fn into_sized_iterator(self, size: usize) -> Result<Self::SizedIterator, crate::de::Error> {
-            Ok(SizedValuesIterator::new(
-                self.slice,
-                self.delimiter,
-                Some(size),
-            ))
-        }
-
-        #[inline]
-        fn into_unsized_iterator(self) -> Self::UnSizedIterator {
-            SizedValuesIterator::new(self.slice, self.delimiter, None)
-        }
-
-        #[inline]
-        fn into_single_slice(self) -> RawSlice<'a> {
-            RawSlice(self.slice)
-        }
-    }
-
-    pub struct SizedValuesIterator<'a> {
-        slice: &'a [u8],
-        delimiter: u8,
-        remaining: Option<usize>,
-        index: usize,
-    }
-
-    impl<'a> SizedValuesIterator<'a> {
-        fn new(slice: &'a [u8], delimiter: u8, size: Option<usize>) -> Self {
-            Self {
-                slice,
-                delimiter,
-                remaining: size,
-                index: 0,
-            }
-        }
-
-        #[inline]
-        fn decrease_remaining(&mut self) {
-            if let Some(remaining) = self.remaining {
-                self.remaining = Some(remaining - 1);
-            }
-        }
-    }
-
-    impl<'a> Iterator for SizedValuesIterator<'a> {
-        type Item = RawSlice<'a>;
-
-        fn next(&mut self) -> Option<Self::Item> {
-            if self.index >= self.slice.len() {
-                return None;
-            }
-
-            if let Some(remaining) = self.remaining {
-                match remaining {
-                    0 => {
-                        return None;
-                    }
-                    1 => {
-                        self.remaining = Some(0);
-                        return Some(RawSlice(&self.slice[self.index..]));
-                    }
-                    _ => {}
-                }
-            }
-
-            let start = self.index;
-            for c in &self.slice[self.index..] {
-                if *c == self.delimiter {
-                    let end = self.index;
-                    self.index += 1;
-
-                    self.decrease_remaining();
-                    return Some(RawSlice(&self.slice[start..end]));
-                }
-                self.index += 1;
-            }
-
-            self.decrease_remaining();
-            Some(RawSlice(&self.slice[start..]))
-        }
-    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::borrow::Cow;
+    use alloc::borrow::Cow;
 
     use super::DelimiterQS;
 
@@ -1,47 +1,43 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use alloc::{borrow::Cow, vec, vec::Vec};
 
-use crate::decode::{parse_bytes, Reference};
+use crate::config::{PairSeparator, ParseMode, QsConfig};
+use crate::decode::{invalid_pair_len, parse_bytes_with, DecodeOptions, KeyDecoder, Reference};
 
+use super::ordered::OrderedMap;
+#[cfg(feature = "serde")]
+use super::QsParser;
+
+#[derive(Clone, Copy)]
 struct Key<'a>(&'a [u8]);
 
 impl<'a> Key<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
-        let mut index = 0;
-        while index < slice.len() {
-            match slice[index] {
-                b'&' | b'=' => break,
-                _ => index += 1,
-            }
-        }
-
-        Self(&slice[..index])
+    fn parse(slice: &'a [u8], separator: PairSeparator) -> Self {
+        Self(&slice[..separator.find_or(b'=', slice)])
     }
 
     fn len(&self) -> usize {
         self.0.len()
     }
 
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(
+        &self,
+        keys: &'s mut KeyDecoder<'a>,
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        keys.decode(self.0, options)
     }
 }
 
+#[derive(Clone, Copy)]
 struct Value<'a>(&'a [u8]);
 
 impl<'a> Value<'a> {
-    fn parse(slice: &'a [u8]) -> Option<Self> {
-        if *slice.get(0)? == b'&' {
+    fn parse(slice: &'a [u8], separator: PairSeparator) -> Option<Self> {
+        if separator.matches(*slice.first()?) {
             return None;
         }
 
-        let mut index = 1;
-        while index < slice.len() {
-            match slice[index] {
-                b'&' => break,
-                _ => index += 1,
-            }
-        }
-
+        let index = 1 + separator.find(&slice[1..]);
         Some(Self(&slice[1..index]))
     }
 
@@ -49,21 +45,27 @@ impl<'a> Value<'a> {
         self.0.len()
     }
 
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        parse_bytes_with(self.0, scratch, options)
     }
 
+    #[cfg(feature = "serde")]
     fn slice(&self) -> &'a [u8] {
         self.0
     }
 }
 
+#[derive(Clone, Copy)]
 struct Pair<'a>(Key<'a>, Option<Value<'a>>);
 
 impl<'a> Pair<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
-        let key = Key::parse(slice);
-        let value = Value::parse(&slice[key.len()..]);
+    fn parse(slice: &'a [u8], separator: PairSeparator) -> Self {
+        let key = Key::parse(slice, separator);
+        let value = Value::parse(&slice[key.len()..], separator);
 
         Self(key, value)
     }
@@ -109,34 +111,79 @@ impl<'a> Pair<'a> {
 /// assert_eq!(parser.value(b"foo"), Some(Some("".as_bytes().into())));
 /// ```
 pub struct DuplicateQS<'a> {
-    pairs: BTreeMap<Cow<'a, [u8]>, Vec<Pair<'a>>>,
+    pairs: OrderedMap<Cow<'a, [u8]>, Vec<Pair<'a>>>,
+    config: QsConfig,
 }
 
 impl<'a> DuplicateQS<'a> {
     /// Parse a slice of bytes into a `DuplicateQS`
     pub fn parse(slice: &'a [u8]) -> Self {
-        let mut pairs: BTreeMap<Cow<'a, [u8]>, Vec<Pair<'a>>> = BTreeMap::new();
-        let mut scratch = Vec::new();
+        Self::parse_with_config(slice, &QsConfig::new(ParseMode::Duplicate))
+    }
+
+    /// Parse a slice of bytes into a `DuplicateQS`, using the options of the config
+    ///
+    /// The parse mode of the config is ignored.
+    pub fn parse_with_config(slice: &'a [u8], config: &QsConfig) -> Self {
+        let mut pairs: OrderedMap<Cow<'a, [u8]>, Vec<Pair<'a>>> = OrderedMap::new();
+        let mut keys = KeyDecoder::default();
 
         let mut index = 0;
 
         while index < slice.len() {
-            let pair = Pair::parse(&slice[index..]);
+            // Empty pairs(`&&` or empty lines) are skipped
+            if config.pair_separator.matches(slice[index]) {
+                index += 1;
+                continue;
+            }
+            // Pairs which can't be parsed are skipped, with `skip_invalid_pairs`
+            if let Some(len) = invalid_pair_len(&slice[index..], config) {
+                index += len;
+                continue;
+            }
+
+            let pair = Pair::parse(&slice[index..], config.pair_separator);
             index += pair.skip_len();
 
-            let decoded_key = pair.0.decode(&mut scratch);
+            let decoded_key = pair.0.decode(&mut keys, config.decode_options);
+
+            if let Some(values) = pairs.get_mut(&decoded_key[..]) {
+                values.push(pair);
+            } else {
+                pairs.insert(decoded_key.into_cow(), vec![pair]);
+            }
+        }
+
+        Self {
+            pairs,
+            config: *config,
+        }
+    }
+
+    /// Groups the pairs split by a custom parser by their keys, for `ParseMode::Custom`
+    #[cfg(feature = "serde")]
+    pub(crate) fn parse_custom(slice: &'a [u8], parser: &dyn QsParser, config: &QsConfig) -> Self {
+        let mut pairs: OrderedMap<Cow<'a, [u8]>, Vec<Pair<'a>>> = OrderedMap::new();
+        let mut keys = KeyDecoder::default();
+
+        for (key, value) in parser.parse(slice, config) {
+            let pair = Pair(Key(key), value.map(Value));
+            let decoded_key = pair.0.decode(&mut keys, config.decode_options);
 
-            if let Some(values) = pairs.get_mut(decoded_key.as_ref()) {
+            if let Some(values) = pairs.get_mut(&decoded_key[..]) {
                 values.push(pair);
             } else {
                 pairs.insert(decoded_key.into_cow(), vec![pair]);
             }
         }
 
-        Self { pairs }
+        Self {
+            pairs,
+            config: *config,
+        }
     }
 
-    /// Returns a vector containing all the keys in querystring.
+    /// Returns a vector containing all the keys in querystring, in the order they first appear.
     pub fn keys(&self) -> Vec<&Cow<'a, [u8]>> {
         self.pairs.keys().collect()
     }
@@ -148,14 +195,19 @@ impl<'a> DuplicateQS<'a> {
     ///
     /// # Note
     /// Percent decoding the value is done on-the-fly **every time** this function is called.
-    pub fn values(&self, key: &'a [u8]) -> Option<Vec<Option<Cow<'a, [u8]>>>> {
+    pub fn values(&self, key: &[u8]) -> Option<Vec<Option<Cow<'a, [u8]>>>> {
         let mut scratch = Vec::new();
 
         Some(
             self.pairs
                 .get(key)?
                 .iter()
-                .map(|p| p.1.as_ref().map(|v| v.decode(&mut scratch).into_cow()))
+                .map(|p| {
+                    p.1.as_ref().map(|v| {
+                        v.decode(&mut scratch, self.config.decode_options)
+                            .into_cow()
+                    })
+                })
                 .collect(),
         )
     }
@@ -167,24 +219,29 @@ impl<'a> DuplicateQS<'a> {
     ///
     /// # Note
     /// Percent decoding the value is done on-the-fly **every time** this function is called.
-    pub fn value(&self, key: &'a [u8]) -> Option<Option<Cow<'a, [u8]>>> {
+    pub fn value(&self, key: &[u8]) -> Option<Option<Cow<'a, [u8]>>> {
         let mut scratch = Vec::new();
 
-        self.pairs
-            .get(key)?
-            .iter()
-            .last()
-            .map(|p| p.1.as_ref().map(|v| v.decode(&mut scratch).into_cow()))
+        self.pairs.get(key)?.iter().last().map(|p| {
+            p.1.as_ref().map(|v| {
+                v.decode(&mut scratch, self.config.decode_options)
+                    .into_cow()
+            })
+        })
     }
 }
 
 #[cfg(feature = "serde")]
 mod de {
+    use alloc::string::String;
+    use core::iter::Map;
+
     use _serde::Deserialize;
 
+    use crate::arena::Arena;
     use crate::de::{
-        Error, ErrorKind, QSDeserializer,
-        __implementors::{DecodedSlice, IntoRawSlices, RawSlice},
+        __implementors::{DecodedSlice, IntoRawSlices, RawSlice, Value},
+        invalid_state_error, DuplicateKeys, Error, ErrorKind, QSDeserializer, QsBuffers,
     };
 
     use super::DuplicateQS;
@@ -192,7 +249,18 @@ mod de {
     impl<'a> DuplicateQS<'a> {
         /// Deserialize the parsed slice into T
         pub fn deserialize<T: Deserialize<'a>>(self) -> Result<T, Error> {
-            T::deserialize(QSDeserializer::new(self.into_iter()))
+            self.deserialize_with(&mut QsBuffers::new(), None)
+        }
+
+        /// Deserialize the parsed slice into T, decoding into the buffers, or the arena if
+        /// there is one
+        pub(crate) fn deserialize_with<T: Deserialize<'a>>(
+            self,
+            buffers: &mut QsBuffers,
+            arena: Option<&'a Arena>,
+        ) -> Result<T, Error> {
+            let config = self.config;
+            QSDeserializer::deserialize(self.into_iter(), &config, buffers, arena)
         }
 
         pub(crate) fn into_iter(
@@ -200,57 +268,99 @@ mod de {
         ) -> impl Iterator<
             Item = (
                 DecodedSlice<'a>,
-                DuplicateValueIter<impl Iterator<Item = RawSlice<'a>>>,
+                DuplicateValueIter<impl Iterator<Item = Option<RawSlice<'a>>> + Clone>,
             ),
         > {
             self.pairs.into_iter().map(|(key, pairs)| {
                 (
                     DecodedSlice(key),
-                    DuplicateValueIter(
-                        pairs
-                            .into_iter()
-                            .map(|v| RawSlice(v.1.map(|v| v.slice()).unwrap_or_default())),
-                    ),
+                    DuplicateValueIter(pairs.into_iter().map(|v| v.1.map(|v| RawSlice(v.slice())))),
                 )
             })
         }
     }
 
+    /// The values of a key, `None` for the pairs without a value(`key`)
     pub(crate) struct DuplicateValueIter<I>(I);
 
+    type Values<'a, I> = Map<I, fn(Option<RawSlice<'a>>) -> RawSlice<'a>>;
+
+    impl<'a, I> DuplicateValueIter<I>
+    where
+        I: Iterator<Item = Option<RawSlice<'a>>> + Clone,
+    {
+        fn values(self) -> Values<'a, I> {
+            self.0.map(Option::unwrap_or_default)
+        }
+
+        fn single_value(
+            self,
+            duplicate_keys: DuplicateKeys,
+        ) -> Result<Option<RawSlice<'a>>, Error> {
+            let mut values = self.0;
+            let value = match duplicate_keys {
+                DuplicateKeys::Last => values.last(),
+                DuplicateKeys::First => values.next(),
+                DuplicateKeys::Error => {
+                    let value = values.next();
+                    if values.next().is_some() {
+                        return Err(Error::new(ErrorKind::DuplicateKey).message(String::from(
+                            "the key is repeated, but a single value is expected",
+                        )));
+                    }
+                    value
+                }
+            };
+            value.ok_or_else(|| invalid_state_error("a key without any value"))
+        }
+    }
+
     impl<'a, I> IntoRawSlices<'a> for DuplicateValueIter<I>
     where
-        I: Iterator<Item = RawSlice<'a>>,
+        I: Iterator<Item = Option<RawSlice<'a>>> + Clone,
     {
-        type SizedIterator = I;
-        type UnSizedIterator = I;
+        type SizedIterator = Values<'a, I>;
+        type UnSizedIterator = Values<'a, I>;
 
         #[inline]
-        fn into_sized_iterator(self, size: usize) -> Result<I, Error> {
+        fn into_sized_iterator(self, size: usize) -> Result<Self::SizedIterator, Error> {
             if self.0.size_hint().0 == size {
-                Ok(self.0)
+                Ok(self.values())
             } else {
                 Err(Error::new(ErrorKind::InvalidLength))
             }
         }
 
         #[inline]
-        fn into_unsized_iterator(self) -> I {
-            self.0
+        fn into_unsized_iterator(self) -> Self::UnSizedIterator {
+            self.values()
+        }
+
+        #[inline]
+        fn into_single_slice(self, duplicate_keys: DuplicateKeys) -> Result<RawSlice<'a>, Error> {
+            self.single_value(duplicate_keys)
+                .map(Option::unwrap_or_default)
+        }
+
+        #[inline]
+        fn is_null(&self, duplicate_keys: DuplicateKeys) -> bool {
+            DuplicateValueIter(self.0.clone())
+                .into_single_slice(duplicate_keys)
+                .map_or(false, |value| value.is_null())
         }
 
         #[inline]
-        fn into_single_slice(self) -> RawSlice<'a> {
-            self.0
-                .last()
-                .expect("Iterator has at least one value in it")
+        fn is_flag(&self, duplicate_keys: DuplicateKeys) -> bool {
+            DuplicateValueIter(self.0.clone())
+                .single_value(duplicate_keys)
+                .map_or(false, |value| value.is_none())
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::borrow::Cow;
+    use alloc::borrow::Cow;
 
     use super::DuplicateQS;
 
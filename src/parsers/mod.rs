@@ -1,9 +1,271 @@
+//! The parsers behind each `ParseMode`, usable without serde, and `CookieQS` for the values of
+//! `Cookie` headers.
+//!
+//! Each parser splits a query string into its keys and their raw values once, and then
+//! gives access to them using these methods, which are part of the public API:
+//!
+//! - `parse` parses a slice of bytes, borrowing from it as much as possible
+//! - `keys` returns all the decoded keys, in the order they first appear
+//! - `value` returns the last decoded value of a key
+//! - `values` returns all the decoded values of a key(not available for `UrlEncodedQS`)
+//! - `sub_values` returns the values of the sub keys of a key, only for `BracketsQS`
+//! - `deserialize` deserializes the parsed query string into a type, with the `serde` feature
+//!
+//! Keys are percent decoded while parsing, but values are decoded lazily every time they are
+//! requested.
+//!
+//! Each parser is behind the feature of its mode(`urlencoded`, `duplicate`, `delimiter` and
+//! `brackets`), which are all enabled by default.
+//!
+//! # Example
+//! ```rust
+//! use serde_querystring::parsers::DuplicateQS;
+//!
+//! let parser = DuplicateQS::parse(b"tag=a&tag=b+c&page=2");
+//!
+//! let keys: Vec<&[u8]> = parser.keys().into_iter().map(|key| &key[..]).collect();
+//! assert_eq!(keys, [&b"tag"[..], &b"page"[..]]);
+//!
+//! assert_eq!(parser.values(b"tag").unwrap().len(), 2);
+//! assert_eq!(parser.values(b"page").unwrap().len(), 1);
+//! assert_eq!(parser.values(b"sort"), None);
+//!
+//! assert_eq!(parser.value(b"tag"), Some(Some("b c".as_bytes().into())));
+//! ```
+
+#[cfg(any(feature = "serde", feature = "delimiter", feature = "brackets"))]
+use crate::{
+    config::QsConfig,
+    decode::{parse_char, DecodeOptions, Reference},
+    scan::memchr,
+};
+#[cfg(any(feature = "serde", feature = "delimiter", feature = "brackets"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "brackets")]
 mod brackets;
+mod cookie;
+mod custom;
+#[cfg(feature = "delimiter")]
 mod delimiter;
+#[cfg(feature = "duplicate")]
 mod duplicate;
+mod ordered;
+#[cfg(feature = "urlencoded")]
 mod urlencoded;
+#[cfg(feature = "serde")]
+mod values;
 
+#[cfg(all(feature = "brackets", feature = "serde"))]
+pub(crate) use brackets::key_segments;
+#[cfg(feature = "brackets")]
 pub use brackets::BracketsQS;
+pub use cookie::CookieQS;
+pub use custom::QsParser;
+#[cfg(feature = "delimiter")]
 pub use delimiter::DelimiterQS;
+#[cfg(feature = "duplicate")]
 pub use duplicate::DuplicateQS;
+#[cfg(feature = "serde")]
+pub(crate) use ordered::OrderedMap;
+#[cfg(feature = "urlencoded")]
 pub use urlencoded::UrlEncodedQS;
+#[cfg(feature = "serde")]
+pub(crate) use values::SeparatorValues;
+
+/// Every byte as a byte string, so single byte delimiters are split like the longer ones
+#[cfg(any(feature = "serde", feature = "delimiter", feature = "brackets"))]
+static BYTES: [u8; 256] = all_bytes();
+
+#[cfg(any(feature = "serde", feature = "delimiter", feature = "brackets"))]
+const fn all_bytes() -> [u8; 256] {
+    let mut bytes = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        bytes[index] = index as u8;
+        index += 1;
+    }
+    bytes
+}
+
+/// A delimiter splitting the values, which can be matched in its percent encoded form too
+#[cfg(any(feature = "serde", feature = "delimiter", feature = "brackets"))]
+#[derive(Clone, Copy)]
+pub(crate) struct Delimiter {
+    bytes: &'static [u8],
+    // Matched once decoded, with the options of the config
+    decoded: Option<DecodeOptions>,
+    escape: Option<u8>,
+}
+
+#[cfg(any(feature = "serde", feature = "delimiter", feature = "brackets"))]
+impl Delimiter {
+    /// A single byte delimiter, matched as it is
+    #[cfg(any(feature = "serde", feature = "brackets"))]
+    pub(crate) fn byte(byte: u8) -> Self {
+        Self {
+            bytes: &BYTES[byte as usize..byte as usize + 1],
+            decoded: None,
+            escape: None,
+        }
+    }
+
+    /// The delimiter set in the config, or the byte of the parse mode
+    pub(crate) fn from_config(byte: u8, config: &QsConfig) -> Self {
+        Self {
+            bytes: match config.delimiter {
+                Some(delimiter) => delimiter.as_bytes(),
+                None => &BYTES[byte as usize..byte as usize + 1],
+            },
+            decoded: if config.decode_before_split {
+                Some(config.decode_options)
+            } else {
+                None
+            },
+            escape: config.delimiter_escape,
+        }
+    }
+
+    /// Returns the index and the length of the first delimiter in the slice, which is not
+    /// escaped
+    #[inline]
+    pub(crate) fn find(&self, slice: &[u8]) -> Option<(usize, usize)> {
+        if self.decoded.is_none() && self.escape.is_none() {
+            let len = self.bytes.len();
+            return match self.bytes {
+                [byte] => memchr(*byte, slice),
+                bytes => slice.windows(len).position(|window| window == bytes),
+            }
+            .map(|index| (index, len));
+        }
+
+        let mut index = 0;
+        while index < slice.len() {
+            if let Some(len) = self.matches(&slice[index..], self.bytes) {
+                return Some((index, len));
+            }
+            match self.escape_len(&slice[index..]) {
+                Some(len) => {
+                    // The escaped delimiter or escape is skipped as a whole
+                    index += len;
+                    index += self
+                        .matches(&slice[index..], self.bytes)
+                        .or_else(|| self.escape_len(&slice[index..]))
+                        .unwrap_or(1);
+                }
+                None => index += 1,
+            }
+        }
+        None
+    }
+
+    /// Returns the length of the escape at the start of the slice, if there is one
+    fn escape_len(&self, slice: &[u8]) -> Option<usize> {
+        let escape = self.escape?;
+        self.matches(slice, core::slice::from_ref(&escape))
+    }
+
+    /// Returns the length of the bytes at the start of the slice, which can also be percent
+    /// encoded when the delimiter is matched once decoded
+    fn matches(&self, slice: &[u8], bytes: &[u8]) -> Option<usize> {
+        let options = match self.decoded {
+            Some(options) => options,
+            None if slice.starts_with(bytes) => return Some(bytes.len()),
+            None => return None,
+        };
+
+        let mut index = 0;
+        for byte in bytes {
+            match slice.get(index..)? {
+                [b'+', ..] if options.plus_as_space => match byte {
+                    b' ' => index += 1,
+                    _ => return None,
+                },
+                [b, ..] if b == byte => index += 1,
+                [b'%', high, low, ..] if parse_char(*high, *low) == Some(*byte) => index += 3,
+                _ => return None,
+            }
+        }
+        Some(index)
+    }
+
+    /// Removes the escapes from a decoded value, of the delimiters and of the escape itself
+    pub(crate) fn unescape<'b, 'c>(
+        &self,
+        value: Reference<'b, 'c, [u8]>,
+    ) -> Reference<'b, 'c, [u8]> {
+        let escape = match self.escape {
+            Some(escape) if value.contains(&escape) => escape,
+            _ => return value,
+        };
+
+        let mut unescaped = Vec::with_capacity(value.len());
+        let mut index = 0;
+        while index < value.len() {
+            let remains = &value[index + 1..];
+            if value[index] == escape && remains.starts_with(self.bytes) {
+                unescaped.extend_from_slice(self.bytes);
+                index += 1 + self.bytes.len();
+            } else if value[index] == escape && remains.first() == Some(&escape) {
+                unescaped.push(escape);
+                index += 2;
+            } else {
+                unescaped.push(value[index]);
+                index += 1;
+            }
+        }
+        Reference::Owned(unescaped)
+    }
+}
+
+/// Iterates over the parts of a slice split by a delimiter, the same way `slice::split` does
+/// for a single byte. Without a delimiter the whole slice is the only part.
+#[cfg(any(feature = "serde", feature = "delimiter", feature = "brackets"))]
+#[derive(Clone)]
+pub(crate) struct Delimited<'a> {
+    remains: Option<&'a [u8]>,
+    delimiter: Option<Delimiter>,
+}
+
+#[cfg(any(feature = "serde", feature = "delimiter", feature = "brackets"))]
+impl<'a> Delimited<'a> {
+    pub(crate) fn new(slice: &'a [u8], delimiter: Option<Delimiter>) -> Self {
+        Self {
+            remains: Some(slice),
+            delimiter,
+        }
+    }
+}
+
+#[cfg(any(feature = "serde", feature = "delimiter", feature = "brackets"))]
+impl<'a> Iterator for Delimited<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remains = self.remains?;
+        match self.delimiter.and_then(|delimiter| delimiter.find(remains)) {
+            Some((index, len)) => {
+                self.remains = Some(&remains[index + len..]);
+                Some(&remains[..index])
+            }
+            None => {
+                self.remains = None;
+                Some(remains)
+            }
+        }
+    }
+}
+
+/// Removes the escapes from a decoded value of the delimited modes
+#[cfg(any(feature = "serde", feature = "brackets"))]
+pub(crate) fn unescape<'b, 'c>(
+    value: Reference<'b, 'c, [u8]>,
+    config: &QsConfig,
+) -> Reference<'b, 'c, [u8]> {
+    match config.mode.delimiter() {
+        Some(byte) if config.delimiter_escape.is_some() => {
+            Delimiter::from_config(byte, config).unescape(value)
+        }
+        _ => value,
+    }
+}
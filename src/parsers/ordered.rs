@@ -0,0 +1,137 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::borrow::Borrow;
+
+/// A map keeping its keys in the order they are first inserted, used for the decoded keys of a
+/// query string and the maps of `Value`
+///
+/// Keys are looked up by their position, the same way as the arrays of `Php` mode.
+#[derive(Clone)]
+pub(crate) struct OrderedMap<K, V> {
+    entries: Vec<(K, V)>,
+    positions: BTreeMap<K, usize>,
+}
+
+impl<K, V> OrderedMap<K, V>
+where
+    K: Ord + Clone,
+{
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            positions: BTreeMap::new(),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let position = *self.positions.get(key)?;
+        Some(&self.entries[position].1)
+    }
+
+    pub(crate) fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let position = *self.positions.get(key)?;
+        Some(&mut self.entries[position].1)
+    }
+
+    /// Inserts a key after the others, or replaces its value in place if it exists
+    pub(crate) fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.positions.get(&key) {
+            Some(position) => Some(core::mem::replace(&mut self.entries[*position].1, value)),
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Returns the value of a key, inserting it after the others if it is missing
+    #[cfg(feature = "serde")]
+    pub(crate) fn get_or_insert_with<F>(&mut self, key: K, default: F) -> &mut V
+    where
+        F: FnOnce() -> V,
+    {
+        let position = match self.positions.get(&key) {
+            Some(position) => *position,
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, default()));
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[position].1
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let position = self.positions.remove(key)?;
+        for entry in self.positions.values_mut() {
+            if *entry > position {
+                *entry -= 1;
+            }
+        }
+        Some(self.entries.remove(position).1)
+    }
+
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+}
+
+impl<K, V> IntoIterator for OrderedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = alloc::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use alloc::borrow::Cow;
+
+    use super::OrderedMap;
+
+    #[test]
+    fn keep_insertion_order() {
+        let mut map = OrderedMap::new();
+        map.insert(Cow::Borrowed(&b"b"[..]), 1);
+        map.insert(Cow::Borrowed(&b"a"[..]), 2);
+        map.insert(Cow::Borrowed(&b"c"[..]), 3);
+        assert_eq!(map.insert(Cow::Borrowed(&b"b"[..]), 4), Some(1));
+
+        let keys: Vec<_> = map.keys().map(|key| &**key).collect();
+        assert_eq!(keys, [b"b", b"a", b"c"]);
+        assert_eq!(map.get(&b"b"[..]), Some(&4));
+
+        assert_eq!(map.remove(&b"a"[..]), Some(2));
+        assert_eq!(map.get(&b"c"[..]), Some(&3));
+        *map.get_or_insert_with(Cow::Borrowed(&b"d"[..]), || 0) += 5;
+        assert_eq!(
+            map.into_iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            [4, 3, 5]
+        );
+    }
+}
@@ -1,47 +1,39 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use alloc::{borrow::Cow, vec::Vec};
 
-use crate::decode::{parse_bytes, Reference};
+use crate::config::{DuplicateKeys, PairSeparator, ParseMode, QsConfig};
+use crate::decode::{invalid_pair_len, parse_bytes_with, DecodeOptions, KeyDecoder, Reference};
+
+use super::ordered::OrderedMap;
 
 struct Key<'a>(&'a [u8]);
 
 impl<'a> Key<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
-        let mut index = 0;
-        while index < slice.len() {
-            match slice[index] {
-                b'&' | b'=' => break,
-                _ => index += 1,
-            }
-        }
-
-        Self(&slice[..index])
+    fn parse(slice: &'a [u8], separator: PairSeparator) -> Self {
+        Self(&slice[..separator.find_or(b'=', slice)])
     }
 
     fn len(&self) -> usize {
         self.0.len()
     }
 
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(
+        &self,
+        keys: &'s mut KeyDecoder<'a>,
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        keys.decode(self.0, options)
     }
 }
 
 struct Value<'a>(&'a [u8]);
 
 impl<'a> Value<'a> {
-    fn parse(slice: &'a [u8]) -> Option<Self> {
-        if *slice.get(0)? == b'&' {
+    fn parse(slice: &'a [u8], separator: PairSeparator) -> Option<Self> {
+        if separator.matches(*slice.first()?) {
             return None;
         }
 
-        let mut index = 1;
-        while index < slice.len() {
-            match slice[index] {
-                b'&' => break,
-                _ => index += 1,
-            }
-        }
-
+        let index = 1 + separator.find(&slice[1..]);
         Some(Self(&slice[1..index]))
     }
 
@@ -49,17 +41,21 @@ impl<'a> Value<'a> {
         self.0.len()
     }
 
-    fn decode_to<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode_to<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        options: DecodeOptions,
+    ) -> Reference<'a, 's, [u8]> {
+        parse_bytes_with(self.0, scratch, options)
     }
 }
 
 struct Pair<'a>(Key<'a>, Option<Value<'a>>);
 
 impl<'a> Pair<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
-        let key = Key::parse(slice);
-        let value = Value::parse(&slice[key.len()..]);
+    fn parse(slice: &'a [u8], separator: PairSeparator) -> Self {
+        let key = Key::parse(slice, separator);
+        let value = Value::parse(&slice[key.len()..], separator);
 
         Self(key, value)
     }
@@ -101,60 +97,102 @@ impl<'a> Pair<'a> {
 /// );
 /// ```
 pub struct UrlEncodedQS<'a> {
-    pairs: BTreeMap<Cow<'a, [u8]>, Pair<'a>>,
+    pairs: OrderedMap<Cow<'a, [u8]>, Pair<'a>>,
+    config: QsConfig,
+    // The first repeated key, when repeated keys are errors
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    duplicate: Option<Cow<'a, [u8]>>,
 }
 
 impl<'a> UrlEncodedQS<'a> {
     /// Parse a slice of bytes into a `UrlEncodedQS`
     pub fn parse(slice: &'a [u8]) -> Self {
-        let mut pairs = BTreeMap::new();
-        let mut scratch = Vec::new();
+        Self::parse_with_config(slice, &QsConfig::new(ParseMode::UrlEncoded))
+    }
+
+    /// Parse a slice of bytes into a `UrlEncodedQS`, using the options of the config
+    ///
+    /// The parse mode of the config is ignored.
+    pub fn parse_with_config(slice: &'a [u8], config: &QsConfig) -> Self {
+        let mut pairs = OrderedMap::new();
+        let mut keys = KeyDecoder::default();
+        let mut duplicate = None;
 
         let mut index = 0;
 
         while index < slice.len() {
-            let pair = Pair::parse(&slice[index..]);
-            index += pair.skip_len();
+            // Empty pairs(`&&` or empty lines) are skipped
+            if config.pair_separator.matches(slice[index]) {
+                index += 1;
+                continue;
+            }
+            // Pairs which can't be parsed are skipped, with `skip_invalid_pairs`
+            if let Some(len) = invalid_pair_len(&slice[index..], config) {
+                index += len;
+                continue;
+            }
 
-            let decoded_key = pair.0.decode(&mut scratch);
+            let pair = Pair::parse(&slice[index..], config.pair_separator);
+            index += pair.skip_len();
 
-            if let Some(old_pair) = pairs.get_mut(decoded_key.as_ref()) {
-                *old_pair = pair;
+            let decoded_key = pair.0.decode(&mut keys, config.decode_options);
+
+            if let Some(old_pair) = pairs.get_mut(&decoded_key[..]) {
+                match config.duplicate_keys {
+                    DuplicateKeys::Last => *old_pair = pair,
+                    DuplicateKeys::First => {}
+                    DuplicateKeys::Error => {
+                        if duplicate.is_none() {
+                            duplicate = Some(decoded_key.into_cow());
+                        }
+                        *old_pair = pair;
+                    }
+                }
             } else {
                 pairs.insert(decoded_key.into_cow(), pair);
             }
         }
 
-        Self { pairs }
+        Self {
+            pairs,
+            config: *config,
+            duplicate,
+        }
     }
 
-    /// Returns a vector containing all the keys in querystring.
+    /// Returns a vector containing all the keys in querystring, in the order they first appear.
     pub fn keys(&self) -> Vec<&Cow<'a, [u8]>> {
         self.pairs.keys().collect()
     }
 
-    /// Returns the last value assigned to a key.
+    /// Returns the last value assigned to a key, or the first one with `DuplicateKeys::First`.
     ///
     /// It returns `None` if the **key doesn't exist** in the querystring,
     /// and returns `Some(None)` if the last assignment to a **key doesn't have a value**, ex `"&key&"`
     ///
     /// # Note
     /// Percent decoding the value is done on-the-fly **every time** this function is called.
-    pub fn value(&self, key: &'a [u8]) -> Option<Option<Cow<'a, [u8]>>> {
+    pub fn value(&self, key: &[u8]) -> Option<Option<Cow<'a, [u8]>>> {
         let mut scratch = Vec::new();
-        self.pairs
-            .get(key)
-            .map(|p| p.1.as_ref().map(|v| v.decode_to(&mut scratch).into_cow()))
+        self.pairs.get(key).map(|p| {
+            p.1.as_ref().map(|v| {
+                v.decode_to(&mut scratch, self.config.decode_options)
+                    .into_cow()
+            })
+        })
     }
 }
 
 #[cfg(feature = "serde")]
 mod de {
+    use alloc::string::String;
+
     use _serde::Deserialize;
 
+    use crate::arena::Arena;
     use crate::de::{
-        Error, QSDeserializer,
         __implementors::{DecodedSlice, RawSlice},
+        Error, ErrorKind, QSDeserializer, QsBuffers,
     };
 
     use super::UrlEncodedQS;
@@ -162,7 +200,24 @@ mod de {
     impl<'a> UrlEncodedQS<'a> {
         /// Deserialize the parsed slice into T
         pub fn deserialize<T: Deserialize<'a>>(self) -> Result<T, Error> {
-            T::deserialize(QSDeserializer::new(self.into_iter()))
+            self.deserialize_with(&mut QsBuffers::new(), None)
+        }
+
+        /// Deserialize the parsed slice into T, decoding into the buffers, or the arena if
+        /// there is one
+        pub(crate) fn deserialize_with<T: Deserialize<'a>>(
+            self,
+            buffers: &mut QsBuffers,
+            arena: Option<&'a Arena>,
+        ) -> Result<T, Error> {
+            if let Some(key) = &self.duplicate {
+                return Err(Error::new(ErrorKind::DuplicateKey)
+                    .message(String::from("the key is repeated"))
+                    .value(key));
+            }
+
+            let config = self.config;
+            QSDeserializer::deserialize(self.into_iter(), &config, buffers, arena)
         }
 
         pub(crate) fn into_iter(
@@ -177,7 +232,7 @@ mod de {
 
 #[cfg(test)]
 mod tests {
-    use std::borrow::Cow;
+    use alloc::borrow::Cow;
 
     use super::UrlEncodedQS;
 
@@ -0,0 +1,132 @@
+//! The values of a key split by a delimiter, deserialized as a sequence
+
+use crate::de::{
+    __implementors::{IntoRawSlices, RawSlice, Value},
+    DuplicateKeys,
+};
+
+use super::Delimiter;
+
+pub(crate) struct SeparatorValues<'a> {
+    slice: &'a [u8],
+    delimiter: Delimiter,
+    // The key has no value at all(`key`)
+    flag: bool,
+}
+
+impl<'a> SeparatorValues<'a> {
+    pub(crate) fn from_slice(slice: &'a [u8], delimiter: Delimiter) -> Self {
+        Self {
+            slice,
+            delimiter,
+            flag: false,
+        }
+    }
+
+    /// The values of a key, which is a flag when it has no value
+    #[cfg(feature = "delimiter")]
+    pub(crate) fn from_value(value: Option<&'a [u8]>, delimiter: Delimiter) -> Self {
+        Self {
+            flag: value.is_none(),
+            ..Self::from_slice(value.unwrap_or_default(), delimiter)
+        }
+    }
+}
+
+impl<'a> IntoRawSlices<'a> for SeparatorValues<'a> {
+    type SizedIterator = SizedValuesIterator<'a>;
+
+    type UnSizedIterator = SizedValuesIterator<'a>;
+
+    #[inline]
+    fn into_sized_iterator(self, size: usize) -> Result<Self::SizedIterator, crate::de::Error> {
+        Ok(SizedValuesIterator::new(
+            self.slice,
+            self.delimiter,
+            Some(size),
+        ))
+    }
+
+    #[inline]
+    fn into_unsized_iterator(self) -> Self::UnSizedIterator {
+        SizedValuesIterator::new(self.slice, self.delimiter, None)
+    }
+
+    #[inline]
+    fn into_single_slice(self, _: DuplicateKeys) -> Result<RawSlice<'a>, crate::de::Error> {
+        // Repeated keys are handled while parsing
+        Ok(RawSlice(self.slice))
+    }
+
+    #[inline]
+    fn is_null(&self, _: DuplicateKeys) -> bool {
+        RawSlice(self.slice).is_null()
+    }
+
+    #[inline]
+    fn is_flag(&self, _: DuplicateKeys) -> bool {
+        self.flag
+    }
+}
+
+pub struct SizedValuesIterator<'a> {
+    slice: &'a [u8],
+    delimiter: Delimiter,
+    remaining: Option<usize>,
+    index: usize,
+}
+
+impl<'a> SizedValuesIterator<'a> {
+    fn new(slice: &'a [u8], delimiter: Delimiter, size: Option<usize>) -> Self {
+        Self {
+            slice,
+            delimiter,
+            remaining: size,
+            index: 0,
+        }
+    }
+
+    #[inline]
+    fn decrease_remaining(&mut self) {
+        if let Some(remaining) = self.remaining {
+            self.remaining = Some(remaining - 1);
+        }
+    }
+}
+
+impl<'a> Iterator for SizedValuesIterator<'a> {
+    type Item = RawSlice<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.slice.len() {
+            return None;
+        }
+
+        if let Some(remaining) = self.remaining {
+            match remaining {
+                0 => {
+                    return None;
+                }
+                1 => {
+                    self.remaining = Some(0);
+                    return Some(RawSlice(&self.slice[self.index..]));
+                }
+                _ => {}
+            }
+        }
+
+        let start = self.index;
+        self.decrease_remaining();
+
+        match self.delimiter.find(&self.slice[start..]) {
+            Some((index, len)) => {
+                self.index = start + index + len;
+                Some(RawSlice(&self.slice[start..start + index]))
+            }
+            None => {
+                self.index = self.slice.len();
+                Some(RawSlice(&self.slice[start..]))
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::config::QsConfig;
+use crate::de::Error;
+use crate::decode::invalid_pair_len;
+use crate::pairs::{split_pair, split_pairs};
+use crate::value::{decode, Map, Value};
+
+/// A key of a PHP array, the strings of decimal integers are integer keys
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    Int(i64),
+    Str(String),
+}
+
+impl Key {
+    fn new(key: &str) -> Self {
+        let digits = key.strip_prefix('-').unwrap_or(key);
+        let numeric = match digits.as_bytes() {
+            // `-0` and leading zeros are kept as strings
+            [b'0'] => digits.len() == key.len(),
+            [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+            _ => false,
+        };
+
+        match key.parse() {
+            Ok(index) if numeric => Key::Int(index),
+            _ => Key::Str(key.to_string()),
+        }
+    }
+
+    fn into_string(self) -> String {
+        match self {
+            Key::Int(index) => index.to_string(),
+            Key::Str(key) => key,
+        }
+    }
+}
+
+enum Zval {
+    String(String),
+    Array(Array),
+}
+
+/// An ordered PHP array
+#[derive(Default)]
+struct Array {
+    entries: Vec<(Key, Zval)>,
+    positions: BTreeMap<Key, usize>,
+    // The key of the next appended value(`key[]`), after the largest integer key
+    next_index: Option<i64>,
+}
+
+impl Array {
+    fn insert(&mut self, key: Key, value: Zval) -> &mut Zval {
+        if let Key::Int(index) = key {
+            if self.next_index.map_or(true, |next| index >= next) {
+                self.next_index = Some(index.saturating_add(1));
+            }
+        }
+
+        let position = match self.positions.get(&key) {
+            Some(position) => {
+                self.entries[*position].1 = value;
+                *position
+            }
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[position].1
+    }
+
+    /// Sets the value of a key, or appends it without a key. Nothing is appended when the
+    /// next index would overflow.
+    fn set(&mut self, key: Option<&str>, value: Zval) -> Option<&mut Zval> {
+        match key {
+            Some(key) => Some(self.insert(Key::new(key), value)),
+            None => match self.next_index.unwrap_or(0) {
+                i64::MAX => None,
+                index => Some(self.insert(Key::Int(index), value)),
+            },
+        }
+    }
+
+    /// Returns the array of a key, a missing key or a string is replaced by an empty array
+    fn array(&mut self, key: Option<&str>) -> Option<&mut Array> {
+        let existing = match key {
+            Some(key) => self.positions.get(&Key::new(key)).copied(),
+            None => None,
+        };
+
+        let value = match existing {
+            Some(position) => &mut self.entries[position].1,
+            None => self.set(key, Zval::Array(Array::default()))?,
+        };
+        if let Zval::String(_) = value {
+            *value = Zval::Array(Array::default());
+        }
+        match value {
+            Zval::Array(array) => Some(array),
+            Zval::String(_) => None,
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(position) = self.positions.remove(&Key::new(key)) {
+            self.entries.remove(position);
+            for entry in self.positions.values_mut() {
+                if *entry > position {
+                    *entry -= 1;
+                }
+            }
+        }
+    }
+
+    /// Arrays with the `0..n` keys in order are sequences, the others are maps
+    fn into_value(self) -> Value {
+        let is_list = self
+            .entries
+            .iter()
+            .enumerate()
+            .all(|(index, (key, _))| *key == Key::Int(index as i64));
+
+        if is_list {
+            Value::Sequence(self.entries.into_iter().map(|e| e.1.into_value()).collect())
+        } else {
+            Value::Map(self.into_map())
+        }
+    }
+
+    fn into_map(self) -> Map {
+        self.entries
+            .into_iter()
+            .map(|(key, value)| (key.into_string(), value.into_value()))
+            .collect()
+    }
+}
+
+impl Zval {
+    fn into_value(self) -> Value {
+        match self {
+            Zval::String(s) => Value::String(s),
+            Zval::Array(array) => array.into_value(),
+        }
+    }
+}
+
+/// Spaces and dots can't be part of PHP variable names
+fn mangle(name: &str, brackets: bool) -> String {
+    name.chars()
+        .map(|c| match c {
+            ' ' | '.' => '_',
+            '[' if brackets => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// A port of `php_register_variable_ex` from PHP 8, adding a single pair to the result
+fn register(result: &mut Array, name: &str, value: String, config: &QsConfig) {
+    let name = name.trim_start_matches(' ');
+    let (name, mut brackets) = match name.find('[') {
+        Some(index) => (&name[..index], Some(&name[index..])),
+        None => (name, None),
+    };
+    let mut name = mangle(name, false);
+    if name.is_empty() {
+        return;
+    }
+
+    // The indexes inside the brackets, `None` appends the value
+    let mut indexes = Vec::new();
+    while let Some(rest) = brackets {
+        if indexes.len() == config.max_depth {
+            // PHP removes the whole variable
+            result.remove(&name);
+            return;
+        }
+
+        let inner = &rest[1..];
+        match inner.find(']') {
+            Some(end) => {
+                let index = &inner[..end];
+                indexes.push(match index.strip_prefix(' ').unwrap_or(index) {
+                    "" => None,
+                    _ => Some(index),
+                });
+                // Anything after the brackets is ignored
+                brackets = Some(&inner[end + 1..]).filter(|rest| rest.starts_with('['));
+            }
+            None => {
+                // An unterminated bracket is part of the name, unless it follows another one
+                if indexes.is_empty() {
+                    name.push('_');
+                    name.push_str(&mangle(inner, true));
+                }
+                break;
+            }
+        }
+    }
+
+    let mut array = result;
+    let mut key = Some(name.as_str());
+    for index in indexes {
+        array = match array.array(key) {
+            Some(array) => array,
+            None => return,
+        };
+        key = index;
+    }
+    array.set(key, Zval::String(value));
+}
+
+/// Parse a query string the same way as PHP's `parse_str`
+pub(crate) fn parse(input: &[u8], config: &QsConfig) -> Result<Map, Error> {
+    let mut result = Array::default();
+    let mut scratch = Vec::new();
+
+    for pair in split_pairs(input, config.pair_separator) {
+        // Pairs which can't be parsed are skipped, with `skip_invalid_pairs`
+        if invalid_pair_len(pair, config).is_some() {
+            continue;
+        }
+
+        // Keys without a value get an empty string
+        let (key, value) = split_pair(pair);
+        let key = decode(key, &mut scratch, config)?;
+        let value = decode(value, &mut scratch, config).map_err(|mut error| {
+            error.key = key.clone();
+            error
+        })?;
+
+        register(&mut result, &key, value, config);
+    }
+
+    Ok(result.into_map())
+}
@@ -0,0 +1,127 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+use _serde::de;
+
+/// The name used to recognize `Presence` in the deserializers
+pub(crate) const TOKEN: &str = "$serde_querystring::private::Presence";
+
+/// A value telling an absent key, a bare key(`debug`) and a key with a value(`debug=1`) apart
+///
+/// A missing field is `Absent` with `#[serde(default)]`, a key without `=` is a `Flag`, and
+/// any other value, including an empty one(`debug=`), is deserialized into `Value`.
+///
+/// The modes parsing into a [`Value`](crate::Value) first(`Rack` and `Php`) have no way to tell
+/// a bare key from an empty value, so both give an empty `Value`.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_str, ParseMode, Presence};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     #[serde(default)]
+///     debug: Presence<String>,
+///     #[serde(default)]
+///     verbose: Presence<u32>,
+///     #[serde(default)]
+///     trace: Presence<String>,
+/// }
+///
+/// let query: Query = from_str("debug&verbose=2", ParseMode::UrlEncoded).unwrap();
+///
+/// assert_eq!(query.debug, Presence::Flag);
+/// assert_eq!(query.verbose, Presence::Value(2));
+/// assert_eq!(query.trace, Presence::Absent);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Presence<T> {
+    /// The key is not in the query string
+    Absent,
+    /// The key has no value, like `debug`
+    Flag,
+    /// The key has a value, like `debug=` or `debug=1`
+    Value(T),
+}
+
+impl<T> Presence<T> {
+    /// Returns true if the key is in the query string, with or without a value
+    pub fn is_present(&self) -> bool {
+        !matches!(self, Presence::Absent)
+    }
+
+    /// Returns the value of the key, if it has one
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Presence::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the key, the default value for a flag, and `None` if it is absent
+    pub fn into_option(self) -> Option<T>
+    where
+        T: Default,
+    {
+        match self {
+            Presence::Absent => None,
+            Presence::Flag => Some(T::default()),
+            Presence::Value(value) => Some(value),
+        }
+    }
+}
+
+impl<T> Default for Presence<T> {
+    fn default() -> Self {
+        Presence::Absent
+    }
+}
+
+struct PresenceVisitor<T>(PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for PresenceVisitor<T>
+where
+    T: de::Deserialize<'de>,
+{
+    type Value = Presence<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a key with or without a value")
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Presence::Absent)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Presence::Flag)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Presence::Value)
+    }
+}
+
+impl<'de, T> de::Deserialize<'de> for Presence<T>
+where
+    T: de::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        // Other deserializers always give a value
+        deserializer.deserialize_newtype_struct(TOKEN, PresenceVisitor(PhantomData))
+    }
+}
@@ -0,0 +1,253 @@
+//! Strategies generating query strings for property tests, enabled by the `proptest` feature
+//!
+//! The query strings are valid for the default config of their mode, and written in as many
+//! ways as the parsers accept: needless and lowercase percent encodings, `+` for spaces, multi
+//! byte characters, malformed escapes which are kept as they are, repeated keys and nested
+//! brackets in the modes supporting them.
+
+use std::collections::BTreeSet;
+
+use _proptest::collection::vec;
+use _proptest::prelude::*;
+use _proptest::sample::select;
+
+use crate::ParseMode;
+
+/// A generated string, as written in the query string and as deserialized
+///
+/// The decoded form is the one of the default options, `Php` mode also replaces the spaces
+/// and dots of the names with underscores.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodedString {
+    /// The string as written in the query string
+    pub encoded: String,
+    /// The string as deserialized
+    pub decoded: String,
+}
+
+/// Characters written as they are, or percent encoded
+const PLAIN: &[char] = &[
+    'a', 'b', 'q', 'z', 'A', 'Q', 'Z', '0', '1', '9', '-', '_', '~',
+];
+
+/// Characters which are always percent encoded
+const SPECIAL: &[char] = &[
+    ' ', '&', '=', '%', '+', '#', '?', '/', ';', ',', '|', '\n', 'é', '€', '😀',
+];
+
+/// Characters which are always percent encoded, but never in keys as the bracket modes split
+/// keys on their encoded forms too
+const SPECIAL_VALUE: &[char] = &['[', ']', '.'];
+
+/// Malformed escapes, and their decoded forms
+const MALFORMED: &[(&str, &str)] = &[
+    ("%zz", "%zz"),
+    ("%G1", "%G1"),
+    ("%u00E9", "%u00E9"),
+    ("%%41", "%A"),
+];
+
+/// Percent encode the utf-8 bytes of a character
+fn percent_encode(c: char, uppercase: bool) -> String {
+    let mut buf = [0; 4];
+    c.encode_utf8(&mut buf)
+        .bytes()
+        .map(|b| {
+            if uppercase {
+                format!("%{:02X}", b)
+            } else {
+                format!("%{:02x}", b)
+            }
+        })
+        .collect()
+}
+
+fn plain() -> impl Strategy<Value = EncodedString> {
+    (select(PLAIN), 0..4u8).prop_map(|(c, encoding)| EncodedString {
+        encoded: match encoding {
+            0 => percent_encode(c, true),
+            1 => percent_encode(c, false),
+            _ => c.to_string(),
+        },
+        decoded: c.to_string(),
+    })
+}
+
+fn special(chars: &'static [char]) -> impl Strategy<Value = EncodedString> {
+    (select(chars), any::<bool>()).prop_map(|(c, uppercase)| EncodedString {
+        encoded: match c {
+            ' ' if uppercase => String::from("+"),
+            _ => percent_encode(c, uppercase),
+        },
+        decoded: c.to_string(),
+    })
+}
+
+fn malformed() -> impl Strategy<Value = EncodedString> {
+    select(MALFORMED).prop_map(|(encoded, decoded)| EncodedString {
+        encoded: encoded.to_string(),
+        decoded: decoded.to_string(),
+    })
+}
+
+fn concat(pieces: Vec<EncodedString>) -> EncodedString {
+    EncodedString {
+        encoded: pieces.iter().map(|piece| &*piece.encoded).collect(),
+        decoded: pieces.iter().map(|piece| &*piece.decoded).collect(),
+    }
+}
+
+/// Generates a non empty key, without brackets or dots
+pub fn key() -> BoxedStrategy<EncodedString> {
+    vec(prop_oneof![4 => plain(), 1 => special(SPECIAL)], 1..8)
+        .prop_map(concat)
+        .boxed()
+}
+
+/// Generates a value, which may be empty
+pub fn value() -> BoxedStrategy<EncodedString> {
+    vec(
+        prop_oneof![
+            6 => plain(),
+            2 => special(SPECIAL),
+            1 => special(SPECIAL_VALUE),
+            1 => malformed(),
+        ],
+        0..12,
+    )
+    .prop_map(concat)
+    .boxed()
+}
+
+/// The values of a key, and of its sub keys
+#[derive(Clone, Debug)]
+enum Node {
+    /// Repeated pairs, or a single pair in the delimiter modes
+    Values(Vec<EncodedString>),
+    /// Indexed sub keys, or empty brackets when all the items are values
+    Sequence(Vec<Node>, bool),
+    /// Sub keys in brackets, or after dots in dot notation
+    Map(Vec<(EncodedString, Node)>, bool),
+}
+
+fn values() -> impl Strategy<Value = Node> {
+    vec(value(), 1..4).prop_map(Node::Values)
+}
+
+fn node() -> impl Strategy<Value = Node> {
+    values().prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            (vec(inner.clone(), 1..4), any::<bool>())
+                .prop_map(|(items, append)| Node::Sequence(items, append)),
+            (vec((key(), inner), 1..4), any::<bool>())
+                .prop_map(|(entries, dots)| Node::Map(entries, dots)),
+        ]
+    })
+}
+
+/// The byte values are joined with, in the modes which split them
+fn delimiter(mode: ParseMode) -> Option<u8> {
+    match mode {
+        ParseMode::Delimiter(delimiter) | ParseMode::BracketsDelimiter(delimiter) => {
+            Some(delimiter)
+        }
+        ParseMode::Matrix => Some(b','),
+        _ => None,
+    }
+}
+
+/// Whether the keys of the mode can have sub keys
+fn nested(mode: ParseMode) -> bool {
+    matches!(
+        mode,
+        ParseMode::Brackets
+            | ParseMode::DotNotation
+            | ParseMode::BracketsDelimiter(_)
+            | ParseMode::Rack
+            | ParseMode::Php
+    )
+}
+
+fn render(node: &Node, key: &str, mode: ParseMode, pairs: &mut Vec<String>) {
+    match node {
+        Node::Values(values) => match delimiter(mode) {
+            Some(delimiter) => {
+                let values: Vec<_> = values.iter().map(|value| &*value.encoded).collect();
+                let delimiter = char::from(delimiter).to_string();
+                pairs.push(format!("{}={}", key, values.join(&delimiter)));
+            }
+            None => {
+                for value in values {
+                    pairs.push(format!("{}={}", key, value.encoded));
+                }
+            }
+        },
+        Node::Sequence(items, append) => {
+            let append = *append && items.iter().all(|item| matches!(item, Node::Values(_)));
+            for (index, item) in items.iter().enumerate() {
+                let key = if append {
+                    format!("{}[]", key)
+                } else {
+                    format!("{}[{}]", key, index)
+                };
+                render(item, &key, mode, pairs);
+            }
+        }
+        Node::Map(entries, dots) => {
+            let dots = *dots && matches!(mode, ParseMode::DotNotation);
+            let mut seen = BTreeSet::new();
+            for (sub_key, node) in entries {
+                if !seen.insert(&*sub_key.decoded) {
+                    continue;
+                }
+                let key = if dots {
+                    format!("{}.{}", key, sub_key.encoded)
+                } else {
+                    format!("{}[{}]", key, sub_key.encoded)
+                };
+                render(node, &key, mode, pairs);
+            }
+        }
+    }
+}
+
+/// Generates query strings which are valid in the mode, with its default config
+///
+/// The keys are nested in the modes supporting it, and the values are joined with the
+/// delimiter in the delimiter modes(which should be ASCII). `Custom` modes get the query
+/// strings of `Duplicate` mode.
+///
+/// # Example
+/// ```rust
+/// use _proptest::prelude::*;
+/// use serde_querystring::proptest::query;
+/// use serde_querystring::{from_str_value, ParseMode};
+///
+/// proptest!(|(input in query(ParseMode::Brackets))| {
+///     prop_assert!(from_str_value(&input, ParseMode::Brackets).is_ok());
+/// });
+/// ```
+pub fn query(mode: ParseMode) -> BoxedStrategy<String> {
+    let node = if nested(mode) {
+        node().boxed()
+    } else {
+        values().boxed()
+    };
+
+    vec((key(), node), 0..8)
+        .prop_map(move |entries| {
+            let mut pairs = Vec::new();
+            let mut seen = BTreeSet::new();
+            for (key, node) in &entries {
+                if seen.insert(&*key.decoded) {
+                    render(node, &key.encoded, mode, &mut pairs);
+                }
+            }
+
+            match mode {
+                ParseMode::Matrix => pairs.iter().map(|pair| format!(";{}", pair)).collect(),
+                _ => pairs.join("&"),
+            }
+        })
+        .boxed()
+}
@@ -0,0 +1,262 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::config::QsConfig;
+use crate::de::{Error, ErrorKind};
+use crate::decode::invalid_pair_len;
+use crate::pairs::split_pairs;
+use crate::parsers::OrderedMap;
+use crate::value::{decode, Map, Value};
+
+/// A parameter of Rack's nested params, `Null` is a key without a value(`key`)
+enum Param {
+    Null,
+    String(String),
+    Array(Vec<Param>),
+    Hash(OrderedMap<String, Param>),
+}
+
+impl Param {
+    /// The name of the ruby class, used in the errors the same way as Rack
+    fn class_name(&self) -> &'static str {
+        match self {
+            Param::Null => "NilClass",
+            Param::String(_) => "String",
+            Param::Array(_) => "Array",
+            Param::Hash(_) => "Hash",
+        }
+    }
+
+    fn into_value(self) -> Value {
+        match self {
+            Param::Null => Value::String(String::new()),
+            Param::String(s) => Value::String(s),
+            Param::Array(values) => {
+                Value::Sequence(values.into_iter().map(Param::into_value).collect())
+            }
+            Param::Hash(map) => Value::Map(into_map(map)),
+        }
+    }
+}
+
+fn into_map(params: OrderedMap<String, Param>) -> Map {
+    params
+        .into_iter()
+        .map(|(key, param)| (key, param.into_value()))
+        .collect()
+}
+
+/// What `normalize` returns in Rack, which is usually the params it was given
+enum Normalized {
+    Params,
+    Nothing,
+    Single(Param),
+}
+
+fn type_error(expected: &str, found: &Param, key: &str, depth: usize) -> Error {
+    let mut error = Error::new(ErrorKind::KeyConflict)
+        .message(format!(
+            "expected {} (got {}) for param `{}'",
+            expected,
+            found.class_name(),
+            key
+        ))
+        .value(key.as_bytes());
+    error.key = segment(key, depth);
+    error
+}
+
+/// A key as it is written in the path of its parents, `key` at the top and `[key]` below it
+fn segment(key: &str, depth: usize) -> String {
+    if depth == 0 {
+        key.to_string()
+    } else {
+        format!("[{}]", key)
+    }
+}
+
+/// Adds the key of a parent to the path of the key of an error, like `x[y]`
+fn with_parent(mut error: Error, parent: String) -> Error {
+    error.key.insert_str(0, &parent);
+    error
+}
+
+/// `params_hash_has_key?` of Rack, true if the path of the key already exists in the hash
+fn has_key(hash: &OrderedMap<String, Param>, key: &str) -> bool {
+    if key.contains("[]") {
+        return false;
+    }
+
+    let mut current = hash;
+    let mut parts = key.split(['[', ']']).filter(|part| !part.is_empty());
+    let mut next = parts.next();
+    while let Some(part) = next {
+        let param = match current.get(part) {
+            Some(param) => param,
+            None => return false,
+        };
+        next = parts.next();
+        match param {
+            Param::Hash(hash) => current = hash,
+            _ if next.is_none() => return true,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Returns the array of a key, creating it if the key is missing or nil
+fn array_entry<'p>(
+    params: &'p mut OrderedMap<String, Param>,
+    key: &str,
+    depth: usize,
+) -> Result<&'p mut Vec<Param>, Error> {
+    let param = params.get_or_insert_with(key.to_string(), || Param::Null);
+    if let Param::Null = param {
+        *param = Param::Array(Vec::new());
+    }
+    match param {
+        Param::Array(values) => Ok(values),
+        param => Err(type_error("Array", param, key, depth)),
+    }
+}
+
+/// A port of `_normalize_params` from Rack's `QueryParser`, adding a single pair to the params
+fn normalize(
+    params: &mut OrderedMap<String, Param>,
+    name: &str,
+    value: Param,
+    depth: usize,
+    config: &QsConfig,
+) -> Result<Normalized, Error> {
+    if depth > config.max_depth {
+        let mut error = Error::new(ErrorKind::DepthLimitExceeded)
+            .message(format!(
+                "key is nested deeper than the max depth of {}",
+                config.max_depth
+            ))
+            .value(name.as_bytes());
+        error.key = name.to_string();
+        return Err(error);
+    }
+
+    let (key, after) = if depth == 0 {
+        // A leading bracket is part of the key
+        match name.bytes().skip(1).position(|b| b == b'[') {
+            Some(index) => name.split_at(index + 1),
+            None => (name, ""),
+        }
+    } else if let Some(after) = name.strip_prefix("[]") {
+        ("[]", after)
+    } else {
+        match name.bytes().skip(1).position(|b| b == b']') {
+            Some(index) if name.starts_with('[') => (&name[1..index + 1], &name[index + 2..]),
+            // Malformed keys are kept as they are
+            _ => (name, ""),
+        }
+    };
+
+    if key.is_empty() {
+        return Ok(Normalized::Nothing);
+    }
+
+    match after {
+        "" if key == "[]" && depth != 0 => return Ok(Normalized::Single(value)),
+        "" => {
+            params.insert(key.to_string(), value);
+        }
+        "[" => {
+            params.insert(name.to_string(), value);
+        }
+        "[]" => array_entry(params, key, depth)?.push(value),
+        _ if after.starts_with("[]") => {
+            // A hash inside an array(`x[][y]`) only has a single key, or other nested arrays
+            let child_key = match after.strip_suffix(']') {
+                Some(inner) if after.as_bytes()[2] == b'[' => &inner[3..],
+                _ => "",
+            };
+            let child_key = if child_key.is_empty() || child_key.contains(['[', ']']) {
+                &after[2..]
+            } else {
+                child_key
+            };
+
+            let values = array_entry(params, key, depth)?;
+            let parent = || format!("{}[]", segment(key, depth));
+            match values.last_mut() {
+                Some(Param::Hash(last)) if !has_key(last, child_key) => {
+                    normalize(last, child_key, value, depth + 1, config)
+                        .map_err(|error| with_parent(error, parent()))?;
+                }
+                _ => {
+                    let mut child = OrderedMap::new();
+                    let normalized = normalize(&mut child, child_key, value, depth + 1, config)
+                        .map_err(|error| with_parent(error, parent()))?;
+                    values.push(match normalized {
+                        Normalized::Params => Param::Hash(child),
+                        Normalized::Nothing => Param::Null,
+                        Normalized::Single(value) => Param::Array(vec![value]),
+                    });
+                }
+            }
+        }
+        _ => {
+            let param = params.get_or_insert_with(key.to_string(), || Param::Null);
+            if let Param::Null = param {
+                *param = Param::Hash(OrderedMap::new());
+            }
+            let child = match param {
+                Param::Hash(child) => child,
+                param => return Err(type_error("Hash", param, key, depth)),
+            };
+            let normalized = normalize(child, after, value, depth + 1, config)
+                .map_err(|error| with_parent(error, segment(key, depth)))?;
+            match normalized {
+                Normalized::Params => {}
+                Normalized::Nothing => *param = Param::Null,
+                Normalized::Single(value) => *param = Param::Array(vec![value]),
+            }
+        }
+    }
+
+    Ok(Normalized::Params)
+}
+
+/// Parse a query string the same way as `Rack::Utils.parse_nested_query`
+pub(crate) fn parse(input: &[u8], config: &QsConfig) -> Result<Map, Error> {
+    let mut params = OrderedMap::new();
+    let mut scratch = Vec::new();
+
+    for mut pair in split_pairs(input, config.pair_separator) {
+        // Pairs which can't be parsed are skipped, with `skip_invalid_pairs`
+        if invalid_pair_len(pair, config).is_some() {
+            continue;
+        }
+
+        // Rack also removes the spaces after the separators
+        while let [b' ', rest @ ..] = pair {
+            pair = rest;
+        }
+
+        let (key, value) = match pair.iter().position(|b| *b == b'=') {
+            Some(index) => (&pair[..index], Some(&pair[index + 1..])),
+            None => (pair, None),
+        };
+        let key = decode(key, &mut scratch, config)?;
+        let value = match value {
+            Some(value) => {
+                Param::String(decode(value, &mut scratch, config).map_err(|mut error| {
+                    error.key = key.clone();
+                    error
+                })?)
+            }
+            None => Param::Null,
+        };
+
+        normalize(&mut params, &key, value, 0, config)?;
+    }
+
+    Ok(into_map(params))
+}
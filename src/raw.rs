@@ -0,0 +1,186 @@
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use _serde::{de, ser};
+
+use crate::decode::parse_bytes;
+#[cfg(feature = "uuid")]
+use crate::{Uuid, UuidError};
+
+/// The name used to recognize `RawQsValue` in the (de)serializers
+pub(crate) const TOKEN: &str = "$serde_querystring::private::RawQsValue";
+
+/// A value of the query string kept as it appeared in the input, without percent decoding
+///
+/// Borrowing it as `&RawQsValue` avoids any allocation, and `Box<RawQsValue>` can be used
+/// to store it. It is written back as is when serialized with this crate, so passthrough
+/// values are never re-encoded.
+///
+/// In modes with multiple values per key, it holds the value a single value would be
+/// deserialized from (the last one, or the whole delimited value in delimiter mode).
+///
+/// # Example
+/// ```rust
+///# use _serde::{Deserialize, Serialize};
+/// use serde_querystring::{from_str, to_string, ParseMode, RawQsValue};
+///
+/// #[derive(Deserialize, Serialize)]
+///# #[serde(crate = "_serde")]
+/// struct Proxy<'a> {
+///     #[serde(borrow)]
+///     token: &'a RawQsValue,
+/// }
+///
+/// let proxy: Proxy = from_str("token=a%2fb+c", ParseMode::Duplicate).unwrap();
+///
+/// assert_eq!(proxy.token.as_bytes(), b"a%2fb+c");
+/// assert_eq!(proxy.token.decode(), &b"a/b c"[..]);
+/// assert_eq!(to_string(&proxy, ParseMode::Duplicate).unwrap(), "token=a%2fb+c");
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct RawQsValue {
+    slice: [u8],
+}
+
+impl RawQsValue {
+    /// Wraps a raw (percent encoded) slice
+    pub fn from_bytes(slice: &[u8]) -> &RawQsValue {
+        // Safety: RawQsValue is a transparent wrapper around [u8]
+        unsafe { &*(slice as *const [u8] as *const RawQsValue) }
+    }
+
+    fn from_boxed_bytes(slice: Box<[u8]>) -> Box<RawQsValue> {
+        // Safety: RawQsValue is a transparent wrapper around [u8]
+        unsafe { Box::from_raw(Box::into_raw(slice) as *mut RawQsValue) }
+    }
+
+    /// Returns the raw bytes, as they appeared in the query string
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.slice
+    }
+
+    /// Percent decodes the value, `+` is decoded as a space
+    pub fn decode(&self) -> Cow<'_, [u8]> {
+        let mut scratch = Vec::new();
+        parse_bytes(&self.slice, &mut scratch).into_cow()
+    }
+
+    /// Parses the value as a UUID, in any of the forms of `Uuid::parse_str`
+    ///
+    /// The raw bytes are parsed as is, and only decoded when they are percent encoded.
+    #[cfg(feature = "uuid")]
+    pub fn parse_uuid(&self) -> Result<Uuid, UuidError> {
+        Uuid::try_parse_ascii(&self.slice).or_else(|e| {
+            if self.slice.contains(&b'%') {
+                Uuid::try_parse_ascii(&self.decode())
+            } else {
+                Err(e)
+            }
+        })
+    }
+}
+
+impl fmt::Debug for RawQsValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawQsValue")
+            .field(&String::from_utf8_lossy(&self.slice))
+            .finish()
+    }
+}
+
+impl Clone for Box<RawQsValue> {
+    fn clone(&self) -> Self {
+        RawQsValue::from_boxed_bytes(self.slice.into())
+    }
+}
+
+impl ToOwned for RawQsValue {
+    type Owned = Box<RawQsValue>;
+
+    fn to_owned(&self) -> Box<RawQsValue> {
+        RawQsValue::from_boxed_bytes(self.slice.into())
+    }
+}
+
+impl ser::Serialize for RawQsValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        // Other serializers get the raw value as a string
+        match core::str::from_utf8(&self.slice) {
+            Ok(s) => serializer.serialize_newtype_struct(TOKEN, s),
+            Err(_) => serializer.serialize_newtype_struct(TOKEN, &self.slice),
+        }
+    }
+}
+
+struct BorrowedVisitor;
+
+impl<'de> de::Visitor<'de> for BorrowedVisitor {
+    type Value = &'de RawQsValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a raw value borrowed from the query string")
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(RawQsValue::from_bytes(v))
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(RawQsValue::from_bytes(v.as_bytes()))
+    }
+}
+
+impl<'de: 'a, 'a> de::Deserialize<'de> for &'a RawQsValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TOKEN, BorrowedVisitor)
+    }
+}
+
+struct BoxedVisitor;
+
+impl<'de> de::Visitor<'de> for BoxedVisitor {
+    type Value = Box<RawQsValue>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a raw value of the query string")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(RawQsValue::from_boxed_bytes(v.into()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(v.as_bytes())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Box<RawQsValue> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(TOKEN, BoxedVisitor)
+    }
+}
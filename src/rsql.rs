@@ -0,0 +1,457 @@
+//! [RSQL](https://github.com/jirutka/rsql-parser) and FIQL filter expressions, parsed from a
+//! single value(`filter=name==foo;age=gt=30`)
+//!
+//! Enabled by the `rsql` feature.
+//!
+//! # Example
+//! ```rust
+//!# use _serde::Deserialize;
+//! use serde_querystring::rsql::{Comparison, Operator, RsqlExpr};
+//! use serde_querystring::{from_str, ParseMode};
+//!
+//! #[derive(Deserialize)]
+//!# #[serde(crate = "_serde")]
+//! struct Query {
+//!     filter: RsqlExpr,
+//! }
+//!
+//! let query: Query = from_str("filter=name==foo;age=gt=30", ParseMode::UrlEncoded).unwrap();
+//!
+//! assert_eq!(
+//!     query.filter,
+//!     RsqlExpr::And(vec![
+//!         RsqlExpr::Comparison(Comparison::new("name", Operator::Eq, vec!["foo"])),
+//!         RsqlExpr::Comparison(Comparison::new("age", Operator::Gt, vec!["30"])),
+//!     ])
+//! );
+//! assert_eq!(query.filter.to_string(), "name==foo;age=gt=30");
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use _serde::{de, ser};
+
+use crate::de::{Error, ErrorKind};
+
+/// The operator of a [`Comparison`]
+///
+/// The FIQL forms(`=lt=`) and the symbolic forms(`<`) of the ordering operators are the same.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `==`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `=gt=` or `>`
+    Gt,
+    /// `=ge=` or `>=`
+    Ge,
+    /// `=lt=` or `<`
+    Lt,
+    /// `=le=` or `<=`
+    Le,
+    /// `=in=`
+    In,
+    /// `=out=`
+    Out,
+    /// Any other FIQL operator, without the equal signs(`=like=` is `Custom("like")`)
+    Custom(String),
+}
+
+impl Operator {
+    fn from_fiql(name: &str) -> Self {
+        match name {
+            "gt" => Operator::Gt,
+            "ge" => Operator::Ge,
+            "lt" => Operator::Lt,
+            "le" => Operator::Le,
+            "in" => Operator::In,
+            "out" => Operator::Out,
+            _ => Operator::Custom(name.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operator::Eq => f.write_str("=="),
+            Operator::Ne => f.write_str("!="),
+            Operator::Gt => f.write_str("=gt="),
+            Operator::Ge => f.write_str("=ge="),
+            Operator::Lt => f.write_str("=lt="),
+            Operator::Le => f.write_str("=le="),
+            Operator::In => f.write_str("=in="),
+            Operator::Out => f.write_str("=out="),
+            Operator::Custom(name) => write!(f, "={}=", name),
+        }
+    }
+}
+
+/// A comparison of a selector with its arguments(`age=gt=30`, `id=in=(1,2)`)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Comparison {
+    pub selector: String,
+    pub operator: Operator,
+    pub arguments: Vec<String>,
+}
+
+impl Comparison {
+    /// Creates a comparison
+    pub fn new<S: Into<String>>(selector: S, operator: Operator, arguments: Vec<S>) -> Self {
+        Self {
+            selector: selector.into(),
+            operator,
+            arguments: arguments.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.selector, self.operator)?;
+        match self.arguments.as_slice() {
+            [argument] => write_argument(f, argument),
+            arguments => {
+                f.write_str("(")?;
+                for (index, argument) in arguments.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_argument(f, argument)?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// Writes an argument, quoted if it has any reserved char
+fn write_argument(f: &mut fmt::Formatter<'_>, argument: &str) -> fmt::Result {
+    if !argument.is_empty() && argument.bytes().all(is_unreserved) {
+        return f.write_str(argument);
+    }
+
+    f.write_str("\"")?;
+    for c in argument.chars() {
+        if c == '"' || c == '\\' {
+            f.write_str("\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    f.write_str("\"")
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    !matches!(
+        byte,
+        b'"' | b'\'' | b'(' | b')' | b';' | b',' | b'=' | b'!' | b'~' | b'<' | b'>' | b' '
+    )
+}
+
+/// A RSQL expression
+///
+/// `;` or ` and ` join the comparisons with a logical and, `,` or ` or ` with a logical or, and
+/// the and binds tighter. The parentheses group the expressions. The arguments are unquoted, or
+/// quoted by `"` or `'` with `\` escaping the next char.
+///
+/// It is parsed from a single string, the same in any parse mode, and serialized back to the
+/// canonical form.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RsqlExpr {
+    And(Vec<RsqlExpr>),
+    Or(Vec<RsqlExpr>),
+    Comparison(Comparison),
+}
+
+impl RsqlExpr {
+    /// Parse a RSQL expression
+    ///
+    /// It fails with `ErrorKind::InvalidType` and the index of the byte causing the error.
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let mut parser = Parser { input: s, index: 0 };
+
+        let expr = parser.parse_or()?;
+        parser.skip_spaces();
+        if parser.index < s.len() {
+            return Err(parser.error("unexpected char after the expression"));
+        }
+        Ok(expr)
+    }
+
+    /// Returns every comparison of the expression, in order
+    pub fn comparisons(&self) -> Vec<&Comparison> {
+        let mut comparisons = Vec::new();
+        self.collect_comparisons(&mut comparisons);
+        comparisons
+    }
+
+    fn collect_comparisons<'a>(&'a self, comparisons: &mut Vec<&'a Comparison>) {
+        match self {
+            RsqlExpr::And(exprs) | RsqlExpr::Or(exprs) => {
+                for expr in exprs {
+                    expr.collect_comparisons(comparisons);
+                }
+            }
+            RsqlExpr::Comparison(comparison) => comparisons.push(comparison),
+        }
+    }
+}
+
+impl FromStr for RsqlExpr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for RsqlExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RsqlExpr::And(exprs) => {
+                for (index, expr) in exprs.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(";")?;
+                    }
+                    // Or binds looser, so it needs the parentheses inside of an and
+                    match expr {
+                        RsqlExpr::Or(_) => write!(f, "({})", expr)?,
+                        _ => write!(f, "{}", expr)?,
+                    }
+                }
+                Ok(())
+            }
+            RsqlExpr::Or(exprs) => {
+                for (index, expr) in exprs.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", expr)?;
+                }
+                Ok(())
+            }
+            RsqlExpr::Comparison(comparison) => write!(f, "{}", comparison),
+        }
+    }
+}
+
+impl ser::Serialize for RsqlExpr {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for RsqlExpr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|e: Error| de::Error::custom(e.message))
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.index).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(b' ') {
+            self.index += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::new(ErrorKind::InvalidType)
+            .message(String::from(message))
+            .value(self.input.as_bytes())
+            .index(self.index)
+    }
+
+    /// Eats a separator, either the char or the keyword surrounded by spaces
+    fn eat_separator(&mut self, separator: u8, keyword: &str) -> bool {
+        let start = self.index;
+        self.skip_spaces();
+        if self.peek() == Some(separator) {
+            self.index += 1;
+            return true;
+        }
+
+        if self.index > start && self.input[self.index..].starts_with(keyword) {
+            let end = self.index + keyword.len();
+            if self.input.as_bytes().get(end) == Some(&b' ') {
+                self.index = end;
+                return true;
+            }
+        }
+
+        self.index = start;
+        false
+    }
+
+    fn parse_or(&mut self) -> Result<RsqlExpr, Error> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.eat_separator(b',', "or") {
+            exprs.push(self.parse_and()?);
+        }
+
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            RsqlExpr::Or(exprs)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<RsqlExpr, Error> {
+        let mut exprs = vec![self.parse_constraint()?];
+        while self.eat_separator(b';', "and") {
+            exprs.push(self.parse_constraint()?);
+        }
+
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            RsqlExpr::And(exprs)
+        })
+    }
+
+    fn parse_constraint(&mut self) -> Result<RsqlExpr, Error> {
+        self.skip_spaces();
+        if self.peek() == Some(b'(') {
+            self.index += 1;
+            let expr = self.parse_or()?;
+            self.skip_spaces();
+            if self.peek() != Some(b')') {
+                return Err(self.error("expected a closing parenthesis"));
+            }
+            self.index += 1;
+            return Ok(expr);
+        }
+
+        let selector = self.parse_unreserved();
+        if selector.is_empty() {
+            return Err(self.error("expected a selector"));
+        }
+        let operator = self.parse_operator()?;
+        let arguments = self.parse_arguments()?;
+
+        Ok(RsqlExpr::Comparison(Comparison {
+            selector: selector.to_string(),
+            operator,
+            arguments,
+        }))
+    }
+
+    fn parse_unreserved(&mut self) -> &'a str {
+        let start = self.index;
+        while self.peek().map_or(false, is_unreserved) {
+            self.index += 1;
+        }
+        &self.input[start..self.index]
+    }
+
+    fn parse_operator(&mut self) -> Result<Operator, Error> {
+        let rest = &self.input[self.index..];
+        let (operator, len) = if rest.starts_with("==") {
+            (Operator::Eq, 2)
+        } else if rest.starts_with("!=") {
+            (Operator::Ne, 2)
+        } else if rest.starts_with(">=") {
+            (Operator::Ge, 2)
+        } else if rest.starts_with("<=") {
+            (Operator::Le, 2)
+        } else if rest.starts_with('>') {
+            (Operator::Gt, 1)
+        } else if rest.starts_with('<') {
+            (Operator::Lt, 1)
+        } else if let Some(name) = rest.strip_prefix('=') {
+            let len = name
+                .bytes()
+                .take_while(|byte| byte.is_ascii_alphabetic())
+                .count();
+            if len == 0 || name.as_bytes().get(len) != Some(&b'=') {
+                return Err(self.error("expected a comparison operator"));
+            }
+            (Operator::from_fiql(&name[..len]), len + 2)
+        } else {
+            return Err(self.error("expected a comparison operator"));
+        };
+
+        self.index += len;
+        Ok(operator)
+    }
+
+    fn parse_arguments(&mut self) -> Result<Vec<String>, Error> {
+        if self.peek() != Some(b'(') {
+            return Ok(vec![self.parse_argument()?]);
+        }
+        self.index += 1;
+
+        let mut arguments = Vec::new();
+        loop {
+            self.skip_spaces();
+            arguments.push(self.parse_argument()?);
+            self.skip_spaces();
+            match self.peek() {
+                Some(b',') => self.index += 1,
+                Some(b')') => {
+                    self.index += 1;
+                    return Ok(arguments);
+                }
+                _ => return Err(self.error("expected a comma or a closing parenthesis")),
+            }
+        }
+    }
+
+    fn parse_argument(&mut self) -> Result<String, Error> {
+        let quote = match self.peek() {
+            Some(quote @ (b'"' | b'\'')) => quote,
+            _ => {
+                let argument = self.parse_unreserved();
+                if argument.is_empty() {
+                    return Err(self.error("expected an argument"));
+                }
+                return Ok(argument.to_string());
+            }
+        };
+
+        let start = self.index;
+        self.index += 1;
+
+        let mut argument = String::new();
+        let mut chars = self.input[self.index..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            if c == quote as char {
+                self.index += offset + 1;
+                return Ok(argument);
+            }
+
+            if c == '\\' {
+                match chars.next() {
+                    Some((_, c)) => argument.push(c),
+                    None => break,
+                }
+            } else {
+                argument.push(c);
+            }
+        }
+
+        self.index = start;
+        Err(self.error("unterminated quoted argument"))
+    }
+}
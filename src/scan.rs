@@ -0,0 +1,46 @@
+//! Searching the bytes which delimit the pairs, keys and escapes
+//!
+//! With the `simd` feature they are found with `memchr`, which compares a whole register of
+//! bytes at once. Otherwise they are found one byte at a time.
+
+#[cfg(all(
+    feature = "simd",
+    any(
+        feature = "urlencoded",
+        feature = "duplicate",
+        feature = "delimiter",
+        feature = "brackets"
+    )
+))]
+pub(crate) use _memchr::memchr3;
+#[cfg(feature = "simd")]
+pub(crate) use _memchr::{memchr, memchr2};
+
+/// Returns the index of the first occurrence of the byte
+#[cfg(not(feature = "simd"))]
+#[inline]
+pub(crate) fn memchr(b1: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b == b1)
+}
+
+/// Returns the index of the first occurrence of either byte
+#[cfg(not(feature = "simd"))]
+#[inline]
+pub(crate) fn memchr2(b1: u8, b2: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b == b1 || b == b2)
+}
+
+/// Returns the index of the first occurrence of any of the bytes
+#[cfg(all(
+    not(feature = "simd"),
+    any(
+        feature = "urlencoded",
+        feature = "duplicate",
+        feature = "delimiter",
+        feature = "brackets"
+    )
+))]
+#[inline]
+pub(crate) fn memchr3(b1: u8, b2: u8, b3: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b == b1 || b == b2 || b == b3)
+}
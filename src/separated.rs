@@ -0,0 +1,139 @@
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use _serde::{de, ser};
+
+/// The names used to recognize the separated wrappers in the (de)serializers
+const COMMA_TOKEN: &str = "$serde_querystring::private::CommaSeparated";
+const PIPE_TOKEN: &str = "$serde_querystring::private::PipeSeparated";
+
+/// Returns the delimiter of the wrapper with the given newtype name, if it is one
+pub(crate) fn delimiter(name: &str) -> Option<u8> {
+    match name {
+        COMMA_TOKEN => Some(b','),
+        PIPE_TOKEN => Some(b'|'),
+        _ => None,
+    }
+}
+
+struct SeparatedVisitor<T>(PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for SeparatedVisitor<T>
+where
+    T: de::Deserialize<'de>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a delimited value")
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
+macro_rules! separated {
+    ($(#[$doc:meta])* $name:ident, $token:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name<T>(pub T);
+
+        impl<T> $name<T> {
+            /// Returns the wrapped value
+            pub fn into_inner(self) -> T {
+                self.0
+            }
+        }
+
+        impl<T> From<T> for $name<T> {
+            fn from(value: T) -> Self {
+                $name(value)
+            }
+        }
+
+        impl<T> Deref for $name<T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $name<T> {
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+
+        impl<T> ser::Serialize for $name<T>
+        where
+            T: ser::Serialize,
+        {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ser::Serializer,
+            {
+                // Other serializers see the wrapped value as is
+                serializer.serialize_newtype_struct($token, &self.0)
+            }
+        }
+
+        impl<'de, T> de::Deserialize<'de> for $name<T>
+        where
+            T: de::Deserialize<'de>,
+        {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                deserializer
+                    .deserialize_newtype_struct($token, SeparatedVisitor(PhantomData))
+                    .map($name)
+            }
+        }
+    };
+}
+
+separated!(
+    /// A value whose items are separated by commas(`ids=1,2,3`), whatever the parse mode is
+    ///
+    /// It lets a single field be delimited, without switching the whole query string to
+    /// the delimiter mode. The last value of the key is split when it is repeated, and a
+    /// sequence is joined by commas when serialized.
+    ///
+    /// Percent encoded commas(`%2C`) are kept inside the items, except for a [`Value`](crate::Value)
+    /// whose strings are already decoded.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use _serde::{Deserialize, Serialize};
+    /// use serde_querystring::{from_str, to_string, CommaSeparated, ParseMode};
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    ///# #[serde(crate = "_serde")]
+    /// struct Query {
+    ///     name: String,
+    ///     ids: CommaSeparated<Vec<u64>>,
+    /// }
+    ///
+    /// let query: Query = from_str("name=foo&ids=1,2,3", ParseMode::UrlEncoded).unwrap();
+    ///
+    /// assert_eq!(query.ids.0, vec![1, 2, 3]);
+    /// assert_eq!(to_string(&query, ParseMode::UrlEncoded).unwrap(), "name=foo&ids=1,2,3");
+    /// ```
+    CommaSeparated,
+    COMMA_TOKEN
+);
+
+separated!(
+    /// A value whose items are separated by pipes(`ids=1|2|3`), whatever the parse mode is
+    ///
+    /// It works the same as [`CommaSeparated`], with `|` as the delimiter.
+    PipeSeparated,
+    PIPE_TOKEN
+);
@@ -0,0 +1,164 @@
+use _serde::Serialize;
+
+use crate::de::{Error, ParseMode};
+use crate::decode::parse_bytes;
+
+use super::{to_bytes, SerializerConfig};
+
+/// A single pair of the query string
+struct Pair {
+    // The decoded key used to match pairs when overriding, which in brackets mode
+    // is the part before the first bracket (`user` for `user[name]`)
+    root: Vec<u8>,
+    // The pair as it appeared in the query, or as it was serialized
+    raw: Vec<u8>,
+}
+
+/// Builds a query string on top of an existing one
+///
+/// Pairs of the existing query are kept as they are, while serialized values are either
+/// appended to them or override the pairs with the same keys.
+///
+/// # Example
+/// ```rust
+///# use _serde::Serialize;
+/// use serde_querystring::{ParseMode, QueryStringBuilder};
+///
+/// #[derive(Serialize)]
+///# #[serde(crate = "_serde")]
+/// struct Page {
+///     page: u32,
+/// }
+///
+/// let next = QueryStringBuilder::from_query("?page=2&sort=asc", ParseMode::Duplicate)
+///     .set(&Page { page: 3 })
+///     .unwrap()
+///     .build();
+///
+/// assert_eq!(next, "page=3&sort=asc");
+/// ```
+pub struct QueryStringBuilder {
+    config: SerializerConfig,
+    pairs: Vec<Pair>,
+}
+
+impl QueryStringBuilder {
+    /// Creates a builder with an empty query
+    pub fn new<C: Into<SerializerConfig>>(config: C) -> Self {
+        Self {
+            config: config.into(),
+            pairs: Vec::new(),
+        }
+    }
+
+    /// Creates a builder starting from an existing query, with or without the leading `?`
+    pub fn from_query<C: Into<SerializerConfig>>(query: &str, config: C) -> Self {
+        let mut builder = Self::new(config);
+        let query = query.strip_prefix('?').unwrap_or(query);
+        builder.pairs = builder.split_pairs(query.as_bytes());
+        builder
+    }
+
+    /// Serializes the value and appends its pairs after the existing ones
+    pub fn append<T>(mut self, value: &T) -> Result<Self, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let pairs = self.serialize_pairs(value)?;
+        self.pairs.extend(pairs);
+        Ok(self)
+    }
+
+    /// Serializes the value and replaces the existing pairs having the same keys.
+    ///
+    /// The new pairs take the place of the first pair they replace, and the ones not replacing
+    /// anything are appended. In brackets mode, the whole key is replaced, so setting `user[name]`
+    /// also removes `user[age]`. Fields which serialize to nothing (like `None`) are kept untouched.
+    pub fn set<T>(mut self, value: &T) -> Result<Self, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut new_pairs = self.serialize_pairs(value)?;
+        let roots: Vec<Vec<u8>> = new_pairs.iter().map(|new| new.root.clone()).collect();
+
+        let mut pairs = Vec::with_capacity(self.pairs.len() + new_pairs.len());
+        for pair in self.pairs {
+            if !roots.contains(&pair.root) {
+                pairs.push(pair);
+                continue;
+            }
+
+            // Pairs with this key are only inserted once, at the first pair they replace
+            let (matching, rest): (Vec<_>, Vec<_>) =
+                new_pairs.into_iter().partition(|new| new.root == pair.root);
+            pairs.extend(matching);
+            new_pairs = rest;
+        }
+        pairs.extend(new_pairs);
+
+        self.pairs = pairs;
+        Ok(self)
+    }
+
+    /// Returns the bytes of the query string, without the leading `?`
+    pub fn build_bytes(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        for pair in &self.pairs {
+            if !output.is_empty() || self.config.leading_separator() {
+                output.push(self.config.pair_separator());
+            }
+            output.extend_from_slice(&pair.raw);
+        }
+        output
+    }
+
+    /// Returns the query string, without the leading `?`
+    pub fn build(&self) -> String {
+        // The existing query was a str, and serialized pairs are always valid utf-8
+        String::from_utf8(self.build_bytes()).expect("query string is always valid utf-8")
+    }
+
+    fn serialize_pairs<T>(&self, value: &T) -> Result<Vec<Pair>, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let bytes = to_bytes(value, self.config)?;
+        Ok(self.split_pairs(&bytes))
+    }
+
+    fn split_pairs(&self, query: &[u8]) -> Vec<Pair> {
+        let mut scratch = Vec::new();
+
+        query
+            .split(|b| *b == self.config.pair_separator())
+            .filter(|raw| !raw.is_empty())
+            .map(|raw| {
+                let key = match raw.iter().position(|b| *b == b'=') {
+                    Some(index) => &raw[..index],
+                    None => raw,
+                };
+                let key = match self.config.mode {
+                    ParseMode::Brackets
+                    | ParseMode::BracketsDelimiter(_)
+                    | ParseMode::Rack
+                    | ParseMode::Php => match key.iter().position(|b| *b == b'[') {
+                        Some(index) => &key[..index],
+                        None => key,
+                    },
+                    ParseMode::DotNotation => {
+                        match key.iter().position(|b| *b == b'[' || *b == b'.') {
+                            Some(index) => &key[..index],
+                            None => key,
+                        }
+                    }
+                    _ => key,
+                };
+
+                Pair {
+                    root: parse_bytes(key, &mut scratch).to_vec(),
+                    raw: raw.to_vec(),
+                }
+            })
+            .collect()
+    }
+}
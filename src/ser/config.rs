@@ -0,0 +1,115 @@
+use crate::de::ParseMode;
+use crate::encode::EncodeSet;
+
+/// How `None` values of maps and structs are serialized
+///
+/// The parsers treat these differently, `key=` and `key` are both errors for `Option<T>` when
+/// deserializing with this crate, while some other parsers read them as empty values or flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoneStyle {
+    /// Skip the key entirely, which is the default
+    Skip,
+    /// Write the key with an empty value: `key=`
+    Empty,
+    /// Write the key without the `=`: `key`
+    KeyOnly,
+}
+
+impl Default for NoneStyle {
+    fn default() -> Self {
+        NoneStyle::Skip
+    }
+}
+
+/// Configures how values are serialized into a query string
+///
+/// It can be created from a `ParseMode`, and further options can be set by chaining methods.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{ParseMode, SerializerConfig};
+///
+/// let config = SerializerConfig::new(ParseMode::Brackets).empty_brackets(true);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SerializerConfig {
+    pub(crate) mode: ParseMode,
+    pub(crate) empty_brackets: bool,
+    pub(crate) none_style: NoneStyle,
+    pub(crate) sort_keys: bool,
+    pub(crate) encode_set: EncodeSet,
+}
+
+impl SerializerConfig {
+    /// Creates a config which serializes values the way the given `ParseMode` parses them
+    pub fn new(mode: ParseMode) -> Self {
+        Self {
+            mode,
+            empty_brackets: false,
+            none_style: NoneStyle::Skip,
+            sort_keys: false,
+            encode_set: EncodeSet::FORM,
+        }
+    }
+
+    /// Serialize sequences with empty brackets as `key[]=1&key[]=2`, as expected by PHP and Rack.
+    ///
+    /// It replaces the indexed keys(`key[0]=1`) in brackets mode and the repeated plain keys
+    /// (`key=1`) in duplicate mode. Other modes are not affected.
+    pub fn empty_brackets(mut self, empty_brackets: bool) -> Self {
+        self.empty_brackets = empty_brackets;
+        self
+    }
+
+    /// Sets how `None` values are serialized, they are skipped by default.
+    pub fn none_style(mut self, none_style: NoneStyle) -> Self {
+        self.none_style = none_style;
+        self
+    }
+
+    /// Sort the pairs lexicographically by their encoded keys, so the output is deterministic
+    /// regardless of the order of fields or the iteration order of maps.
+    ///
+    /// The sort is stable, so the values of a sequence keep their order in duplicate mode.
+    /// As all the pairs are buffered until the end, `to_writer` no longer writes them as they are
+    /// serialized.
+    pub fn sort_keys(mut self, sort_keys: bool) -> Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Sets the bytes which are written as is, defaults to [`EncodeSet::FORM`]
+    pub fn encode_set(mut self, encode_set: EncodeSet) -> Self {
+        self.encode_set = encode_set;
+        self
+    }
+}
+
+impl SerializerConfig {
+    /// The byte written between the pairs, `;` for the matrix parameters
+    pub(crate) fn pair_separator(&self) -> u8 {
+        match self.mode {
+            ParseMode::Matrix => b';',
+            _ => b'&',
+        }
+    }
+
+    /// Matrix parameters all start with the separator, including the first one
+    pub(crate) fn leading_separator(&self) -> bool {
+        matches!(self.mode, ParseMode::Matrix)
+    }
+
+    /// The set used to encode the keys of maps, dots would split them in dot notation mode
+    pub(crate) fn key_encode_set(&self) -> EncodeSet {
+        match self.mode {
+            ParseMode::DotNotation => self.encode_set.remove(b'.').remove(b'['),
+            _ => self.encode_set,
+        }
+    }
+}
+
+impl From<ParseMode> for SerializerConfig {
+    fn from(mode: ParseMode) -> Self {
+        Self::new(mode)
+    }
+}
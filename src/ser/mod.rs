@@ -0,0 +1,472 @@
+mod builder;
+mod config;
+mod value;
+
+use std::io;
+
+use _serde::ser::{self, Impossible, Serialize};
+
+use crate::de::{Error, ErrorKind};
+use crate::encode::encode_bytes;
+
+use value::{entry_key, ScalarSerializer, ValueSerializer};
+
+pub use crate::encode::EncodeSet;
+pub use builder::QueryStringBuilder;
+pub use config::{NoneStyle, SerializerConfig};
+
+/// The writer serialized pairs are written into
+pub(crate) struct Output<W> {
+    writer: W,
+    pub(crate) config: SerializerConfig,
+    // Holds the raw bytes of scalar values
+    scratch: Vec<u8>,
+    // Holds the encoded bytes of scalar values
+    encoded: Vec<u8>,
+    // If we have written any pairs yet
+    written: bool,
+    // Pairs waiting to be sorted by their keys, when `sort_keys` is set
+    pending: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl<W: io::Write> Output<W> {
+    fn new(writer: W, config: SerializerConfig) -> Self {
+        Self {
+            writer,
+            config,
+            scratch: Vec::new(),
+            encoded: Vec::new(),
+            written: false,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Writes a pair with an already encoded key and value
+    pub(crate) fn write_encoded_pair(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        self.write_all(key, Some(value)).map_err(write_error)
+    }
+
+    /// Writes an already encoded key without any value or `=`
+    pub(crate) fn write_encoded_key(&mut self, key: &[u8]) -> Result<(), Error> {
+        self.write_all(key, None).map_err(write_error)
+    }
+
+    /// Writes the pairs held back for sorting
+    fn finish(&mut self) -> Result<(), Error> {
+        let mut pending = core::mem::take(&mut self.pending);
+        pending.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (key, value) in pending {
+            self.write_raw(&key, value.as_deref())
+                .map_err(write_error)?;
+        }
+        Ok(())
+    }
+
+    fn write_all(&mut self, key: &[u8], value: Option<&[u8]>) -> io::Result<()> {
+        if self.config.sort_keys {
+            self.pending
+                .push((key.to_vec(), value.map(|value| value.to_vec())));
+            return Ok(());
+        }
+
+        self.write_raw(key, value)
+    }
+
+    fn write_raw(&mut self, key: &[u8], value: Option<&[u8]>) -> io::Result<()> {
+        if self.written || self.config.leading_separator() {
+            self.writer.write_all(&[self.config.pair_separator()])?;
+        }
+        self.written = true;
+
+        self.writer.write_all(key)?;
+        if let Some(value) = value {
+            self.writer.write_all(b"=")?;
+            self.writer.write_all(value)?;
+        }
+        Ok(())
+    }
+
+    /// Serializes a scalar value and writes it as the value of the encoded key
+    pub(crate) fn write_scalar<T>(&mut self, key: &[u8], value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut scratch = core::mem::take(&mut self.scratch);
+        let mut encoded = core::mem::take(&mut self.encoded);
+        scratch.clear();
+        encoded.clear();
+
+        let res = value
+            .serialize(ScalarSerializer::new(&mut scratch))
+            .and_then(|raw| {
+                // Raw values are already encoded
+                if raw {
+                    return self.write_encoded_pair(key, &scratch);
+                }
+                encode_bytes(&scratch, &self.config.encode_set, &mut encoded);
+                self.write_encoded_pair(key, &encoded)
+            });
+
+        self.scratch = scratch;
+        self.encoded = encoded;
+        res
+    }
+}
+
+#[inline]
+fn write_error(e: io::Error) -> Error {
+    Error::new(ErrorKind::Other).message(format!("failed to write the pair: {}", e))
+}
+
+#[inline]
+fn top_level_error() -> Error {
+    Error::new(ErrorKind::InvalidType).message(String::from(
+        "only structs and maps are supported at the top level",
+    ))
+}
+
+/// Serializer for the top level of a querystring, which should always be a map or a struct
+struct QSSerializer<'o, W> {
+    output: &'o mut Output<W>,
+}
+
+impl<'o, W: io::Write> ser::Serializer for QSSerializer<'o, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = MapSerializer<'o, W>;
+    type SerializeStruct = MapSerializer<'o, W>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bool(self, _: bool) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_char(self, _: char) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_str(self, _: &str) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, _: &'static str) -> Result<(), Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_newtype_struct<T>(self, _: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(top_level_error())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(MapSerializer::new(self.output))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, Error> {
+        Ok(MapSerializer::new(self.output))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(top_level_error())
+    }
+}
+
+/// Serializes the entries of a map or a struct as pairs
+///
+/// Nested maps(only in brackets and dot notation modes) have a prefix, which is the encoded key of
+/// the map itself, so their entries are serialized as `prefix[key]=value` or `prefix.key=value`
+pub(crate) struct MapSerializer<'o, W> {
+    output: &'o mut Output<W>,
+    prefix: Option<Vec<u8>>,
+    // The encoded key waiting for its value
+    key: Vec<u8>,
+}
+
+impl<'o, W: io::Write> MapSerializer<'o, W> {
+    pub(crate) fn new(output: &'o mut Output<W>) -> Self {
+        Self {
+            output,
+            prefix: None,
+            key: Vec::new(),
+        }
+    }
+
+    pub(crate) fn nested(prefix: Vec<u8>, output: &'o mut Output<W>) -> Self {
+        Self {
+            output,
+            prefix: Some(prefix),
+            key: Vec::new(),
+        }
+    }
+
+    fn set_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut raw = Vec::new();
+        key.serialize(ScalarSerializer::new(&mut raw))?;
+
+        match &self.prefix {
+            Some(prefix) => self.key = entry_key(prefix, &raw, &self.output.config),
+            None => {
+                self.key.clear();
+                encode_bytes(&raw, &self.output.config.key_encode_set(), &mut self.key);
+            }
+        }
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(ValueSerializer::new(&self.key, self.output))
+    }
+}
+
+impl<'o, W: io::Write> ser::SerializeMap for MapSerializer<'o, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.set_key(key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        MapSerializer::serialize_value(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'o, W: io::Write> ser::SerializeStruct for MapSerializer<'o, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.set_key(key)?;
+        self.serialize_value(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'o, W: io::Write> ser::SerializeStructVariant for MapSerializer<'o, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.set_key(key)?;
+        self.serialize_value(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Serialize an instance of type `T` into bytes of query string.
+///
+/// The output uses the same conventions the parser of the given `ParseMode` expects, so it
+/// can be deserialized back using `from_bytes` with the same mode.
+/// A [`SerializerConfig`] can be passed instead of the mode to customize the output.
+pub fn to_bytes<T, C>(value: &T, config: C) -> Result<Vec<u8>, Error>
+where
+    T: ?Sized + Serialize,
+    C: Into<SerializerConfig>,
+{
+    let mut buf = Vec::new();
+    to_writer(&mut buf, value, config)?;
+    Ok(buf)
+}
+
+/// Serialize an instance of type `T` as a query string into the writer.
+///
+/// Pairs are written as soon as they are serialized, without building the whole query string
+/// in memory first. Many small writes are issued, so wrapping unbuffered writers(like sockets)
+/// in a `BufWriter` is recommended.
+pub fn to_writer<W, T, C>(writer: W, value: &T, config: C) -> Result<(), Error>
+where
+    W: io::Write,
+    T: ?Sized + Serialize,
+    C: Into<SerializerConfig>,
+{
+    let mut output = Output::new(writer, config.into());
+    value.serialize(QSSerializer {
+        output: &mut output,
+    })?;
+    output.finish()
+}
+
+/// Serialize an instance of type `T` into a query string.
+///
+/// Accepts a `ParseMode` or a [`SerializerConfig`], just like [`to_bytes`]
+///
+/// # Example
+/// ```rust
+///# use _serde::Serialize;
+/// use serde_querystring::{to_string, ParseMode};
+///
+/// #[derive(Serialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     page: u32,
+///     tags: Vec<&'static str>,
+/// }
+///
+/// let query = Query { page: 2, tags: vec!["rust", "serde"] };
+///
+/// assert_eq!(
+///     to_string(&query, ParseMode::Duplicate).unwrap(),
+///     "page=2&tags=rust&tags=serde"
+/// );
+/// assert_eq!(
+///     to_string(&query, ParseMode::Delimiter(b'|')).unwrap(),
+///     "page=2&tags=rust|serde"
+/// );
+/// assert_eq!(
+///     to_string(&query, ParseMode::Brackets).unwrap(),
+///     "page=2&tags[0]=rust&tags[1]=serde"
+/// );
+/// ```
+pub fn to_string<T, C>(value: &T, config: C) -> Result<String, Error>
+where
+    T: ?Sized + Serialize,
+    C: Into<SerializerConfig>,
+{
+    let bytes = to_bytes(value, config)?;
+    // Every byte outside of the ascii range is percent encoded
+    Ok(String::from_utf8(bytes).expect("percent encoded output is always valid utf-8"))
+}
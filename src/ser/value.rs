@@ -0,0 +1,581 @@
+use std::io;
+
+use _serde::ser::{self, Impossible, Serialize};
+
+use crate::de::{Error, ErrorKind, ParseMode};
+use crate::encode::{encode_bytes, encode_bytes_reserving, EncodeSet};
+
+use crate::{raw, separated};
+
+use super::{MapSerializer, NoneStyle, Output, SerializerConfig};
+
+#[inline]
+fn unsupported_error(what: &str, mode: ParseMode) -> Error {
+    Error::new(ErrorKind::InvalidType).message(format!(
+        "{} are not supported as values in {:?} mode",
+        what, mode
+    ))
+}
+
+/// Appends a sub key to an encoded key, `key` becomes `key[sub_key]`
+pub(super) fn sub_key(key: &[u8], sub_key: &[u8], set: &EncodeSet) -> Vec<u8> {
+    let mut res = Vec::with_capacity(key.len() + sub_key.len() + 2);
+    res.extend_from_slice(key);
+    res.push(b'[');
+    // Brackets inside the sub key would end it early
+    encode_bytes(sub_key, &set.remove(b'[').remove(b']'), &mut res);
+    res.push(b']');
+    res
+}
+
+/// Appends the key of a map entry or an enum variant to an encoded key, it is `key.entry` in
+/// dot notation mode and `key[entry]` otherwise
+pub(super) fn entry_key(key: &[u8], entry: &[u8], config: &SerializerConfig) -> Vec<u8> {
+    match config.mode {
+        ParseMode::DotNotation => {
+            let mut res = Vec::with_capacity(key.len() + entry.len() + 1);
+            res.extend_from_slice(key);
+            res.push(b'.');
+            encode_bytes(entry, &config.key_encode_set(), &mut res);
+            res
+        }
+        _ => sub_key(key, entry, &config.encode_set),
+    }
+}
+
+macro_rules! serialize_scalar {
+    ($($method:ident => $ty:ty)*) => {
+        $(
+            #[inline]
+            fn $method(self, v: $ty) -> Result<(), Error> {
+                self.output.write_scalar(self.key, &v)
+            }
+        )*
+    };
+}
+
+/// Serializes a value which belongs to an already encoded key
+pub(crate) struct ValueSerializer<'k, 'o, W> {
+    key: &'k [u8],
+    output: &'o mut Output<W>,
+    // Set by the separated wrappers to join sequences by the delimiter in any mode
+    delimiter: Option<u8>,
+}
+
+impl<'k, 'o, W: io::Write> ValueSerializer<'k, 'o, W> {
+    pub(crate) fn new(key: &'k [u8], output: &'o mut Output<W>) -> Self {
+        Self {
+            key,
+            output,
+            delimiter: None,
+        }
+    }
+
+    fn into_seq(self) -> Result<SeqSerializer<'o, W>, Error> {
+        if let Some(delimiter) = self.delimiter {
+            return Ok(SeqSerializer::delimited(
+                self.key.to_vec(),
+                self.output,
+                delimiter,
+            ));
+        }
+
+        match self.output.config.mode {
+            ParseMode::UrlEncoded => Err(unsupported_error("sequences", self.output.config.mode)),
+            _ => Ok(SeqSerializer::new(self.key.to_vec(), self.output)),
+        }
+    }
+
+    /// Only the brackets modes support nesting values
+    fn check_nested(&self, what: &str) -> Result<(), Error> {
+        match self.output.config.mode {
+            ParseMode::Brackets
+            | ParseMode::DotNotation
+            | ParseMode::BracketsDelimiter(_)
+            | ParseMode::Rack
+            | ParseMode::Php => Ok(()),
+            _ => Err(unsupported_error(what, self.output.config.mode)),
+        }
+    }
+}
+
+impl<'k, 'o, W: io::Write> ser::Serializer for ValueSerializer<'k, 'o, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = SeqSerializer<'o, W>;
+    type SerializeTuple = SeqSerializer<'o, W>;
+    type SerializeTupleStruct = SeqSerializer<'o, W>;
+    type SerializeTupleVariant = SeqSerializer<'o, W>;
+    type SerializeMap = MapSerializer<'o, W>;
+    type SerializeStruct = MapSerializer<'o, W>;
+    type SerializeStructVariant = MapSerializer<'o, W>;
+
+    serialize_scalar!(
+        serialize_bool => bool
+        serialize_i8 => i8
+        serialize_i16 => i16
+        serialize_i32 => i32
+        serialize_i64 => i64
+        serialize_u8 => u8
+        serialize_u16 => u16
+        serialize_u32 => u32
+        serialize_u64 => u64
+        serialize_f32 => f32
+        serialize_f64 => f64
+        serialize_char => char
+        serialize_str => &str
+        serialize_bytes => &[u8]
+    );
+
+    fn serialize_none(self) -> Result<(), Error> {
+        match self.output.config.none_style {
+            NoneStyle::Skip => Ok(()),
+            NoneStyle::Empty => self.output.write_encoded_pair(self.key, b""),
+            NoneStyle::KeyOnly => self.output.write_encoded_key(self.key),
+        }
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.output.write_encoded_pair(self.key, b"")
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.output.write_scalar(self.key, variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        // Raw values are written as is
+        if name == raw::TOKEN {
+            let mut bytes = Vec::new();
+            value.serialize(ScalarSerializer::new(&mut bytes))?;
+            return self.output.write_encoded_pair(self.key, &bytes);
+        }
+        if let Some(delimiter) = separated::delimiter(name) {
+            return value.serialize(ValueSerializer {
+                delimiter: Some(delimiter),
+                ..self
+            });
+        }
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.check_nested("newtype enums")?;
+
+        let key = entry_key(self.key, variant.as_bytes(), &self.output.config);
+        value.serialize(ValueSerializer::new(&key, self.output))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        self.into_seq()
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Error> {
+        self.into_seq()
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        self.into_seq()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        self.check_nested("tuple enums")?;
+
+        let key = entry_key(self.key, variant.as_bytes(), &self.output.config);
+        Ok(SeqSerializer::new(key, self.output))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        self.check_nested("maps")?;
+        Ok(MapSerializer::nested(self.key.to_vec(), self.output))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, Error> {
+        self.check_nested("structs")?;
+        Ok(MapSerializer::nested(self.key.to_vec(), self.output))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        self.check_nested("struct enums")?;
+
+        let key = entry_key(self.key, variant.as_bytes(), &self.output.config);
+        Ok(MapSerializer::nested(key, self.output))
+    }
+}
+
+/// Serializes sequences based on the parse mode
+///
+/// - `Duplicate` repeats the key for each value: `key=1&key=2`
+/// - `Delimiter` and `BracketsDelimiter` join the values by the delimiter: `key=1|2`, the
+///   delimiter is always percent encoded inside the values
+/// - `Brackets` and `DotNotation` add the index of each value to the key: `key[0]=1&key[1]=2`
+///
+/// When `empty_brackets` is set, `Duplicate`, `Brackets` and `DotNotation` modes use `key[]=1&key[]=2`
+pub(crate) struct SeqSerializer<'o, W> {
+    key: Vec<u8>,
+    output: &'o mut Output<W>,
+    index: usize,
+    delimiter: Option<u8>,
+    // Encoded values joined by the delimiter in delimiter mode
+    joined: Vec<u8>,
+}
+
+impl<'o, W: io::Write> SeqSerializer<'o, W> {
+    fn new(key: Vec<u8>, output: &'o mut Output<W>) -> Self {
+        Self {
+            delimiter: output.config.mode.delimiter(),
+            key,
+            output,
+            index: 0,
+            joined: Vec::new(),
+        }
+    }
+
+    /// Joins the values by the delimiter, whatever the parse mode is
+    fn delimited(key: Vec<u8>, output: &'o mut Output<W>, delimiter: u8) -> Self {
+        Self {
+            delimiter: Some(delimiter),
+            ..Self::new(key, output)
+        }
+    }
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        match (self.delimiter, self.output.config.mode) {
+            (Some(delimiter), _) => {
+                if self.index > 0 {
+                    self.joined.push(delimiter);
+                }
+
+                // The delimiter should never appear inside the values, so they can be
+                // split correctly when parsing.
+                let mut raw = Vec::new();
+                if value.serialize(ScalarSerializer::new(&mut raw))? {
+                    self.joined.extend_from_slice(&raw);
+                } else {
+                    encode_bytes_reserving(
+                        &raw,
+                        &self.output.config.encode_set,
+                        delimiter,
+                        &mut self.joined,
+                    );
+                }
+            }
+            // Rack only builds arrays from empty brackets, `key[0]` is a hash
+            (_, ParseMode::Rack) => {
+                let key = sub_key(&self.key, b"", &self.output.config.encode_set);
+                value.serialize(ValueSerializer::new(&key, self.output))?;
+            }
+            (_, ParseMode::Brackets) | (_, ParseMode::DotNotation) | (_, ParseMode::Php) => {
+                let key = if self.output.config.empty_brackets {
+                    sub_key(&self.key, b"", &self.output.config.encode_set)
+                } else {
+                    sub_key(
+                        &self.key,
+                        self.index.to_string().as_bytes(),
+                        &self.output.config.encode_set,
+                    )
+                };
+                value.serialize(ValueSerializer::new(&key, self.output))?;
+            }
+            _ if self.output.config.empty_brackets => {
+                let key = sub_key(&self.key, b"", &self.output.config.encode_set);
+                self.output.write_scalar(&key, value)?
+            }
+            _ => self.output.write_scalar(&self.key, value)?,
+        }
+
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), Error> {
+        if self.delimiter.is_some() && self.index > 0 {
+            self.output.write_encoded_pair(&self.key, &self.joined)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'o, W: io::Write> ser::SerializeSeq for SeqSerializer<'o, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        SeqSerializer::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        SeqSerializer::end(self)
+    }
+}
+
+impl<'o, W: io::Write> ser::SerializeTuple for SeqSerializer<'o, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        SeqSerializer::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        SeqSerializer::end(self)
+    }
+}
+
+impl<'o, W: io::Write> ser::SerializeTupleStruct for SeqSerializer<'o, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        SeqSerializer::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        SeqSerializer::end(self)
+    }
+}
+
+impl<'o, W: io::Write> ser::SerializeTupleVariant for SeqSerializer<'o, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        SeqSerializer::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        SeqSerializer::end(self)
+    }
+}
+
+#[inline]
+fn not_scalar_error() -> Error {
+    Error::new(ErrorKind::InvalidType).message(String::from(
+        "only strings, numbers, booleans and unit enums are supported here",
+    ))
+}
+
+macro_rules! serialize_display {
+    ($($method:ident => $ty:ty)*) => {
+        $(
+            #[inline]
+            fn $method(self, v: $ty) -> Result<bool, Error> {
+                self.0.extend_from_slice(v.to_string().as_bytes());
+                Ok(false)
+            }
+        )*
+    };
+}
+
+/// Writes the raw(not encoded) bytes of a scalar value into a buffer
+///
+/// It returns true if the value is a `RawQsValue`, whose bytes are already encoded and
+/// should be written as is.
+pub(crate) struct ScalarSerializer<'b>(&'b mut Vec<u8>);
+
+impl<'b> ScalarSerializer<'b> {
+    pub(crate) fn new(buf: &'b mut Vec<u8>) -> Self {
+        Self(buf)
+    }
+}
+
+impl<'b> ser::Serializer for ScalarSerializer<'b> {
+    type Ok = bool;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<bool, Error>;
+    type SerializeTuple = Impossible<bool, Error>;
+    type SerializeTupleStruct = Impossible<bool, Error>;
+    type SerializeTupleVariant = Impossible<bool, Error>;
+    type SerializeMap = Impossible<bool, Error>;
+    type SerializeStruct = Impossible<bool, Error>;
+    type SerializeStructVariant = Impossible<bool, Error>;
+
+    serialize_display!(
+        serialize_i8 => i8
+        serialize_i16 => i16
+        serialize_i32 => i32
+        serialize_i64 => i64
+        serialize_u8 => u8
+        serialize_u16 => u16
+        serialize_u32 => u32
+        serialize_u64 => u64
+        serialize_f32 => f32
+        serialize_f64 => f64
+    );
+
+    fn serialize_bool(self, v: bool) -> Result<bool, Error> {
+        self.0
+            .extend_from_slice(if v { b"true" as &[u8] } else { b"false" });
+        Ok(false)
+    }
+
+    fn serialize_char(self, v: char) -> Result<bool, Error> {
+        self.0
+            .extend_from_slice(v.encode_utf8(&mut [0; 4]).as_bytes());
+        Ok(false)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<bool, Error> {
+        self.0.extend_from_slice(v.as_bytes());
+        Ok(false)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<bool, Error> {
+        self.0.extend_from_slice(v);
+        Ok(false)
+    }
+
+    fn serialize_none(self) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<bool, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<bool, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<bool, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let raw = value.serialize(self)?;
+        Ok(raw || name == raw::TOKEN)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<bool, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_scalar_error())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(not_scalar_error())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(not_scalar_error())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(not_scalar_error())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(not_scalar_error())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(not_scalar_error())
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, Error> {
+        Err(not_scalar_error())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(not_scalar_error())
+    }
+}
@@ -0,0 +1,155 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::Deref;
+
+use _bytes::Bytes;
+use _serde::{de, ser};
+
+use crate::de::{from_bytes, Error, QsConfig};
+
+thread_local! {
+    /// The input of the `from_bytes_shared` call running on this thread
+    static INPUT: RefCell<Option<Bytes>> = const { RefCell::new(None) };
+}
+
+/// Restores the input of the outer call once an inner one returns
+struct InputGuard(Option<Bytes>);
+
+impl Drop for InputGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        INPUT.with(|input| *input.borrow_mut() = previous);
+    }
+}
+
+/// Deserialize an instance of type `T` from a query string held in a `Bytes`
+///
+/// The [`SharedBytes`] values of `T` with nothing to decode are slices of the input, sharing its
+/// buffer instead of copying it. `T` can borrow from the input like with `from_bytes`.
+///
+/// # Example
+#[cfg_attr(feature = "bytes", doc = "```rust")]
+#[cfg_attr(not(feature = "bytes"), doc = "```rust,ignore")]
+///# use _serde::Deserialize;
+///# use _bytes as bytes;
+/// use bytes::Bytes;
+/// use serde_querystring::{from_bytes_shared, ParseMode, SharedBytes};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     token: SharedBytes,
+/// }
+///
+/// let input = Bytes::from_static(b"token=abc123");
+/// let query: Query = from_bytes_shared(&input, ParseMode::UrlEncoded).unwrap();
+///
+/// assert_eq!(query.token.as_ptr(), input[6..].as_ptr());
+/// ```
+pub fn from_bytes_shared<'de, T, C>(input: &'de Bytes, config: C) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+    C: Into<QsConfig>,
+{
+    let previous = INPUT.with(|current| current.borrow_mut().replace(input.clone()));
+    let _guard = InputGuard(previous);
+    from_bytes(input, config)
+}
+
+/// Bytes which share the buffer of the input of [`from_bytes_shared`] when they need no
+/// decoding, and are copied otherwise
+///
+/// It is deserialized from the percent decoded bytes as is, and serialized as bytes, the same
+/// as `serde_bytes::ByteBuf`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SharedBytes(pub Bytes);
+
+impl SharedBytes {
+    /// Returns the wrapped value
+    pub fn into_inner(self) -> Bytes {
+        self.0
+    }
+}
+
+impl From<Bytes> for SharedBytes {
+    fn from(value: Bytes) -> Self {
+        SharedBytes(value)
+    }
+}
+
+impl Deref for SharedBytes {
+    type Target = Bytes;
+
+    fn deref(&self) -> &Bytes {
+        &self.0
+    }
+}
+
+impl ser::Serialize for SharedBytes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+struct SharedBytesVisitor;
+
+impl SharedBytesVisitor {
+    /// Returns a slice of the input if the bytes are part of it, or a copy
+    fn shared(v: &[u8]) -> SharedBytes {
+        INPUT.with(|input| {
+            let input = input.borrow();
+            let input = input.as_ref().filter(|input| {
+                let range = input.as_ptr_range();
+                range.start <= v.as_ptr() && v.as_ptr_range().end <= range.end
+            });
+            match input {
+                Some(input) if !v.is_empty() => SharedBytes(input.slice_ref(v)),
+                _ => SharedBytes(Bytes::copy_from_slice(v)),
+            }
+        })
+    }
+}
+
+impl<'de> de::Visitor<'de> for SharedBytesVisitor {
+    type Value = SharedBytes;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("bytes")
+    }
+
+    fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+        Ok(Self::shared(v))
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(Self::shared(v.as_bytes()))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(SharedBytes(Bytes::copy_from_slice(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(SharedBytes(Bytes::from(v)))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        self.visit_byte_buf(v.into_bytes())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for SharedBytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(SharedBytesVisitor)
+    }
+}
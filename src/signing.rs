@@ -0,0 +1,135 @@
+//! Signing query strings with HMAC-SHA256 for pre-signed URLs, enabled by the `signing` feature
+//!
+//! `sign` appends an `expires` parameter, the unix time the query expires at, and a
+//! `signature` parameter, the hex HMAC of the query before it. The HMAC is computed over the
+//! canonical form of the query with the order of its pairs and its bare keys kept, so
+//! re-encoding the query(`%7e` for `~`) keeps the signature valid, while any change the
+//! parsers would see breaks it. `+` is signed as a space, as decoded by the default config.
+//!
+//! # Example
+//! ```rust
+//! use std::time::{Duration, SystemTime};
+//!
+//! use serde_querystring::signing::{sign, verify};
+//!
+//! let key = b"secret";
+//! let expires = SystemTime::now() + Duration::from_secs(3600);
+//! let query = sign("file=report.pdf&user=42", key, expires);
+//!
+//! assert_eq!(verify(&query, key).unwrap(), "file=report.pdf&user=42");
+//! assert!(verify(&query.replace("user=42", "user=43"), key).is_err());
+//! ```
+
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use _hmac::{Hmac, Mac};
+use _sha2::Sha256;
+
+use crate::canonical::{canonicalize, CanonicalizeOptions};
+use crate::de::{Error, ErrorKind};
+use crate::decode::parse_char;
+
+/// The key of the parameter holding the unix time the signature expires at
+pub const EXPIRES_KEY: &str = "expires";
+
+/// The key of the parameter holding the signature
+pub const SIGNATURE_KEY: &str = "signature";
+
+/// Returns the HMAC of the canonical form of the signed part of a query
+fn mac(key: &[u8], signed: &str) -> Hmac<Sha256> {
+    let options = CanonicalizeOptions::new()
+        .sort(false)
+        .remove_empty(false)
+        .bare_keys(true);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(canonicalize(signed, options).as_bytes());
+    mac
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Sign a query string, appending the `expires` and `signature` parameters to it
+///
+/// The query string is kept as is, so it can be serialized beforehand with `to_string`.
+pub fn sign(params: &str, key: &[u8], expires: SystemTime) -> String {
+    let mut query = String::from(params);
+    if !query.is_empty() {
+        query.push('&');
+    }
+    write!(query, "{}={}", EXPIRES_KEY, unix_time(expires)).unwrap();
+
+    let signature = mac(key, &query).finalize().into_bytes();
+    write!(query, "&{}=", SIGNATURE_KEY).unwrap();
+    for b in signature {
+        write!(query, "{:02x}", b).unwrap();
+    }
+    query
+}
+
+/// Verify the signature of a query string signed by [`sign`], and return its parameters
+/// without the `expires` and `signature` ones, ready to be deserialized
+///
+/// The signatures are compared in constant time. It fails with `ErrorKind::InvalidSignature`
+/// if the signature is missing or doesn't match, and with `ErrorKind::SignatureExpired` once
+/// the query has expired.
+pub fn verify<'a>(input: &'a str, key: &[u8]) -> Result<&'a str, Error> {
+    verify_at(input, key, SystemTime::now())
+}
+
+/// Verify the signature of a query string like [`verify`], at the given time
+pub fn verify_at<'a>(input: &'a str, key: &[u8], now: SystemTime) -> Result<&'a str, Error> {
+    let (signed, signature) = split_last(input, SIGNATURE_KEY)
+        .ok_or_else(|| invalid_signature(SIGNATURE_KEY, "the signature is missing"))?;
+    let (params, expires) = split_last(signed, EXPIRES_KEY)
+        .ok_or_else(|| invalid_signature(EXPIRES_KEY, "the expiry is missing"))?;
+
+    let signature = decode_hex(signature)
+        .ok_or_else(|| invalid_signature(SIGNATURE_KEY, "the signature is not hex encoded"))?;
+    mac(key, signed)
+        .verify_slice(&signature)
+        .map_err(|_| invalid_signature(SIGNATURE_KEY, "the signature doesn't match"))?;
+
+    let expires = expires
+        .parse::<u64>()
+        .map_err(|_| invalid_signature(EXPIRES_KEY, "the expiry is not a unix time"))?;
+    if unix_time(now) >= expires {
+        let mut error = Error::new(ErrorKind::SignatureExpired)
+            .message(format!("the signature expired at {}", expires));
+        error.key = String::from(EXPIRES_KEY);
+        return Err(error);
+    }
+
+    Ok(params)
+}
+
+fn invalid_signature(key: &str, message: &str) -> Error {
+    let mut error = Error::new(ErrorKind::InvalidSignature).message(String::from(message));
+    error.key = String::from(key);
+    error
+}
+
+/// Splits the last pair of a query if its key is `key`, returning the query before it and
+/// the raw value of the pair
+fn split_last<'a>(query: &'a str, key: &str) -> Option<(&'a str, &'a str)> {
+    let (rest, pair) = match query.rfind('&') {
+        Some(index) => (&query[..index], &query[index + 1..]),
+        None => ("", query),
+    };
+    let value = pair.strip_prefix(key)?.strip_prefix('=')?;
+    Some((rest, value))
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| parse_char(pair[0], pair[1]))
+        .collect()
+}
@@ -0,0 +1,255 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::str::FromStr;
+
+use _serde::{de, ser};
+
+use crate::de::{Error, ErrorKind};
+use crate::separated::CommaSeparated;
+
+/// The direction of a [`SortField`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// A field of a sort expression, descending when it starts with `-` and ascending otherwise
+///
+/// A leading `+` is removed too, including the space it decodes to(`sort=+name`).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SortField {
+    pub field: String,
+    pub direction: SortDirection,
+}
+
+impl SortField {
+    /// Returns true if the direction is descending
+    pub fn is_descending(&self) -> bool {
+        self.direction == SortDirection::Descending
+    }
+}
+
+impl FromStr for SortField {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let (field, direction) = match s.as_bytes().first() {
+            Some(b'-') => (&s[1..], SortDirection::Descending),
+            Some(b'+' | b' ') => (&s[1..], SortDirection::Ascending),
+            _ => (s, SortDirection::Ascending),
+        };
+
+        if field.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidType)
+                .message(String::from("the sort field is empty"))
+                .value(s.as_bytes()));
+        }
+
+        Ok(Self {
+            field: field.to_string(),
+            direction,
+        })
+    }
+}
+
+impl fmt::Display for SortField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_descending() {
+            f.write_str("-")?;
+        }
+        f.write_str(&self.field)
+    }
+}
+
+impl ser::Serialize for SortField {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for SortField {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|e: Error| de::Error::custom(e.message))
+    }
+}
+
+/// The limits of a [`SortSpec`], checked when it is parsed
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{SortRules, SortSpec};
+///
+/// struct ArticleSort;
+///
+/// impl SortRules for ArticleSort {
+///     const MAX_FIELDS: usize = 2;
+///
+///     fn is_allowed(field: &str) -> bool {
+///         matches!(field, "created_at" | "title")
+///     }
+/// }
+///
+/// assert!(SortSpec::<ArticleSort>::parse("-created_at,title").is_ok());
+/// assert!(SortSpec::<ArticleSort>::parse("author").is_err());
+/// assert!(SortSpec::<ArticleSort>::parse("title,title,title").is_err());
+/// ```
+pub trait SortRules {
+    /// The maximum number of fields, there is no limit by default
+    const MAX_FIELDS: usize = usize::MAX;
+
+    /// Returns true if the field can be sorted by, every field can by default
+    fn is_allowed(field: &str) -> bool {
+        let _ = field;
+        true
+    }
+}
+
+/// The rules of a [`SortSpec`] without any limit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Unrestricted;
+
+impl SortRules for Unrestricted {}
+
+/// A comma separated sort expression(`sort=-created_at,+name`), checked by the [`SortRules`]
+///
+/// It is split by commas in any parse mode, the same as [`CommaSeparated`], and the empty fields
+/// are skipped. It dereferences to the fields, in order.
+///
+/// # Example
+/// ```rust
+///# use _serde::Deserialize;
+/// use serde_querystring::{from_str, ParseMode, SortDirection, SortSpec};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     sort: SortSpec,
+/// }
+///
+/// let query: Query = from_str("sort=-created_at,+name", ParseMode::UrlEncoded).unwrap();
+///
+/// assert_eq!(query.sort[0].field, "created_at");
+/// assert_eq!(query.sort[0].direction, SortDirection::Descending);
+/// assert_eq!(query.sort[1].direction, SortDirection::Ascending);
+/// ```
+pub struct SortSpec<R = Unrestricted> {
+    fields: Vec<SortField>,
+    rules: PhantomData<R>,
+}
+
+impl<R: SortRules> SortSpec<R> {
+    /// Parse a sort expression
+    ///
+    /// It fails with `ErrorKind::LimitExceeded` if there are more fields than the rules allow,
+    /// and `ErrorKind::UnknownKey` if a field is not allowed.
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let fields = s
+            .split(',')
+            .filter(|field| !field.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        Self::new(fields)
+    }
+
+    /// Checks the fields with the rules
+    pub fn new(fields: Vec<SortField>) -> Result<Self, Error> {
+        if fields.len() > R::MAX_FIELDS {
+            return Err(Error::new(ErrorKind::LimitExceeded)
+                .message(format!("more than {} sort fields", R::MAX_FIELDS)));
+        }
+
+        if let Some(field) = fields.iter().find(|field| !R::is_allowed(&field.field)) {
+            return Err(Error::new(ErrorKind::UnknownKey)
+                .message(format!("`{}` is not a sortable field", field.field))
+                .value(field.field.as_bytes()));
+        }
+
+        Ok(Self {
+            fields,
+            rules: PhantomData,
+        })
+    }
+}
+
+impl<R> SortSpec<R> {
+    /// Returns the fields
+    pub fn into_fields(self) -> Vec<SortField> {
+        self.fields
+    }
+}
+
+impl<R> Deref for SortSpec<R> {
+    type Target = [SortField];
+
+    fn deref(&self) -> &[SortField] {
+        &self.fields
+    }
+}
+
+impl<R> Clone for SortSpec<R> {
+    fn clone(&self) -> Self {
+        Self {
+            fields: self.fields.clone(),
+            rules: PhantomData,
+        }
+    }
+}
+
+impl<R> fmt::Debug for SortSpec<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.fields).finish()
+    }
+}
+
+impl<R> PartialEq for SortSpec<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields == other.fields
+    }
+}
+
+impl<R> Default for SortSpec<R> {
+    fn default() -> Self {
+        Self {
+            fields: Vec::new(),
+            rules: PhantomData,
+        }
+    }
+}
+
+impl<R> ser::Serialize for SortSpec<R> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        CommaSeparated(&self.fields).serialize(serializer)
+    }
+}
+
+impl<'de, R: SortRules> de::Deserialize<'de> for SortSpec<R> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let fields = CommaSeparated::<Vec<String>>::deserialize(deserializer)?
+            .iter()
+            .filter(|field| !field.is_empty())
+            .map(|field| field.parse())
+            .collect::<Result<_, _>>()
+            .and_then(Self::new);
+
+        fields.map_err(|e: Error| de::Error::custom(e.message))
+    }
+}
@@ -0,0 +1,184 @@
+//! Helpers to test that values survive a serialize and deserialize cycle
+//!
+//! Enabled by the `test-util` feature.
+
+use std::fmt;
+
+use _serde::{de::DeserializeOwned, Serialize};
+
+use crate::de::{from_bytes, Error};
+use crate::decode::parse_bytes;
+use crate::ser::{to_bytes, SerializerConfig};
+
+/// The values of a key which are different between the original value and the deserialized one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// The decoded key
+    pub key: String,
+    /// The decoded values of the key, as serialized from the original value
+    pub expected: Vec<String>,
+    /// The decoded values of the key, as serialized from the deserialized value
+    pub actual: Vec<String>,
+}
+
+/// The reason a value didn't survive the round trip
+#[derive(Debug, PartialEq)]
+pub enum RoundtripError {
+    /// The original value couldn't be serialized
+    Serialize(Error),
+    /// The serialized query couldn't be deserialized back
+    Deserialize { query: String, error: Box<Error> },
+    /// The deserialized value is not equal to the original one
+    Mismatch {
+        query: String,
+        expected: String,
+        actual: String,
+        /// The keys which are serialized differently, it is empty when both values serialize
+        /// to the same query
+        differences: Vec<Difference>,
+    },
+}
+
+impl fmt::Display for RoundtripError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoundtripError::Serialize(error) => write!(f, "failed to serialize: {}", error),
+            RoundtripError::Deserialize { query, error } => {
+                write!(f, "failed to deserialize `{}`: {}", query, error)
+            }
+            RoundtripError::Mismatch {
+                query,
+                expected,
+                actual,
+                differences,
+            } => {
+                writeln!(f, "value changed after the round trip of `{}`", query)?;
+                writeln!(f, "expected: {}", expected)?;
+                write!(f, "actual: {}", actual)?;
+                for difference in differences {
+                    write!(
+                        f,
+                        "\n  {}: {:?} != {:?}",
+                        difference.key, difference.expected, difference.actual
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for RoundtripError {}
+
+/// Serializes the value and deserializes it back, returning an error if the result is not equal
+/// to the original value.
+///
+/// The value is deserialized using the mode of the config.
+///
+/// # Example
+/// ```rust
+///# use _serde::{Deserialize, Serialize};
+/// use serde_querystring::test_util::roundtrip;
+/// use serde_querystring::ParseMode;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     tags: Vec<String>,
+/// }
+///
+/// let query = Query { tags: vec![String::from("a"), String::from("b")] };
+/// assert!(roundtrip(&query, ParseMode::Brackets).is_ok());
+/// ```
+pub fn roundtrip<T, C>(value: &T, config: C) -> Result<T, RoundtripError>
+where
+    T: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+    C: Into<SerializerConfig>,
+{
+    let config = config.into();
+
+    let query = to_bytes(value, config).map_err(RoundtripError::Serialize)?;
+    let result: T =
+        from_bytes(&query, config.mode).map_err(|error| RoundtripError::Deserialize {
+            query: String::from_utf8_lossy(&query).into_owned(),
+            error: Box::new(error),
+        })?;
+
+    if &result == value {
+        return Ok(result);
+    }
+
+    // Serializing the result again shows which keys have changed
+    let differences = match to_bytes(&result, config) {
+        Ok(actual) => diff_pairs(&query, &actual),
+        Err(_) => Vec::new(),
+    };
+
+    Err(RoundtripError::Mismatch {
+        query: String::from_utf8_lossy(&query).into_owned(),
+        expected: format!("{:?}", value),
+        actual: format!("{:?}", result),
+        differences,
+    })
+}
+
+/// Like [`roundtrip`], but panics with the description of the error
+#[track_caller]
+pub fn assert_roundtrip<T, C>(value: &T, config: C)
+where
+    T: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+    C: Into<SerializerConfig>,
+{
+    if let Err(e) = roundtrip(value, config) {
+        panic!("{}", e)
+    }
+}
+
+/// Groups the decoded values of each query by their decoded keys, in the order they first appear
+fn group_pairs(query: &[u8]) -> Vec<(String, Vec<String>)> {
+    let mut scratch = Vec::new();
+    let mut decode =
+        |slice: &[u8]| String::from_utf8_lossy(&parse_bytes(slice, &mut scratch)).into_owned();
+
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for pair in query.split(|b| *b == b'&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = match pair.iter().position(|b| *b == b'=') {
+            Some(index) => (decode(&pair[..index]), decode(&pair[index + 1..])),
+            None => (decode(pair), String::new()),
+        };
+
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, values)) => values.push(value),
+            None => groups.push((key, vec![value])),
+        }
+    }
+    groups
+}
+
+fn diff_pairs(expected: &[u8], actual: &[u8]) -> Vec<Difference> {
+    let expected = group_pairs(expected);
+    let mut actual = group_pairs(actual);
+
+    let mut differences = Vec::new();
+    for (key, expected) in expected {
+        let actual = match actual.iter().position(|(k, _)| *k == key) {
+            Some(index) => actual.remove(index).1,
+            None => Vec::new(),
+        };
+        if expected != actual {
+            differences.push(Difference {
+                key,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    // Keys which only appear in the actual query
+    differences.extend(actual.into_iter().map(|(key, actual)| Difference {
+        key,
+        expected: Vec::new(),
+        actual,
+    }));
+    differences
+}
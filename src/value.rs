@@ -0,0 +1,845 @@
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Index;
+
+use _serde::de::{
+    self,
+    value::{MapDeserializer, SeqDeserializer},
+};
+use _serde::forward_to_deserialize_any;
+
+#[cfg(not(all(
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+)))]
+use crate::de::disabled_mode_error;
+#[cfg(feature = "delimiter")]
+use crate::de::strip_matrix;
+use crate::de::{
+    __implementors::{DecodedSlice, IntoDeserializer, Scratch},
+    check_limits, Error, ErrorKind, InvalidUtf8, ParseMode, QsConfig,
+};
+use crate::decode::parse_bytes_with;
+#[cfg(feature = "brackets")]
+use crate::parsers::BracketsQS;
+#[cfg(feature = "delimiter")]
+use crate::parsers::DelimiterQS;
+#[cfg(feature = "duplicate")]
+use crate::parsers::DuplicateQS;
+use crate::parsers::OrderedMap;
+#[cfg(feature = "urlencoded")]
+use crate::parsers::UrlEncodedQS;
+use crate::separated;
+use crate::{php, rack};
+
+/// An untyped representation of a parsed query string
+///
+/// It can be used to inspect query strings whose shape isn't known at compile time, and
+/// it can be deserialized into a typed value later using [`Value::deserialize_into`].
+///
+/// - Keys with a single value are parsed as `String`
+/// - Keys with multiple values(repeated keys, delimited values or indexed brackets) are parsed
+///   as `Sequence`
+/// - Keys with sub keys in the brackets modes are parsed as `Map`, including sparse indexes
+///   (`key[3]`), which can be deserialized into both integer keyed maps and sequences
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{from_bytes_value, ParseMode, Value};
+///
+/// let value = from_bytes_value(b"user[name]=John&tags[]=a&tags[]=b", ParseMode::Brackets).unwrap();
+///
+/// assert_eq!(
+///     value.get("user").and_then(|user| user.get("name")),
+///     Some(&Value::String(String::from("John")))
+/// );
+/// assert_eq!(value.get("tags").and_then(Value::as_sequence).map(Vec::len), Some(2));
+/// ```
+///
+/// The pairs without a matching field can be kept with `#[serde(flatten)]` into a map of
+/// values, in every mode. A map of strings works too, as long as the other keys have a single
+/// value.
+///
+/// ```rust
+///# use _serde::Deserialize;
+///# use std::collections::BTreeMap;
+/// use serde_querystring::{from_str, ParseMode, Value};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     page: u32,
+///     #[serde(flatten)]
+///     rest: BTreeMap<String, Value>,
+/// }
+///
+/// let query: Query = from_str("page=2&user[name]=John&tags=a&tags=b", ParseMode::Brackets).unwrap();
+///
+/// assert_eq!(query.rest["user"].get("name").and_then(Value::as_str), Some("John"));
+/// assert_eq!(query.rest["tags"].as_sequence().map(Vec::len), Some(2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    String(String),
+    Sequence(Vec<Value>),
+    Map(Map),
+}
+
+/// The entries of a [`Value::Map`], in the order their keys first appear in the query string
+///
+/// Maps are equal when they have the same entries, whatever their order.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{from_str_value, ParseMode};
+///
+/// let value = from_str_value("z=1&a=2&z=3", ParseMode::Rack).unwrap();
+/// let map = value.as_map().unwrap();
+///
+/// assert_eq!(map.keys().collect::<Vec<_>>(), ["z", "a"]);
+/// assert_eq!(map["z"].as_str(), Some("3"));
+/// ```
+#[derive(Clone)]
+pub struct Map(OrderedMap<String, Value>);
+
+impl Map {
+    /// Creates an empty map
+    pub fn new() -> Self {
+        Self(OrderedMap::new())
+    }
+
+    /// Returns the number of entries
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the map has no entries
+    pub fn is_empty(&self) -> bool {
+        self.0.len() == 0
+    }
+
+    /// Returns the value of a key
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    /// Returns the value of a key, which can be modified
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.0.get_mut(key)
+    }
+
+    /// Returns true if the map has the key
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.get(key).is_some()
+    }
+
+    /// Inserts a key after the others, or replaces its value in place and returns the old one
+    pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        self.0.insert(key, value)
+    }
+
+    /// Removes a key and returns its value, the other keys keep their order
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.0.remove(key)
+    }
+
+    /// Iterates over the keys in their order
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.0.keys()
+    }
+
+    /// Iterates over the values in the order of their keys
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.0.iter().map(|(_, value)| value)
+    }
+
+    /// Iterates over the entries in their order
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.0.iter()
+    }
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl Eq for Map {}
+
+impl Index<&str> for Map {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).expect("key not found in the map")
+    }
+}
+
+impl FromIterator<(String, Value)> for Map {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        let mut map = Map::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (String, Value);
+    type IntoIter = alloc::vec::IntoIter<(String, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Value {
+    /// Returns the string if the value is a `String`
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the values if the value is a `Sequence`
+    pub fn as_sequence(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Sequence(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if the value is a `Map`
+    pub fn as_map(&self) -> Option<&Map> {
+        match self {
+            Value::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a key if the value is a `Map` containing it
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_map()?.get(key)
+    }
+
+    /// Deserialize the value into an instance of type `T`
+    ///
+    /// Strings are parsed the same way the values of a query string are, and sequences used
+    /// as a single value give their last value.
+    pub fn deserialize_into<T>(self) -> Result<T, Error>
+    where
+        T: de::DeserializeOwned,
+    {
+        T::deserialize(self)
+    }
+}
+
+/// Parse bytes of query string into an untyped [`Value`], which is always a `Map`
+///
+/// The config can be a `ParseMode`, or a `QsConfig` for more options.
+pub fn from_bytes_value<C: Into<QsConfig>>(input: &[u8], config: C) -> Result<Value, Error> {
+    let config = config.into();
+    check_limits(input, &config)?;
+
+    let map = match config.mode {
+        #[cfg(feature = "urlencoded")]
+        ParseMode::UrlEncoded => {
+            let qs = UrlEncodedQS::parse_with_config(input, &config);
+            collect_map(qs.keys(), &config, |key| {
+                Ok(Value::String(decode_string(
+                    qs.value(key).flatten().unwrap_or_default(),
+                    &config,
+                )?))
+            })?
+        }
+        #[cfg(feature = "duplicate")]
+        ParseMode::Duplicate => {
+            let qs = DuplicateQS::parse_with_config(input, &config);
+            duplicate_map(&qs, &config)?
+        }
+        #[cfg(feature = "duplicate")]
+        ParseMode::Custom(parser) => {
+            let qs = DuplicateQS::parse_custom(input, parser, &config);
+            duplicate_map(&qs, &config)?
+        }
+        #[cfg(feature = "delimiter")]
+        ParseMode::Delimiter(delimiter) => {
+            let qs = DelimiterQS::parse_with_config(input, delimiter, &config);
+            delimited_map(&qs, &config)?
+        }
+        #[cfg(feature = "delimiter")]
+        ParseMode::Matrix => {
+            let qs = DelimiterQS::parse_with_config(strip_matrix(input), b',', &config);
+            delimited_map(&qs, &config)?
+        }
+        #[cfg(feature = "brackets")]
+        ParseMode::Brackets | ParseMode::DotNotation | ParseMode::BracketsDelimiter(_) => {
+            let qs = BracketsQS::parse_with_config(input, &config);
+            qs.check_depth()?;
+            qs.check_conflicts()?;
+            brackets_map(&qs, &config)?
+        }
+        ParseMode::Rack => rack::parse(input, &config)?,
+        ParseMode::Php => php::parse(input, &config)?,
+        #[cfg(not(all(
+            feature = "urlencoded",
+            feature = "duplicate",
+            feature = "delimiter",
+            feature = "brackets"
+        )))]
+        mode => return Err(disabled_mode_error(mode)),
+    };
+
+    Ok(Value::Map(map))
+}
+
+/// Parse a query string into an untyped [`Value`], which is always a `Map`
+pub fn from_str_value<C: Into<QsConfig>>(input: &str, config: C) -> Result<Value, Error> {
+    from_bytes_value(input.as_bytes(), config)
+}
+
+#[cfg(any(
+    feature = "urlencoded",
+    feature = "duplicate",
+    feature = "delimiter",
+    feature = "brackets"
+))]
+fn collect_map<F>(keys: Vec<&Cow<[u8]>>, config: &QsConfig, mut value: F) -> Result<Map, Error>
+where
+    F: FnMut(&[u8]) -> Result<Value, Error>,
+{
+    keys.into_iter()
+        .map(|key| Ok((decode_string(Cow::Borrowed(key), config)?, value(key)?)))
+        .collect()
+}
+
+/// Converts decoded bytes into a `String`, invalid utf-8 is only replaced with
+/// `InvalidUtf8::Replace` as a `Value` can't hold bytes
+fn decode_string(slice: Cow<[u8]>, config: &QsConfig) -> Result<String, Error> {
+    if config.invalid_utf8 == InvalidUtf8::Replace {
+        return Ok(String::from_utf8_lossy(&slice).into_owned());
+    }
+
+    String::from_utf8(slice.into_owned()).map_err(|e| {
+        Error::new(ErrorKind::InvalidEncoding)
+            .message("invalid utf-8 sequence found in the percent decoded value".to_string())
+            .value(e.as_bytes())
+            .index(e.utf8_error().valid_up_to())
+    })
+}
+
+/// Percent decodes a key or a value into a `String`
+pub(crate) fn decode(
+    slice: &[u8],
+    scratch: &mut Vec<u8>,
+    config: &QsConfig,
+) -> Result<String, Error> {
+    decode_string(
+        Cow::Borrowed(&*parse_bytes_with(slice, scratch, config.decode_options)),
+        config,
+    )
+}
+
+/// A single value is a `String`, while more values make a `Sequence`
+#[cfg(any(feature = "duplicate", feature = "delimiter", feature = "brackets"))]
+fn from_values(values: Vec<Option<Cow<[u8]>>>, config: &QsConfig) -> Result<Value, Error> {
+    let mut values = values
+        .into_iter()
+        .map(|v| decode_string(v.unwrap_or_default(), config).map(Value::String))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    if values.len() == 1 {
+        Ok(values.remove(0))
+    } else {
+        Ok(Value::Sequence(values))
+    }
+}
+
+/// Returns true if the indexes of the non empty sub keys are 0 to n, in any order
+#[cfg(feature = "brackets")]
+fn is_dense(sub_keys: &[Vec<u8>], indexes: &[usize]) -> bool {
+    let mut indexes: Vec<usize> = sub_keys
+        .iter()
+        .zip(indexes)
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(_, index)| *index)
+        .collect();
+    indexes.sort_unstable();
+    indexes.iter().enumerate().all(|(i, index)| i == *index)
+}
+
+#[cfg(feature = "duplicate")]
+fn duplicate_map(qs: &DuplicateQS, config: &QsConfig) -> Result<Map, Error> {
+    collect_map(qs.keys(), config, |key| {
+        from_values(qs.values(key).unwrap_or_default(), config)
+    })
+}
+
+#[cfg(feature = "delimiter")]
+fn delimited_map(qs: &DelimiterQS, config: &QsConfig) -> Result<Map, Error> {
+    collect_map(qs.keys(), config, |key| {
+        // Keys without a value have a single empty value, as in the other modes
+        let values = qs
+            .values(key)
+            .flatten()
+            .unwrap_or_else(|| vec![Cow::Borrowed(&b""[..])]);
+        from_values(values.into_iter().map(Some).collect(), config)
+    })
+}
+
+#[cfg(feature = "brackets")]
+fn brackets_map(qs: &BracketsQS, config: &QsConfig) -> Result<Map, Error> {
+    collect_map(qs.keys(), config, |key| brackets_value(qs, key, config))
+}
+
+/// Keys without sub keys have plain values, and the ones with only empty or numeric
+/// sub keys(`key[]` or `key[0]`) are sequences, as long as the indexes start at 0 without
+/// gaps. Sparse indexes(`key[3]`) are kept as the keys of a map instead.
+#[cfg(feature = "brackets")]
+fn brackets_value(qs: &BracketsQS, key: &[u8], config: &QsConfig) -> Result<Value, Error> {
+    let sub_values = match qs.sub_values(key) {
+        Some(sub_values) if !sub_values.keys().is_empty() && !qs.scalar_wins(key) => sub_values,
+        _ => return from_values(qs.values(key).unwrap_or_default(), config),
+    };
+
+    let sub_keys: Vec<Vec<u8>> = sub_values.keys().into_iter().map(|k| k.to_vec()).collect();
+    let indexes = sub_keys
+        .iter()
+        .map(|k| {
+            if k.is_empty() {
+                Some(0)
+            } else {
+                core::str::from_utf8(k).ok()?.parse::<usize>().ok()
+            }
+        })
+        .collect::<Option<Vec<usize>>>();
+
+    let indexes = match indexes {
+        Some(indexes) if is_dense(&sub_keys, &indexes) => indexes,
+        _ => return brackets_map(&sub_values, config).map(Value::Map),
+    };
+
+    // The empty brackets are placed among the indexes in the order they appear
+    let empty_indexes = qs.empty_indexes(key)?;
+    let empty_index = |i: usize, index| empty_indexes.get(i).copied().unwrap_or(index);
+
+    let mut items = Vec::new();
+    for (index, sub_key) in indexes.into_iter().zip(sub_keys.iter()) {
+        let value = brackets_value(&sub_values, sub_key, config)?;
+        match value {
+            // Each `key[]` is a separate item
+            Value::Sequence(values) if sub_key.is_empty() => items.extend(
+                values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, v)| (empty_index(i, index), v)),
+            ),
+            value if sub_key.is_empty() => items.push((empty_index(0, index), value)),
+            value => items.push((index, value)),
+        }
+    }
+    items.sort_by_key(|item| item.0);
+
+    Ok(Value::Sequence(
+        items.into_iter().map(|item| item.1).collect(),
+    ))
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[inline]
+fn invalid_type_error(found: &str, expected: &str) -> Error {
+    Error::new(ErrorKind::InvalidType).message(format!("expected {}, found {}", expected, found))
+}
+
+macro_rules! forward_to_string {
+    ($($method:ident => $expected:expr)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: de::Visitor<'de>,
+            {
+                match self {
+                    Value::String(s) => {
+                        let mut scratch = Scratch::default();
+                        DecodedSlice::<'de>(Cow::Owned(s.into_bytes()))
+                            .into_deserializer(&mut scratch)
+                            .$method(visitor)
+                    }
+                    Value::Sequence(mut values) => match values.pop() {
+                        Some(value) => value.$method(visitor),
+                        None => Err(invalid_type_error("an empty sequence", $expected)),
+                    },
+                    Value::Map(_) => Err(invalid_type_error("a map", $expected)),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::String(s) => visitor.visit_string(s),
+            Value::Sequence(values) => visit_seq(values, visitor),
+            Value::Map(map) => visit_map(map, visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::String(s) if s.is_empty() => visitor.visit_none(),
+            Value::Sequence(values) if values.is_empty() => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        // The values are already decoded, so the delimiter is split on the decoded string
+        if let (Some(delimiter), Value::String(s)) = (separated::delimiter(name), &self) {
+            let values = s
+                .split(char::from(delimiter))
+                .filter(|_| !s.is_empty())
+                .map(|v| Value::String(v.to_string()))
+                .collect();
+            return visitor.visit_newtype_struct(Value::Sequence(values));
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::String(s) => visit_seq(vec![Value::String(s)], visitor),
+            Value::Sequence(values) => visit_seq(values, visitor),
+            // Sparse indexes are kept as a map, their values are sorted by the indexes
+            Value::Map(map) => {
+                let mut items = map
+                    .into_iter()
+                    .map(|(key, value)| Some((key.parse::<usize>().ok()?, value)))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| invalid_type_error("a map", "a sequence"))?;
+                items.sort_by_key(|item| item.0);
+                visit_seq(items.into_iter().map(|item| item.1).collect(), visitor)
+            }
+        }
+    }
+
+    fn deserialize_tuple<V>(self, _: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _: &'static str,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Value::Map(map) => visit_map(map, visitor),
+            Value::String(_) => Err(invalid_type_error("a string", "a map")),
+            // The items are keyed by their indexes
+            Value::Sequence(values) => visit_map(
+                values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, value)| (index.to_string(), value))
+                    .collect(),
+                visitor,
+            ),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            // Variants with values are maps of a single entry, like brackets mode does
+            Value::Map(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().expect("map has one entry");
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            Value::Map(_) => Err(invalid_type_error("a map", "an enum")),
+            Value::String(s) => {
+                let mut scratch = Scratch::default();
+                DecodedSlice::<'de>(Cow::Owned(s.into_bytes()))
+                    .into_deserializer(&mut scratch)
+                    .deserialize_enum(name, variants, visitor)
+            }
+            Value::Sequence(mut values) => match values.pop() {
+                Some(value) => value.deserialize_enum(name, variants, visitor),
+                None => Err(invalid_type_error("an empty sequence", "an enum")),
+            },
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_to_string! {
+        deserialize_bool => "a boolean"
+        deserialize_i8 => "a number"
+        deserialize_i16 => "a number"
+        deserialize_i32 => "a number"
+        deserialize_i64 => "a number"
+        deserialize_u8 => "a number"
+        deserialize_u16 => "a number"
+        deserialize_u32 => "a number"
+        deserialize_u64 => "a number"
+        deserialize_f32 => "a number"
+        deserialize_f64 => "a number"
+        deserialize_char => "a char"
+        deserialize_str => "a string"
+        deserialize_string => "a string"
+        deserialize_bytes => "bytes"
+        deserialize_byte_buf => "bytes"
+        deserialize_identifier => "an identifier"
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 unit unit_struct
+    }
+}
+
+fn visit_seq<'de, V>(values: Vec<Value>, visitor: V) -> Result<V::Value, Error>
+where
+    V: de::Visitor<'de>,
+{
+    let mut deserializer = SeqDeserializer::new(values.into_iter());
+    let value = visitor.visit_seq(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+fn visit_map<'de, V>(map: Map, visitor: V) -> Result<V::Value, Error>
+where
+    V: de::Visitor<'de>,
+{
+    // Keys are parsed the same way as the values, so they can be numbers too
+    let mut deserializer = MapDeserializer::new(
+        map.into_iter()
+            .map(|(key, value)| (Value::String(key), value)),
+    );
+    let value = visitor.visit_map(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+pub(crate) struct ValueVisitor;
+
+impl<'de> de::Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string, a sequence or a map")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::String(String::new()))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::String(String::new()))
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        de::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Value::Sequence(values))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut values = Map::new();
+        while let Some((key, value)) = map.next_entry()? {
+            values.insert(key, value);
+        }
+        Ok(Value::Map(values))
+    }
+}
+
+/// Values can be deserialized from any self describing format, and from a query string to
+/// capture the pairs without a matching field with `#[serde(flatten)]`
+impl<'de> de::Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+impl<'de> de::IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = Value;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Value), Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = self.value;
+        seed.deserialize(de::IntoDeserializer::<Error>::into_deserializer(
+            self.variant,
+        ))
+        .map(|variant| (variant, value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Value {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Err(invalid_type_error("a value", "a unit variant"))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
@@ -0,0 +1,105 @@
+//! Parsing query strings in the browser, enabled by the `wasm` feature
+//!
+//! Frontends written in Rust can deserialize with the rest of the crate as usual, this module
+//! exposes the parser to JavaScript so both sides of an app share the same semantics.
+
+use _js_sys::{Array, Object, Reflect};
+use _wasm_bindgen::prelude::*;
+
+use crate::value::{from_str_value, Value};
+use crate::ParseMode;
+
+/// Parse a query string into a JavaScript object, with the same structure as [`Value`]
+///
+/// The keys with a single value have a string, and the ones with multiple values have an array
+/// or a nested object. The mode is one of `urlencoded`, `duplicate`, `brackets`,
+/// `dot_notation`, `matrix`, `rack` and `php`, or `delimiter:` and `brackets_delimiter:`
+/// followed by the delimiter(`delimiter:|`).
+///
+/// It throws an `Error` if the mode is unknown or the query string can't be parsed.
+#[wasm_bindgen(wasm_bindgen = _wasm_bindgen, js_sys = _js_sys)]
+pub fn parse_query(input: &str, mode: &str) -> Result<JsValue, JsValue> {
+    let mode = parse_mode(mode)
+        .ok_or_else(|| _js_sys::Error::new(&format!("unknown parse mode `{}`", mode)))?;
+
+    let value =
+        from_str_value(input, mode).map_err(|error| _js_sys::Error::new(&error.to_string()))?;
+    to_js(&value)
+}
+
+/// Returns the parse mode of its name, the delimiter modes are followed by their delimiter
+fn parse_mode(name: &str) -> Option<ParseMode> {
+    let delimiter = |name: &str| match name.as_bytes() {
+        [byte] => Some(*byte),
+        _ => None,
+    };
+
+    if let Some(rest) = name.strip_prefix("delimiter:") {
+        return delimiter(rest).map(ParseMode::Delimiter);
+    }
+    if let Some(rest) = name.strip_prefix("brackets_delimiter:") {
+        return delimiter(rest).map(ParseMode::BracketsDelimiter);
+    }
+
+    match name {
+        "urlencoded" => Some(ParseMode::UrlEncoded),
+        "duplicate" => Some(ParseMode::Duplicate),
+        "brackets" => Some(ParseMode::Brackets),
+        "dot_notation" => Some(ParseMode::DotNotation),
+        "matrix" => Some(ParseMode::Matrix),
+        "rack" => Some(ParseMode::Rack),
+        "php" => Some(ParseMode::Php),
+        _ => None,
+    }
+}
+
+/// Converts the value into a string, an array or an object
+fn to_js(value: &Value) -> Result<JsValue, JsValue> {
+    match value {
+        Value::String(s) => Ok(JsValue::from_str(s)),
+        Value::Sequence(values) => {
+            let array = Array::new();
+            for value in values {
+                array.push(&to_js(value)?);
+            }
+            Ok(array.into())
+        }
+        Value::Map(map) => {
+            let object = Object::new();
+            for (key, value) in map.iter() {
+                Reflect::set(&object, &JsValue::from_str(key), &to_js(value)?)?;
+            }
+            Ok(object.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_mode;
+    use crate::ParseMode;
+
+    #[test]
+    fn parse_mode_names() {
+        assert!(matches!(
+            parse_mode("duplicate"),
+            Some(ParseMode::Duplicate)
+        ));
+        assert!(matches!(
+            parse_mode("dot_notation"),
+            Some(ParseMode::DotNotation)
+        ));
+        assert!(matches!(
+            parse_mode("delimiter:|"),
+            Some(ParseMode::Delimiter(b'|'))
+        ));
+        assert!(matches!(
+            parse_mode("brackets_delimiter:,"),
+            Some(ParseMode::BracketsDelimiter(b','))
+        ));
+
+        assert!(parse_mode("delimiter:").is_none());
+        assert!(parse_mode("delimiter:||").is_none());
+        assert!(parse_mode("Brackets").is_none());
+    }
+}
@@ -0,0 +1,79 @@
+//! These tests are meant for the allocations made while deserializing
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use _serde::Deserialize;
+use serde_querystring::{BracketsQS, DuplicateQS, UrlEncodedQS};
+
+/// Counts the allocations of each thread, so the tests can run in parallel
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Returns the result of the function, and the number of allocations it made
+fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Query<'a> {
+    name: &'a str,
+    page: u32,
+}
+
+/// A query string with 50 parameters besides `name` and `page`, with values to decode
+fn long_query() -> String {
+    let mut input = String::from("name=john&page=2");
+    for i in 0..50 {
+        input.push_str(&format!("&param{:02}=a%20long+value%FF", i));
+    }
+    input
+}
+
+#[test]
+fn deserialize_keys_without_allocations() {
+    let input = long_query();
+    let parser = UrlEncodedQS::parse(input.as_bytes());
+
+    let (query, allocations) = count_allocations(|| parser.deserialize::<Query>().unwrap());
+
+    assert_eq!(query.name, "john");
+    // The key of the errors is the only buffer, allocated once and reused for every key
+    assert!(allocations <= 1, "{} allocations", allocations);
+}
+
+#[test]
+fn deserialize_ignored_values_without_decoding() {
+    // The values of unknown keys are never decoded, even the invalid utf-8 ones
+    let input = long_query();
+
+    let parser = DuplicateQS::parse(input.as_bytes());
+    let (query, allocations) = count_allocations(|| parser.deserialize::<Query>().unwrap());
+    assert_eq!(query.page, 2);
+    assert!(allocations <= 1, "{} allocations", allocations);
+
+    let parser = BracketsQS::parse(input.as_bytes());
+    let (query, allocations) = count_allocations(|| parser.deserialize::<Query>().unwrap());
+    assert_eq!(query.page, 2);
+    assert!(allocations <= 1, "{} allocations", allocations);
+}
@@ -0,0 +1,52 @@
+//! These tests are meant for the `Arbitrary` implementations of the `arbitrary` feature
+#![cfg(feature = "arbitrary")]
+
+use std::collections::HashMap;
+
+use _arbitrary::{Arbitrary, Unstructured};
+use serde_querystring::{from_bytes, from_bytes_value, parse_pairs, ParseMode, QsConfig};
+
+/// Some bytes to generate values from, every length is tried so each option is reached
+fn data() -> Vec<u8> {
+    (0..=255u8).map(|b| b.wrapping_mul(167)).collect()
+}
+
+#[test]
+fn arbitrary_parse_modes() {
+    let data = data();
+    let mut u = Unstructured::new(&data);
+
+    let mut seen = [false; 9];
+    while !u.is_empty() {
+        let index = match ParseMode::arbitrary(&mut u).unwrap() {
+            ParseMode::UrlEncoded => 0,
+            ParseMode::Duplicate => 1,
+            ParseMode::Delimiter(_) => 2,
+            ParseMode::Brackets => 3,
+            ParseMode::DotNotation => 4,
+            ParseMode::BracketsDelimiter(_) => 5,
+            ParseMode::Matrix => 6,
+            ParseMode::Rack => 7,
+            ParseMode::Php => 8,
+            mode => panic!("{:?} is never generated", mode),
+        };
+        seen[index] = true;
+    }
+    assert!(seen.iter().all(|seen| *seen));
+}
+
+#[test]
+fn arbitrary_configs_parse() {
+    let data = data();
+    let input = b"a=1&b[]=2&b[]=3&c[d][e]=x%20y;f=1|2,3&g.h=%FF&&=&i";
+
+    for start in 0..data.len() {
+        let mut u = Unstructured::new(&data[start..]);
+        let config = QsConfig::arbitrary(&mut u).unwrap();
+
+        // Only panics are failures, the options can make any of these return an error
+        let _ = from_bytes_value(input, config);
+        let _ = parse_pairs(input, config);
+        let _ = from_bytes::<HashMap<String, String>, _>(input, config);
+    }
+}
@@ -0,0 +1,68 @@
+//! These tests are meant for deserializing into the arena of the `bumpalo` feature
+#![cfg(feature = "bumpalo")]
+
+use std::collections::HashMap;
+
+use _serde::Deserialize;
+use serde_querystring::{from_bytes_in, from_str_in, Bump, CowStr, ParseMode};
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(crate = "_serde")]
+struct Query<'a> {
+    name: &'a str,
+    #[serde(with = "serde_bytes")]
+    token: &'a [u8],
+    tags: Vec<&'a str>,
+}
+
+#[test]
+fn deserialize_borrowed_decoded() {
+    let arena = Bump::new();
+    let input = "name=john+doe&token=%FF%00&tags=a%2Cb&tags=c";
+
+    let query: Query = from_str_in(&arena, input, ParseMode::Duplicate).unwrap();
+    assert_eq!(
+        query,
+        Query {
+            name: "john doe",
+            token: b"\xFF\x00",
+            tags: vec!["a,b", "c"],
+        }
+    );
+
+    let input = "name=john+doe&token=%FF%00&tags[]=a%2Cb&tags[]=c";
+    let res: Query = from_str_in(&arena, input, ParseMode::Brackets).unwrap();
+    assert_eq!(res, query);
+
+    let input = "name=john+doe&token=%FF%00&tags=a%2Cb|c";
+    let res: Query = from_str_in(&arena, input, ParseMode::Delimiter(b'|')).unwrap();
+    assert_eq!(res, query);
+}
+
+#[test]
+fn deserialize_borrowed_keys() {
+    let arena = Bump::new();
+    let input = b"first%20name=john&last+name=doe&age=30";
+
+    let map: HashMap<&str, &str> = from_bytes_in(&arena, input, ParseMode::UrlEncoded).unwrap();
+    assert_eq!(map["first name"], "john");
+    assert_eq!(map["last name"], "doe");
+
+    // Values with nothing to decode are borrowed from the input, the others from the arena
+    let map: HashMap<CowStr, CowStr> = from_bytes_in(&arena, input, ParseMode::UrlEncoded).unwrap();
+    assert!(map.iter().all(|(k, v)| k.is_borrowed() && v.is_borrowed()));
+    let age = map["age"].as_ptr();
+    assert!(input.as_ptr_range().contains(&age));
+}
+
+#[test]
+fn deserialize_arena_errors() {
+    let arena = Bump::new();
+
+    let res: Result<Query, _> = from_str_in(&arena, "name=%FF&token=&tags=", ParseMode::UrlEncoded);
+    assert!(res.is_err());
+
+    // The tree of owned values can't be borrowed from
+    let res: Result<Query, _> = from_str_in(&arena, "name=john&token=&tags[]=a", ParseMode::Rack);
+    assert!(res.is_err());
+}
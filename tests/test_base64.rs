@@ -0,0 +1,60 @@
+//! These tests are meant for the `Base64` and `Base64Url` types
+#![cfg(feature = "base64")]
+
+use _serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use serde_querystring::{from_str, to_string, Base64, Base64Url, ParseMode};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Query {
+    token: Base64,
+    cursor: Option<Base64Url<ByteBuf>>,
+}
+
+#[test]
+fn base64_deserialize() {
+    let expected = Query {
+        token: Base64(b"\xfb\xff?".to_vec()),
+        cursor: Some(Base64Url(ByteBuf::from(b"\xfb\xff?a".to_vec()))),
+    };
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+        ParseMode::DotNotation,
+    ] {
+        // Padded and percent encoded, or not
+        assert_eq!(
+            from_str::<Query, _>("token=%2B%2F8%2F&cursor=-_8_YQ%3D%3D", mode).unwrap(),
+            expected
+        );
+        // An unencoded `+` is decoded as a space first
+        assert_eq!(
+            from_str::<Query, _>("token=+/8/&cursor=-_8_YQ", mode).unwrap(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn base64_invalid() {
+    assert!(from_str::<Query, _>("token=a*b", ParseMode::UrlEncoded).is_err());
+    // The alphabets are not mixed
+    assert!(from_str::<Query, _>("token=-_8_", ParseMode::UrlEncoded).is_err());
+    assert!(from_str::<Query, _>("token=AA&cursor=%2B%2F8%2F", ParseMode::UrlEncoded).is_err());
+}
+
+#[test]
+fn base64_serialize() {
+    let query = Query {
+        token: Base64(b"\xfb\xff?".to_vec()),
+        cursor: Some(Base64Url(ByteBuf::from(b"\xfb\xff?a".to_vec()))),
+    };
+
+    let serialized = to_string(&query, ParseMode::UrlEncoded).unwrap();
+    assert_eq!(serialized, "token=%2B%2F8%2F&cursor=-_8_YQ");
+    assert_eq!(from_str(&serialized, ParseMode::UrlEncoded), Ok(query));
+}
@@ -1,7 +1,11 @@
 //! These tests are meant for the `BracketsQS` method
 
 use _serde::Deserialize;
-use serde_querystring::de::{from_bytes, ParseMode};
+use serde_querystring::de::{from_bytes, from_str, ErrorKind, ParseMode};
+use serde_querystring::{
+    camel_to_snake, from_bytes_value, EmptyBrackets, JsQsOptions, KeyConflict, QsConfig,
+    SparseIndexes, Value, DEFAULT_MAX_DEPTH,
+};
 
 /// It is a helper struct we use to test primitive types
 /// as we don't support anything beside maps/structs at the root level
@@ -166,7 +170,7 @@ fn deserialize_unit_enums() {
         winner: Side,
     }
     assert_eq!(
-        from_bytes::<A>(b"looser=Left&winner=God", ParseMode::Brackets),
+        from_bytes::<A, _>(b"looser=Left&winner=God", ParseMode::Brackets),
         Ok(A {
             looser: Side::Left,
             winner: Side::God
@@ -251,26 +255,41 @@ fn deserialize_enums() {
         ),
         Ok(p!(Enum::Unit))
     );
+
+    // Percent encoded variants
+    assert_eq!(
+        from_bytes(
+            b"value[Str%75ct][bee]=1&value[Struct][loose]=2",
+            ParseMode::Brackets
+        ),
+        Ok(p!(Enum::Struct { bee: 1, loose: 2 }))
+    );
+    assert_eq!(
+        from_bytes::<Primitive<Enum>, _>(b"value[Struct%20][bee]=1", ParseMode::Brackets)
+            .unwrap_err()
+            .kind,
+        ErrorKind::Other
+    );
 }
 
 #[test]
 fn deserialize_invalid_sequence() {
     // array length
-    assert!(from_bytes::<Primitive<[usize; 3]>>(
+    assert!(from_bytes::<Primitive<[usize; 3]>, _>(
         b"value=1&value=3&value=1337&value=999",
         ParseMode::Brackets
     )
     .is_err());
 
     // tuple length
-    assert!(from_bytes::<Primitive<(usize, usize, usize)>>(
+    assert!(from_bytes::<Primitive<(usize, usize, usize)>, _>(
         b"value=1&value=3&value=1337&value=999",
         ParseMode::Brackets
     )
     .is_err());
 
     // tuple value types
-    assert!(from_bytes::<Primitive<(&str, usize, &str)>>(
+    assert!(from_bytes::<Primitive<(&str, usize, &str)>, _>(
         b"value=foo&value=bar&value=baz",
         ParseMode::Brackets
     )
@@ -409,3 +428,639 @@ fn deserialize_option() {
         Ok(expected)
     );
 }
+
+#[test]
+fn deserialize_dot_notation() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Address {
+        city: String,
+        zip: u32,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct User {
+        name: String,
+        address: Address,
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Query {
+        user: User,
+    }
+
+    let expected = Query {
+        user: User {
+            name: "John".to_string(),
+            address: Address {
+                city: "Oslo".to_string(),
+                zip: 150,
+            },
+            tags: vec!["a".to_string(), "b".to_string()],
+        },
+    };
+
+    assert_eq!(
+        from_bytes(
+            b"user.name=John&user.address.city=Oslo&user.address.zip=150&user.tags[1]=b&user.tags[0]=a",
+            ParseMode::DotNotation
+        ),
+        Ok(expected)
+    );
+
+    // Brackets and indexes after dots are still supported
+    assert_eq!(
+        from_bytes(
+            b"value[a].b=1&value.a[c]=2&value%5Ba%5D.d=3",
+            ParseMode::DotNotation
+        ),
+        Ok(p!(map! {"a" => map! {"b" => 1, "c" => 2, "d" => 3}}))
+    );
+    assert_eq!(
+        from_bytes(b"value.1=b&value.0=a", ParseMode::DotNotation),
+        Ok(p!(vec!["a", "b"]))
+    );
+
+    // Encoded dots are part of the key
+    assert_eq!(
+        from_bytes(b"value.a%2Eb=1", ParseMode::DotNotation),
+        Ok(p!(map! {String::from("a.b") => 1}))
+    );
+
+    // Dots are not separators in brackets mode
+    assert_eq!(
+        from_bytes(b"value.a=1", ParseMode::Brackets),
+        Ok(map! {"value.a" => 1})
+    );
+}
+
+#[test]
+fn deserialize_brackets_delimiter() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Filter {
+        ids: Vec<u32>,
+        status: String,
+        tags: Option<Vec<String>>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Query {
+        filter: Filter,
+        sort: Vec<String>,
+    }
+
+    let expected = Query {
+        filter: Filter {
+            ids: vec![1, 2, 3, 4],
+            status: "a,b".to_string(),
+            tags: None,
+        },
+        sort: vec!["name".to_string(), "-age".to_string()],
+    };
+
+    // Indexed and repeated keys are combined with the delimited values
+    assert_eq!(
+        from_bytes(
+            b"filter[ids][1]=4&filter[ids][0]=1,2,3&filter[status]=a,b&sort=name,-age",
+            ParseMode::BracketsDelimiter(b',')
+        ),
+        Ok(expected)
+    );
+
+    // Encoded delimiters are part of the values
+    assert_eq!(
+        from_bytes(b"value=a%2Cb,c", ParseMode::BracketsDelimiter(b',')),
+        Ok(p!(vec![String::from("a,b"), String::from("c")]))
+    );
+
+    // Values are not split in brackets mode
+    assert_eq!(
+        from_bytes(b"value=a,b", ParseMode::Brackets),
+        Ok(p!(vec!["a,b"]))
+    );
+}
+
+#[test]
+fn deserialize_max_depth() {
+    let config = |mode| QsConfig::new(mode).max_depth(2);
+
+    assert_eq!(
+        from_bytes(b"value[a][b]=1", config(ParseMode::Brackets)),
+        Ok(p!(map! {"a" => map! {"b" => 1}}))
+    );
+    let error =
+        from_bytes::<Primitive<u32>, _>(b"value=1&other[a][b][c]=2", config(ParseMode::Brackets))
+            .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::DepthLimitExceeded);
+    assert_eq!(error.key, "other[a][b][c]");
+
+    // The key is reported with brackets in dot notation too, and decoded
+    let error = from_bytes::<Primitive<u32>, _>(
+        b"value=1&other.a[b%20c].d=2",
+        config(ParseMode::DotNotation),
+    )
+    .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::DepthLimitExceeded);
+    assert_eq!(error.key, "other[a][b c][d]");
+
+    // Empty brackets count as a level too
+    assert_eq!(
+        from_bytes::<Primitive<Vec<u32>>, _>(
+            b"value[]=1",
+            QsConfig::new(ParseMode::Brackets).max_depth(0)
+        )
+        .unwrap_err()
+        .kind,
+        ErrorKind::DepthLimitExceeded
+    );
+
+    let deep = format!("value{}=1", "[a]".repeat(DEFAULT_MAX_DEPTH + 1));
+    assert_eq!(
+        from_bytes::<Primitive<u32>, _>(deep.as_bytes(), ParseMode::Brackets)
+            .unwrap_err()
+            .kind,
+        ErrorKind::DepthLimitExceeded
+    );
+    assert_eq!(
+        from_bytes_value(deep.as_bytes(), ParseMode::Brackets)
+            .unwrap_err()
+            .kind,
+        ErrorKind::DepthLimitExceeded
+    );
+}
+
+#[test]
+fn deserialize_js_qs_options() {
+    let options = JsQsOptions::default();
+    assert_eq!(
+        from_bytes(b"value[a][]=1&value[a][]=2", options),
+        Ok(p!(map! {"a" => vec![1, 2]}))
+    );
+    assert_eq!(
+        from_bytes(b"value.a=1", options.allow_dots(true)),
+        Ok(p!(map! {"a" => 1}))
+    );
+    assert_eq!(
+        from_bytes(b"value[a]=1,2", options.comma(true)),
+        Ok(p!(map! {"a" => vec![1, 2]}))
+    );
+
+    // The limits of qs
+    let deep = format!("value{}=1", "[a]".repeat(6));
+    assert_eq!(
+        from_bytes::<Primitive<u32>, _>(deep.as_bytes(), options)
+            .unwrap_err()
+            .kind,
+        ErrorKind::DepthLimitExceeded
+    );
+    assert_eq!(from_bytes(b"value[20]=1", options), Ok(p!(vec![1])));
+    assert_eq!(
+        from_bytes::<Primitive<Vec<u32>>, _>(b"value[21]=1", options)
+            .unwrap_err()
+            .kind,
+        ErrorKind::LimitExceeded
+    );
+    assert_eq!(
+        from_bytes::<Primitive<u32>, _>(b"value=1&a&b", options.parameter_limit(2))
+            .unwrap_err()
+            .kind,
+        ErrorKind::TooManyParams
+    );
+}
+
+#[test]
+fn deserialize_strict_keys() {
+    let config = QsConfig::new(ParseMode::Brackets).strict_keys(true);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Inner {
+        a: u32,
+    }
+
+    assert_eq!(from_bytes(b"value[a]=1", config), Ok(p!(Inner { a: 1 })));
+
+    let error = from_bytes::<Primitive<Inner>, _>(b"value[a]=1&value[b][c]=2", config).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::UnknownKey);
+    assert_eq!(error.key, "value[b]");
+
+    let error = from_bytes::<Primitive<Primitive<Inner>>, _>(
+        b"value[value][a]=1&value[value][b]=2",
+        config,
+    )
+    .unwrap_err();
+    assert_eq!(error.key, "value[value][b]");
+}
+
+#[test]
+fn deserialize_error_key() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Range {
+        gte: u32,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Filter {
+        age: Range,
+    }
+
+    for (mode, input) in [
+        (ParseMode::Brackets, &b"value[age][gte]=ten"[..]),
+        (ParseMode::DotNotation, &b"value.age.gte=ten"[..]),
+    ] {
+        let error = from_bytes::<Primitive<Filter>, _>(input, mode).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidNumber);
+        assert_eq!(error.key, "value[age][gte]");
+        assert_eq!(error.value, "ten");
+        assert_eq!(error.key_span(), Some(0..input.len() - 4));
+        assert_eq!(error.value_span(), Some(input.len() - 3..input.len()));
+    }
+}
+
+#[test]
+fn deserialize_key_transform() {
+    let config = QsConfig::new(ParseMode::Brackets).key_transform(camel_to_snake);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Filter {
+        min_age: u32,
+        sort_by: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Query {
+        filter_by: Filter,
+    }
+
+    assert_eq!(
+        from_bytes(
+            b"filterBy[minAge]=3&filterBy[sortBy][1]=name&filterBy[sortBy][0]=age",
+            config
+        ),
+        Ok(Query {
+            filter_by: Filter {
+                min_age: 3,
+                sort_by: vec![String::from("age"), String::from("name")]
+            }
+        })
+    );
+}
+
+#[test]
+fn deserialize_integer_keys() {
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Scores {
+        scores: HashMap<u32, u32>,
+        #[serde(default)]
+        names: BTreeMap<u64, BTreeMap<String, String>>,
+    }
+
+    for mode in [ParseMode::Brackets, ParseMode::DotNotation] {
+        assert_eq!(
+            from_bytes(b"scores[3]=10&scores[7]=20&names[42][first]=John", mode),
+            Ok(Scores {
+                scores: vec![(3, 10), (7, 20)].into_iter().collect(),
+                names: vec![(
+                    42,
+                    vec![(String::from("first"), String::from("John"))]
+                        .into_iter()
+                        .collect()
+                )]
+                .into_iter()
+                .collect(),
+            })
+        );
+    }
+
+    assert!(from_bytes::<Scores, _>(b"scores[x]=10", ParseMode::Brackets).is_err());
+}
+
+#[test]
+fn deserialize_empty_brackets() {
+    let config = |empty_brackets| QsConfig::new(ParseMode::Brackets).empty_brackets(empty_brackets);
+    let value = |slice: &[u8], empty_brackets| {
+        from_bytes_value(slice, config(empty_brackets))
+            .map(|value| value.get("value").and_then(Value::as_sequence).cloned())
+    };
+    let strings = |items: &[&str]| {
+        items
+            .iter()
+            .map(|s| Value::String(s.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    // Empty brackets keep the order they appear in
+    for empty_brackets in [
+        EmptyBrackets::Append,
+        EmptyBrackets::First,
+        EmptyBrackets::Error,
+    ] {
+        assert_eq!(
+            from_bytes(b"value[]=c&value[]=a&value[]=b", config(empty_brackets)),
+            Ok(p!(vec!["c", "a", "b"]))
+        );
+        assert_eq!(
+            value(b"value[]=c&value[]=a&value[]=b", empty_brackets),
+            Ok(Some(strings(&["c", "a", "b"])))
+        );
+    }
+
+    let slice = b"value[]=x&value[1]=y&value[]=z&value[0]=w";
+
+    // Appended after the highest index before them by default
+    assert_eq!(
+        from_bytes(slice, ParseMode::Brackets),
+        Ok(p!(vec!["x", "w", "y", "z"]))
+    );
+    assert_eq!(
+        value(slice, EmptyBrackets::Append),
+        Ok(Some(strings(&["x", "w", "y", "z"])))
+    );
+
+    assert_eq!(
+        from_bytes(slice, config(EmptyBrackets::First)),
+        Ok(p!(vec!["x", "z", "w", "y"]))
+    );
+    assert_eq!(
+        value(slice, EmptyBrackets::First),
+        Ok(Some(strings(&["x", "z", "w", "y"])))
+    );
+
+    assert_eq!(
+        from_bytes::<Primitive<Vec<&str>>, _>(slice, config(EmptyBrackets::Error))
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidIndex
+    );
+    assert_eq!(
+        value(slice, EmptyBrackets::Error).unwrap_err().kind,
+        ErrorKind::InvalidIndex
+    );
+}
+
+#[test]
+fn deserialize_key_conflict() {
+    let config = |key_conflict| QsConfig::new(ParseMode::Brackets).key_conflict(key_conflict);
+    let slice = b"value=1&value[b]=2&value[c]=3";
+
+    // The sub keys are used by default
+    assert_eq!(
+        from_bytes(slice, ParseMode::Brackets),
+        Ok(p!(map! {"b" => 2, "c" => 3}))
+    );
+    assert_eq!(
+        from_bytes_value(slice, ParseMode::Brackets)
+            .unwrap()
+            .get("value")
+            .and_then(|value| value.get("b")),
+        Some(&Value::String(String::from("2")))
+    );
+
+    assert_eq!(from_bytes(slice, config(KeyConflict::Scalar)), Ok(p!(1)));
+    assert_eq!(
+        from_bytes::<Primitive<std::collections::HashMap<&str, u32>>, _>(
+            slice,
+            config(KeyConflict::Scalar)
+        ),
+        Ok(p!(map! {}))
+    );
+    assert_eq!(
+        from_bytes_value(slice, config(KeyConflict::Scalar))
+            .unwrap()
+            .get("value"),
+        Some(&Value::String(String::from("1")))
+    );
+
+    assert_eq!(
+        from_bytes::<Primitive<u32>, _>(slice, config(KeyConflict::Error))
+            .unwrap_err()
+            .kind,
+        ErrorKind::KeyConflict
+    );
+    assert_eq!(
+        from_bytes_value(slice, config(KeyConflict::Error))
+            .unwrap_err()
+            .kind,
+        ErrorKind::KeyConflict
+    );
+
+    // Nested keys are checked too
+    assert_eq!(
+        from_bytes::<Primitive<Primitive<u32>>, _>(
+            b"value[value]=1&value[value][b]=2",
+            config(KeyConflict::Error)
+        )
+        .unwrap_err()
+        .kind,
+        ErrorKind::KeyConflict
+    );
+
+    // Indexes are not a conflict
+    assert_eq!(
+        from_bytes(b"value=1&value[1]=2", config(KeyConflict::Error)),
+        Ok(p!(vec![1, 2]))
+    );
+}
+
+#[test]
+fn deserialize_sparse_indexes() {
+    let config = |sparse_indexes| QsConfig::new(ParseMode::Brackets).sparse_indexes(sparse_indexes);
+    let slice = b"value[3]=y&value[0]=x";
+
+    // The gaps are skipped by default
+    assert_eq!(
+        from_bytes(slice, ParseMode::Brackets),
+        Ok(p!(vec!["x", "y"]))
+    );
+
+    assert_eq!(
+        from_bytes(slice, config(SparseIndexes::Fill)),
+        Ok(p!(vec!["x", "", "", "y"]))
+    );
+    assert_eq!(
+        from_bytes(b"value[2]=5", config(SparseIndexes::Fill)),
+        Ok(p!(vec![None, None, Some(5)]))
+    );
+
+    assert_eq!(
+        from_bytes::<Primitive<Vec<&str>>, _>(slice, config(SparseIndexes::Error))
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidIndex
+    );
+    assert_eq!(
+        from_bytes(b"value[1]=y&value[0]=x", config(SparseIndexes::Error)),
+        Ok(p!(vec!["x", "y"]))
+    );
+
+    // Indexes are limited, whatever the gaps are
+    assert_eq!(
+        from_bytes::<Primitive<Vec<&str>>, _>(b"value[4294967295]=x", ParseMode::Brackets)
+            .unwrap_err()
+            .kind,
+        ErrorKind::LimitExceeded
+    );
+    assert_eq!(
+        from_bytes::<Primitive<Vec<&str>>, _>(
+            b"value[5]=x",
+            config(SparseIndexes::Fill).max_index(4)
+        )
+        .unwrap_err()
+        .kind,
+        ErrorKind::LimitExceeded
+    );
+}
+
+/// Check if struct and tuple variants can be nested in each other
+#[test]
+fn deserialize_nested_enums() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    enum Target {
+        Point { x: i64, y: i64 },
+        Pair(i64, i64),
+        Home,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    enum Action {
+        Move { x: i64, y: i64 },
+        Go { to: Target, speed: Option<u32> },
+        Wrap(Target),
+        Stop,
+    }
+
+    assert_eq!(
+        from_bytes(b"value[Move][x]=1&value[Move][y]=-2", ParseMode::Brackets),
+        Ok(p!(Action::Move { x: 1, y: -2 }))
+    );
+    assert_eq!(
+        from_bytes(
+            b"value[Go][to][Point][x]=1&value[Go][to][Point][y]=2&value[Go][speed]=3",
+            ParseMode::Brackets
+        ),
+        Ok(p!(Action::Go {
+            to: Target::Point { x: 1, y: 2 },
+            speed: Some(3)
+        }))
+    );
+    assert_eq!(
+        from_bytes(b"value.Go.to=Home", ParseMode::DotNotation),
+        Ok(p!(Action::Go {
+            to: Target::Home,
+            speed: None
+        }))
+    );
+    assert_eq!(
+        from_bytes(
+            b"value[Wrap][Pair][0]=1&value[Wrap][Pair][1]=2",
+            ParseMode::Brackets
+        ),
+        Ok(p!(Action::Wrap(Target::Pair(1, 2))))
+    );
+    assert_eq!(
+        from_bytes(b"value[]=Stop&value[]=Stop", ParseMode::Brackets),
+        Ok(p!(vec![Action::Stop, Action::Stop]))
+    );
+
+    // The only key of the query string is the variant of an enum at the root
+    assert_eq!(
+        from_bytes(b"Move[x]=1&Move[y]=2", ParseMode::Brackets),
+        Ok(Action::Move { x: 1, y: 2 })
+    );
+    assert_eq!(
+        from_bytes(b"Wrap[Point][x]=1&Wrap[Point][y]=2", ParseMode::Brackets),
+        Ok(Action::Wrap(Target::Point { x: 1, y: 2 }))
+    );
+    assert_eq!(from_bytes(b"Stop", ParseMode::Duplicate), Ok(Action::Stop));
+    assert_eq!(
+        from_bytes::<Action, _>(b"Stop&Move[x]=1", ParseMode::Brackets)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidType
+    );
+}
+
+#[test]
+fn deserialize_error_path() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Range {
+        max: u32,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Filter {
+        range: Range,
+        tags: Vec<u32>,
+    }
+
+    for (mode, input) in [
+        (ParseMode::Brackets, "value[range][max]=x&value[tags][]=1"),
+        (ParseMode::DotNotation, "value.range.max=x&value.tags.0=1"),
+    ] {
+        let error = from_str::<Primitive<Filter>, _>(input, mode).unwrap_err();
+        assert_eq!(error.key, "value[range][max]");
+        assert_eq!(error.path(), "value.range.max");
+    }
+
+    // The indexes of sequences are part of the path
+    for (mode, input) in [
+        (
+            ParseMode::Brackets,
+            "value[range][max]=1&value[tags][]=1&value[tags][]=x",
+        ),
+        (
+            ParseMode::Brackets,
+            "value[range][max]=1&value[tags][0]=1&value[tags][1]=x",
+        ),
+        (
+            ParseMode::DotNotation,
+            "value.range.max=1&value.tags.0=1&value.tags.1=x",
+        ),
+    ] {
+        let error = from_str::<Primitive<Filter>, _>(input, mode).unwrap_err();
+        assert_eq!(error.key, "value[tags][1]");
+        assert_eq!(error.path(), "value.tags.1");
+    }
+
+    for (mode, input) in [
+        (ParseMode::Duplicate, "value=1&value=x"),
+        (ParseMode::Delimiter(b','), "value=1,x"),
+    ] {
+        let error = from_str::<Primitive<Vec<u32>>, _>(input, mode).unwrap_err();
+        assert_eq!(error.path(), "value.1");
+    }
+}
+
+#[test]
+fn deserialize_skip_invalid_pairs() {
+    let config = QsConfig::new(ParseMode::Brackets)
+        .max_depth(1)
+        .skip_invalid_pairs(true);
+
+    assert_eq!(
+        from_bytes(b"value[a]=1&value[b][c]=2", config),
+        Ok(p!(map! {"a" => 1}))
+    );
+
+    let errors = serde_querystring::invalid_pairs(b"value[a]=1&value[b][c]=2", config);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ErrorKind::DepthLimitExceeded);
+    assert_eq!(errors[0].value, "value[b][c]=2");
+    assert_eq!(errors[0].value_span(), Some(11..24));
+}
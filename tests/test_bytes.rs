@@ -0,0 +1,80 @@
+//! These tests are meant for the `SharedBytes` type and `from_bytes_shared`
+#![cfg(feature = "bytes")]
+
+use std::collections::HashMap;
+
+use _bytes::Bytes;
+use _serde::Deserialize;
+use serde_querystring::{from_bytes_shared, from_str, ParseMode, SharedBytes};
+
+/// Returns true if the bytes are a slice of the buffer of the input
+fn is_shared(bytes: &Bytes, input: &Bytes) -> bool {
+    let range = input.as_ptr_range();
+    range.contains(&bytes.as_ptr())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "_serde")]
+struct Query<'a> {
+    token: SharedBytes,
+    name: &'a str,
+    ids: Vec<SharedBytes>,
+}
+
+#[test]
+fn deserialize_shared() {
+    let input = Bytes::from(b"token=abc&name=john&ids=1&ids=2%203".to_vec());
+    let query: Query = from_bytes_shared(&input, ParseMode::Duplicate).unwrap();
+
+    assert_eq!(query.token.as_ref(), b"abc");
+    assert!(is_shared(&query.token, &input));
+    assert_eq!(query.name, "john");
+    assert_eq!(query.ids[0].as_ref(), b"1");
+    assert!(is_shared(&query.ids[0], &input));
+
+    // Decoded values are copied
+    assert_eq!(query.ids[1].as_ref(), b"2 3");
+    assert!(!is_shared(&query.ids[1], &input));
+}
+
+#[test]
+fn deserialize_shared_modes() {
+    let input = Bytes::from(b"token=abc&name=john&ids[0]=1&ids[1]=2".to_vec());
+    let query: Query = from_bytes_shared(&input, ParseMode::Brackets).unwrap();
+    assert!(is_shared(&query.token, &input));
+    assert!(query.ids.iter().all(|id| is_shared(id, &input)));
+
+    let input = Bytes::from(b"token=abc&name=john&ids=1|2".to_vec());
+    let query: Query = from_bytes_shared(&input, ParseMode::Delimiter(b'|')).unwrap();
+    assert!(is_shared(&query.token, &input));
+    assert_eq!(query.ids[1].as_ref(), b"2");
+    assert!(query.ids.iter().all(|id| is_shared(id, &input)));
+
+    let input = Bytes::from(b"a=1&b=2".to_vec());
+    let map: HashMap<String, SharedBytes> =
+        from_bytes_shared(&input, ParseMode::UrlEncoded).unwrap();
+    assert!(map.values().all(|value| is_shared(value, &input)));
+}
+
+#[test]
+fn deserialize_shared_nested() {
+    #[derive(Debug, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Inner {
+        token: SharedBytes,
+    }
+
+    // A call with another input doesn't change the input of the outer call
+    let outer = Bytes::from(b"token=outer".to_vec());
+    let inner = Bytes::from(b"token=inner".to_vec());
+
+    let first: Inner = from_bytes_shared(&outer, ParseMode::UrlEncoded).unwrap();
+    let second: Inner = from_bytes_shared(&inner, ParseMode::UrlEncoded).unwrap();
+    assert!(is_shared(&first.token, &outer));
+    assert!(is_shared(&second.token, &inner));
+
+    // Outside of `from_bytes_shared`, the values are copied
+    let copied: Inner = from_str("token=abc", ParseMode::UrlEncoded).unwrap();
+    assert_eq!(copied.token.as_ref(), b"abc");
+    assert_eq!(copied.token.into_inner(), Bytes::from_static(b"abc"));
+}
@@ -0,0 +1,79 @@
+//! These tests are meant for rewriting query strings in their canonical form
+use serde_querystring::{canonicalize, CanonicalizeOptions, EncodeSet, PairSeparator};
+
+fn canonical(input: &str) -> String {
+    canonicalize(input, CanonicalizeOptions::new())
+}
+
+#[test]
+fn canonical_sorted_pairs() {
+    assert_eq!(
+        canonical("Version=2010-05-08&Action=ListUsers"),
+        "Action=ListUsers&Version=2010-05-08"
+    );
+    // Bytes are compared, upper case letters come first
+    assert_eq!(canonical("b=2&a=1&A=3"), "A=3&a=1&b=2");
+    // The values of repeated keys are sorted too
+    assert_eq!(canonical("a=2&b=0&a=1"), "a=1&a=2&b=0");
+
+    let options = CanonicalizeOptions::new().sort(false);
+    assert_eq!(canonicalize("a=2&b=0&a=1", options), "a=2&b=0&a=1");
+}
+
+#[test]
+fn canonical_encoding() {
+    // Upper case hex digits, and only the reserved bytes are encoded
+    assert_eq!(canonical("a=%c3%a9&b=%41%2d%7E"), "a=%C3%A9&b=A-~");
+    assert_eq!(canonical("path=/a b/c?d"), "path=%2Fa%20b%2Fc%3Fd");
+    assert_eq!(canonical("k%5B0%5D=v&k[1]=w"), "k%5B0%5D=v&k%5B1%5D=w");
+    // Malformed escapes are encoded like the other bytes
+    assert_eq!(canonical("a=%zz%&b=100%"), "a=%25zz%25&b=100%25");
+
+    let options = CanonicalizeOptions::new().encode_set(EncodeSet::FORM);
+    assert_eq!(canonicalize("q=a%20b~", options), "q=a+b%7E");
+}
+
+#[test]
+fn canonical_spaces() {
+    assert_eq!(canonical("q=a+b&r=a%20b"), "q=a%20b&r=a%20b");
+
+    let options = CanonicalizeOptions::new().plus_as_space(false);
+    assert_eq!(canonicalize("q=a+b&r=a%20b", options), "q=a%2Bb&r=a%20b");
+}
+
+#[test]
+fn canonical_empty_pairs() {
+    assert_eq!(canonical("a=&b&&c=1&"), "c=1");
+    assert_eq!(canonical(""), "");
+
+    let options = CanonicalizeOptions::new().remove_empty(false);
+    assert_eq!(canonicalize("b&a=&&c=1", options), "a=&b=&c=1");
+    assert_eq!(
+        canonicalize("b&a=&&c=1", options.bare_keys(true)),
+        "a=&b&c=1"
+    );
+}
+
+#[test]
+fn canonical_equal_queries() {
+    let queries = [
+        "name=John+Doe&tags=a&tags=b&page=",
+        "tags=a&name=John%20Doe&tags=b",
+        "tags=%61&&name=%4a%6f%68%6e+%44%6f%65&tags=b&",
+    ];
+    for query in queries {
+        assert_eq!(canonical(query), "name=John%20Doe&tags=a&tags=b");
+    }
+}
+
+#[test]
+fn canonical_separators() {
+    let options = CanonicalizeOptions::new().pair_separator(PairSeparator::Semicolon);
+    assert_eq!(canonicalize("b=2;a=1", options), "a=1&b=2");
+
+    // Bytes work as input too
+    assert_eq!(
+        canonicalize(&b"b=%FF&a=1"[..], CanonicalizeOptions::new()),
+        "a=1&b=%FF"
+    );
+}
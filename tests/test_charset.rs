@@ -0,0 +1,80 @@
+//! These tests are meant for the `charset` feature
+#![cfg(feature = "charset")]
+
+use std::borrow::Cow;
+
+use _serde::Deserialize;
+use serde_querystring::charset::encoding_rs::{EUC_KR, SHIFT_JIS, UTF_8, WINDOWS_1252};
+use serde_querystring::charset::{detect_charset, from_bytes, from_str, transcode};
+use serde_querystring::{ErrorKind, ParseMode};
+
+#[test]
+fn transcode_query() {
+    // `テスト` in Shift_JIS, the trail bytes are not escaped
+    assert_eq!(
+        &*transcode(b"name=%83e%83X%83g", SHIFT_JIS),
+        b"name=%E3%83%86%E3%82%B9%E3%83%88"
+    );
+    // `한` in EUC-KR
+    assert_eq!(&*transcode(b"q=%C7%D1+a", EUC_KR), b"q=%ED%95%9C+a");
+
+    // The structure and the ascii escapes are kept
+    assert_eq!(
+        &*transcode(b"a%5B%E9%5D=%7C%E9%7C|x&b=%zz", WINDOWS_1252),
+        b"a%5B%C3%A9%5D=%7C%C3%A9%7C|x&b=%zz"
+    );
+
+    assert!(matches!(
+        transcode(b"a=%41&b=c", WINDOWS_1252),
+        Cow::Borrowed(_)
+    ));
+    assert!(matches!(transcode(b"a=%E9", UTF_8), Cow::Borrowed(_)));
+}
+
+#[test]
+fn detect_query_charset() {
+    assert_eq!(
+        detect_charset(b"a=1&_charset_=Shift_JIS", ParseMode::UrlEncoded),
+        Ok(Some(SHIFT_JIS))
+    );
+    assert_eq!(detect_charset(b"a=1", ParseMode::UrlEncoded), Ok(None));
+    assert_eq!(
+        detect_charset(b"_charset_=klingon", ParseMode::UrlEncoded)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidEncoding
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(crate = "_serde")]
+struct Form {
+    name: String,
+    tags: Vec<String>,
+}
+
+#[test]
+fn deserialize_charset() {
+    let form: Form = from_bytes(
+        b"name=caf%E9&tags[]=%E0+la&tags[]=x",
+        WINDOWS_1252,
+        ParseMode::Brackets,
+    )
+    .unwrap();
+    assert_eq!(
+        form,
+        Form {
+            name: String::from("café"),
+            tags: vec![String::from("à la"), String::from("x")]
+        }
+    );
+
+    // The `_charset_` parameter takes precedence
+    let form: Form = from_str(
+        "_charset_=shift_jis&name=%83e%83X%83g&tags=a",
+        WINDOWS_1252,
+        ParseMode::Duplicate,
+    )
+    .unwrap();
+    assert_eq!(form.name, "テスト");
+}
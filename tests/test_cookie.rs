@@ -0,0 +1,61 @@
+//! These tests are meant for the `CookieQS` parser
+
+use _serde::Deserialize;
+use serde_querystring::de::{DuplicateKeys, ErrorKind, ParseMode, QsConfig};
+use serde_querystring::CookieQS;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(crate = "_serde")]
+struct Cookies<'a> {
+    session: &'a str,
+    name: String,
+    visits: u32,
+    #[serde(default)]
+    consent: bool,
+    theme: Option<String>,
+}
+
+#[test]
+fn deserialize_cookies() {
+    let header = b"session=abc123; name=hello%20world;visits=3 ; consent; session=other";
+
+    assert_eq!(
+        CookieQS::parse(header).deserialize(),
+        Ok(Cookies {
+            session: "abc123",
+            name: String::from("hello world"),
+            visits: 3,
+            consent: true,
+            theme: None,
+        })
+    );
+
+    // Quoted values are borrowed without their quotes
+    let cookies: Cookies = CookieQS::parse(b"session=\"abc\"; name=\"\"; visits=1; theme=dark")
+        .deserialize()
+        .unwrap();
+    assert_eq!(cookies.session, "abc");
+    assert_eq!(cookies.name, "");
+    assert!(!cookies.consent);
+    assert_eq!(cookies.theme.as_deref(), Some("dark"));
+}
+
+#[test]
+fn deserialize_cookies_with_config() {
+    let header = b"session=a; name=b; visits=1; session=c";
+
+    let config = QsConfig::new(ParseMode::UrlEncoded).duplicate_keys(DuplicateKeys::Last);
+    let cookies: Cookies = CookieQS::parse_with_config(header, &config)
+        .deserialize()
+        .unwrap();
+    assert_eq!(cookies.session, "c");
+
+    let config = QsConfig::new(ParseMode::UrlEncoded).duplicate_keys(DuplicateKeys::Error);
+    assert_eq!(
+        CookieQS::parse_with_config(header, &config)
+            .deserialize::<Cookies>()
+            .unwrap_err()
+            .kind,
+        ErrorKind::DuplicateKey
+    );
+}
@@ -0,0 +1,86 @@
+//! These tests are meant for the `Custom` parse mode
+
+use _serde::Deserialize;
+use serde_querystring::de::{
+    from_bytes_scoped, from_str, DuplicateKeys, ErrorKind, ParseMode, QsConfig,
+};
+use serde_querystring::{from_bytes_value, get_value, parse_pairs, QsParser};
+
+/// Pairs like `key:value`, separated by commas
+struct Colons;
+
+impl QsParser for Colons {
+    fn parse<'a>(&self, input: &'a [u8], _: &QsConfig) -> Vec<(&'a [u8], Option<&'a [u8]>)> {
+        input
+            .split(|b| *b == b',')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.iter().position(|b| *b == b':') {
+                Some(index) => (&pair[..index], Some(&pair[index + 1..])),
+                None => (pair, None),
+            })
+            .collect()
+    }
+}
+
+const COLONS: ParseMode = ParseMode::Custom(&Colons);
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(crate = "_serde")]
+struct Query<'a> {
+    name: &'a str,
+    ids: Vec<u32>,
+    #[serde(default)]
+    flag: bool,
+    page: Option<u32>,
+}
+
+#[test]
+fn deserialize_custom() {
+    assert_eq!(
+        from_str("name:foo,ids:1,ids:2,flag,page:3,page:4", COLONS),
+        Ok(Query {
+            name: "foo",
+            ids: vec![1, 2],
+            flag: true,
+            page: Some(4),
+        })
+    );
+
+    let config = QsConfig::new(COLONS).duplicate_keys(DuplicateKeys::First);
+    let query: Query = from_str("name:a,ids:1,page:3,page:4", config).unwrap();
+    assert_eq!(query.page, Some(3));
+
+    // The values are still percent decoded
+    let query: Query = from_str("name:a,ids:1,n%61me:b", COLONS).unwrap();
+    assert_eq!(query.name, "b");
+
+    assert_eq!(
+        from_bytes_scoped::<Query, _>(b"name:a", "filter", COLONS)
+            .unwrap_err()
+            .kind,
+        ErrorKind::Other
+    );
+}
+
+#[test]
+fn parse_custom() {
+    let pairs = parse_pairs(b"name:foo+bar,ids:1,ids:2", COLONS);
+    assert_eq!(pairs.len(), 3);
+    assert_eq!(pairs[0].1, "foo bar");
+
+    assert_eq!(
+        get_value(b"ids:1,ids:2", b"ids", COLONS).unwrap(),
+        &b"2"[..]
+    );
+
+    let value = from_bytes_value(b"name:foo,ids:1,ids:2", COLONS).unwrap();
+    assert_eq!(value.get("ids").unwrap().as_sequence().unwrap().len(), 2);
+}
+
+#[test]
+fn custom_unwind_safe() {
+    fn assert_unwind_safe<T: std::panic::UnwindSafe + std::panic::RefUnwindSafe>(_: T) {}
+
+    assert_unwind_safe(COLONS);
+    assert_unwind_safe(QsConfig::new(COLONS));
+}
@@ -0,0 +1,154 @@
+//! These tests are meant for the date and time wrappers
+#![cfg(any(feature = "chrono", feature = "time"))]
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::{
+    from_str, to_string, DateTimeValue, LenientDateTime, ParseMode, Rfc3339, UnixMillis,
+    UnixSeconds,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde", bound = "T: DateTimeValue")]
+struct Query<T> {
+    seconds: UnixSeconds<T>,
+    millis: UnixMillis<T>,
+    rfc3339: Rfc3339<T>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(crate = "_serde", bound = "T: DateTimeValue")]
+struct Lenient<T> {
+    value: LenientDateTime<T>,
+}
+
+/// 2024-01-02T03:04:05Z
+const MILLIS: i64 = 1_704_164_645_000;
+
+fn check_formats<T>()
+where
+    T: DateTimeValue + std::fmt::Debug + PartialEq,
+{
+    let expected = || T::from_unix_millis(MILLIS).unwrap();
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+        ParseMode::DotNotation,
+    ] {
+        let query: Query<T> = from_str(
+            "seconds=1704164645&millis=1704164645000&rfc3339=2024-01-02T05:04:05%2B02:00",
+            mode,
+        )
+        .unwrap();
+        assert_eq!(query.seconds.to_unix_millis(), MILLIS);
+        assert_eq!(query.millis.to_unix_millis(), MILLIS);
+        assert_eq!(query.rfc3339.to_unix_millis(), MILLIS);
+    }
+
+    // The offset is required
+    assert!(from_str::<Query<T>, _>(
+        "seconds=1704164645&millis=1704164645000&rfc3339=2024-01-02T03:04:05",
+        ParseMode::UrlEncoded
+    )
+    .is_err());
+    assert!(from_str::<Query<T>, _>(
+        "seconds=soon&millis=1704164645000&rfc3339=2024-01-02T03:04:05Z",
+        ParseMode::UrlEncoded
+    )
+    .is_err());
+
+    for value in [
+        "2024-01-02T03:04:05Z",
+        "2024-01-02T03:04:05",
+        "2024-01-02%2003:04:05",
+        "2024-01-02T05:04:05%2B02:00",
+        "1704164645000",
+    ] {
+        let res: Lenient<T> = from_str(&format!("value={}", value), ParseMode::UrlEncoded).unwrap();
+        assert_eq!(res.value.0, expected(), "{}", value);
+    }
+
+    let res: Lenient<T> = from_str("value=2024-01-02", ParseMode::UrlEncoded).unwrap();
+    assert_eq!(
+        res.value.to_unix_millis(),
+        MILLIS - (3 * 3600 + 4 * 60 + 5) * 1000
+    );
+
+    for value in ["2024-13-02", "2024-1-2", "yesterday", ""] {
+        assert!(
+            from_str::<Lenient<T>, _>(&format!("value={}", value), ParseMode::UrlEncoded).is_err(),
+            "{}",
+            value
+        );
+    }
+
+    let query = Query {
+        seconds: UnixSeconds(expected()),
+        millis: UnixMillis(expected()),
+        rfc3339: Rfc3339(expected()),
+    };
+    assert_eq!(
+        to_string(&query, ParseMode::UrlEncoded).unwrap(),
+        "seconds=1704164645&millis=1704164645000&rfc3339=2024-01-02T03%3A04%3A05Z"
+    );
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn datetime_chrono() {
+    use _chrono::{DateTime, FixedOffset, Utc};
+
+    check_formats::<DateTime<Utc>>();
+    check_formats::<DateTime<FixedOffset>>();
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn datetime_time() {
+    check_formats::<_time::OffsetDateTime>();
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn datetime_time_out_of_range() {
+    use _time::{Date, Month};
+
+    // RFC 3339 only has the years 0 to 9999
+    let datetime = Date::from_calendar_date(-1, Month::January, 1)
+        .unwrap()
+        .midnight()
+        .assume_utc();
+
+    #[derive(Serialize)]
+    #[serde(crate = "_serde")]
+    struct Value<T> {
+        value: T,
+    }
+
+    assert!(to_string(
+        &Value {
+            value: Rfc3339(datetime)
+        },
+        ParseMode::UrlEncoded
+    )
+    .is_err());
+    assert!(to_string(
+        &Value {
+            value: LenientDateTime(datetime)
+        },
+        ParseMode::UrlEncoded
+    )
+    .is_err());
+    assert_eq!(
+        to_string(
+            &Value {
+                value: UnixSeconds(datetime)
+            },
+            ParseMode::UrlEncoded
+        )
+        .unwrap(),
+        "value=-62198755200"
+    );
+}
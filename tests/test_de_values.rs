@@ -1,7 +1,15 @@
 //! These tests are common between different deserialization methods
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+
 use _serde::Deserialize;
 use serde_querystring::de::{from_bytes, from_str, ErrorKind, ParseMode};
+use serde_querystring::{
+    from_bytes_owned, from_str_auto, from_str_collect_errors, from_str_owned, from_str_partial,
+    from_str_with, CowStr, DuplicateKeys, InvalidUtf8, PairSeparator, Presence, QsBuffers,
+    QsConfig, QsConfigBuilder,
+};
 
 /// It is a helper struct we use to test primitive types
 /// as we don't support anything beside maps/structs at the root level
@@ -46,6 +54,8 @@ where
     assert_eq!(f(ParseMode::Duplicate), r);
     assert_eq!(f(ParseMode::Delimiter(b'|')), r);
     assert_eq!(f(ParseMode::Brackets), r);
+    assert_eq!(f(ParseMode::DotNotation), r);
+    assert_eq!(f(ParseMode::BracketsDelimiter(b'|')), r);
 }
 
 #[derive(Debug, Deserialize, Hash, Eq, PartialEq)]
@@ -280,6 +290,83 @@ fn deserialize_byte_vecs() {
     );
 }
 
+#[test]
+fn deserialize_non_utf8_bytes() {
+    use std::borrow::Cow;
+
+    use serde_bytes::ByteBuf;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Token<'a> {
+        #[serde(with = "serde_bytes")]
+        value: Vec<u8>,
+        #[serde(borrow, with = "serde_bytes")]
+        cow: Cow<'a, [u8]>,
+        #[serde(default)]
+        other: Option<ByteBuf>,
+    }
+
+    // The decoded bytes are never checked to be valid utf-8
+    check_result(
+        |mode| from_str("value=%FF%00%C3&cow=%FE+x&other=%80", mode),
+        Ok(Token {
+            value: vec![0xFF, 0x00, 0xC3],
+            cow: Cow::Owned(vec![0xFE, b' ', b'x']),
+            other: Some(ByteBuf::from(vec![0x80])),
+        }),
+    );
+
+    // Slices are borrowed when there is nothing to decode
+    let token: Token = from_str("value=a&cow=raw", ParseMode::UrlEncoded).unwrap();
+    assert!(matches!(token.cow, Cow::Borrowed(b"raw")));
+
+    // Sequences of bytes in the modes supporting them
+    let expected = || p!(vec![ByteBuf::from(vec![0xFF]), ByteBuf::from(vec![0xFE])]);
+    assert_eq!(
+        from_str("value=%FF&value=%FE", ParseMode::Duplicate),
+        Ok(expected())
+    );
+    assert_eq!(
+        from_str("value=%FF|%FE", ParseMode::Delimiter(b'|')),
+        Ok(expected())
+    );
+    assert_eq!(
+        from_str("value[]=%FF&value[]=%FE", ParseMode::Brackets),
+        Ok(expected())
+    );
+    assert_eq!(
+        from_str("value.0=%FF&value.1=%FE", ParseMode::DotNotation),
+        Ok(expected())
+    );
+    assert_eq!(
+        from_str("value=%FF|%FE", ParseMode::BracketsDelimiter(b'|')),
+        Ok(expected())
+    );
+}
+
+#[cfg(feature = "bstr")]
+#[test]
+fn deserialize_bstr() {
+    use serde_querystring::{BStr, BString};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Token<'a> {
+        value: BString,
+        #[serde(borrow)]
+        raw: &'a BStr,
+    }
+
+    check_result(
+        |mode| from_str("value=a%FFb+c&raw=xyz", mode),
+        Ok(Token {
+            value: BString::from(&b"a\xFFb c"[..]),
+            raw: BStr::new("xyz"),
+        }),
+    );
+}
+
 /// Check if unit enums work as keys and values
 #[test]
 fn deserialize_unit_enum() {
@@ -291,7 +378,7 @@ fn deserialize_unit_enum() {
         winner: Side,
     }
     assert_eq!(
-        from_bytes::<A>(b"looser=Left&winner=God", ParseMode::UrlEncoded),
+        from_bytes::<A, _>(b"looser=Left&winner=God", ParseMode::UrlEncoded),
         Ok(A {
             looser: Side::Left,
             winner: Side::God
@@ -317,11 +404,11 @@ fn deserialize_option() {
     );
 
     check_result(
-        |mode| from_str::<Primitive<Option<u32>>>("value=", mode).is_err(),
+        |mode| from_str::<Primitive<Option<u32>>, _>("value=", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<Option<u32>>>("value=1337&value=", mode).is_err(),
+        |mode| from_str::<Primitive<Option<u32>>, _>("value=1337&value=", mode).is_err(),
         true,
     );
 }
@@ -348,6 +435,191 @@ fn deserialize_extra_ampersands() {
     );
 }
 
+#[test]
+fn deserialize_pair_separators() {
+    let semicolon = |mode| QsConfig::new(mode).pair_separator(PairSeparator::Semicolon);
+    let both = |mode| QsConfig::new(mode).pair_separator(PairSeparator::Both);
+
+    check_result(
+        |mode| from_str("value=foo;value=bar", semicolon(mode)),
+        Ok(p!("bar")),
+    );
+    check_result(
+        |mode| from_str("value=foo;bar&baz", semicolon(mode)),
+        Ok(p!("foo")),
+    );
+    check_result(
+        |mode| from_str(";;value=bar&value=baz;;", both(mode)),
+        Ok(p!("baz")),
+    );
+    check_result(
+        |mode| from_str("value;value=bar", both(mode)),
+        Ok(p!("bar")),
+    );
+
+    // Semicolons are part of the values by default
+    check_result(|mode| from_str("value=foo;bar", mode), Ok(p!("foo;bar")));
+}
+
+#[test]
+fn deserialize_newline_separator() {
+    let newline = |mode| QsConfig::new(mode).pair_separator(PairSeparator::Newline);
+
+    check_result(
+        |mode| from_str("a=1\r\nb=hello%20world\n\nc=x&y;z\n", newline(mode)),
+        Ok(map! {
+            String::from("a") => String::from("1"),
+            String::from("b") => String::from("hello world"),
+            String::from("c") => String::from("x&y;z"),
+        }),
+    );
+    check_result(
+        |mode| from_str("value=foo\nvalue=bar\r\n", newline(mode)),
+        Ok(p!("bar")),
+    );
+
+    // Empty pairs don't give an empty key
+    check_result(
+        |mode| from_str("&&a=1&", mode),
+        Ok(map! {String::from("a") => 1}),
+    );
+}
+
+#[test]
+fn deserialize_plus_as_space() {
+    let literal_plus = |mode| QsConfig::new(mode).plus_as_space(false);
+
+    check_result(
+        |mode| from_str("value=a+b%20c%2B", literal_plus(mode)),
+        Ok(p!(String::from("a+b c+"))),
+    );
+    check_result(
+        |mode| from_str("value=a+b%20c%2B", mode),
+        Ok(p!(String::from("a b c+"))),
+    );
+
+    // Keys are decoded the same way
+    check_result(
+        |mode| from_str("a+b=1", literal_plus(mode)),
+        Ok(map! {String::from("a+b") => 1}),
+    );
+}
+
+#[test]
+fn deserialize_unicode_escapes() {
+    let escapes = |mode| QsConfig::new(mode).unicode_escapes(true);
+
+    check_result(
+        |mode| from_str("value=caf%u00e9%20%uD83D%uDE00", escapes(mode)),
+        Ok(p!(String::from("café 😀"))),
+    );
+    // They are kept as is by default
+    check_result(
+        |mode| from_str("value=caf%u00E9", mode),
+        Ok(p!(String::from("caf%u00E9"))),
+    );
+    // Keys are decoded the same way
+    check_result(
+        |mode| from_str("%u00e9=1", escapes(mode)),
+        Ok(map! {String::from("é") => 1}),
+    );
+    // The decoded length is checked against the limits
+    check_result(
+        |mode| from_str("value=%u00e9", escapes(mode).max_length(5)),
+        Ok(p!(String::from("é"))),
+    );
+
+    for input in [
+        "value=%u00",
+        "value=%u00zz",
+        "value=%uD83D",
+        "value=%uDE00%uD83D",
+    ] {
+        check_result(
+            |mode| {
+                from_str::<Primitive<String>, _>(input, escapes(mode))
+                    .map_err(|e| (e.kind, e.index))
+            },
+            Err((ErrorKind::InvalidUnicodeEscape, Some(6))),
+        );
+    }
+}
+
+#[test]
+fn deserialize_invalid_utf8() {
+    use serde_bytes::ByteBuf;
+
+    let policy = |mode, invalid_utf8| QsConfig::new(mode).invalid_utf8(invalid_utf8);
+
+    check_result(
+        |mode| {
+            from_str::<Primitive<String>, _>("value=a%FFb", policy(mode, InvalidUtf8::Error))
+                .map_err(|e| (e.kind, e.index, e.key))
+        },
+        Err((ErrorKind::InvalidEncoding, Some(1), String::from("value"))),
+    );
+    check_result(
+        |mode| from_str("value=a%FFb", policy(mode, InvalidUtf8::Replace)),
+        Ok(p!(String::from("a\u{FFFD}b"))),
+    );
+    // Keys are replaced too
+    check_result(
+        |mode| from_str("%FF=1", policy(mode, InvalidUtf8::Replace)),
+        Ok(map! {String::from("\u{FFFD}") => 1}),
+    );
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde", untagged)]
+    enum Token {
+        Text(String),
+        Binary(ByteBuf),
+    }
+
+    check_result(
+        |mode| from_str("value=a%FFb", policy(mode, InvalidUtf8::Bytes)),
+        Ok(p!(Token::Binary(ByteBuf::from(b"a\xFFb".to_vec())))),
+    );
+    check_result(
+        |mode| from_str("value=a%20b", policy(mode, InvalidUtf8::Bytes)),
+        Ok(p!(Token::Text(String::from("a b")))),
+    );
+    // Strings still fail, as the bytes are not valid utf-8
+    check_result(
+        |mode| {
+            from_str::<Primitive<String>, _>("value=%FF", policy(mode, InvalidUtf8::Bytes)).is_err()
+        },
+        true,
+    );
+}
+
+#[test]
+fn deserialize_config_builder() {
+    let config = |mode| {
+        QsConfig::builder()
+            .mode(mode)
+            .pair_separator(PairSeparator::Semicolon)
+            .plus_as_space(false)
+            .build()
+    };
+
+    check_result(
+        |mode| from_str("value=a+b;value=c+d", config(mode)),
+        Ok(p!(String::from("c+d"))),
+    );
+
+    // Options are kept when building from an existing config
+    check_result(
+        |mode| {
+            let config = QsConfig::new(ParseMode::UrlEncoded).pair_separator(PairSeparator::Both);
+            from_str(
+                "value=a;value=c+d",
+                QsConfigBuilder::from(config).mode(mode).build(),
+            )
+        },
+        Ok(p!(String::from("c d"))),
+    );
+}
+
 #[test]
 fn deserialize_no_value() {
     check_result(|mode| from_str("value", mode), Ok(p!("")));
@@ -368,99 +640,99 @@ fn deserialize_no_value() {
 fn deserialize_integer_overflow() {
     // u8
     check_result(
-        |mode| from_str::<Primitive<u8>>("value=-10", mode).is_err(),
+        |mode| from_str::<Primitive<u8>, _>("value=-10", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<u8>>("value=260", mode).is_err(),
+        |mode| from_str::<Primitive<u8>, _>("value=260", mode).is_err(),
         true,
     );
 
     // i8
     check_result(
-        |mode| from_str::<Primitive<i8>>("value=255", mode).is_err(),
+        |mode| from_str::<Primitive<i8>, _>("value=255", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<i8>>("value=-200", mode).is_err(),
+        |mode| from_str::<Primitive<i8>, _>("value=-200", mode).is_err(),
         true,
     );
 
     // u16
     check_result(
-        |mode| from_str::<Primitive<u16>>("value=65537", mode).is_err(),
+        |mode| from_str::<Primitive<u16>, _>("value=65537", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<u16>>("value=-200", mode).is_err(),
+        |mode| from_str::<Primitive<u16>, _>("value=-200", mode).is_err(),
         true,
     );
 
     // i16
     check_result(
-        |mode| from_str::<Primitive<i16>>("value=32768", mode).is_err(),
+        |mode| from_str::<Primitive<i16>, _>("value=32768", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<i16>>("value=-32769", mode).is_err(),
+        |mode| from_str::<Primitive<i16>, _>("value=-32769", mode).is_err(),
         true,
     );
 
     // u32
     check_result(
-        |mode| from_str::<Primitive<u32>>("value=4294967296", mode).is_err(),
+        |mode| from_str::<Primitive<u32>, _>("value=4294967296", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<u32>>("value=-200", mode).is_err(),
+        |mode| from_str::<Primitive<u32>, _>("value=-200", mode).is_err(),
         true,
     );
 
     // i32
     check_result(
-        |mode| from_str::<Primitive<i32>>("value=2147483648", mode).is_err(),
+        |mode| from_str::<Primitive<i32>, _>("value=2147483648", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<i32>>("value=-2147483649", mode).is_err(),
+        |mode| from_str::<Primitive<i32>, _>("value=-2147483649", mode).is_err(),
         true,
     );
 
     // u64
     check_result(
-        |mode| from_str::<Primitive<u64>>("value=18446744073709551616", mode).is_err(),
+        |mode| from_str::<Primitive<u64>, _>("value=18446744073709551616", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<u64>>("value=-200", mode).is_err(),
+        |mode| from_str::<Primitive<u64>, _>("value=-200", mode).is_err(),
         true,
     );
 
     // i64
     check_result(
-        |mode| from_str::<Primitive<i64>>("value=9223372036854775808", mode).is_err(),
+        |mode| from_str::<Primitive<i64>, _>("value=9223372036854775808", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<i64>>("value=-9223372036854775809", mode).is_err(),
+        |mode| from_str::<Primitive<i64>, _>("value=-9223372036854775809", mode).is_err(),
         true,
     );
 
     // invalid for integer
     check_result(
-        |mode| from_str::<Primitive<i64>>("value=1.5", mode).is_err(),
+        |mode| from_str::<Primitive<i64>, _>("value=1.5", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<i64>>("value=-1.5", mode).is_err(),
+        |mode| from_str::<Primitive<i64>, _>("value=-1.5", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<i64>>("value=1.2E3", mode).is_err(),
+        |mode| from_str::<Primitive<i64>, _>("value=1.2E3", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<i64>>("value=1.2E-3", mode).is_err(),
+        |mode| from_str::<Primitive<i64>, _>("value=1.2E-3", mode).is_err(),
         true,
     );
 }
@@ -468,32 +740,32 @@ fn deserialize_integer_overflow() {
 #[test]
 fn deserialize_invalid_number() {
     check_result(
-        |mode| from_str::<Primitive<i64>>("value=number", mode).is_err(),
+        |mode| from_str::<Primitive<i64>, _>("value=number", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<i64>>("value=123n", mode).is_err(),
+        |mode| from_str::<Primitive<i64>, _>("value=123n", mode).is_err(),
         true,
     );
 
     check_result(
-        |mode| from_str::<Primitive<f64>>("value=number", mode).is_err(),
+        |mode| from_str::<Primitive<f64>, _>("value=number", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<f64>>("value=-1.5num", mode).is_err(),
+        |mode| from_str::<Primitive<f64>, _>("value=-1.5num", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<f64>>("value=&", mode).is_err(),
+        |mode| from_str::<Primitive<f64>, _>("value=&", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<f64>>("value=1.0a1.0", mode).is_err(),
+        |mode| from_str::<Primitive<f64>, _>("value=1.0a1.0", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<f64>>("value=%2222", mode).is_err(),
+        |mode| from_str::<Primitive<f64>, _>("value=%2222", mode).is_err(),
         true,
     );
 }
@@ -502,23 +774,23 @@ fn deserialize_invalid_number() {
 #[test]
 fn deserialize_invalid_bool() {
     check_result(
-        |mode| from_str::<Primitive<bool>>("value=bla", mode).is_err(),
+        |mode| from_str::<Primitive<bool>, _>("value=bla", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<bool>>("value=0off", mode).is_err(),
+        |mode| from_str::<Primitive<bool>, _>("value=0off", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<bool>>("value=of", mode).is_err(),
+        |mode| from_str::<Primitive<bool>, _>("value=of", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<bool>>("value=onoff", mode).is_err(),
+        |mode| from_str::<Primitive<bool>, _>("value=onoff", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<bool>>("value=on%25", mode).is_err(),
+        |mode| from_str::<Primitive<bool>, _>("value=on%25", mode).is_err(),
         true,
     );
 }
@@ -528,7 +800,7 @@ fn deserialize_invalid_type() {
     // Percent encoded values should fail to deserialize for &str
     check_result(
         |mode| {
-            from_str::<Primitive<&str>>(
+            from_str::<Primitive<&str>, _>(
                 "value=%D8%A8%D8%A7%D8%A8%D8%A7%D8%A8%D8%B2%D8%B1%DA%AF",
                 mode,
             )
@@ -537,22 +809,22 @@ fn deserialize_invalid_type() {
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<&str>>("value=rum+rum", mode).is_err(),
+        |mode| from_str::<Primitive<&str>, _>("value=rum+rum", mode).is_err(),
         true,
     );
 
     // Invalid type for option
     check_result(
-        |mode| from_str::<Primitive<Option<u32>>>("value=foo", mode).is_err(),
+        |mode| from_str::<Primitive<Option<u32>>, _>("value=foo", mode).is_err(),
         true,
     );
     check_result(
-        |mode| from_str::<Primitive<Option<bool>>>("value=foo", mode).is_err(),
+        |mode| from_str::<Primitive<Option<bool>>, _>("value=foo", mode).is_err(),
         true,
     );
 
     // Only struct/map accepted as start point
-    check_result(|mode| from_str::<String>("value", mode).is_err(), true);
+    check_result(|mode| from_str::<String, _>("value", mode).is_err(), true);
 }
 
 #[test]
@@ -571,7 +843,7 @@ fn deserialize_invalid_precent_decoding() {
 
     // If the provided char is invalid, we throw error
     check_result(
-        |mode| from_str::<Primitive<String>>("value=Test%88", mode).is_err(),
+        |mode| from_str::<Primitive<String>, _>("value=Test%88", mode).is_err(),
         true,
     );
 }
@@ -580,7 +852,7 @@ fn deserialize_invalid_precent_decoding() {
 fn deserialize_error_test() {
     check_result(
         |mode| {
-            from_str::<Primitive<String>>("value=Test%88%88", mode)
+            from_str::<Primitive<String>, _>("value=Test%88%88", mode)
                 .unwrap_err()
                 .kind
         },
@@ -589,7 +861,7 @@ fn deserialize_error_test() {
 
     check_result(
         |mode| {
-            from_str::<Primitive<i32>>("value=12foo", mode)
+            from_str::<Primitive<i32>, _>("value=12foo", mode)
                 .unwrap_err()
                 .kind
         },
@@ -598,10 +870,1073 @@ fn deserialize_error_test() {
 
     check_result(
         |mode| {
-            from_str::<Primitive<bool>>("value=foo", mode)
+            from_str::<Primitive<bool>, _>("value=foo", mode)
                 .unwrap_err()
                 .kind
         },
         ErrorKind::InvalidBoolean,
     );
+
+    // The errors hold the key and the value causing them, and their spans in the input
+    check_result(
+        |mode| {
+            from_str::<Primitive<i32>, _>("other=1&value=12foo", mode)
+                .map_err(|e| (e.key_span(), e.value_span()))
+        },
+        Err((Some(8..13), Some(14..19))),
+    );
+    check_result(
+        |mode| from_str::<Primitive<i32>, _>("value=12foo", mode).map_err(|e| (e.key, e.value)),
+        Err((String::from("value"), String::from("12foo"))),
+    );
+    check_result(
+        |mode| from_str::<Primitive<Side>, _>("value=Up", mode).map_err(|e| (e.key, e.value)),
+        Err((String::from("value"), String::from("Up"))),
+    );
+    assert_eq!(
+        from_str::<Primitive<bool>, _>("value=foo", ParseMode::UrlEncoded)
+            .unwrap_err()
+            .to_string(),
+        "Error InvalidBoolean: invalid boolean foo, supported values are 1, on and true for \
+        true and 0, off and false for false in `foo` of `value`"
+    );
+}
+
+#[test]
+fn deserialize_limits() {
+    let max_pairs = |mode| QsConfig::new(mode).max_pairs(2);
+    let max_length = |mode| QsConfig::new(mode).max_length(5);
+
+    check_result(
+        |mode| from_str("value=1&&value=2", max_pairs(mode)),
+        Ok(p!(2)),
+    );
+    check_result(
+        |mode| {
+            from_str::<Primitive<u32>, _>("value=1&value=2&other=3", max_pairs(mode))
+                .map_err(|e| e.kind)
+        },
+        Err(ErrorKind::TooManyParams),
+    );
+
+    // The length is checked after decoding
+    check_result(
+        |mode| from_str("value=%61bcd+", max_length(mode)),
+        Ok(p!(String::from("abcd "))),
+    );
+    check_result(
+        |mode| {
+            from_str::<Primitive<String>, _>("value=abcdef", max_length(mode)).map_err(|e| e.kind)
+        },
+        Err(ErrorKind::LimitExceeded),
+    );
+    check_result(
+        |mode| {
+            from_str::<Primitive<String>, _>("value=a&long_key", max_length(mode))
+                .map_err(|e| e.kind)
+        },
+        Err(ErrorKind::LimitExceeded),
+    );
+}
+
+#[test]
+fn deserialize_duplicate_keys() {
+    let policy = |mode, duplicate_keys| QsConfig::new(mode).duplicate_keys(duplicate_keys);
+
+    check_result(
+        |mode| from_str("value=1&value=2", policy(mode, DuplicateKeys::Last)),
+        Ok(p!(2)),
+    );
+    check_result(
+        |mode| from_str("value=1&value=2", policy(mode, DuplicateKeys::First)),
+        Ok(p!(1)),
+    );
+    check_result(
+        |mode| {
+            from_str::<Primitive<u32>, _>("value=1&value=2", policy(mode, DuplicateKeys::Error))
+                .map_err(|e| e.kind)
+        },
+        Err(ErrorKind::DuplicateKey),
+    );
+    check_result(
+        |mode| from_str("value=1&other=2", policy(mode, DuplicateKeys::Error)),
+        Ok(p!(1)),
+    );
+}
+
+#[test]
+fn deserialize_strict_keys() {
+    let strict = |mode| QsConfig::new(mode).strict_keys(true);
+
+    check_result(|mode| from_str("value=1", strict(mode)), Ok(p!(1)));
+    check_result(
+        |mode| {
+            from_str::<Primitive<u32>, _>("value=1&other=2", strict(mode))
+                .map_err(|e| (e.kind, e.key))
+        },
+        Err((ErrorKind::UnknownKey, String::from("other"))),
+    );
+
+    // Unknown keys are ignored by default
+    check_result(|mode| from_str("value=1&other=2", mode), Ok(p!(1)));
+}
+
+#[test]
+fn deserialize_present_as_some() {
+    let present = |mode| QsConfig::new(mode).present_as_some(true);
+
+    check_result(
+        |mode| from_str("value", present(mode)),
+        Ok(p!(Some(true), Option<bool>)),
+    );
+    check_result(
+        |mode| from_str("value", present(mode)),
+        Ok(p!(Some(""), Option<&str>)),
+    );
+    check_result(
+        |mode| from_str("value=", present(mode)),
+        Ok(p!(Some(""), Option<&str>)),
+    );
+    check_result(
+        |mode| from_str("other=1", present(mode)),
+        Ok(p!(None, Option<&str>)),
+    );
+    check_result(
+        |mode| from_str("value=1", present(mode)),
+        Ok(p!(Some(1), Option<u32>)),
+    );
+}
+
+#[test]
+fn deserialize_presence() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Query<T> {
+        #[serde(default)]
+        value: Presence<T>,
+    }
+
+    // A bare key is a flag, even for types which can't be parsed from an empty value
+    check_result(
+        |mode| from_str("value", mode),
+        Ok(Query::<u32> {
+            value: Presence::Flag,
+        }),
+    );
+    check_result(
+        |mode| from_str("value=", mode),
+        Ok(Query {
+            value: Presence::Value(""),
+        }),
+    );
+    check_result(
+        |mode| from_str("other=1", mode),
+        Ok(Query::<&str> {
+            value: Presence::Absent,
+        }),
+    );
+    check_result(
+        |mode| from_str("value=1", mode),
+        Ok(Query {
+            value: Presence::Value(1),
+        }),
+    );
+    // The last value is used
+    check_result(
+        |mode| from_str("value=1&value", mode),
+        Ok(Query::<u32> {
+            value: Presence::Flag,
+        }),
+    );
+
+    let flag: Presence<u32> = Presence::Flag;
+    assert!(flag.is_present());
+    assert_eq!(flag.into_option(), Some(0));
+    assert_eq!(Presence::<u32>::Absent.into_option(), None);
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(crate = "_serde")]
+struct Flags {
+    #[serde(default)]
+    a: bool,
+    #[serde(default)]
+    b: bool,
+    #[serde(default)]
+    c: bool,
+}
+
+#[test]
+fn deserialize_flags() {
+    use std::collections::HashSet;
+
+    check_result(
+        |mode| from_str::<HashSet<String>, _>("a&b&c&a", mode),
+        Ok(["a", "b", "c"].iter().map(|s| s.to_string()).collect()),
+    );
+    check_result(
+        |mode| from_str::<HashSet<Side>, _>("Left&God", mode),
+        Ok(vec![Side::Left, Side::God].into_iter().collect()),
+    );
+    check_result(|mode| from_str::<Vec<&str>, _>("", mode), Ok(vec![]));
+
+    check_result(
+        |mode| from_str("a&c", mode),
+        Ok(Flags {
+            a: true,
+            b: false,
+            c: true,
+        }),
+    );
+}
+
+#[test]
+fn deserialize_lenient_bool() {
+    let lenient = |mode| QsConfig::new(mode).lenient_bool(true);
+
+    check_result(|mode| from_str("value=yes", lenient(mode)), Ok(p!(true)));
+    check_result(|mode| from_str("value=No", lenient(mode)), Ok(p!(false)));
+    check_result(|mode| from_str("value=ON", lenient(mode)), Ok(p!(true)));
+    check_result(|mode| from_str("value=0", lenient(mode)), Ok(p!(false)));
+    check_result(
+        |mode| from_str::<Primitive<bool>, _>("value=maybe", lenient(mode)).is_err(),
+        true,
+    );
+
+    // Only the default booleans are accepted otherwise
+    check_result(
+        |mode| from_str::<Primitive<bool>, _>("value=yes", mode).is_err(),
+        true,
+    );
+    check_result(
+        |mode| from_str::<Primitive<bool>, _>("value=ON", mode).is_err(),
+        true,
+    );
+}
+
+#[test]
+fn deserialize_lenient_numbers() {
+    let lenient = |mode| QsConfig::new(mode).lenient_numbers(true);
+
+    check_result(|mode| from_str("value=%2B42", lenient(mode)), Ok(p!(42)));
+    check_result(|mode| from_str("value=+42", lenient(mode)), Ok(p!(42)));
+    check_result(|mode| from_str("value=1_000", lenient(mode)), Ok(p!(1000)));
+    check_result(|mode| from_str("value=0x1F", lenient(mode)), Ok(p!(31)));
+    check_result(|mode| from_str("value=-0X1f", lenient(mode)), Ok(p!(-31)));
+    check_result(
+        |mode| from_str("value=1_000.5", lenient(mode)),
+        Ok(p!(1000.5)),
+    );
+    for value in [
+        "value=1__000",
+        "value=_1",
+        "value=1_",
+        "value=0x",
+        "value=0xG",
+        "value=0x%2B1F",
+        "value=0x-1F",
+    ] {
+        check_result(
+            |mode| from_str::<Primitive<i32>, _>(value, lenient(mode)).is_err(),
+            true,
+        );
+    }
+    check_result(
+        |mode| from_str::<Primitive<u8>, _>("value=0x100", lenient(mode)).is_err(),
+        true,
+    );
+
+    // Floats accept these either way
+    check_result(|mode| from_str("value=1.5e3", mode), Ok(p!(1500.0)));
+    check_result(
+        |mode| from_str::<Primitive<f64>, _>("value=inf", mode).map(|p| p.value.is_infinite()),
+        Ok(true),
+    );
+    check_result(
+        |mode| from_str::<Primitive<f64>, _>("value=nan", mode).map(|p| p.value.is_nan()),
+        Ok(true),
+    );
+
+    // Only the plain integers are accepted otherwise
+    for value in ["value=%2B42", "value=1_000", "value=0x1F"] {
+        check_result(
+            |mode| from_str::<Primitive<i32>, _>(value, mode).is_err(),
+            true,
+        );
+    }
+}
+
+#[test]
+fn deserialize_trim_whitespace() {
+    let trim = |mode| QsConfig::new(mode).trim_whitespace(true);
+
+    check_result(|mode| from_str("value=%202%20", trim(mode)), Ok(p!(2)));
+    check_result(|mode| from_str("value=+2.5+", trim(mode)), Ok(p!(2.5)));
+    check_result(|mode| from_str("value=%09true", trim(mode)), Ok(p!(true)));
+    check_result(
+        |mode| from_str("value=+a+b+", trim(mode)),
+        Ok(p!("a b".to_string())),
+    );
+    check_result(|mode| from_str("+value%20=2", trim(mode)), Ok(p!(2)));
+    check_result(
+        |mode| from_str::<Primitive<u32>, _>("value=+2+x", trim(mode)).is_err(),
+        true,
+    );
+
+    // The whitespace is kept otherwise
+    check_result(
+        |mode| from_str::<Primitive<u32>, _>("value=%202", mode).is_err(),
+        true,
+    );
+    check_result(
+        |mode| from_str("value=+a+", mode),
+        Ok(p!(" a ".to_string())),
+    );
+}
+
+#[test]
+fn deserialize_case_insensitive_enums() {
+    let insensitive = |mode| QsConfig::new(mode).case_insensitive_enums(true);
+
+    check_result(
+        |mode| from_str("value=left", insensitive(mode)),
+        Ok(p!(Side::Left)),
+    );
+    check_result(
+        |mode| from_str("value=GOD", insensitive(mode)),
+        Ok(p!(Side::God)),
+    );
+    check_result(
+        |mode| from_str("value=Right", insensitive(mode)),
+        Ok(p!(Side::Right)),
+    );
+    check_result(
+        |mode| from_str::<Primitive<Side>, _>("value=up", insensitive(mode)).is_err(),
+        true,
+    );
+
+    // Renamed variants are matched by their serialized name
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde", rename_all = "snake_case")]
+    enum Status {
+        InReview,
+    }
+    check_result(
+        |mode| from_str("value=In_Review", insensitive(mode)),
+        Ok(p!(Status::InReview)),
+    );
+
+    // The case must match otherwise
+    check_result(
+        |mode| from_str::<Primitive<Side>, _>("value=left", mode).is_err(),
+        true,
+    );
+}
+
+#[test]
+fn deserialize_numeric_enums() {
+    let numeric = |mode| QsConfig::new(mode).numeric_enums(true);
+
+    check_result(
+        |mode| from_str("value=0", numeric(mode)),
+        Ok(p!(Side::Left)),
+    );
+    check_result(|mode| from_str("value=2", numeric(mode)), Ok(p!(Side::God)));
+    check_result(
+        |mode| from_str("value=Right", numeric(mode)),
+        Ok(p!(Side::Right)),
+    );
+    check_result(
+        |mode| from_str::<Primitive<Side>, _>("value=3", numeric(mode)).is_err(),
+        true,
+    );
+    check_result(
+        |mode| from_str::<Primitive<Side>, _>("value=-1", numeric(mode)).is_err(),
+        true,
+    );
+
+    // Along with case insensitive variants
+    check_result(
+        |mode| from_str("value=god", numeric(mode).case_insensitive_enums(true)),
+        Ok(p!(Side::God)),
+    );
+
+    // Only the names are matched otherwise
+    check_result(
+        |mode| from_str::<Primitive<Side>, _>("value=0", mode).is_err(),
+        true,
+    );
+}
+
+#[test]
+fn deserialize_null_as_none() {
+    let null = |mode| QsConfig::new(mode).null_as_none(true);
+
+    check_result(
+        |mode| from_str("value=null", null(mode)),
+        Ok(p!(None, Option<u32>)),
+    );
+    check_result(
+        |mode| from_str("value=", null(mode)),
+        Ok(p!(None, Option<f64>)),
+    );
+    check_result(
+        |mode| from_str("value=", null(mode)),
+        Ok(p!(None, Option<&str>)),
+    );
+    check_result(
+        |mode| from_str("value=1", null(mode)),
+        Ok(p!(Some(1), Option<u32>)),
+    );
+
+    // Otherwise, they fail to parse as numbers
+    check_result(
+        |mode| from_str::<Primitive<Option<u32>>, _>("value=null", mode).is_err(),
+        true,
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(crate = "_serde")]
+struct SnakeCase {
+    user_id: u32,
+    created_after: Option<u32>,
+}
+
+#[test]
+fn deserialize_key_transform() {
+    use serde_querystring::camel_to_snake;
+
+    let snake = |mode| QsConfig::new(mode).key_transform(camel_to_snake);
+
+    check_result(
+        |mode| from_str("userId=5&createdAfter=10", snake(mode)),
+        Ok(SnakeCase {
+            user_id: 5,
+            created_after: Some(10),
+        }),
+    );
+    check_result(
+        |mode| from_str("user_id=5", snake(mode)),
+        Ok(SnakeCase {
+            user_id: 5,
+            created_after: None,
+        }),
+    );
+
+    // Keys are kept as they are by default
+    check_result(
+        |mode| from_str::<SnakeCase, _>("userId=5", mode).is_err(),
+        true,
+    );
+
+    assert_eq!(camel_to_snake("HTTPServer"), "http_server");
+    assert_eq!(camel_to_snake("userId2Name"), "user_id2_name");
+    assert_eq!(camel_to_snake("ID"), "id");
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(crate = "_serde")]
+struct Scoped<'a> {
+    age: u32,
+    name: Option<&'a str>,
+}
+
+#[test]
+fn deserialize_scoped() {
+    use serde_querystring::from_str_scoped;
+
+    check_result(
+        |mode| {
+            from_str_scoped(
+                "age=1&filter[age]=30&filter[name]=John&name=x",
+                "filter",
+                mode,
+            )
+        },
+        Ok(Scoped {
+            age: 30,
+            name: Some("John"),
+        }),
+    );
+    check_result(
+        |mode| from_str_scoped("age=1&filter[age]=30", "filter", mode),
+        Ok(Scoped {
+            age: 30,
+            name: None,
+        }),
+    );
+    check_result(
+        |mode| from_str_scoped::<Scoped, _>("age=1", "filter", mode).is_err(),
+        true,
+    );
+
+    // Dots separate the scope in flat modes too
+    assert_eq!(
+        from_str_scoped("filter.age=30", "filter", ParseMode::UrlEncoded),
+        Ok(Scoped {
+            age: 30,
+            name: None
+        })
+    );
+
+    // Sequences follow the parse mode
+    assert_eq!(
+        from_str_scoped("ids=1&f[value]=2&f[value]=3", "f", ParseMode::Duplicate),
+        Ok(p!(vec![2, 3]))
+    );
+    assert_eq!(
+        from_str_scoped("ids=1&f[value]=2|3", "f", ParseMode::Delimiter(b'|')),
+        Ok(p!(vec![2, 3]))
+    );
+}
+
+#[test]
+fn deserialize_tagged_enums() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde", tag = "type", rename_all = "lowercase")]
+    enum Shape {
+        Circle { radius: u32 },
+        Rect { width: f32, filled: bool },
+        Empty,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(
+        crate = "_serde",
+        tag = "type",
+        content = "data",
+        rename_all = "lowercase"
+    )]
+    enum Adjacent {
+        Id(i64),
+        Name(String),
+    }
+
+    let infer = |mode| QsConfig::new(mode).infer_types(true);
+
+    check_result(
+        |mode| from_str("type=circle&radius=3", infer(mode)),
+        Ok(Shape::Circle { radius: 3 }),
+    );
+    check_result(
+        |mode| from_str("filled=true&type=rect&width=-1.5", infer(mode)),
+        Ok(Shape::Rect {
+            width: -1.5,
+            filled: true,
+        }),
+    );
+    check_result(|mode| from_str("type=empty", infer(mode)), Ok(Shape::Empty));
+    check_result(
+        |mode| from_str("type=id&data=-7", infer(mode)),
+        Ok(Adjacent::Id(-7)),
+    );
+    check_result(
+        |mode| from_str("data=bob&type=name", infer(mode)),
+        Ok(Adjacent::Name(String::from("bob"))),
+    );
+
+    // Tagged enums as fields, in the brackets modes
+    assert_eq!(
+        from_str(
+            "value[type]=circle&value[radius]=3",
+            infer(ParseMode::Brackets)
+        ),
+        Ok(p!(Shape::Circle { radius: 3 }))
+    );
+
+    // The values are strings otherwise
+    check_result(
+        |mode| from_str::<Shape, _>("type=circle&radius=3", mode).is_err(),
+        true,
+    );
+}
+
+#[test]
+fn deserialize_untagged_enums() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde", untagged)]
+    enum StringOrVec {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde", untagged)]
+    enum IdOrName {
+        Id(u32),
+        Name(String),
+    }
+
+    check_result(
+        |mode| from_str("value=a", mode),
+        Ok(p!(StringOrVec::One(String::from("a")))),
+    );
+    check_result(
+        |mode| from_str("value=5", QsConfig::new(mode).infer_types(true)),
+        Ok(p!(IdOrName::Id(5))),
+    );
+    check_result(
+        |mode| from_str("value=bob", QsConfig::new(mode).infer_types(true)),
+        Ok(p!(IdOrName::Name(String::from("bob")))),
+    );
+
+    // Numbers are strings without inferring the types
+    check_result(
+        |mode| from_str("value=5", mode),
+        Ok(p!(IdOrName::Name(String::from("5")))),
+    );
+
+    // Repeated keys are sequences in the modes supporting them
+    let many = Ok(p!(StringOrVec::Many(vec![
+        String::from("a"),
+        String::from("b")
+    ])));
+    assert_eq!(from_str("value=a&value=b", ParseMode::Duplicate), many);
+    assert_eq!(from_str("value=a|b", ParseMode::Delimiter(b'|')), many);
+    assert_eq!(from_str("value[]=a&value[]=b", ParseMode::Brackets), many);
+    assert_eq!(
+        from_str("value[]=a", ParseMode::Brackets),
+        Ok(p!(StringOrVec::Many(vec![String::from("a")])))
+    );
+}
+
+#[test]
+fn deserialize_auto_detected() {
+    for (input, mode) in [
+        ("value[1]=b&value[0]=a", "Brackets"),
+        ("value%5b%5d=a&value%5B%5D=b", "Brackets"),
+        ("value=a&value=b", "Duplicate"),
+        ("value=a|b&other=c,d", "Delimiter(124)"),
+        ("value=a,b", "Delimiter(44)"),
+        ("value=a,b&value=c", "Duplicate"),
+        ("value=a&other", "Duplicate"),
+        ("", "Duplicate"),
+    ] {
+        assert_eq!(format!("{:?}", ParseMode::detect(input.as_bytes())), mode);
+    }
+
+    for input in ["value[]=a&value[]=b", "value=a&value=b", "value=a|b"] {
+        assert_eq!(
+            from_str_auto(input),
+            Ok(p!(vec![String::from("a"), String::from("b")]))
+        );
+    }
+
+    // Single values are never split
+    assert_eq!(from_str_auto("value=a,b"), Ok(p!(String::from("a,b"))));
+    assert_eq!(from_str_auto("value=a"), Ok(p!(vec![String::from("a")])));
+}
+
+#[test]
+fn deserialize_collect_errors() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Form {
+        age: u32,
+        name: String,
+        score: Option<f32>,
+        admin: Option<bool>,
+    }
+
+    let keys = |errors: Vec<serde_querystring::Error>| -> Vec<String> {
+        errors.into_iter().map(|e| e.key).collect()
+    };
+
+    check_result(
+        |mode| {
+            from_str_collect_errors::<Form, _>("age=x&name=bob&score=y&admin=maybe", mode)
+                .map_err(keys)
+        },
+        Err(vec![
+            "age".to_string(),
+            "score".to_string(),
+            "admin".to_string(),
+        ]),
+    );
+    check_result(
+        |mode| from_str_collect_errors("age=2&name=bob&admin=true", mode),
+        Ok(Form {
+            age: 2,
+            name: "bob".to_string(),
+            score: None,
+            admin: Some(true),
+        }),
+    );
+
+    // A missing field is reported too
+    check_result(
+        |mode| from_str_collect_errors::<Form, _>("score=y", mode).map_err(keys),
+        Err(vec!["score".to_string(), "age".to_string()]),
+    );
+
+    // The spans are of the input
+    let errors = from_str_collect_errors::<Form, _>(
+        "age=x&name=bob&score=y",
+        QsConfig::new(ParseMode::Duplicate),
+    )
+    .unwrap_err();
+    assert_eq!(errors[1].value_span(), Some(21..22));
+
+    // Dots only separate the sub keys in DotNotation mode
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Dotted {
+        #[serde(default)]
+        a: u32,
+        #[serde(rename = "a.x")]
+        a_x: Option<u32>,
+    }
+    let (dotted, errors) =
+        from_str_partial::<Dotted, _>("a=y&a.x=1", ParseMode::Duplicate).unwrap();
+    assert_eq!(dotted, Dotted { a: 0, a_x: Some(1) });
+    assert_eq!(errors.len(), 1);
+
+    // The skipped keys are limited
+    let input: Vec<String> = (0..100).map(|i| format!("k{}=x", i)).collect();
+    let errors =
+        from_str_collect_errors::<HashMap<String, u32>, _>(&input.join("&"), ParseMode::UrlEncoded)
+            .unwrap_err();
+    assert_eq!(errors.len(), 65);
+}
+
+#[test]
+fn deserialize_skip_invalid_pairs() {
+    let skip = |mode| {
+        QsConfig::new(mode)
+            .unicode_escapes(true)
+            .max_length(5)
+            .skip_invalid_pairs(true)
+    };
+
+    check_result(|mode| from_str("value=1&value=%u12", skip(mode)), Ok(p!(1)));
+    check_result(
+        |mode| from_str("value=1&value=123456", skip(mode)),
+        Ok(p!(1)),
+    );
+    check_result(
+        |mode| from_str("value=a&value=%FF", skip(mode)),
+        Ok(p!("a".to_string())),
+    );
+    check_result(
+        |mode| {
+            from_str(
+                "value=a&value=%FF",
+                skip(mode).invalid_utf8(InvalidUtf8::Replace),
+            )
+        },
+        Ok(p!("\u{FFFD}".to_string())),
+    );
+
+    // The dropped pairs are not counted in the max pairs
+    check_result(
+        |mode| from_str("value=%u12&value=1", skip(mode).max_pairs(1)),
+        Ok(p!(1)),
+    );
+
+    // They fail otherwise
+    check_result(
+        |mode| {
+            from_str::<Primitive<u32>, _>(
+                "value=1&value=%u12",
+                skip(mode).skip_invalid_pairs(false),
+            )
+            .map_err(|e| e.kind)
+        },
+        Err(ErrorKind::InvalidUnicodeEscape),
+    );
+
+    let errors = serde_querystring::invalid_pairs(
+        b"value=1&value=123456&value=%u12",
+        skip(ParseMode::UrlEncoded),
+    );
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].kind, ErrorKind::LimitExceeded);
+    assert_eq!(errors[0].value, "value=123456");
+    assert_eq!(errors[0].value_span(), Some(8..20));
+    assert_eq!(errors[1].kind, ErrorKind::InvalidUnicodeEscape);
+    assert_eq!(errors[1].index, Some(6));
+}
+
+#[test]
+fn deserialize_partial() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Search {
+        q: String,
+        #[serde(default)]
+        page: u32,
+        sort: Option<Side>,
+    }
+
+    let keys = |(search, errors): (Search, Vec<serde_querystring::Error>)| {
+        let keys: Vec<String> = errors.into_iter().map(|e| e.key).collect();
+        (search, keys)
+    };
+
+    check_result(
+        |mode| {
+            from_str_partial("q=rust&page=two&sort=Up", mode)
+                .map(keys)
+                .unwrap()
+        },
+        (
+            Search {
+                q: "rust".to_string(),
+                page: 0,
+                sort: None,
+            },
+            vec!["page".to_string(), "sort".to_string()],
+        ),
+    );
+    check_result(
+        |mode| from_str_partial("q=rust&page=2", mode).map(keys).unwrap(),
+        (
+            Search {
+                q: "rust".to_string(),
+                page: 2,
+                sort: None,
+            },
+            vec![],
+        ),
+    );
+
+    // A required field can't be skipped
+    check_result(
+        |mode| {
+            from_str_partial::<Search, _>("q=%FF&page=2", mode)
+                .map_err(|errors| errors.into_iter().map(|e| e.kind).collect::<Vec<_>>())
+                .unwrap_err()
+        },
+        vec![ErrorKind::InvalidEncoding],
+    );
+}
+
+#[test]
+fn deserialize_map_access_out_of_order() {
+    /// Calls `next_value` before `next_key`, which breaks the contract of `MapAccess`
+    #[derive(Debug, PartialEq)]
+    struct OutOfOrder;
+
+    impl<'de> _serde::Deserialize<'de> for OutOfOrder {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: _serde::Deserializer<'de>,
+        {
+            struct Visitor;
+
+            impl<'de> _serde::de::Visitor<'de> for Visitor {
+                type Value = OutOfOrder;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a map")
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: _serde::de::MapAccess<'de>,
+                {
+                    map.next_value::<String>()?;
+                    Ok(OutOfOrder)
+                }
+            }
+
+            deserializer.deserialize_map(Visitor)
+        }
+    }
+
+    check_result(
+        |mode| from_str::<OutOfOrder, _>("value=1", mode).map_err(|e| e.message),
+        Err("next_value is called before next_key".to_string()),
+    );
+
+    // In a nested map of the brackets modes
+    assert_eq!(
+        from_str::<Primitive<OutOfOrder>, _>("value[a]=1", ParseMode::Brackets)
+            .map_err(|e| e.message),
+        Err("next_value is called before next_key".to_string())
+    );
+}
+
+#[test]
+fn deserialize_borrowed() {
+    #[derive(Debug, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Borrowed<'a> {
+        #[serde(borrow)]
+        value: Cow<'a, str>,
+    }
+
+    let borrowed = |input: &'static str, mode| {
+        let value = from_str::<Borrowed, _>(input, mode).unwrap().value;
+        (matches!(value, Cow::Borrowed(_)), value.into_owned())
+    };
+
+    // Values with nothing to decode are borrowed in every mode
+    check_result(
+        |mode| borrowed("value=abc", mode),
+        (true, "abc".to_string()),
+    );
+    check_result(
+        |mode| borrowed("value=a+c", mode),
+        (false, "a c".to_string()),
+    );
+    check_result(
+        |mode| borrowed("value=a%2Cc", mode),
+        (false, "a,c".to_string()),
+    );
+
+    // The keys and values of maps, and the items of sequences
+    check_result(
+        |mode| {
+            let map: HashMap<CowStr, CowStr> = from_str("key=v&k%20ey=w", mode).unwrap();
+            (
+                map.get_key_value("key")
+                    .map(|(k, v)| (k.is_borrowed(), v.is_borrowed())),
+                map.get_key_value("k ey")
+                    .map(|(k, v)| (k.is_borrowed(), v.is_borrowed())),
+            )
+        },
+        (Some((true, true)), Some((false, true))),
+    );
+    for (mode, input) in [
+        (ParseMode::Duplicate, "value=a&value=b%20"),
+        (ParseMode::Delimiter(b'|'), "value=a|b%20"),
+        (ParseMode::Brackets, "value[]=a&value[]=b%20"),
+        (ParseMode::DotNotation, "value.0=a&value.1=b%20"),
+        (ParseMode::BracketsDelimiter(b'|'), "value[]=a|b%20"),
+    ] {
+        let values: Primitive<Vec<CowStr>> = from_str(input, mode).unwrap();
+        let borrowed: Vec<bool> = values.value.iter().map(CowStr::is_borrowed).collect();
+        assert_eq!(borrowed, [true, false]);
+        assert_eq!(values.value[1], "b ".into());
+    }
+}
+
+#[test]
+fn deserialize_owned() {
+    /// The buffer is dropped before the value is returned
+    fn read(input: &str, mode: ParseMode) -> Result<Primitive<String>, ErrorKind> {
+        let buffer = input.as_bytes().to_vec();
+        from_bytes_owned(&buffer, mode).map_err(|e| e.kind)
+    }
+
+    check_result(|mode| read("value=abc", mode), Ok(p!("abc".to_string())));
+    check_result(|mode| read("value=a%20c", mode), Ok(p!("a c".to_string())));
+    check_result(
+        |mode| from_str_owned::<Primitive<u32>, _>("value=x", mode).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidNumber),
+    );
+    assert_eq!(read("value=a", ParseMode::Rack), Ok(p!("a".to_string())));
+}
+
+#[test]
+fn deserialize_long_values() {
+    // Escapes at every offset of a value longer than a vector register
+    let mut raw = String::new();
+    let mut expected = String::new();
+    for i in 0..80 {
+        match i % 7 {
+            0 => {
+                raw.push_str("%20");
+                expected.push(' ');
+            }
+            3 => {
+                raw.push('+');
+                expected.push(' ');
+            }
+            5 => {
+                raw.push_str("%zz");
+                expected.push_str("%zz");
+            }
+            _ => {
+                raw.push('a');
+                expected.push('a');
+            }
+        }
+    }
+
+    check_result(
+        |mode| from_str(&format!("value={}&other=1", raw), mode),
+        Ok(p!(expected.clone())),
+    );
+    check_result(
+        |mode| from_str(&format!("{}=1", raw), mode),
+        Ok(map! {expected.clone() => 1}),
+    );
+
+    // Nothing to decode, and a trailing percent sign
+    let plain = "b".repeat(100);
+    check_result(
+        |mode| from_str(&format!("value={}%", plain), mode),
+        Ok(p!(format!("{}%", plain))),
+    );
+}
+
+#[test]
+fn deserialize_with_buffers() {
+    // The same buffers are reused by every call, in every mode
+    let mut buffers = QsBuffers::with_capacity(16);
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+        ParseMode::DotNotation,
+        ParseMode::BracketsDelimiter(b'|'),
+    ] {
+        let res: Primitive<String> =
+            from_str_with(&mut buffers, "value=first%20value", mode).unwrap();
+        assert_eq!(res, p!(String::from("first value")));
+
+        // Errors leave the buffers usable
+        let res: Result<Primitive<u32>, _> = from_str_with(&mut buffers, "value=%41", mode);
+        assert!(res.is_err());
+
+        let res: HashMap<String, Side> =
+            from_str_with(&mut buffers, "%73ide=Left&other=R%69ght", mode).unwrap();
+        assert_eq!(
+            res,
+            map! {"side".to_string() => Side::Left, "other".to_string() => Side::Right}
+        );
+
+        let res: Side = from_str_with(&mut buffers, "G%6Fd", mode).unwrap();
+        assert_eq!(res, Side::God);
+    }
+}
+
+#[test]
+fn deserialize_repeated_keys() {
+    // A key repeated in a row is decoded once, the others are decoded again
+    let res: Result<Primitive<Vec<u32>>, _> = from_str(
+        "v%61lue=1&v%61lue=2&value=3&v%61lue=4",
+        ParseMode::Duplicate,
+    );
+    assert_eq!(res, Ok(p!(vec![1, 2, 3, 4])));
+
+    let res: Result<HashMap<String, Vec<u32>>, _> =
+        from_str("%61=1&%61=2&%62=3&%61=4", ParseMode::Duplicate);
+    assert_eq!(
+        res,
+        Ok(map! {"a".to_string() => vec![1, 2, 4], "b".to_string() => vec![3]})
+    );
+
+    let res: Result<Primitive<Vec<u32>>, _> = from_str(
+        "v%61lue[]=1&v%61lue[]=2&value[]=3&v%61lue[]=4",
+        ParseMode::Brackets,
+    );
+    assert_eq!(res, Ok(p!(vec![1, 2, 3, 4])));
+
+    let res: Result<HashMap<String, HashMap<String, Vec<u32>>>, _> = from_str(
+        "v[%61][]=1&v[%61][]=2&v[%62][]=3&v[a][]=4",
+        ParseMode::Brackets,
+    );
+    assert_eq!(
+        res,
+        Ok(map! {"v".to_string() => map! {
+            "a".to_string() => vec![1, 2, 4],
+            "b".to_string() => vec![3],
+        }})
+    );
 }
@@ -3,7 +3,8 @@
 use std::collections::HashMap;
 
 use _serde::Deserialize;
-use serde_querystring::de::{from_bytes, ParseMode};
+use serde_querystring::de::{from_bytes, from_str, ParseMode, QsConfig};
+use serde_querystring::{parse_pairs, DelimiterQS};
 
 /// It is a helper struct we use to test primitive types
 /// as we don't support anything beside maps/structs at the root level
@@ -163,7 +164,7 @@ fn deserialize_unit_enums() {
         winner: Side,
     }
     assert_eq!(
-        from_bytes::<A>(b"looser=Left&winner=God", ParseMode::Delimiter(b'|')),
+        from_bytes::<A, _>(b"looser=Left&winner=God", ParseMode::Delimiter(b'|')),
         Ok(A {
             looser: Side::Left,
             winner: Side::God
@@ -189,22 +190,173 @@ fn deserialize_unit_enums() {
 #[test]
 fn deserialize_invalid_sequence() {
     // array length
-    assert!(
-        from_bytes::<Primitive<[usize; 3]>>(b"value=1|3|1337|999", ParseMode::Delimiter(b'|'))
-            .is_err()
-    );
+    assert!(from_bytes::<Primitive<[usize; 3]>, _>(
+        b"value=1|3|1337|999",
+        ParseMode::Delimiter(b'|')
+    )
+    .is_err());
 
     // tuple length
-    assert!(from_bytes::<Primitive<(usize, usize, usize)>>(
+    assert!(from_bytes::<Primitive<(usize, usize, usize)>, _>(
         b"1|3|1337|999",
         ParseMode::Delimiter(b'|')
     )
     .is_err());
 
     // tuple value types
-    assert!(from_bytes::<Primitive<(&str, usize, &str)>>(
+    assert!(from_bytes::<Primitive<(&str, usize, &str)>, _>(
         b"value=foo|bar|baz",
         ParseMode::Delimiter(b'|')
     )
     .is_err());
 }
+
+#[test]
+fn deserialize_multi_byte_delimiter() {
+    let config = |delimiter| QsConfig::new(ParseMode::Delimiter(b'|')).delimiter(delimiter);
+
+    assert_eq!(
+        from_str("value=1::2::3", config("::")),
+        Ok(p!(vec![1, 2, 3]))
+    );
+    assert_eq!(
+        from_str("value=a、b|c", config("、")),
+        Ok(p!(vec!["a", "b|c"]))
+    );
+    assert_eq!(from_str("value=1::2", config("::")), Ok(p!((1, 2))));
+    // A partial delimiter is part of the value
+    assert_eq!(
+        from_str("value=a:b::c:", config("::")),
+        Ok(p!(vec!["a:b", "c:"]))
+    );
+
+    // The byte of the mode is used without a delimiter
+    assert_eq!(from_str("value=1|2", config("")), Ok(p!(vec![1, 2])));
+
+    // It applies to the brackets with delimiter mode and the parsers too
+    assert_eq!(
+        from_str(
+            "value[a]=1::2",
+            QsConfig::new(ParseMode::BracketsDelimiter(b',')).delimiter("::")
+        ),
+        Ok(p!(HashMap::from([("a", vec![1, 2])])))
+    );
+    let parser = DelimiterQS::parse_with_config(b"value=x::y", b'|', &config("::"));
+    assert_eq!(
+        parser.values(b"value"),
+        Some(Some(vec!["x".as_bytes().into(), "y".as_bytes().into()]))
+    );
+    assert_eq!(parse_pairs(b"value=x::y", config("::")).len(), 2);
+}
+
+#[test]
+fn deserialize_decode_before_split() {
+    let config =
+        |delimiter| QsConfig::new(ParseMode::Delimiter(delimiter)).decode_before_split(true);
+
+    // Encoded delimiters are kept inside the values by default
+    assert_eq!(
+        from_str("value=a%2Cb,c", ParseMode::Delimiter(b',')),
+        Ok(p!(vec![String::from("a,b"), String::from("c")]))
+    );
+    assert_eq!(
+        from_str("value=a%2Cb,c%2cd", config(b',')),
+        Ok(p!(vec!["a", "b", "c", "d"]))
+    );
+    assert_eq!(
+        from_str("value=a+b%20c", config(b' ')),
+        Ok(p!(vec!["a", "b", "c"]))
+    );
+
+    // Non ASCII delimiters are usually encoded
+    assert_eq!(
+        from_str(
+            "value=a%E3%80%81b、c",
+            QsConfig::new(ParseMode::BracketsDelimiter(b','))
+                .delimiter("、")
+                .decode_before_split(true)
+        ),
+        Ok(p!(vec!["a", "b", "c"]))
+    );
+    assert_eq!(parse_pairs(b"value=x%7Cy", config(b'|')).len(), 2);
+}
+
+#[test]
+fn deserialize_escaped_delimiter() {
+    let config = |delimiter| QsConfig::new(ParseMode::Delimiter(delimiter)).delimiter_escape(b'\\');
+
+    assert_eq!(
+        from_str(r"value=a\,b,c", config(b',')),
+        Ok(p!(vec![String::from("a,b"), String::from("c")]))
+    );
+    assert_eq!(
+        from_str(r"value=a\\,b\c", config(b',')),
+        Ok(p!(vec![String::from(r"a\"), String::from(r"b\c")]))
+    );
+
+    // Escaped delimiters are not split without the escape
+    assert_eq!(
+        from_str(r"value=a\,b", ParseMode::Delimiter(b',')),
+        Ok(p!(vec![String::from(r"a\"), String::from("b")]))
+    );
+
+    // Encoded escapes work too when the values are decoded before being split
+    assert_eq!(
+        from_str("value=a%5C%2Cb,c", config(b',').decode_before_split(true)),
+        Ok(p!(vec![String::from("a,b"), String::from("c")]))
+    );
+    assert_eq!(
+        from_str(
+            r"value=a\::b::c",
+            QsConfig::new(ParseMode::BracketsDelimiter(b','))
+                .delimiter("::")
+                .delimiter_escape(b'\\')
+        ),
+        Ok(p!(vec![String::from("a::b"), String::from("c")]))
+    );
+
+    let pairs = parse_pairs(br"value=a\|b|c", config(b'|'));
+    assert_eq!(pairs.len(), 2);
+    assert_eq!(pairs[0].1, "a|b");
+
+    let qs = DelimiterQS::parse_with_config(br"value=a\,b,c", b',', &config(b','));
+    assert_eq!(
+        qs.values(b"value"),
+        Some(Some(vec![b"a,b".to_vec().into(), b"c".to_vec().into()]))
+    );
+}
+
+#[test]
+fn deserialize_empty_as_empty_seq() {
+    // The delimiter mode always gives an empty sequence
+    assert_eq!(
+        from_str("value=", ParseMode::Delimiter(b',')),
+        Ok(p!(Vec::<u32>::new()))
+    );
+    assert_eq!(
+        from_str("value=", ParseMode::BracketsDelimiter(b',')),
+        Ok(p!(vec![String::new()]))
+    );
+    assert!(from_str::<Primitive<Vec<u32>>, _>("value=", ParseMode::Duplicate).is_err());
+
+    for mode in [
+        ParseMode::BracketsDelimiter(b','),
+        ParseMode::Duplicate,
+        ParseMode::Brackets,
+    ] {
+        let config = QsConfig::new(mode).empty_as_empty_seq(true);
+
+        assert_eq!(from_str("value=", config), Ok(p!(Vec::<u32>::new())));
+        assert_eq!(from_str("value", config), Ok(p!(Vec::<String>::new())));
+        assert_eq!(from_str("value=1", config), Ok(p!(vec![1])));
+
+        // Tuples keep their length
+        assert_eq!(from_str("value=", config), Ok(p!((String::new(),))));
+    }
+
+    let config = QsConfig::new(ParseMode::Duplicate).empty_as_empty_seq(true);
+    assert_eq!(
+        from_str("value=&value=", config),
+        Ok(p!(vec![String::new(), String::new()]))
+    );
+}
@@ -1,7 +1,8 @@
 //! These tests are meant for the `DuplicateQS` method
 
 use _serde::Deserialize;
-use serde_querystring::de::{from_bytes, ParseMode};
+use serde_querystring::de::{from_bytes, ErrorKind, ParseMode};
+use serde_querystring::{DuplicateKeys, QsConfig};
 
 /// It is a helper struct we use to test primitive types
 /// as we don't support anything beside maps/structs at the root level
@@ -156,23 +157,37 @@ fn deserialize_decoded_keys() {
 #[test]
 fn deserialize_invalid_sequence() {
     // array length
-    assert!(from_bytes::<Primitive<[usize; 3]>>(
+    assert!(from_bytes::<Primitive<[usize; 3]>, _>(
         b"value=1&value=3&value=1337&value=999",
         ParseMode::Duplicate
     )
     .is_err());
 
     // tuple length
-    assert!(from_bytes::<Primitive<(usize, usize, usize)>>(
+    assert!(from_bytes::<Primitive<(usize, usize, usize)>, _>(
         b"value=1&value=3&value=1337&value=999",
         ParseMode::Duplicate
     )
     .is_err());
 
     // tuple value types
-    assert!(from_bytes::<Primitive<(&str, usize, &str)>>(
+    assert!(from_bytes::<Primitive<(&str, usize, &str)>, _>(
         b"value=foo&value=bar&value=baz",
         ParseMode::Duplicate
     )
     .is_err());
 }
+
+#[test]
+fn deserialize_duplicate_keys_error() {
+    let config = QsConfig::new(ParseMode::Duplicate).duplicate_keys(DuplicateKeys::Error);
+
+    // Sequences still get all the values
+    assert_eq!(from_bytes(b"value=1&value=2", config), Ok(p!(vec![1, 2])));
+    assert_eq!(
+        from_bytes::<Primitive<u32>, _>(b"value=1&value=2", config)
+            .unwrap_err()
+            .kind,
+        ErrorKind::DuplicateKey
+    );
+}
@@ -0,0 +1,74 @@
+//! These tests are meant for the C interface of the `ffi` feature
+#![cfg(feature = "ffi")]
+
+use std::ptr;
+
+use serde_querystring::ffi::*;
+
+/// Calls the parser, and returns the status and the data of the result as a string
+fn parse(input: &[u8], mode: u32) -> (i32, Option<String>) {
+    unsafe {
+        let result = qs_parse_to_json(input.as_ptr(), input.len(), mode);
+        let data = match result.data.is_null() {
+            true => None,
+            false => {
+                let data = std::slice::from_raw_parts(result.data, result.len);
+                Some(String::from_utf8(data.to_vec()).unwrap())
+            }
+        };
+        let status = result.status;
+        qs_result_free(result);
+        (status, data)
+    }
+}
+
+#[test]
+fn parse_to_json() {
+    assert_eq!(
+        parse(b"a=1&b=x+y", QS_MODE_URLENCODED),
+        (QS_OK, Some(String::from(r#"{"a":"1","b":"x y"}"#)))
+    );
+    assert_eq!(
+        parse(b"tags[]=a&tags[]=b&user[name]=john", QS_MODE_BRACKETS),
+        (
+            QS_OK,
+            Some(String::from(r#"{"tags":["a","b"],"user":{"name":"john"}}"#))
+        )
+    );
+    assert_eq!(
+        parse(b"ids=1|2", QS_MODE_DELIMITER | (u32::from(b'|') << 8)),
+        (QS_OK, Some(String::from(r#"{"ids":["1","2"]}"#)))
+    );
+    assert_eq!(
+        parse(b"", QS_MODE_DUPLICATE),
+        (QS_OK, Some(String::from("{}")))
+    );
+}
+
+#[test]
+fn parse_to_json_errors() {
+    assert_eq!(
+        parse(b"a=1", QS_MODE_PHP + 1),
+        (QS_ERROR_INVALID_MODE, None)
+    );
+
+    // A delimiter mode needs its delimiter
+    assert_eq!(parse(b"a=1", QS_MODE_DELIMITER).0, QS_ERROR_INVALID_MODE);
+
+    // The message of the parse errors is returned
+    let (status, message) = parse(b"a=%FF", QS_MODE_URLENCODED);
+    assert_eq!(status, QS_ERROR_PARSE);
+    assert!(message.unwrap().contains("utf-8"));
+
+    // A null input is only valid when empty
+    unsafe {
+        let result = qs_parse_to_json(ptr::null(), 0, QS_MODE_URLENCODED);
+        assert_eq!(result.status, QS_OK);
+        qs_result_free(result);
+
+        let result = qs_parse_to_json(ptr::null(), 1, QS_MODE_URLENCODED);
+        assert_eq!(result.status, QS_ERROR_NULL_INPUT);
+        assert!(result.data.is_null());
+        qs_result_free(result);
+    }
+}
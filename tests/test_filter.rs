@@ -0,0 +1,81 @@
+//! These tests are meant for the `Filter` type
+
+use std::collections::BTreeMap;
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::{from_str, to_string, Comparison, ErrorKind, Filter, ParseMode};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(crate = "_serde")]
+struct Filters {
+    #[serde(default)]
+    price: Filter<u32>,
+    #[serde(default)]
+    name: Filter<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(crate = "_serde")]
+struct Query {
+    filter: Filters,
+}
+
+#[test]
+fn deserialize_filter() {
+    let query: Query = from_str(
+        "filter[price][gte]=10&filter[price][lte]=99&filter[price][ne]=50&filter[name][like]=foo%25",
+        ParseMode::Brackets,
+    )
+    .unwrap();
+    assert_eq!(
+        *query.filter.price,
+        [Comparison::Gte(10), Comparison::Lte(99), Comparison::Ne(50)]
+    );
+    assert_eq!(*query.filter.name, [Comparison::Like(String::from("foo%"))]);
+    assert_eq!(query.filter.name[0].operator(), "like");
+
+    let query: Query =
+        from_str("filter[price]=10&filter[name][eq]=bar", ParseMode::Brackets).unwrap();
+    assert_eq!(*query.filter.price, [Comparison::Eq(10)]);
+    assert_eq!(*query.filter.name, [Comparison::Eq(String::from("bar"))]);
+
+    for input in [
+        "filter[price][in]=1,2,3",
+        "filter[price][in][]=1&filter[price][in][]=2&filter[price][in][]=3",
+        "filter[price][in][0]=1&filter[price][in][1]=2&filter[price][in][2]=3",
+    ] {
+        let query: Query = from_str(input, ParseMode::Brackets).unwrap();
+        assert_eq!(*query.filter.price, [Comparison::In(vec![1, 2, 3])]);
+    }
+
+    let filters: BTreeMap<String, Filter<String>> =
+        from_str("status[ne]=draft&author=john", ParseMode::Brackets).unwrap();
+    assert_eq!(*filters["status"], [Comparison::Ne(String::from("draft"))]);
+    assert_eq!(*filters["author"], [Comparison::Eq(String::from("john"))]);
+}
+
+#[test]
+fn deserialize_invalid_filter() {
+    let error = from_str::<Query, _>("filter[price][between]=1", ParseMode::Brackets).unwrap_err();
+    assert_eq!(error.message, "`between` is not a filter operator");
+
+    let error = from_str::<Query, _>("filter[price][gt]=cheap", ParseMode::Brackets).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Other);
+}
+
+#[test]
+fn serialize_filter() {
+    let query = Query {
+        filter: Filters {
+            price: Filter::new(vec![Comparison::Gt(10), Comparison::In(vec![20, 30])]),
+            name: Filter::new(vec![Comparison::Like(String::from("a%"))]),
+        },
+    };
+
+    let encoded = to_string(&query, ParseMode::Brackets).unwrap();
+    assert_eq!(
+        encoded,
+        "filter[price][gt]=10&filter[price][in]=20,30&filter[name][like]=a%25"
+    );
+    assert_eq!(from_str(&encoded, ParseMode::Brackets), Ok(query));
+}
@@ -0,0 +1,87 @@
+//! These tests are meant for the `Flattened` type
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::{from_str, to_string, Flattened, ParseMode};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Pagination {
+    page: u32,
+    per_page: Option<u32>,
+    all: Option<bool>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Sorting {
+    sort: String,
+    ids: Option<Vec<u32>>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Request {
+    #[serde(flatten)]
+    pagination: Flattened<Pagination>,
+    #[serde(flatten)]
+    sorting: Flattened<Sorting>,
+}
+
+#[test]
+fn flattened_deserialize() {
+    let expected = Request {
+        pagination: Flattened(Pagination {
+            page: 2,
+            per_page: Some(10),
+            all: Some(true),
+        }),
+        sorting: Flattened(Sorting {
+            sort: String::from("123"),
+            ids: None,
+        }),
+    };
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+        ParseMode::DotNotation,
+        ParseMode::BracketsDelimiter(b'|'),
+    ] {
+        assert_eq!(
+            from_str::<Request, _>("page=2&per_page=10&sort=123&all=on", mode).unwrap(),
+            expected
+        );
+    }
+
+    // Sequences follow the parse mode
+    let request: Request = from_str("page=1&sort=a&ids=1&ids=2", ParseMode::Duplicate).unwrap();
+    assert_eq!(request.sorting.ids, Some(vec![1, 2]));
+
+    let request: Request = from_str("page=1&sort=a&ids=1|2", ParseMode::Delimiter(b'|')).unwrap();
+    assert_eq!(request.sorting.ids, Some(vec![1, 2]));
+
+    assert!(from_str::<Request, _>("page=x&sort=a", ParseMode::UrlEncoded).is_err());
+    assert!(from_str::<Request, _>("sort=a", ParseMode::UrlEncoded).is_err());
+}
+
+#[test]
+fn flattened_serialize() {
+    let request = Request {
+        pagination: Flattened(Pagination {
+            page: 2,
+            per_page: None,
+            all: Some(true),
+        }),
+        sorting: Flattened(Sorting {
+            sort: String::from("name"),
+            ids: None,
+        }),
+    };
+
+    assert_eq!(
+        to_string(&request, ParseMode::UrlEncoded).unwrap(),
+        "page=2&all=true&sort=name"
+    );
+}
@@ -0,0 +1,74 @@
+//! These tests are meant for the key order of maps, with the `indexmap` feature
+#![cfg(feature = "indexmap")]
+
+use _serde::Deserialize;
+use serde_querystring::{from_str, IndexMap, ParseMode};
+
+fn keys<V>(map: &IndexMap<String, V>) -> Vec<&str> {
+    map.keys().map(String::as_str).collect()
+}
+
+#[test]
+fn deserialize_keys_in_order() {
+    let modes = [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+        ParseMode::DotNotation,
+        ParseMode::BracketsDelimiter(b','),
+    ];
+    for mode in modes {
+        let map: IndexMap<String, String> = from_str("z=1&a=2&m=3&b=4", mode).unwrap();
+        assert_eq!(keys(&map), ["z", "a", "m", "b"], "{:?}", mode);
+    }
+
+    let map: IndexMap<String, String> = from_str(";z=1;a=2", ParseMode::Matrix).unwrap();
+    assert_eq!(keys(&map), ["z", "a"]);
+}
+
+#[test]
+fn deserialize_repeated_keys_in_order() {
+    // A repeated key keeps the position of its first pair
+    let map: IndexMap<String, String> = from_str("z=1&a=2&z=3", ParseMode::UrlEncoded).unwrap();
+    assert_eq!(keys(&map), ["z", "a"]);
+    assert_eq!(map["z"], "3");
+
+    let map: IndexMap<String, Vec<u32>> = from_str("z=1&a=2&z=3", ParseMode::Duplicate).unwrap();
+    assert_eq!(keys(&map), ["z", "a"]);
+    assert_eq!(map["z"], [1, 3]);
+}
+
+#[test]
+fn deserialize_sub_keys_in_order() {
+    #[derive(Debug, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Query {
+        filter: IndexMap<String, IndexMap<String, String>>,
+    }
+
+    let input = "filter[price][lte]=10&filter[name][eq]=x&filter[price][gte]=1";
+    let query: Query = from_str(input, ParseMode::Brackets).unwrap();
+    assert_eq!(keys(&query.filter), ["price", "name"]);
+    assert_eq!(keys(&query.filter["price"]), ["lte", "gte"]);
+
+    let input = "filter.price.lte=10&filter.name.eq=x&filter.price.gte=1";
+    let query: Query = from_str(input, ParseMode::DotNotation).unwrap();
+    assert_eq!(keys(&query.filter), ["price", "name"]);
+    assert_eq!(keys(&query.filter["price"]), ["lte", "gte"]);
+}
+
+#[test]
+fn deserialize_tree_modes_in_order() {
+    // Rack and Php build a tree of values first, whose maps keep the order too
+    let map: IndexMap<String, String> = from_str("z=1&a=2", ParseMode::Rack).unwrap();
+    assert_eq!(keys(&map), ["z", "a"]);
+
+    let map: IndexMap<String, String> = from_str("z=1&a=2", ParseMode::Php).unwrap();
+    assert_eq!(keys(&map), ["z", "a"]);
+
+    let map: IndexMap<String, IndexMap<String, String>> =
+        from_str("u[z]=1&u[a]=2&u[z]=3", ParseMode::Rack).unwrap();
+    assert_eq!(keys(&map["u"]), ["z", "a"]);
+    assert_eq!(map["u"]["z"], "3");
+}
@@ -0,0 +1,118 @@
+//! These tests are meant for the conversions of the `json` feature
+#![cfg(feature = "json")]
+
+use _serde::Deserialize;
+use _serde_json::json;
+use serde_querystring::json::{from_json, to_json};
+use serde_querystring::{from_str, ErrorKind, NoneStyle, ParseMode, SerializerConfig};
+
+#[test]
+fn json_from_query() {
+    assert_eq!(
+        to_json(b"a=1&a=2&b=x+y&c", ParseMode::Duplicate).unwrap(),
+        json!({ "a": ["1", "2"], "b": "x y", "c": "" })
+    );
+    assert_eq!(
+        to_json(b"a=1|2", ParseMode::Delimiter(b'|')).unwrap(),
+        json!({ "a": ["1", "2"] })
+    );
+    assert_eq!(
+        to_json(
+            b"user[name]=John&user[langs][0]=go&user[langs][1]=rust",
+            ParseMode::Brackets
+        )
+        .unwrap(),
+        json!({ "user": { "name": "John", "langs": ["go", "rust"] } })
+    );
+}
+
+#[test]
+fn json_to_query() {
+    let value = json!({
+        "page": 2,
+        "price": 1.5,
+        "active": true,
+        "tags": ["a", "b c"],
+        "filter": null,
+    });
+
+    assert_eq!(
+        from_json(&value, ParseMode::Duplicate).unwrap(),
+        "active=true&page=2&price=1.5&tags=a&tags=b+c"
+    );
+    let config = SerializerConfig::new(ParseMode::Duplicate).none_style(NoneStyle::Empty);
+    assert_eq!(
+        from_json(&json!({ "filter": null }), config).unwrap(),
+        "filter="
+    );
+    assert_eq!(
+        from_json(
+            &json!({ "user": { "name": "John" }, "ids": [1, 2] }),
+            ParseMode::Brackets
+        )
+        .unwrap(),
+        "ids[0]=1&ids[1]=2&user[name]=John"
+    );
+
+    // Only objects are supported at the top level
+    assert_eq!(
+        from_json(&json!([1, 2]), ParseMode::Brackets)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidType
+    );
+    // Nested objects are only supported in brackets mode
+    assert_eq!(
+        from_json(&json!({ "user": { "name": "John" } }), ParseMode::Duplicate)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidType
+    );
+}
+
+#[test]
+fn json_roundtrip() {
+    let query = "ids[0]=1&ids[1]=2&user[name]=John";
+    let json = to_json(query.as_bytes(), ParseMode::Brackets).unwrap();
+    assert_eq!(from_json(&json, ParseMode::Brackets).unwrap(), query);
+}
+
+#[test]
+fn json_errors() {
+    #[derive(Debug, Deserialize)]
+    #[serde(crate = "_serde")]
+    #[allow(dead_code)]
+    struct Filter {
+        age: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(crate = "_serde")]
+    #[allow(dead_code)]
+    struct Query {
+        filter: Filter,
+    }
+
+    let error = from_str::<Query, _>("filter[age]=ten", ParseMode::Brackets).unwrap_err();
+    assert_eq!(
+        _serde_json::to_value(&error).unwrap(),
+        json!({
+            "kind": "InvalidNumber",
+            "key": "filter[age]",
+            "message": error.message,
+            "position": 12,
+        })
+    );
+
+    // The position is unknown for the errors of the whole query string
+    let error = from_str::<Query, _>("", ParseMode::Brackets).unwrap_err();
+    assert_eq!(
+        _serde_json::to_value(&error).unwrap(),
+        json!({
+            "kind": "Other",
+            "key": "filter",
+            "message": "missing field `filter`",
+            "position": null,
+        })
+    );
+}
@@ -0,0 +1,84 @@
+//! These tests are meant for the `jsonapi` feature
+#![cfg(feature = "jsonapi")]
+
+use std::collections::HashMap;
+
+use _serde::Deserialize;
+use serde_querystring::jsonapi::{JsonApiQuery, Page};
+use serde_querystring::{from_str, ErrorKind, ParseMode, SortDirection, SortField};
+
+#[test]
+fn parse_jsonapi_query() {
+    let query: JsonApiQuery =
+        "fields[articles]=title,body&fields[people]=&include=author,comments.author\
+         &page[number]=3&page[size]=10&sort=-created,title&filter[tag]=x"
+            .parse()
+            .unwrap();
+
+    assert_eq!(query.fields("articles").unwrap(), ["title", "body"]);
+    assert_eq!(query.fields("people").unwrap(), [] as [String; 0]);
+    assert!(query.fields("comments").is_none());
+
+    assert_eq!(query.include, vec!["author", "comments.author"]);
+    assert!(query.includes("comments"));
+    assert!(query.includes("comments.author"));
+    assert!(!query.includes("comment"));
+
+    assert_eq!(
+        query.page,
+        Page {
+            number: Some(3),
+            size: Some(10)
+        }
+    );
+    assert_eq!(
+        query.sort,
+        vec![
+            SortField {
+                field: String::from("created"),
+                direction: SortDirection::Descending
+            },
+            SortField {
+                field: String::from("title"),
+                direction: SortDirection::Ascending
+            },
+        ]
+    );
+    assert_eq!(query.sort[0].to_string(), "-created");
+
+    assert_eq!(JsonApiQuery::from_bytes(b""), Ok(JsonApiQuery::default()));
+}
+
+#[test]
+fn parse_invalid_jsonapi_query() {
+    for (input, kind) in [
+        (&b"sort=-"[..], ErrorKind::InvalidType),
+        (b"fields=title", ErrorKind::InvalidType),
+        (b"page[number]=first", ErrorKind::InvalidNumber),
+    ] {
+        assert_eq!(JsonApiQuery::from_bytes(input).unwrap_err().kind, kind);
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+struct Query {
+    filter: HashMap<String, String>,
+    #[serde(flatten)]
+    jsonapi: JsonApiQuery,
+}
+
+#[test]
+fn deserialize_jsonapi_query() {
+    let query: JsonApiQuery = from_str("include=author&sort=title", ParseMode::Brackets).unwrap();
+    assert_eq!(query.include, vec!["author"]);
+
+    let query: Query = from_str(
+        "filter[tag]=x&sort=-title,id&page[size]=5",
+        ParseMode::BracketsDelimiter(b','),
+    )
+    .unwrap();
+    assert_eq!(query.filter["tag"], "x");
+    assert_eq!(query.jsonapi.sort.len(), 2);
+    assert_eq!(query.jsonapi.page.size, Some(5));
+}
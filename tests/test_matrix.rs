@@ -0,0 +1,96 @@
+//! These tests are meant for the `Matrix` mode
+
+use std::collections::HashMap;
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::de::{from_str, ParseMode, QsConfig};
+use serde_querystring::ser::{to_string, QueryStringBuilder};
+use serde_querystring::{from_bytes_value, parse_pairs, PairSeparator, Value};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Car {
+    color: String,
+    size: Vec<u32>,
+    brand: Option<String>,
+}
+
+#[test]
+fn deserialize_matrix() {
+    let expected = Car {
+        color: String::from("dark red"),
+        size: vec![10, 20],
+        brand: None,
+    };
+
+    assert_eq!(
+        from_str(";color=dark%20red;size=10,20", ParseMode::Matrix),
+        Ok(expected)
+    );
+    // The leading `;` is optional, and ampersands are part of the values
+    assert_eq!(
+        from_str::<Car, _>("color=a&b;size=1;brand=c%2Cd", ParseMode::Matrix),
+        Ok(Car {
+            color: String::from("a&b"),
+            size: vec![1],
+            brand: Some(String::from("c,d")),
+        })
+    );
+
+    // The leading `;` is not an empty key
+    let map: HashMap<String, String> = from_str(";color=red", ParseMode::Matrix).unwrap();
+    assert_eq!(map.len(), 1);
+
+    let config = QsConfig::builder().mode(ParseMode::Matrix).build();
+    assert_eq!(
+        from_str::<Car, _>(";color=red;size=", config).unwrap().size,
+        Vec::<u32>::new()
+    );
+
+    // The separator can still be changed
+    let config = QsConfig::new(ParseMode::Matrix).pair_separator(PairSeparator::Ampersand);
+    assert_eq!(
+        from_str::<Car, _>("color=red;blue&size=1", config)
+            .unwrap()
+            .color,
+        "red;blue"
+    );
+}
+
+#[test]
+fn parse_matrix() {
+    let pairs = parse_pairs(b";color=red;size=10,20", ParseMode::Matrix);
+    assert_eq!(pairs.len(), 3);
+    assert_eq!(pairs[2].0, "size");
+    assert_eq!(pairs[2].1, "20");
+
+    let value = from_bytes_value(b";color=red;size=10,20", ParseMode::Matrix).unwrap();
+    assert_eq!(value.get("color").and_then(Value::as_str), Some("red"));
+    assert_eq!(
+        value.get("size").and_then(Value::as_sequence).map(Vec::len),
+        Some(2)
+    );
+}
+
+#[test]
+fn serialize_matrix() {
+    let car = Car {
+        color: String::from("red;blue"),
+        size: vec![10, 20],
+        brand: None,
+    };
+
+    let matrix = to_string(&car, ParseMode::Matrix).unwrap();
+    assert_eq!(matrix, ";color=red%3Bblue;size=10,20");
+    assert_eq!(from_str(&matrix, ParseMode::Matrix), Ok(car));
+
+    let matrix = QueryStringBuilder::from_query(";color=red;brand=foo", ParseMode::Matrix)
+        .set(&Car {
+            color: String::from("blue"),
+            size: vec![1],
+            brand: None,
+        })
+        .unwrap()
+        .build();
+    assert_eq!(matrix, ";color=blue;brand=foo;size=1");
+}
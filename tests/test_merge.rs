@@ -0,0 +1,268 @@
+//! These tests are meant for merging and comparing query strings
+use serde_querystring::{
+    diff, merge, DuplicateKeys, MergePolicy, ParseMode, QsConfig, QueryChange,
+};
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+#[test]
+fn merge_replace() {
+    let query = "page=1&q=a&q=b&sort=name";
+    assert_eq!(
+        merge(
+            query,
+            "q=c&page=2",
+            MergePolicy::Replace,
+            ParseMode::Duplicate
+        ),
+        "page=2&q=c&sort=name"
+    );
+    assert_eq!(
+        merge(
+            query,
+            "q=c&q=d&limit=10",
+            MergePolicy::Replace,
+            ParseMode::Duplicate
+        ),
+        "page=1&q=c&q=d&sort=name&limit=10"
+    );
+
+    // The other pairs are kept as they are
+    assert_eq!(
+        merge(
+            "a=%7e&flag&b=x+y",
+            "b=2",
+            MergePolicy::Replace,
+            ParseMode::UrlEncoded
+        ),
+        "a=%7e&flag&b=2"
+    );
+    // Keys are compared once decoded
+    assert_eq!(
+        merge(
+            "a%20b=1&c=2",
+            "a+b=3",
+            MergePolicy::Replace,
+            ParseMode::UrlEncoded
+        ),
+        "a+b=3&c=2"
+    );
+    assert_eq!(
+        merge("", "a=1", MergePolicy::Replace, ParseMode::UrlEncoded),
+        "a=1"
+    );
+}
+
+#[test]
+fn merge_replace_brackets() {
+    let query = "filter[age]=30&filter[name]=John&tags[0]=a&tags[1]=b&page=3";
+
+    assert_eq!(
+        merge(
+            query,
+            "filter[age]=40",
+            MergePolicy::Replace,
+            ParseMode::Brackets
+        ),
+        "filter[age]=40&filter[name]=John&tags[0]=a&tags[1]=b&page=3"
+    );
+    // Keys replace their sub keys, and the other way around
+    assert_eq!(
+        merge(
+            query,
+            "tags[]=c&filter=none",
+            MergePolicy::Replace,
+            ParseMode::Brackets
+        ),
+        "filter=none&tags[]=c&page=3"
+    );
+    assert_eq!(
+        merge(
+            "page=3&sort=asc",
+            "page[size]=10",
+            MergePolicy::Replace,
+            ParseMode::Brackets
+        ),
+        "page[size]=10&sort=asc"
+    );
+    assert_eq!(
+        merge(
+            "filter%5Bage%5D=30",
+            "filter[age]=40",
+            MergePolicy::Replace,
+            ParseMode::Brackets
+        ),
+        "filter[age]=40"
+    );
+    assert_eq!(
+        merge(
+            "user.name=a&page=1",
+            "user[name]=b",
+            MergePolicy::Replace,
+            ParseMode::DotNotation
+        ),
+        "user[name]=b&page=1"
+    );
+}
+
+#[test]
+fn merge_append() {
+    assert_eq!(
+        merge(
+            "q=a&page=1&q=b",
+            "q=c",
+            MergePolicy::Append,
+            ParseMode::Duplicate
+        ),
+        "q=a&page=1&q=b&q=c"
+    );
+    assert_eq!(
+        merge(
+            "tags[]=a&page=1",
+            "tags[]=b&sort=asc",
+            MergePolicy::Append,
+            ParseMode::Brackets
+        ),
+        "tags[]=a&tags[]=b&page=1&sort=asc"
+    );
+
+    // Keys with a single value are replaced
+    assert_eq!(
+        merge("a=1&b=2", "a=3", MergePolicy::Append, ParseMode::UrlEncoded),
+        "a=3&b=2"
+    );
+    assert_eq!(
+        merge(
+            "tags[]=a&page=1",
+            "tags[]=b&page=2",
+            MergePolicy::Append,
+            ParseMode::Rack
+        ),
+        "tags[]=a&tags[]=b&page=2"
+    );
+
+    // Delimited values are joined
+    assert_eq!(
+        merge(
+            "ids=1|2&a=",
+            "ids=3|4&a=x",
+            MergePolicy::Append,
+            ParseMode::Delimiter(b'|')
+        ),
+        "ids=1|2|3|4&a=x"
+    );
+    assert_eq!(
+        merge(
+            ";color=red;size=10",
+            "size=20",
+            MergePolicy::Append,
+            ParseMode::Matrix
+        ),
+        ";color=red;size=10,20"
+    );
+    let config = QsConfig::new(ParseMode::Delimiter(b',')).delimiter("::");
+    assert_eq!(
+        merge("ids=1::2", "ids=3", MergePolicy::Append, config),
+        "ids=1::2::3"
+    );
+}
+
+#[test]
+fn merge_matrix() {
+    assert_eq!(
+        merge(";a=1;b=2", ";a=3", MergePolicy::Replace, ParseMode::Matrix),
+        ";a=3;b=2"
+    );
+    assert_eq!(
+        merge("a=1", "b=2", MergePolicy::KeepExisting, ParseMode::Matrix),
+        ";a=1;b=2"
+    );
+    assert_eq!(
+        merge("", "a=1", MergePolicy::Replace, ParseMode::Matrix),
+        ";a=1"
+    );
+    assert_eq!(merge("", "", MergePolicy::Replace, ParseMode::Matrix), "");
+}
+
+#[test]
+fn merge_keep_existing() {
+    let query = "page=3&filter[age]=30";
+    assert_eq!(
+        merge(
+            query,
+            "page=1&filter=all&sort=asc&sort=desc",
+            MergePolicy::KeepExisting,
+            ParseMode::Brackets
+        ),
+        "page=3&filter[age]=30&sort=asc&sort=desc"
+    );
+}
+
+#[test]
+fn diff_changes() {
+    let changes = diff(
+        "page=1&q=a&q=b&sort=name",
+        "q=a&q=c&page=1&limit=10",
+        ParseMode::Duplicate,
+    );
+    assert_eq!(
+        changes,
+        vec![
+            QueryChange::Changed {
+                key: "q".to_string(),
+                old: strings(&["a", "b"]),
+                new: strings(&["a", "c"]),
+            },
+            QueryChange::Removed {
+                key: "sort".to_string(),
+                values: strings(&["name"]),
+            },
+            QueryChange::Added {
+                key: "limit".to_string(),
+                values: strings(&["10"]),
+            },
+        ]
+    );
+
+    assert!(diff("a=x+y&b", "b=&a=x%20y", ParseMode::Duplicate).is_empty());
+}
+
+#[test]
+fn diff_modes() {
+    // A single value is kept in UrlEncoded mode
+    assert!(diff("a=1&a=2", "a=2", ParseMode::UrlEncoded).is_empty());
+    assert_eq!(diff("a=1&a=2", "a=2", ParseMode::Duplicate).len(), 1);
+
+    let config = QsConfig::new(ParseMode::UrlEncoded).duplicate_keys(DuplicateKeys::First);
+    assert!(diff("a=1&a=2", "a=1", config).is_empty());
+
+    assert!(diff("ids=1|2", "ids=1&ids=1|2", ParseMode::Delimiter(b'|')).is_empty());
+    assert!(diff("a[b]=1&c[]=2", "a%5Bb%5D=1&c=2", ParseMode::Brackets).is_empty());
+    assert!(diff("a.b=1", "a[b]=1", ParseMode::DotNotation).is_empty());
+
+    // Sequences are compared by their items, whether they have indexes or not
+    assert!(diff("a[]=1&a[]=2", "a[0]=1&a[1]=2", ParseMode::Brackets).is_empty());
+    assert!(diff("a[1]=2&a[0]=1", "a[]=1&a[]=2", ParseMode::Brackets).is_empty());
+    assert!(diff("a.0=1&a[]=2", "a[]=1&a[]=2", ParseMode::DotNotation).is_empty());
+    assert_eq!(
+        diff("a[]=1&a[]=2", "a[0]=1&a[1]=3", ParseMode::Brackets),
+        vec![QueryChange::Changed {
+            key: "a".to_string(),
+            old: strings(&["1", "2"]),
+            new: strings(&["1", "3"]),
+        }]
+    );
+    // Rack keeps indexes as the keys of a hash
+    assert_eq!(diff("a[]=1", "a[0]=1", ParseMode::Rack).len(), 2);
+
+    assert_eq!(
+        diff("ids=1|2", "ids=1|3", ParseMode::Delimiter(b'|')),
+        vec![QueryChange::Changed {
+            key: "ids".to_string(),
+            old: strings(&["1", "2"]),
+            new: strings(&["1", "3"]),
+        }]
+    );
+}
@@ -0,0 +1,54 @@
+//! These tests are meant for the diagnostics of the `miette` feature
+#![cfg(feature = "miette")]
+
+use _miette::{Diagnostic, LabeledSpan, NarratableReportHandler, ReportHandler};
+use _serde::Deserialize;
+use serde_querystring::{from_str, ParseMode};
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Range {
+    gte: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Query {
+    age: Range,
+}
+
+/// Renders the diagnostic as text
+struct Render<'a>(&'a dyn Diagnostic);
+
+impl std::fmt::Display for Render<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        NarratableReportHandler::new().debug(self.0, f)
+    }
+}
+
+#[test]
+fn miette_labels() {
+    let input = "q=rust&age[gte]=ten";
+    let error = from_str::<Query, _>(input, ParseMode::Brackets).unwrap_err();
+
+    assert_eq!(
+        error.code().unwrap().to_string(),
+        "serde_querystring::InvalidNumber"
+    );
+    let labels: Vec<LabeledSpan> = error.labels().unwrap().collect();
+    assert_eq!(labels.len(), 2);
+    assert_eq!(&input[labels[0].offset()..][..labels[0].len()], "age[gte]");
+    assert_eq!(labels[0].label(), Some("in `age[gte]`"));
+    assert_eq!(&input[labels[1].offset()..][..labels[1].len()], "ten");
+    assert!(labels[1].primary());
+
+    let report = _miette::Report::new(error).with_source_code(input.to_string());
+    let rendered = Render(report.as_ref()).to_string();
+    assert!(rendered.contains("age[gte]=ten"), "{}", rendered);
+
+    // Errors without spans have no labels
+    let error = from_str::<Query, _>("", ParseMode::Brackets).unwrap_err();
+    assert!(error.labels().is_none());
+}
@@ -0,0 +1,173 @@
+//! These tests are meant for the `odata` feature
+#![cfg(feature = "odata")]
+
+use _serde::Deserialize;
+use serde_querystring::odata::{
+    tokenize, CompareOp, FilterExpr, Literal, ODataQuery, Operand, Token,
+};
+use serde_querystring::{from_str, ErrorKind, ParseMode, SortDirection, SortField};
+
+fn property(name: &str) -> Operand {
+    Operand::Property(name.to_string())
+}
+
+fn string(value: &str) -> Operand {
+    Operand::Literal(Literal::String(value.to_string()))
+}
+
+fn compare(left: Operand, op: CompareOp, right: Operand) -> Box<FilterExpr> {
+    Box::new(FilterExpr::Compare(left, op, right))
+}
+
+#[test]
+fn parse_odata_query() {
+    let query: ODataQuery = "$top=10&$skip=20&$count=true&$orderby=Price%20desc,Name asc\
+         &$select=Name,Price&$expand=Category($select=Name,Id),Supplier&$search=blue&custom=x"
+        .parse()
+        .unwrap();
+
+    assert_eq!(query.top, Some(10));
+    assert_eq!(query.skip, Some(20));
+    assert_eq!(query.count, Some(true));
+    assert_eq!(
+        query.orderby,
+        vec![
+            SortField {
+                field: String::from("Price"),
+                direction: SortDirection::Descending
+            },
+            SortField {
+                field: String::from("Name"),
+                direction: SortDirection::Ascending
+            },
+        ]
+    );
+    assert_eq!(query.select, vec!["Name", "Price"]);
+    assert_eq!(query.expand, vec!["Category($select=Name,Id)", "Supplier"]);
+    assert_eq!(query.search.as_deref(), Some("blue"));
+    assert_eq!(query.filter, None);
+
+    assert_eq!(ODataQuery::from_bytes(b""), Ok(ODataQuery::default()));
+}
+
+#[test]
+fn parse_invalid_odata_query() {
+    for (input, kind) in [
+        (&b"$top=ten"[..], ErrorKind::InvalidNumber),
+        (b"$orderby=Price down", ErrorKind::InvalidType),
+        (b"$count=maybe", ErrorKind::InvalidBoolean),
+        (b"$filter=Price gt", ErrorKind::InvalidType),
+    ] {
+        assert_eq!(ODataQuery::from_bytes(input).unwrap_err().kind, kind);
+    }
+}
+
+#[test]
+fn tokenize_filter() {
+    assert_eq!(
+        tokenize("Address/City eq 'O''Neil' and(Price le -2.5)").unwrap(),
+        vec![
+            (0, Token::Identifier(String::from("Address/City"))),
+            (13, Token::Identifier(String::from("eq"))),
+            (16, Token::String(String::from("O'Neil"))),
+            (26, Token::Identifier(String::from("and"))),
+            (29, Token::OpenParen),
+            (30, Token::Identifier(String::from("Price"))),
+            (36, Token::Identifier(String::from("le"))),
+            (39, Token::Number(-2.5)),
+            (43, Token::CloseParen),
+        ]
+    );
+
+    let error = tokenize("Name eq 'x").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidType);
+    assert_eq!(error.index, Some(8));
+
+    assert_eq!(tokenize("Price # 5").unwrap_err().index, Some(6));
+}
+
+#[test]
+fn parse_filter() {
+    let filter: FilterExpr = "Price gt 5 or Name eq 'x' and not (Stock lt 1)"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        filter,
+        FilterExpr::Or(
+            compare(
+                property("Price"),
+                CompareOp::Gt,
+                Operand::Literal(Literal::Number(5.0))
+            ),
+            Box::new(FilterExpr::And(
+                compare(property("Name"), CompareOp::Eq, string("x")),
+                Box::new(FilterExpr::Not(compare(
+                    property("Stock"),
+                    CompareOp::Lt,
+                    Operand::Literal(Literal::Number(1.0))
+                ))),
+            )),
+        )
+    );
+
+    let filter: FilterExpr = "contains(Name,'blue') and tolower(City) ne null"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        filter,
+        FilterExpr::And(
+            Box::new(FilterExpr::Call(
+                String::from("contains"),
+                vec![property("Name"), string("blue")]
+            )),
+            compare(
+                Operand::Call(String::from("tolower"), vec![property("City")]),
+                CompareOp::Ne,
+                Operand::Literal(Literal::Null)
+            ),
+        )
+    );
+
+    assert_eq!(
+        "true".parse::<FilterExpr>().unwrap(),
+        FilterExpr::Literal(Literal::Bool(true))
+    );
+}
+
+#[test]
+fn parse_invalid_filter() {
+    for (input, index) in [
+        ("", 0),
+        ("(Price gt 5", 11),
+        ("Price gt 5 Name", 11),
+        ("contains(Name 'x')", 14),
+        ("Price eq and", 9),
+    ] {
+        let error = input.parse::<FilterExpr>().unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidType);
+        assert_eq!(error.index, Some(index), "{}", input);
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+struct Query {
+    tenant: String,
+    #[serde(flatten)]
+    odata: ODataQuery,
+}
+
+#[test]
+fn deserialize_odata_query() {
+    let query: Query = from_str(
+        "tenant=acme&$top=5&$filter=Name eq 'a b'",
+        ParseMode::UrlEncoded,
+    )
+    .unwrap();
+    assert_eq!(query.tenant, "acme");
+    assert_eq!(query.odata.top, Some(5));
+    assert_eq!(
+        query.odata.filter,
+        Some(*compare(property("Name"), CompareOp::Eq, string("a b")))
+    );
+}
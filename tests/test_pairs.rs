@@ -0,0 +1,197 @@
+//! These tests are meant for the pairs and multimap API
+
+use std::borrow::Cow;
+
+use serde_querystring::{parse_multimap, PairSeparator, PairStream, ParseMode, QsConfig};
+
+fn parse_pairs<C: Into<QsConfig>>(slice: &[u8], mode: C) -> Vec<(String, String)> {
+    serde_querystring::parse_pairs(slice, mode)
+        .into_iter()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect()
+}
+
+macro_rules! pairs {
+    ($(($key:expr, $value:expr)),* $(,)?) => {
+        vec![$((String::from($key), String::from($value))),*]
+    };
+}
+
+#[test]
+fn pairs_in_order() {
+    let slice = b"b=1&a=x+y&&b=2&c&d=&a%5B0%5D=%F0%9F%A6%80";
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Brackets,
+    ] {
+        assert_eq!(
+            parse_pairs(slice, mode),
+            pairs![
+                ("b", "1"),
+                ("a", "x y"),
+                ("b", "2"),
+                ("c", ""),
+                ("d", ""),
+                ("a[0]", "🦀")
+            ]
+        );
+    }
+}
+
+#[test]
+fn pairs_borrowed() {
+    let pairs = serde_querystring::parse_pairs(b"a=b&c%20d=e+f", ParseMode::Duplicate);
+
+    assert!(matches!(pairs[0].0, Cow::Borrowed("a")));
+    assert!(matches!(pairs[0].1, Cow::Borrowed("b")));
+    assert!(matches!(pairs[1].0, Cow::Owned(_)));
+    assert!(matches!(pairs[1].1, Cow::Owned(_)));
+}
+
+#[test]
+fn pairs_delimiter() {
+    assert_eq!(
+        parse_pairs(b"a=1,2&b=3&a=4", ParseMode::Delimiter(b',')),
+        pairs![("a", "1"), ("a", "2"), ("b", "3"), ("a", "4")]
+    );
+}
+
+#[test]
+fn pairs_invalid_utf8() {
+    assert_eq!(
+        parse_pairs(b"a=%FF", ParseMode::Duplicate),
+        pairs![("a", "\u{FFFD}")]
+    );
+}
+
+#[test]
+fn multimap() {
+    let map = parse_multimap(b"a=1&b=2&a=3&c", ParseMode::Duplicate);
+
+    assert_eq!(map.len(), 3);
+    assert_eq!(map["a"], vec!["1", "3"]);
+    assert_eq!(map["b"], vec!["2"]);
+    assert_eq!(map["c"], vec![""]);
+}
+
+#[test]
+fn point_query() {
+    use serde_querystring::{get_path, get_value};
+
+    let slice = b"trace%5Fid=a+b&page=1&page=2&flag&f[a][b]=1&f[a]=2&f[a][b]=3&f%5Bc%5D=4";
+
+    assert_eq!(
+        get_value(slice, b"trace_id", ParseMode::UrlEncoded).unwrap(),
+        &b"a b"[..]
+    );
+    assert_eq!(
+        get_value(slice, b"page", ParseMode::Duplicate).unwrap(),
+        &b"2"[..]
+    );
+    assert_eq!(
+        get_value(slice, b"flag", ParseMode::Duplicate).unwrap(),
+        &b""[..]
+    );
+    assert_eq!(get_value(slice, b"missing", ParseMode::Duplicate), None);
+
+    // Sub keys only match by their path in brackets mode
+    assert_eq!(get_value(slice, b"f", ParseMode::Brackets), None);
+    assert_eq!(
+        get_value(slice, b"f[a]", ParseMode::Duplicate).unwrap(),
+        &b"2"[..]
+    );
+    assert_eq!(get_value(slice, b"f[a]", ParseMode::Brackets), None);
+    assert_eq!(
+        get_value(b"a.b=1&a=2&a%2Eb=3", b"a", ParseMode::DotNotation).unwrap(),
+        &b"2"[..]
+    );
+    assert_eq!(
+        get_value(b"a.b=1&a=2", b"a.b", ParseMode::DotNotation),
+        None
+    );
+
+    assert_eq!(get_path(slice, &["f", "a", "b"]).unwrap(), &b"3"[..]);
+    assert_eq!(get_path(slice, &["f", "a"]).unwrap(), &b"2"[..]);
+    assert_eq!(get_path(slice, &["f", "c"]).unwrap(), &b"4"[..]);
+    assert_eq!(get_path(slice, &[b"page"]).unwrap(), &b"2"[..]);
+    assert_eq!(get_path(slice, &["f"]), None);
+    assert_eq!(get_path(slice, &["f", "a", "b", "c"]), None);
+}
+
+#[test]
+fn pair_separators() {
+    use serde_querystring::get_value;
+
+    let slice = b"a=1;b=2&a=3";
+    let config = QsConfig::new(ParseMode::Duplicate).pair_separator(PairSeparator::Both);
+
+    assert_eq!(
+        parse_pairs(slice, config),
+        pairs![("a", "1"), ("b", "2"), ("a", "3")]
+    );
+    assert_eq!(
+        parse_pairs(slice, config.pair_separator(PairSeparator::Semicolon)),
+        pairs![("a", "1"), ("b", "2&a=3")]
+    );
+    assert_eq!(get_value(slice, b"b", config).unwrap(), &b"2"[..]);
+}
+
+/// Feeds the slice to a stream in chunks of the size, and collects the pairs
+fn stream_pairs<C: Into<QsConfig>>(slice: &[u8], size: usize, mode: C) -> Vec<(String, String)> {
+    let mut stream = PairStream::new(mode);
+    let mut pairs = Vec::new();
+    for chunk in slice.chunks(size) {
+        for (k, v) in stream.feed(chunk) {
+            pairs.push((k.into_owned(), v.into_owned()));
+        }
+    }
+    for (k, v) in stream.finish() {
+        pairs.push((k.into_owned(), v.into_owned()));
+    }
+    pairs
+}
+
+#[test]
+fn pairs_stream() {
+    let slice = b"b=1&a=x+y&&b=2&c&d=&a%5B0%5D=%F0%9F%A6%80&e=a|b%7Cc";
+
+    for mode in [
+        ParseMode::Duplicate,
+        ParseMode::Brackets,
+        ParseMode::Delimiter(b'|'),
+    ] {
+        // Chunks splitting the escapes, the separators and the utf-8 sequences
+        for size in 1..slice.len() + 1 {
+            assert_eq!(stream_pairs(slice, size, mode), parse_pairs(slice, mode));
+        }
+    }
+
+    let config = QsConfig::new(ParseMode::UrlEncoded).pair_separator(PairSeparator::Newline);
+    let slice = b"a=1\r\nb=2\r\n\r\nc=3\n";
+    for size in 1..slice.len() + 1 {
+        assert_eq!(
+            stream_pairs(slice, size, config),
+            pairs![("a", "1"), ("b", "2"), ("c", "3")]
+        );
+    }
+}
+
+#[test]
+fn pairs_stream_incremental() {
+    let mut stream = PairStream::new(ParseMode::Duplicate);
+
+    // Nothing is returned until the separator of a pair is fed
+    assert!(stream.feed(b"key=va").is_empty());
+    assert!(stream.feed(b"lue").is_empty());
+    let pairs = stream.feed(b"&other=");
+    assert_eq!(pairs, vec![(Cow::Borrowed("key"), Cow::Borrowed("value"))]);
+
+    assert!(stream.feed(b"").is_empty());
+    let pairs = stream.finish();
+    assert_eq!(pairs, vec![(Cow::Borrowed("other"), Cow::Borrowed(""))]);
+
+    // Nothing is left after finishing
+    assert!(stream.finish().is_empty());
+}
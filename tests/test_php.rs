@@ -0,0 +1,180 @@
+//! These tests are meant for the `Php` mode, which follows `parse_str` of PHP 8
+
+use std::collections::BTreeMap;
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::de::{from_str, ErrorKind, InvalidUtf8, ParseMode, QsConfig};
+use serde_querystring::ser::to_string;
+use serde_querystring::{from_str_scoped, from_str_value, Map, Value};
+
+fn s(value: &str) -> Value {
+    Value::String(value.to_string())
+}
+
+fn seq(values: Vec<Value>) -> Value {
+    Value::Sequence(values)
+}
+
+fn map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect::<Map>(),
+    )
+}
+
+fn parse(input: &str) -> Value {
+    from_str_value(input, ParseMode::Php).unwrap()
+}
+
+#[test]
+fn parse_names() {
+    assert_eq!(
+        parse("first=value&arr[]=foo+bar&arr[]=baz"),
+        map(vec![
+            ("first", s("value")),
+            ("arr", seq(vec![s("foo bar"), s("baz")])),
+        ])
+    );
+    assert_eq!(parse("foo"), map(vec![("foo", s(""))]));
+
+    // Spaces and dots are underscores, and leading spaces are removed
+    assert_eq!(
+        parse("a.b=1&a+b=2&+c=3&d%2Ee=4"),
+        map(vec![("a_b", s("2")), ("c", s("3")), ("d_e", s("4"))])
+    );
+    assert_eq!(
+        parse("a.b[c.d]=1"),
+        map(vec![("a_b", map(vec![("c.d", s("1"))]))])
+    );
+    // Names can't be empty
+    assert_eq!(parse("[a]=1&=2&+=3"), map(vec![]));
+}
+
+#[test]
+fn parse_unterminated_brackets() {
+    assert_eq!(parse("a[b=1"), map(vec![("a_b", s("1"))]));
+    assert_eq!(parse("a[=1"), map(vec![("a_", s("1"))]));
+    assert_eq!(parse("a[b.c[d=1"), map(vec![("a_b_c_d", s("1"))]));
+    // After another bracket, the rest of the name is ignored
+    assert_eq!(
+        parse("a[b][c=1"),
+        map(vec![("a", map(vec![("b", s("1"))]))])
+    );
+    assert_eq!(parse("a[b]c=1"), map(vec![("a", map(vec![("b", s("1"))]))]));
+}
+
+#[test]
+fn parse_arrays() {
+    assert_eq!(
+        parse("a[0]=x&a[1]=y"),
+        map(vec![("a", seq(vec![s("x"), s("y")]))])
+    );
+    // Appending continues after the largest integer index
+    assert_eq!(
+        parse("a[5]=x&a[]=y"),
+        map(vec![("a", map(vec![("5", s("x")), ("6", s("y"))]))])
+    );
+    assert_eq!(
+        parse("a[]=x&a[]=y&a[5]=z&a[]=w"),
+        map(vec![(
+            "a",
+            map(vec![
+                ("0", s("x")),
+                ("1", s("y")),
+                ("5", s("z")),
+                ("6", s("w"))
+            ])
+        )])
+    );
+    // Only decimal integers are indexes
+    assert_eq!(
+        parse("a[01]=x&a[]=y&a[-0]=z"),
+        map(vec![(
+            "a",
+            map(vec![("01", s("x")), ("0", s("y")), ("-0", s("z"))])
+        )])
+    );
+    // A single leading space is ignored in empty brackets
+    assert_eq!(
+        parse("a[ ]=x&a[ b]=y"),
+        map(vec![("a", map(vec![("0", s("x")), (" b", s("y"))]))])
+    );
+    assert_eq!(
+        parse("a[][b]=1&a[][b]=2"),
+        map(vec![(
+            "a",
+            seq(vec![map(vec![("b", s("1"))]), map(vec![("b", s("2"))])])
+        )])
+    );
+}
+
+#[test]
+fn parse_overwrites() {
+    assert_eq!(
+        parse("a=1&a[b]=2"),
+        map(vec![("a", map(vec![("b", s("2"))]))])
+    );
+    assert_eq!(parse("a[b]=2&a=1"), map(vec![("a", s("1"))]));
+    assert_eq!(
+        parse("a[]=1&a[0][b]=2"),
+        map(vec![("a", seq(vec![map(vec![("b", s("2"))])]))])
+    );
+}
+
+#[test]
+fn parse_invalid_utf8() {
+    let config = QsConfig::new(ParseMode::Php).invalid_utf8(InvalidUtf8::Error);
+    let error = from_str_value("a[b][]=%FF", config).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidEncoding);
+    assert_eq!(error.key, "a[b][]");
+}
+
+#[test]
+fn parse_depth_limit() {
+    // Variables nested too deep are removed
+    let config = QsConfig::new(ParseMode::Php).max_depth(1);
+    assert_eq!(
+        from_str_value("a=1&a[b][c]=2&d[e]=3", config).unwrap(),
+        map(vec![("d", map(vec![("e", s("3"))]))])
+    );
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Form {
+    user_name: String,
+    tags: Vec<String>,
+    filter: BTreeMap<String, u32>,
+}
+
+#[test]
+fn deserialize_php() {
+    let form: Form = from_str(
+        "user.name=John&tags[]=a&tags[]=b&filter[age]=30",
+        ParseMode::Php,
+    )
+    .unwrap();
+
+    assert_eq!(form.user_name, "John");
+    assert_eq!(form.tags, vec!["a", "b"]);
+    assert_eq!(form.filter["age"], 30);
+
+    let filter: BTreeMap<String, u32> =
+        from_str_scoped("filter[age]=30&age=4", "filter", ParseMode::Php).unwrap();
+    assert_eq!(filter["age"], 30);
+}
+
+#[test]
+fn serialize_php() {
+    let form = Form {
+        user_name: String::from("John"),
+        tags: vec![String::from("a"), String::from("b")],
+        filter: vec![(String::from("age"), 30)].into_iter().collect(),
+    };
+
+    let query = to_string(&form, ParseMode::Php).unwrap();
+    assert_eq!(query, "user_name=John&tags[0]=a&tags[1]=b&filter[age]=30");
+    assert_eq!(from_str(&query, ParseMode::Php), Ok(form));
+}
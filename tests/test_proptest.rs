@@ -0,0 +1,36 @@
+//! These tests are meant for the strategies of the `proptest` feature
+#![cfg(feature = "proptest")]
+
+use _proptest::prelude::*;
+use _proptest::sample::select;
+use serde_querystring::proptest::{key, query, value};
+use serde_querystring::{from_str_value, ParseMode, Value};
+
+const MODES: &[ParseMode] = &[
+    ParseMode::UrlEncoded,
+    ParseMode::Duplicate,
+    ParseMode::Delimiter(b'|'),
+    ParseMode::Brackets,
+    ParseMode::DotNotation,
+    ParseMode::BracketsDelimiter(b','),
+    ParseMode::Matrix,
+    ParseMode::Rack,
+    ParseMode::Php,
+];
+
+proptest! {
+    #[test]
+    fn queries_are_valid(
+        (mode, input) in select(MODES).prop_flat_map(|mode| (Just(mode), query(mode)))
+    ) {
+        let res = from_str_value(&input, mode);
+        prop_assert!(res.is_ok(), "{:?} {:?}: {:?}", mode, input, res);
+    }
+
+    #[test]
+    fn pairs_are_decoded(key in key(), value in value()) {
+        let input = format!("{}={}", key.encoded, value.encoded);
+        let res = from_str_value(&input, ParseMode::UrlEncoded).unwrap();
+        prop_assert_eq!(res.get(&key.decoded), Some(&Value::String(value.decoded)));
+    }
+}
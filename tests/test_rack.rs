@@ -0,0 +1,306 @@
+//! These tests are meant for the `Rack` mode, most cases are taken from the specs of Rack's
+//! `parse_nested_query`
+
+use std::collections::BTreeMap;
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::de::{from_str, ErrorKind, InvalidUtf8, ParseMode, QsConfig};
+use serde_querystring::ser::to_string;
+use serde_querystring::{from_str_scoped, from_str_value, Map, Value};
+
+fn s(value: &str) -> Value {
+    Value::String(value.to_string())
+}
+
+// Rack's nil, which is an empty string here
+fn nil() -> Value {
+    s("")
+}
+
+fn seq(values: Vec<Value>) -> Value {
+    Value::Sequence(values)
+}
+
+fn map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect::<Map>(),
+    )
+}
+
+fn parse(input: &str) -> Value {
+    from_str_value(input, ParseMode::Rack).unwrap()
+}
+
+#[test]
+fn parse_plain_keys() {
+    assert_eq!(parse("foo"), map(vec![("foo", nil())]));
+    assert_eq!(parse("foo="), map(vec![("foo", s(""))]));
+    assert_eq!(parse("foo=bar"), map(vec![("foo", s("bar"))]));
+    assert_eq!(parse("foo=\"bar\""), map(vec![("foo", s("\"bar\""))]));
+    assert_eq!(parse("foo=bar&foo=quux"), map(vec![("foo", s("quux"))]));
+    assert_eq!(parse("foo&foo="), map(vec![("foo", s(""))]));
+    assert_eq!(
+        parse("&foo=1&&bar=2"),
+        map(vec![("foo", s("1")), ("bar", s("2"))])
+    );
+    assert_eq!(
+        parse("my+weird+field=q1%212%22%27w%245%267%2Fz8%29%3F"),
+        map(vec![("my weird field", s("q1!2\"'w$5&7/z8)?"))])
+    );
+    assert_eq!(
+        parse("a=b&pid%3D1234=1023"),
+        map(vec![("pid=1234", s("1023")), ("a", s("b"))])
+    );
+    // Spaces after the separators are removed
+    assert_eq!(parse("a=1& b=2"), map(vec![("a", s("1")), ("b", s("2"))]));
+    // Leading brackets are part of the key
+    assert_eq!(
+        parse("[]=1&[a]=2"),
+        map(vec![("[]", s("1")), ("[a]", s("2"))])
+    );
+}
+
+#[test]
+fn parse_arrays() {
+    assert_eq!(parse("foo[]"), map(vec![("foo", seq(vec![nil()]))]));
+    assert_eq!(parse("foo[]="), map(vec![("foo", seq(vec![s("")]))]));
+    assert_eq!(parse("foo[]=bar"), map(vec![("foo", seq(vec![s("bar")]))]));
+    assert_eq!(parse("foo[]=bar&foo"), map(vec![("foo", nil())]));
+    assert_eq!(
+        parse("foo[]=bar&foo["),
+        map(vec![("foo", seq(vec![s("bar")])), ("foo[", nil())])
+    );
+    assert_eq!(
+        parse("foo[]=bar&foo[=baz"),
+        map(vec![("foo", seq(vec![s("bar")])), ("foo[", s("baz"))])
+    );
+    assert_eq!(
+        parse("foo[]=bar&foo[]"),
+        map(vec![("foo", seq(vec![s("bar"), nil()]))])
+    );
+    assert_eq!(
+        parse("foo=bar&baz[]=1&baz[]=2&baz[]=3"),
+        map(vec![
+            ("foo", s("bar")),
+            ("baz", seq(vec![s("1"), s("2"), s("3")])),
+        ])
+    );
+    // A key without a value is nil, and doesn't conflict with the array
+    assert_eq!(parse("foo&foo[]=1"), map(vec![("foo", seq(vec![s("1")]))]));
+    // Nested arrays
+    assert_eq!(
+        parse("x[][]=1&x[][]=2"),
+        map(vec![("x", seq(vec![seq(vec![s("1")]), seq(vec![s("2")])]))])
+    );
+}
+
+#[test]
+fn parse_hashes() {
+    assert_eq!(
+        parse("x[y][z]"),
+        map(vec![("x", map(vec![("y", map(vec![("z", nil())]))]))])
+    );
+    assert_eq!(
+        parse("x[y][z]=1&x[y][z]=2"),
+        map(vec![("x", map(vec![("y", map(vec![("z", s("2"))]))]))])
+    );
+    assert_eq!(
+        parse("x[y][z][]=1&x[y][z][]=2"),
+        map(vec![(
+            "x",
+            map(vec![("y", map(vec![("z", seq(vec![s("1"), s("2")]))]))])
+        )])
+    );
+    // Indexes are keys of a hash
+    assert_eq!(
+        parse("x[0]=a&x[1]=b"),
+        map(vec![("x", map(vec![("0", s("a")), ("1", s("b"))]))])
+    );
+    // Malformed sub keys are kept as they are
+    assert_eq!(
+        parse("x[y]z=1"),
+        map(vec![("x", map(vec![("y", map(vec![("z", s("1"))]))]))])
+    );
+}
+
+#[test]
+fn parse_arrays_of_hashes() {
+    let y = |hashes| map(vec![("x", map(vec![("y", seq(hashes))]))]);
+
+    assert_eq!(parse("x[y][][z]=1"), y(vec![map(vec![("z", s("1"))])]));
+    assert_eq!(
+        parse("x[y][][z][]=1"),
+        y(vec![map(vec![("z", seq(vec![s("1")]))])])
+    );
+    assert_eq!(
+        parse("x[y][][z]=1&x[y][][w]=2"),
+        y(vec![map(vec![("z", s("1")), ("w", s("2"))])])
+    );
+    assert_eq!(
+        parse("x[y][][v][w]=1"),
+        y(vec![map(vec![("v", map(vec![("w", s("1"))]))])])
+    );
+    assert_eq!(
+        parse("x[y][][z]=1&x[y][][v][w]=2"),
+        y(vec![map(vec![
+            ("z", s("1")),
+            ("v", map(vec![("w", s("2"))])),
+        ])])
+    );
+    // A repeated key starts a new hash
+    assert_eq!(
+        parse("x[y][][z]=1&x[y][][z]=2"),
+        y(vec![map(vec![("z", s("1"))]), map(vec![("z", s("2"))])])
+    );
+    assert_eq!(
+        parse("x[y][][z]=1&x[y][][w]=a&x[y][][z]=2&x[y][][w]=3"),
+        y(vec![
+            map(vec![("z", s("1")), ("w", s("a"))]),
+            map(vec![("z", s("2")), ("w", s("3"))]),
+        ])
+    );
+
+    let expected = map(vec![(
+        "x",
+        seq(vec![
+            map(vec![("y", s("1")), ("z", map(vec![("w", s("a"))]))]),
+            map(vec![("y", s("2")), ("z", map(vec![("w", s("b"))]))]),
+        ]),
+    )]);
+    assert_eq!(parse("x[][y]=1&x[][z][w]=a&x[][y]=2&x[][z][w]=b"), expected);
+    assert_eq!(parse("x[][z][w]=a&x[][y]=1&x[][z][w]=b&x[][y]=2"), expected);
+
+    assert_eq!(
+        parse("data[books][][data][page]=1&data[books][][data][page]=2"),
+        map(vec![(
+            "data",
+            map(vec![(
+                "books",
+                seq(vec![
+                    map(vec![("data", map(vec![("page", s("1"))]))]),
+                    map(vec![("data", map(vec![("page", s("2"))]))]),
+                ])
+            )])
+        )])
+    );
+}
+
+#[test]
+fn parse_conflicts() {
+    for (input, message, key) in [
+        (
+            "x[y]=1&x[y]z=2",
+            "expected Hash (got String) for param `y'",
+            "x[y]",
+        ),
+        (
+            "x[y]=1&x[]=1",
+            "expected Array (got Hash) for param `x'",
+            "x",
+        ),
+        (
+            "x[y]=1&x[y][][w]=2",
+            "expected Array (got String) for param `y'",
+            "x[y]",
+        ),
+        (
+            "x=1&x[y]=2",
+            "expected Hash (got String) for param `x'",
+            "x",
+        ),
+        (
+            "x[][y]=1&x[][y][z]=2",
+            "expected Hash (got String) for param `y'",
+            "x[][y]",
+        ),
+    ] {
+        let error = from_str_value(input, ParseMode::Rack).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::KeyConflict);
+        assert_eq!(error.message, message);
+        assert_eq!(error.key, key);
+    }
+}
+
+#[test]
+fn parse_invalid_utf8() {
+    let config = QsConfig::new(ParseMode::Rack).invalid_utf8(InvalidUtf8::Error);
+    let error = from_str_value("a[b][]=%FF", config).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidEncoding);
+    assert_eq!(error.key, "a[b][]");
+}
+
+#[test]
+fn parse_depth_limit() {
+    let config = QsConfig::new(ParseMode::Rack).max_depth(2);
+    assert!(from_str_value("a[b][c]=1", config).is_ok());
+    let error = from_str_value("a[b][c][d]=1", config).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::DepthLimitExceeded);
+    assert_eq!(error.key, "a[b][c][d]");
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Item {
+    id: u32,
+    name: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Order {
+    user: BTreeMap<String, String>,
+    tags: Vec<String>,
+    items: Vec<Item>,
+}
+
+#[test]
+fn deserialize_rack() {
+    let order: Order = from_str(
+        "user[name]=John+Doe&tags[]=a&tags[]=b&items[][id]=1&items[][name]=x&items[][id]=2",
+        ParseMode::Rack,
+    )
+    .unwrap();
+
+    assert_eq!(order.user["name"], "John Doe");
+    assert_eq!(order.tags, vec!["a", "b"]);
+    assert_eq!(
+        order.items,
+        vec![
+            Item {
+                id: 1,
+                name: Some(String::from("x")),
+            },
+            Item { id: 2, name: None },
+        ]
+    );
+
+    let item: Item = from_str_scoped("items[id]=3&id=4", "items", ParseMode::Rack).unwrap();
+    assert_eq!(item, Item { id: 3, name: None });
+}
+
+#[test]
+fn serialize_rack() {
+    let order = Order {
+        user: vec![(String::from("name"), String::from("John"))]
+            .into_iter()
+            .collect(),
+        tags: vec![String::from("a")],
+        items: vec![
+            Item { id: 1, name: None },
+            Item {
+                id: 2,
+                name: Some(String::from("y")),
+            },
+        ],
+    };
+
+    let query = to_string(&order, ParseMode::Rack).unwrap();
+    assert_eq!(
+        query,
+        "user[name]=John&tags[]=a&items[][id]=1&items[][id]=2&items[][name]=y"
+    );
+    assert_eq!(from_str(&query, ParseMode::Rack), Ok(order));
+}
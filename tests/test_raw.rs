@@ -0,0 +1,88 @@
+//! These tests are meant for the `RawQsValue` type
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::{from_str, to_string, ErrorKind, ParseMode, RawQsValue};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Borrowed<'a> {
+    #[serde(borrow)]
+    raw: &'a RawQsValue,
+    other: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Owned {
+    raw: Box<RawQsValue>,
+    list: Vec<Box<RawQsValue>>,
+}
+
+#[test]
+fn raw_deserialize() {
+    let slice = "raw=a%2fb+c&other=x%20y&raw=%F0%9F%A6%80";
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+    ] {
+        let value: Borrowed = from_str(slice, mode).unwrap();
+        assert_eq!(value.raw.as_bytes(), b"%F0%9F%A6%80");
+        assert_eq!(value.raw.decode(), "🦀".as_bytes());
+        assert_eq!(value.other, "x y");
+    }
+
+    // The whole delimited value is kept
+    let value: Borrowed = from_str("raw=a|b%7C&other=", ParseMode::Delimiter(b'|')).unwrap();
+    assert_eq!(value.raw.as_bytes(), b"a|b%7C");
+
+    let value: Owned = from_str("raw=a+b&list=1%2C&list=2", ParseMode::Duplicate).unwrap();
+    assert_eq!(value.raw.as_bytes(), b"a+b");
+    assert_eq!(value.list.len(), 2);
+    assert_eq!(value.list[0].as_bytes(), b"1%2C");
+
+    let value: Owned = from_str("raw[x]=1&list[1]=b&list[0]=a", ParseMode::Brackets).unwrap();
+    assert_eq!(value.list[0].as_bytes(), b"a");
+    assert_eq!(value.list[1].as_bytes(), b"b");
+}
+
+#[test]
+fn raw_serialize() {
+    let value: Borrowed = from_str("raw=a%2fb+c&other=x%20y", ParseMode::Duplicate).unwrap();
+
+    for mode in [ParseMode::Duplicate, ParseMode::Brackets] {
+        assert_eq!(to_string(&value, mode).unwrap(), "raw=a%2fb+c&other=x+y");
+    }
+
+    let value: Owned = from_str("raw=a+b&list=1%2C&list=2", ParseMode::Duplicate).unwrap();
+    assert_eq!(
+        to_string(&value, ParseMode::Duplicate).unwrap(),
+        "raw=a+b&list=1%2C&list=2"
+    );
+    assert_eq!(
+        to_string(&value, ParseMode::Delimiter(b',')).unwrap(),
+        "raw=a+b&list=1%2C,2"
+    );
+    assert_eq!(
+        to_string(&value, ParseMode::Brackets).unwrap(),
+        "raw=a+b&list[0]=1%2C&list[1]=2"
+    );
+}
+
+#[test]
+fn raw_keys() {
+    use std::collections::BTreeMap;
+
+    // Keys are already decoded, so they are only available when there was nothing to decode
+    let value: BTreeMap<&RawQsValue, String> = from_str("a=1", ParseMode::Duplicate).unwrap();
+    assert_eq!(value.keys().next().unwrap().as_bytes(), b"a");
+
+    assert_eq!(
+        from_str::<BTreeMap<&RawQsValue, String>, _>("a+b=1", ParseMode::Duplicate)
+            .unwrap_err()
+            .kind,
+        ErrorKind::Other
+    );
+}
@@ -0,0 +1,80 @@
+//! These tests are meant for the round trip helpers of the `test-util` feature
+#![cfg(feature = "test-util")]
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::test_util::{assert_roundtrip, roundtrip, Difference, RoundtripError};
+use serde_querystring::{ErrorKind, ParseMode, SerializerConfig};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Query {
+    name: String,
+    tags: Vec<String>,
+}
+
+fn query() -> Query {
+    Query {
+        name: String::from("a b&c"),
+        tags: vec![String::from("x"), String::from("y|z")],
+    }
+}
+
+#[test]
+fn roundtrip_ok() {
+    for mode in [
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+    ] {
+        assert_eq!(roundtrip(&query(), mode), Ok(query()));
+        assert_roundtrip(&query(), mode);
+    }
+}
+
+#[test]
+fn roundtrip_errors() {
+    // Sequences are not supported in UrlEncoded mode
+    match roundtrip(&query(), ParseMode::UrlEncoded) {
+        Err(RoundtripError::Serialize(e)) => assert_eq!(e.kind, ErrorKind::InvalidType),
+        res => panic!("unexpected result {:?}", res),
+    }
+
+    // `key[]` is not a sequence for the duplicate parser
+    let config = SerializerConfig::new(ParseMode::Duplicate).empty_brackets(true);
+    match roundtrip(&query(), config) {
+        Err(RoundtripError::Deserialize { query, .. }) => {
+            assert_eq!(query, "name=a+b%26c&tags[]=x&tags[]=y%7Cz")
+        }
+        res => panic!("unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn roundtrip_mismatch() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Lossy {
+        value: u32,
+        #[serde(skip_deserializing)]
+        other: u32,
+    }
+
+    let value = Lossy { value: 1, other: 2 };
+
+    match roundtrip(&value, ParseMode::Duplicate) {
+        Err(RoundtripError::Mismatch {
+            query, differences, ..
+        }) => {
+            assert_eq!(query, "value=1&other=2");
+            assert_eq!(
+                differences,
+                vec![Difference {
+                    key: String::from("other"),
+                    expected: vec![String::from("2")],
+                    actual: vec![String::from("0")],
+                }]
+            );
+        }
+        res => panic!("unexpected result {:?}", res),
+    }
+}
@@ -0,0 +1,128 @@
+//! These tests are meant for the `rsql` feature
+#![cfg(feature = "rsql")]
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::rsql::{Comparison, Operator, RsqlExpr};
+use serde_querystring::{from_str, to_string, ErrorKind, ParseMode};
+
+fn comparison(selector: &str, operator: Operator, arguments: Vec<&str>) -> RsqlExpr {
+    RsqlExpr::Comparison(Comparison::new(selector, operator, arguments))
+}
+
+#[test]
+fn parse_rsql() {
+    assert_eq!(
+        RsqlExpr::parse("name==\"Kill Bill\";year=gt=2003").unwrap(),
+        RsqlExpr::And(vec![
+            comparison("name", Operator::Eq, vec!["Kill Bill"]),
+            comparison("year", Operator::Gt, vec!["2003"]),
+        ])
+    );
+
+    assert_eq!(
+        RsqlExpr::parse("genres=in=(sci-fi,'action') and (director!=Q*,year<=2000)").unwrap(),
+        RsqlExpr::And(vec![
+            comparison("genres", Operator::In, vec!["sci-fi", "action"]),
+            RsqlExpr::Or(vec![
+                comparison("director", Operator::Ne, vec!["Q*"]),
+                comparison("year", Operator::Le, vec!["2000"]),
+            ]),
+        ])
+    );
+
+    assert_eq!(
+        RsqlExpr::parse("a>1,b<2;c>=3 or d=like='x\\'y'").unwrap(),
+        RsqlExpr::Or(vec![
+            comparison("a", Operator::Gt, vec!["1"]),
+            RsqlExpr::And(vec![
+                comparison("b", Operator::Lt, vec!["2"]),
+                comparison("c", Operator::Ge, vec!["3"]),
+            ]),
+            comparison("d", Operator::Custom(String::from("like")), vec!["x'y"]),
+        ])
+    );
+}
+
+#[test]
+fn parse_invalid_rsql() {
+    for (input, index) in [
+        ("", 0),
+        ("name", 4),
+        ("name=foo", 4),
+        ("name==", 6),
+        ("name==\"foo", 6),
+        ("id=in=(1,2", 10),
+        ("(a==1", 5),
+        ("a==1)", 4),
+    ] {
+        let error = RsqlExpr::parse(input).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidType);
+        assert_eq!(error.index, Some(index), "{}", input);
+    }
+}
+
+#[test]
+fn display_rsql() {
+    for (input, expected) in [
+        ("name==foo;age=gt=30", "name==foo;age=gt=30"),
+        ("a==1;(b==2 or c==3)", "a==1;(b==2,c==3)"),
+        ("a<1,b>=2", "a=lt=1,b=ge=2"),
+        (
+            "name=='a b';id=out=(1,\"x;y\")",
+            "name==\"a b\";id=out=(1,\"x;y\")",
+        ),
+    ] {
+        let expr = RsqlExpr::parse(input).unwrap();
+        assert_eq!(expr.to_string(), expected);
+        assert_eq!(RsqlExpr::parse(expected).unwrap(), expr);
+    }
+}
+
+#[test]
+fn rsql_comparisons() {
+    let expr = RsqlExpr::parse("a==1;(b==2,c==3)").unwrap();
+    let selectors: Vec<_> = expr
+        .comparisons()
+        .iter()
+        .map(|comparison| comparison.selector.as_str())
+        .collect();
+    assert_eq!(selectors, ["a", "b", "c"]);
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(crate = "_serde")]
+struct Query {
+    search: RsqlExpr,
+    #[serde(default)]
+    page: u32,
+}
+
+#[test]
+fn deserialize_rsql() {
+    let expected = Query {
+        search: RsqlExpr::And(vec![
+            comparison("name", Operator::Eq, vec!["foo"]),
+            comparison("age", Operator::Gt, vec!["30"]),
+        ]),
+        page: 2,
+    };
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+    ] {
+        let query: Query = from_str("search=name==foo;age=gt=30&page=2", mode).unwrap();
+        assert_eq!(query, expected);
+    }
+
+    let query: Query = from_str(
+        &to_string(&expected, ParseMode::UrlEncoded).unwrap(),
+        ParseMode::UrlEncoded,
+    )
+    .unwrap();
+    assert_eq!(query, expected);
+
+    assert!(from_str::<Query, _>("search=name=foo", ParseMode::UrlEncoded).is_err());
+}
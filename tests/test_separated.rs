@@ -0,0 +1,75 @@
+//! These tests are meant for the `CommaSeparated` and `PipeSeparated` types
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::{
+    from_bytes_value, from_str, to_string, CommaSeparated, ParseMode, PipeSeparated,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Query {
+    name: String,
+    ids: CommaSeparated<Vec<u64>>,
+    tags: Option<PipeSeparated<Vec<String>>>,
+}
+
+#[test]
+fn separated_deserialize() {
+    let slice = "name=foo&ids=1,2,3&tags=a+b|c%7Cd";
+    let expected = Query {
+        name: String::from("foo"),
+        ids: CommaSeparated(vec![1, 2, 3]),
+        tags: Some(PipeSeparated(vec![
+            String::from("a b"),
+            String::from("c|d"),
+        ])),
+    };
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+        ParseMode::DotNotation,
+    ] {
+        assert_eq!(from_str::<Query, _>(slice, mode).unwrap(), expected);
+    }
+
+    // The last value is split when the key is repeated
+    let query: Query = from_str("name=&ids=1,2&ids=3&ids=", ParseMode::Duplicate).unwrap();
+    assert!(query.ids.is_empty());
+    assert_eq!(query.tags, None);
+
+    let query: Query = from_str("name=&ids=1,2&ids=3", ParseMode::Duplicate).unwrap();
+    assert_eq!(query.ids.into_inner(), vec![3]);
+
+    // Values are already decoded, so encoded delimiters split them too
+    let value = from_bytes_value(slice.as_bytes(), ParseMode::UrlEncoded).unwrap();
+    let query: Query = value.deserialize_into().unwrap();
+    assert_eq!(query.ids, expected.ids);
+    assert_eq!(query.tags.unwrap().len(), 3);
+}
+
+#[test]
+fn separated_serialize() {
+    let query = Query {
+        name: String::from("foo"),
+        ids: CommaSeparated(vec![1, 2, 3]),
+        tags: Some(PipeSeparated(vec![
+            String::from("a b"),
+            String::from("c|d"),
+        ])),
+    };
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+    ] {
+        assert_eq!(
+            to_string(&query, mode).unwrap(),
+            "name=foo&ids=1,2,3&tags=a+b|c%7Cd"
+        );
+    }
+}
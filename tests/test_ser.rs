@@ -0,0 +1,636 @@
+//! These tests are meant for the serializer
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::de::{from_str, ErrorKind, ParseMode};
+use serde_querystring::ser::{
+    to_bytes, to_string, to_writer, EncodeSet, NoneStyle, QueryStringBuilder, SerializerConfig,
+};
+
+/// It is a helper struct we use to test primitive types
+/// as we don't support anything beside maps/structs at the root level
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Primitive<T> {
+    value: T,
+}
+
+impl<T> Primitive<T> {
+    pub fn new(value: T) -> Self {
+        Self { value }
+    }
+}
+
+macro_rules! p {
+    ($value:expr) => {
+        Primitive::new($value)
+    };
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Simple {
+    name: String,
+    age: u8,
+    height: f32,
+    alive: bool,
+    side: Side,
+    nickname: Option<String>,
+}
+
+fn simple() -> Simple {
+    Simple {
+        name: String::from("John Doe"),
+        age: 42,
+        height: 1.75,
+        alive: true,
+        side: Side::Left,
+        nickname: None,
+    }
+}
+
+#[test]
+fn serialize_scalars() {
+    let expected = "name=John+Doe&age=42&height=1.75&alive=true&side=Left";
+
+    assert_eq!(
+        to_string(&simple(), ParseMode::UrlEncoded).unwrap(),
+        expected
+    );
+    assert_eq!(
+        to_string(&simple(), ParseMode::Duplicate).unwrap(),
+        expected
+    );
+    assert_eq!(
+        to_string(&simple(), ParseMode::Delimiter(b'|')).unwrap(),
+        expected
+    );
+    assert_eq!(to_string(&simple(), ParseMode::Brackets).unwrap(), expected);
+    assert_eq!(
+        to_bytes(&simple(), ParseMode::Brackets).unwrap(),
+        expected.as_bytes()
+    );
+}
+
+#[test]
+fn serialize_encoded() {
+    assert_eq!(
+        to_string(&p!("a&b=c d+e%f/ü"), ParseMode::UrlEncoded).unwrap(),
+        "value=a%26b%3Dc+d%2Be%25f%2F%C3%BC"
+    );
+
+    let map: std::collections::BTreeMap<_, _> = vec![("key with space", 1)].into_iter().collect();
+    assert_eq!(
+        to_string(&map, ParseMode::UrlEncoded).unwrap(),
+        "key+with+space=1"
+    );
+}
+
+#[test]
+fn serialize_sequences() {
+    let value = p!(vec![1, 2, 3]);
+
+    assert_eq!(
+        to_string(&value, ParseMode::Duplicate).unwrap(),
+        "value=1&value=2&value=3"
+    );
+    assert_eq!(
+        to_string(&value, ParseMode::Delimiter(b'|')).unwrap(),
+        "value=1|2|3"
+    );
+    assert_eq!(
+        to_string(&value, ParseMode::Brackets).unwrap(),
+        "value[0]=1&value[1]=2&value[2]=3"
+    );
+
+    // Tuples are serialized the same way
+    assert_eq!(
+        to_string(&p!((1, "foo", true)), ParseMode::Duplicate).unwrap(),
+        "value=1&value=foo&value=true"
+    );
+
+    // Sequences are not supported in UrlEncoded mode
+    assert_eq!(
+        to_string(&value, ParseMode::UrlEncoded).unwrap_err().kind,
+        ErrorKind::InvalidType
+    );
+}
+
+#[test]
+fn serialize_roundtrip() {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Sequences {
+        name: String,
+        ids: Vec<u32>,
+        pair: (i32, String),
+    }
+
+    let value = Sequences {
+        name: String::from("a&b|c"),
+        ids: vec![10, 20, 30],
+        pair: (-1, String::from("x y")),
+    };
+
+    for mode in [
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+    ] {
+        let query = to_string(&value, mode).unwrap();
+        assert_eq!(from_str::<Sequences, _>(&query, mode), Ok(value.clone()));
+    }
+
+    let query = to_string(&simple(), ParseMode::UrlEncoded).unwrap();
+    assert_eq!(from_str(&query, ParseMode::UrlEncoded), Ok(simple()));
+}
+
+#[test]
+fn serialize_invalid_top_level() {
+    assert_eq!(
+        to_string(&1, ParseMode::UrlEncoded).unwrap_err().kind,
+        ErrorKind::InvalidType
+    );
+    assert_eq!(
+        to_string(&vec![1, 2], ParseMode::Duplicate)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidType
+    );
+}
+
+#[test]
+fn serialize_invalid_values() {
+    // Nested sequences can't be represented in flat modes
+    assert_eq!(
+        to_string(&p!(vec![vec![1]]), ParseMode::Duplicate)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidType
+    );
+    assert_eq!(
+        to_string(&p!(vec![vec![1]]), ParseMode::Delimiter(b','))
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidType
+    );
+
+    // Nor maps
+    assert_eq!(
+        to_string(&p!(p!(1)), ParseMode::UrlEncoded)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidType
+    );
+}
+
+#[test]
+fn serialize_brackets_nested() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Address {
+        city: String,
+        zip: Option<u32>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct User {
+        name: String,
+        address: Address,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Query {
+        user: User,
+        tags: Vec<String>,
+        scores: std::collections::BTreeMap<String, u32>,
+    }
+
+    let value = Query {
+        user: User {
+            name: String::from("x"),
+            address: Address {
+                city: String::from("New York"),
+                zip: None,
+            },
+        },
+        tags: vec![String::from("a"), String::from("b")],
+        scores: vec![(String::from("math"), 20), (String::from("art"), 18)]
+            .into_iter()
+            .collect(),
+    };
+
+    let query = to_string(&value, ParseMode::Brackets).unwrap();
+    assert_eq!(
+        query,
+        "user[name]=x&user[address][city]=New+York&tags[0]=a&tags[1]=b&scores[art]=18&scores[math]=20"
+    );
+    assert_eq!(from_str(&query, ParseMode::Brackets), Ok(value));
+
+    // Sequences of maps
+    assert_eq!(
+        to_string(&p!(vec![p!(1), p!(2)]), ParseMode::Brackets).unwrap(),
+        "value[0][value]=1&value[1][value]=2"
+    );
+
+    // Encoded sub keys
+    assert_eq!(
+        to_string(&p!(p!("x")), ParseMode::Brackets).unwrap(),
+        "value[value]=x"
+    );
+    let map: std::collections::BTreeMap<_, _> = vec![("a b[c]", 1)].into_iter().collect();
+    assert_eq!(
+        to_string(&p!(map), ParseMode::Brackets).unwrap(),
+        "value[a+b%5Bc%5D]=1"
+    );
+}
+
+#[test]
+fn serialize_dot_notation() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Address {
+        city: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct User {
+        name: String,
+        address: Address,
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Query {
+        user: User,
+    }
+
+    let value = Query {
+        user: User {
+            name: String::from("x"),
+            address: Address {
+                city: String::from("Oslo"),
+            },
+            tags: vec![String::from("a"), String::from("b")],
+        },
+    };
+
+    let query = to_string(&value, ParseMode::DotNotation).unwrap();
+    assert_eq!(
+        query,
+        "user.name=x&user.address.city=Oslo&user.tags[0]=a&user.tags[1]=b"
+    );
+    assert_eq!(from_str(&query, ParseMode::DotNotation), Ok(value));
+
+    // Dots inside keys are encoded
+    let map: std::collections::BTreeMap<_, _> =
+        vec![(String::from("a.b"), 1)].into_iter().collect();
+    let value = p!(map);
+    let query = to_string(&value, ParseMode::DotNotation).unwrap();
+    assert_eq!(query, "value.a%2Eb=1");
+    assert_eq!(from_str(&query, ParseMode::DotNotation), Ok(value));
+}
+
+#[test]
+fn serialize_brackets_delimiter() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Filter {
+        ids: Vec<u32>,
+        names: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Query {
+        filter: Filter,
+        sort: Vec<String>,
+    }
+
+    let value = Query {
+        filter: Filter {
+            ids: vec![1, 2, 3],
+            names: vec![String::from("a,b"), String::from("c")],
+        },
+        sort: vec![String::from("-age")],
+    };
+
+    let query = to_string(&value, ParseMode::BracketsDelimiter(b',')).unwrap();
+    assert_eq!(query, "filter[ids]=1,2,3&filter[names]=a%2Cb,c&sort=-age");
+    assert_eq!(
+        from_str(&query, ParseMode::BracketsDelimiter(b',')),
+        Ok(value)
+    );
+}
+
+#[test]
+fn serialize_brackets_enums() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(crate = "_serde")]
+    enum Enum {
+        Unit,
+        NewType(i32),
+        Tuple(i32, i32),
+        Struct { bee: i32, loose: i32 },
+    }
+
+    let cases = [
+        (Enum::Unit, "value=Unit"),
+        (Enum::NewType(2022), "value[NewType]=2022"),
+        (
+            Enum::Tuple(100, 200),
+            "value[Tuple][0]=100&value[Tuple][1]=200",
+        ),
+        (
+            Enum::Struct {
+                bee: 833,
+                loose: 10053,
+            },
+            "value[Struct][bee]=833&value[Struct][loose]=10053",
+        ),
+    ];
+
+    for (value, expected) in cases {
+        let value = p!(value);
+        let query = to_string(&value, ParseMode::Brackets).unwrap();
+        assert_eq!(query, expected);
+        assert_eq!(from_str(&query, ParseMode::Brackets), Ok(value));
+    }
+
+    // Only unit variants are supported in other modes
+    assert_eq!(
+        to_string(&p!(Enum::NewType(1)), ParseMode::Duplicate)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidType
+    );
+}
+
+#[test]
+fn serialize_delimiter() {
+    let value = p!(vec!["a,b", "c", "d e"]);
+
+    // Any byte can be used as the delimiter
+    assert_eq!(
+        to_string(&value, ParseMode::Delimiter(b',')).unwrap(),
+        "value=a%2Cb,c,d+e"
+    );
+    assert_eq!(
+        to_string(&value, ParseMode::Delimiter(b';')).unwrap(),
+        "value=a%2Cb;c;d+e"
+    );
+
+    // Delimiters which are not percent encoded normally
+    assert_eq!(
+        to_string(&p!(vec!["a.b", "c"]), ParseMode::Delimiter(b'.')).unwrap(),
+        "value=a%2Eb.c"
+    );
+    assert_eq!(
+        to_string(&value, ParseMode::Delimiter(b'+')).unwrap(),
+        "value=a%2Cb+c+d%20e"
+    );
+
+    // Empty sequences are skipped
+    assert_eq!(
+        to_string(&p!(Vec::<u32>::new()), ParseMode::Delimiter(b',')).unwrap(),
+        ""
+    );
+
+    // Values survive the round trip
+    for delimiter in [b',', b'.', b'+', b'|', b'a'] {
+        let value = p!(vec![
+            String::from("a.b,c"),
+            String::from("d+e f"),
+            String::from("|")
+        ]);
+        let query = to_string(&value, ParseMode::Delimiter(delimiter)).unwrap();
+        assert_eq!(from_str(&query, ParseMode::Delimiter(delimiter)), Ok(value));
+    }
+}
+
+#[test]
+fn serialize_empty_brackets() {
+    let value = p!(vec!["a", "b"]);
+
+    let config = SerializerConfig::new(ParseMode::Brackets).empty_brackets(true);
+    let query = to_string(&value, config).unwrap();
+    assert_eq!(query, "value[]=a&value[]=b");
+    // Order of values is preserved when parsing
+    assert_eq!(from_str(&query, ParseMode::Brackets), Ok(value));
+
+    let config = SerializerConfig::new(ParseMode::Duplicate).empty_brackets(true);
+    assert_eq!(
+        to_string(&p!(vec!["a", "b"]), config).unwrap(),
+        "value[]=a&value[]=b"
+    );
+
+    // Other modes are not affected
+    let config = SerializerConfig::new(ParseMode::Delimiter(b',')).empty_brackets(true);
+    assert_eq!(to_string(&p!(vec!["a", "b"]), config).unwrap(), "value=a,b");
+}
+
+#[test]
+fn serialize_to_writer() {
+    let mut buf = Vec::new();
+    to_writer(&mut buf, &simple(), ParseMode::Brackets).unwrap();
+    assert_eq!(buf, to_bytes(&simple(), ParseMode::Brackets).unwrap());
+
+    // Errors of the writer are reported
+    struct Failing;
+
+    impl std::io::Write for Failing {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "closed"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    assert_eq!(
+        to_writer(Failing, &simple(), ParseMode::Duplicate)
+            .unwrap_err()
+            .kind,
+        ErrorKind::Other
+    );
+}
+
+#[test]
+fn serialize_builder() {
+    #[derive(Serialize)]
+    #[serde(crate = "_serde")]
+    struct Page {
+        page: u32,
+        size: Option<u32>,
+    }
+
+    // Overridden pairs keep their place, the others are appended
+    let query = QueryStringBuilder::from_query("?page=2&sort=asc", ParseMode::Duplicate)
+        .set(&Page {
+            page: 3,
+            size: Some(10),
+        })
+        .unwrap()
+        .build();
+    assert_eq!(query, "page=3&sort=asc&size=10");
+
+    // None values don't remove the existing pairs
+    let query = QueryStringBuilder::from_query("size=20&page=1", ParseMode::Duplicate)
+        .set(&Page {
+            page: 2,
+            size: None,
+        })
+        .unwrap()
+        .build();
+    assert_eq!(query, "size=20&page=2");
+
+    // Existing pairs are kept as they are and matched by their decoded keys
+    let query = QueryStringBuilder::from_query("q=a%20b&pa%67e=1&page=5", ParseMode::Duplicate)
+        .set(&p!(vec![1, 2]))
+        .unwrap()
+        .append(&Page {
+            page: 9,
+            size: None,
+        })
+        .unwrap()
+        .build();
+    assert_eq!(query, "q=a%20b&pa%67e=1&page=5&value=1&value=2&page=9");
+
+    // Nested keys are replaced as a whole in brackets mode
+    let query =
+        QueryStringBuilder::from_query("value[a]=1&other=x&value[b]=2", ParseMode::Brackets)
+            .set(&p!(p!("y")))
+            .unwrap()
+            .build();
+    assert_eq!(query, "value[value]=y&other=x");
+
+    assert_eq!(
+        QueryStringBuilder::new(ParseMode::Brackets)
+            .set(&p!(vec!["a", "b"]))
+            .unwrap()
+            .build(),
+        "value[0]=a&value[1]=b"
+    );
+}
+
+#[test]
+fn serialize_none_style() {
+    let value = Primitive::<Option<u32>>::new(None);
+
+    assert_eq!(to_string(&value, ParseMode::Duplicate).unwrap(), "");
+
+    let config = SerializerConfig::new(ParseMode::Duplicate).none_style(NoneStyle::Skip);
+    assert_eq!(to_string(&value, config).unwrap(), "");
+
+    let config = SerializerConfig::new(ParseMode::Duplicate).none_style(NoneStyle::Empty);
+    assert_eq!(to_string(&value, config).unwrap(), "value=");
+
+    let config = SerializerConfig::new(ParseMode::Brackets).none_style(NoneStyle::KeyOnly);
+    assert_eq!(to_string(&value, config).unwrap(), "value");
+    assert_eq!(
+        to_string(&p!(p!(None::<u32>)), config).unwrap(),
+        "value[value]"
+    );
+
+    // Some values are not affected
+    assert_eq!(to_string(&p!(Some(1)), config).unwrap(), "value=1");
+}
+
+#[test]
+fn serialize_sort_keys() {
+    #[derive(Serialize)]
+    #[serde(crate = "_serde")]
+    struct Unordered {
+        zeta: u32,
+        alpha: Vec<u32>,
+        beta: Option<u32>,
+        gamma: String,
+    }
+
+    let value = Unordered {
+        zeta: 1,
+        alpha: vec![3, 1, 2],
+        beta: None,
+        gamma: String::from("x"),
+    };
+
+    let config = SerializerConfig::new(ParseMode::Duplicate).sort_keys(true);
+    assert_eq!(
+        to_string(&value, config).unwrap(),
+        "alpha=3&alpha=1&alpha=2&gamma=x&zeta=1"
+    );
+
+    let config = config.none_style(NoneStyle::KeyOnly);
+    assert_eq!(
+        to_string(&value, config).unwrap(),
+        "alpha=3&alpha=1&alpha=2&beta&gamma=x&zeta=1"
+    );
+
+    // Keys are compared in their encoded form
+    let map: std::collections::HashMap<_, _> = vec![("b", 1), ("a b", 2), ("a", 3), ("_", 4)]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        to_string(
+            &map,
+            SerializerConfig::new(ParseMode::Brackets).sort_keys(true)
+        )
+        .unwrap(),
+        "_=4&a=3&a+b=2&b=1"
+    );
+
+    let mut buf = Vec::new();
+    to_writer(&mut buf, &value, config).unwrap();
+    assert_eq!(buf, to_bytes(&value, config).unwrap());
+}
+
+#[test]
+fn serialize_encode_set() {
+    let value = p!("a b~c*d/e");
+
+    assert_eq!(
+        to_string(&value, ParseMode::Duplicate).unwrap(),
+        "value=a+b%7Ec*d%2Fe"
+    );
+
+    let config = SerializerConfig::new(ParseMode::Duplicate).encode_set(EncodeSet::RFC3986);
+    assert_eq!(to_string(&value, config).unwrap(), "value=a%20b~c%2Ad%2Fe");
+
+    // Custom sets
+    let set = EncodeSet::RFC3986.add(b'/').space_as_plus(true);
+    let config = SerializerConfig::new(ParseMode::Duplicate).encode_set(set);
+    assert_eq!(to_string(&value, config).unwrap(), "value=a+b~c%2Ad/e");
+
+    let set = EncodeSet::FORM.remove(b'a');
+    let config = SerializerConfig::new(ParseMode::Duplicate).encode_set(set);
+    assert_eq!(
+        to_string(&value, config).unwrap(),
+        "v%61lue=%61+b%7Ec*d%2Fe"
+    );
+
+    // Bytes changing the meaning of the query are always encoded
+    let set = EncodeSet::FORM.add(b'&').add(b'=').add(b'%').add(b'+');
+    let config = SerializerConfig::new(ParseMode::Duplicate).encode_set(set);
+    assert_eq!(
+        to_string(&p!("&=%+"), config).unwrap(),
+        "value=%26%3D%25%2B"
+    );
+
+    // So are the brackets of sub keys
+    let set = EncodeSet::FORM.add(b'[').add(b']');
+    let config = SerializerConfig::new(ParseMode::Brackets).encode_set(set);
+    let map: std::collections::BTreeMap<_, _> = vec![("[a]", "[b]")].into_iter().collect();
+    let query = to_string(&p!(map), config).unwrap();
+    assert_eq!(query, "value[%5Ba%5D]=[b]");
+}
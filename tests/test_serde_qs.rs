@@ -0,0 +1,72 @@
+//! These tests are meant for the `compat::serde_qs` module
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::compat::serde_qs;
+use serde_querystring::ErrorKind;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct Address {
+    city: String,
+    postcode: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "_serde")]
+struct QueryParams {
+    id: u8,
+    name: String,
+    address: Address,
+    phone: u32,
+    user_ids: Vec<u8>,
+}
+
+fn params() -> QueryParams {
+    QueryParams {
+        id: 42,
+        name: String::from("Acme"),
+        address: Address {
+            city: String::from("Carrot City"),
+            postcode: String::from("12345"),
+        },
+        phone: 12345,
+        user_ids: vec![1, 2, 3, 4],
+    }
+}
+
+#[test]
+fn serde_qs_roundtrip() {
+    let query = "id=42&name=Acme&address[city]=Carrot+City&address[postcode]=12345&phone=12345\
+                 &user_ids[0]=1&user_ids[1]=2&user_ids[2]=3&user_ids[3]=4";
+
+    assert_eq!(serde_qs::from_str(query), Ok(params()));
+    assert_eq!(serde_qs::from_bytes(query.as_bytes()), Ok(params()));
+
+    let encoded = serde_qs::to_string(&params()).unwrap();
+    assert_eq!(serde_qs::from_str(&encoded), Ok(params()));
+
+    let mut writer = Vec::new();
+    serde_qs::to_writer(&params(), &mut writer).unwrap();
+    assert_eq!(writer, encoded.as_bytes());
+}
+
+#[test]
+fn serde_qs_config() {
+    let config = serde_qs::Config::default();
+    assert_eq!(config.max_depth(), 5);
+    assert!(config.strict());
+
+    let query = "id=42&name=Acme&address%5Bcity%5D=Carrot+City&address[postcode]=12345\
+                 &phone=12345&user_ids[]=1&user_ids[]=2&user_ids[]=3&user_ids[]=4";
+    assert_eq!(
+        serde_qs::Config::new(1, false).deserialize_str(query),
+        Ok(params())
+    );
+    assert_eq!(
+        serde_qs::Config::new(0, false)
+            .deserialize_str::<QueryParams>(query)
+            .unwrap_err()
+            .kind,
+        ErrorKind::DepthLimitExceeded
+    );
+}
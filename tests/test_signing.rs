@@ -0,0 +1,120 @@
+//! These tests are meant for signing query strings, with the `signing` feature
+#![cfg(feature = "signing")]
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use _serde::Deserialize;
+use serde_querystring::de::ErrorKind;
+use serde_querystring::signing::{sign, verify, verify_at};
+use serde_querystring::{from_str, ParseMode};
+
+const KEY: &[u8] = b"secret";
+
+fn at(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+#[test]
+fn signing_round_trip() {
+    let query = sign("file=report.pdf&user=42", KEY, at(1_700_000_000));
+    assert!(query.starts_with("file=report.pdf&user=42&expires=1700000000&signature="));
+    assert_eq!(
+        query.len(),
+        64 + "file=report.pdf&user=42&expires=1700000000&signature=".len()
+    );
+
+    assert_eq!(
+        verify_at(&query, KEY, at(1_699_999_999)).unwrap(),
+        "file=report.pdf&user=42"
+    );
+
+    let query = sign("", KEY, SystemTime::now() + Duration::from_secs(60));
+    assert!(query.starts_with("expires="));
+    assert_eq!(verify(&query, KEY).unwrap(), "");
+}
+
+#[test]
+fn signing_rejects_changes() {
+    let query = sign("user=42&role=admin", KEY, at(2_000_000_000));
+    let now = at(1_000_000_000);
+
+    let changed = [
+        query.replace("user=42", "user=43"),
+        query.replace("user=42&role=admin", "role=admin&user=42"),
+        query.replace("user=42", "user=42&user=43"),
+        query.replace("expires=2000000000", "expires=2000000001"),
+        query.replace("role=admin", "role"),
+    ];
+    for query in &changed {
+        let error = verify_at(query, KEY, now).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidSignature);
+        assert_eq!(error.key, "signature");
+    }
+
+    let error = verify_at(&query, b"other secret", now).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidSignature);
+
+    let error = verify_at("user=42&role=admin", KEY, now).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidSignature);
+    assert_eq!(error.key, "signature");
+
+    let error = verify_at("user=42&signature=00", KEY, now).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidSignature);
+    assert_eq!(error.key, "expires");
+
+    // The hex digits are parsed strictly
+    let signature = &query[query.len() - 64..];
+    let error = verify_at(&query.replace(signature, "+f"), KEY, now).unwrap_err();
+    assert_eq!(error.message, "the signature is not hex encoded");
+}
+
+#[test]
+fn signing_expiry() {
+    let query = sign("user=42", KEY, at(1_700_000_000));
+
+    let error = verify_at(&query, KEY, at(1_700_000_000)).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::SignatureExpired);
+    assert_eq!(error.key, "expires");
+
+    let error = verify(&query, KEY).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::SignatureExpired);
+}
+
+#[test]
+fn signing_ignores_encoding() {
+    let query = sign("path=%7euser%2Fdocs&q=a+b", KEY, at(2_000_000_000));
+    let now = at(1_000_000_000);
+
+    let reencoded = query
+        .replace("%7euser%2Fdocs", "~user%2fdocs")
+        .replace("a+b", "a%20b");
+    assert_eq!(
+        verify_at(&reencoded, KEY, now).unwrap(),
+        "path=~user%2fdocs&q=a%20b"
+    );
+
+    // The upper case hex digits of the signature are accepted too
+    let signature = &query[query.len() - 64..];
+    let upper = query.replace(signature, &signature.to_uppercase());
+    assert!(verify_at(&upper, KEY, now).is_ok());
+}
+
+#[test]
+fn signing_deserialize_params() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Download {
+        file: String,
+        user: u32,
+    }
+
+    let query = sign("file=report.pdf&user=42", KEY, at(2_000_000_000));
+    let params = verify_at(&query, KEY, at(1_000_000_000)).unwrap();
+    assert_eq!(
+        from_str::<Download, _>(params, ParseMode::UrlEncoded).unwrap(),
+        Download {
+            file: "report.pdf".to_string(),
+            user: 42
+        }
+    );
+}
@@ -0,0 +1,105 @@
+//! These tests are meant for the `SortSpec` type
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::{
+    from_str, from_str_value, to_string, ErrorKind, ParseMode, SortDirection, SortField, SortRules,
+    SortSpec,
+};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(crate = "_serde")]
+struct Query {
+    #[serde(default)]
+    sort: SortSpec,
+}
+
+fn field(field: &str, direction: SortDirection) -> SortField {
+    SortField {
+        field: field.to_string(),
+        direction,
+    }
+}
+
+#[test]
+fn deserialize_sort() {
+    let expected = [
+        field("created_at", SortDirection::Descending),
+        field("name", SortDirection::Ascending),
+        field("id", SortDirection::Ascending),
+    ];
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b','),
+        ParseMode::Brackets,
+    ] {
+        let query: Query = from_str("sort=-created_at,+name,%2Bid", mode).unwrap();
+        assert_eq!(*query.sort, expected[..]);
+    }
+
+    let query: Query = from_str("sort=", ParseMode::UrlEncoded).unwrap();
+    assert!(query.sort.is_empty());
+    let query: Query = from_str("", ParseMode::UrlEncoded).unwrap();
+    assert!(query.sort.is_empty());
+
+    let value = from_str_value("sort=-a,b", ParseMode::Delimiter(b',')).unwrap();
+    let query: Query = value.deserialize_into().unwrap();
+    assert_eq!(query.sort.len(), 2);
+}
+
+struct ArticleSort;
+
+impl SortRules for ArticleSort {
+    const MAX_FIELDS: usize = 2;
+
+    fn is_allowed(field: &str) -> bool {
+        matches!(field, "created_at" | "title")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "_serde")]
+struct ArticleQuery {
+    sort: SortSpec<ArticleSort>,
+}
+
+#[test]
+fn deserialize_sort_rules() {
+    let query: ArticleQuery = from_str("sort=title,-created_at", ParseMode::UrlEncoded).unwrap();
+    assert!(query.sort[1].is_descending());
+
+    let error =
+        from_str::<ArticleQuery, _>("sort=title,author", ParseMode::UrlEncoded).unwrap_err();
+    assert_eq!(error.message, "`author` is not a sortable field");
+
+    let error =
+        from_str::<ArticleQuery, _>("sort=title,title,title", ParseMode::UrlEncoded).unwrap_err();
+    assert_eq!(error.message, "more than 2 sort fields");
+
+    assert_eq!(
+        SortSpec::<ArticleSort>::parse("author").unwrap_err().kind,
+        ErrorKind::UnknownKey
+    );
+    assert_eq!(
+        SortSpec::<ArticleSort>::parse("title,title,title")
+            .unwrap_err()
+            .kind,
+        ErrorKind::LimitExceeded
+    );
+    assert_eq!(
+        SortSpec::<ArticleSort>::parse("title,-").unwrap_err().kind,
+        ErrorKind::InvalidType
+    );
+}
+
+#[test]
+fn serialize_sort() {
+    let query = Query {
+        sort: SortSpec::parse("-created_at,name").unwrap(),
+    };
+
+    let encoded = to_string(&query, ParseMode::UrlEncoded).unwrap();
+    assert_eq!(encoded, "sort=-created_at,name");
+    assert_eq!(from_str(&encoded, ParseMode::UrlEncoded), Ok(query));
+}
@@ -70,7 +70,7 @@ fn deserialize_decoded_keys() {
 fn deserialize_error_type() {
     // we don't support sequences in this mode
     assert_eq!(
-        from_bytes::<Primitive<[usize; 3]>>(
+        from_bytes::<Primitive<[usize; 3]>, _>(
             b"value=1&value=3&value=1337&value=999",
             ParseMode::UrlEncoded,
         )
@@ -80,7 +80,7 @@ fn deserialize_error_type() {
     );
 
     assert_eq!(
-        from_bytes::<Primitive<(usize, usize, usize)>>(
+        from_bytes::<Primitive<(usize, usize, usize)>, _>(
             b"value=1&value=3&value=1337&value=999",
             ParseMode::UrlEncoded,
         )
@@ -92,6 +92,7 @@ fn deserialize_error_type() {
     // We don't support non-unit enums
     #[derive(Debug, Deserialize)]
     #[serde(crate = "_serde")]
+    #[allow(dead_code)]
     enum ValueEnum {
         A(i32, i32),
         B(i32),
@@ -99,19 +100,19 @@ fn deserialize_error_type() {
     }
 
     assert_eq!(
-        from_bytes::<Primitive<ValueEnum>>(b"value=A&value=B&key=value", ParseMode::UrlEncoded)
+        from_bytes::<Primitive<ValueEnum>, _>(b"value=A&value=B&key=value", ParseMode::UrlEncoded)
             .unwrap_err()
             .kind,
         ErrorKind::InvalidType
     );
     assert_eq!(
-        from_bytes::<Primitive<ValueEnum>>(b"value=B", ParseMode::UrlEncoded)
+        from_bytes::<Primitive<ValueEnum>, _>(b"value=B", ParseMode::UrlEncoded)
             .unwrap_err()
             .kind,
         ErrorKind::InvalidType
     );
     assert_eq!(
-        from_bytes::<Primitive<ValueEnum>>(b"value=C", ParseMode::UrlEncoded)
+        from_bytes::<Primitive<ValueEnum>, _>(b"value=C", ParseMode::UrlEncoded)
             .unwrap_err()
             .kind,
         ErrorKind::InvalidType
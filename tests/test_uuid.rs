@@ -0,0 +1,91 @@
+//! These tests are meant for the `uuid` feature
+#![cfg(feature = "uuid")]
+
+use _serde::Deserialize;
+use serde_querystring::{from_str, ParseMode, QsConfig, RawQsValue, Uuid};
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(crate = "_serde")]
+struct Query {
+    id: Uuid,
+    parent: Option<Uuid>,
+    ids: Vec<Uuid>,
+}
+
+const ID: Uuid = Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+
+#[test]
+fn uuid_deserialize() {
+    let expected = || Query {
+        id: ID,
+        parent: None,
+        ids: vec![ID, ID],
+    };
+
+    for (mode, ids) in [
+        (
+            ParseMode::Duplicate,
+            "ids=67e5504410b1426f9247bb680e5fe0c8&ids=67e55044-10b1-426f-9247-bb680e5fe0c8",
+        ),
+        (
+            ParseMode::Delimiter(b'|'),
+            "ids=67e5504410b1426f9247bb680e5fe0c8|67e55044-10b1-426f-9247-bb680e5fe0c8",
+        ),
+        (
+            ParseMode::Brackets,
+            "ids[]=67e5504410b1426f9247bb680e5fe0c8&ids[]=67e55044-10b1-426f-9247-bb680e5fe0c8",
+        ),
+        (
+            ParseMode::DotNotation,
+            "ids.0=67e5504410b1426f9247bb680e5fe0c8&ids.1=67e55044-10b1-426f-9247-bb680e5fe0c8",
+        ),
+    ] {
+        // Hyphenated, braced and percent encoded
+        for id in [
+            "67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "%7B67e55044-10b1-426f-9247-bb680e5fe0c8%7D",
+            "urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "67e55044%2D10b1%2D426f%2D9247%2Dbb680e5fe0c8",
+        ] {
+            assert_eq!(
+                from_str::<Query, _>(&format!("id={}&{}", id, ids), mode),
+                Ok(expected()),
+                "{:?} {}",
+                mode,
+                id
+            );
+        }
+
+        assert!(from_str::<Query, _>(&format!("id=67e55044&{}", ids), mode).is_err());
+    }
+}
+
+#[test]
+fn uuid_infer_types() {
+    // A simple uuid made of digits is not inferred as a number
+    let config = QsConfig::new(ParseMode::Duplicate).infer_types(true);
+    let query: Query = from_str(
+        "id=12345678901234567890123456789012&ids=67e55044-10b1-426f-9247-bb680e5fe0c8",
+        config,
+    )
+    .unwrap();
+    assert_eq!(
+        query.id,
+        Uuid::from_u128(0x1234_5678_9012_3456_7890_1234_5678_9012)
+    );
+}
+
+#[test]
+fn uuid_raw_value() {
+    assert_eq!(
+        RawQsValue::from_bytes(b"67e55044-10b1-426f-9247-bb680e5fe0c8").parse_uuid(),
+        Ok(ID)
+    );
+    assert_eq!(
+        RawQsValue::from_bytes(b"%7b67e55044-10b1-426f-9247-bb680e5fe0c8%7d").parse_uuid(),
+        Ok(ID)
+    );
+    assert!(RawQsValue::from_bytes(b"67e55044+10b1")
+        .parse_uuid()
+        .is_err());
+}
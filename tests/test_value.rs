@@ -0,0 +1,245 @@
+//! These tests are meant for the untyped `Value`
+
+use std::collections::BTreeMap;
+
+use _serde::Deserialize;
+use serde_querystring::{
+    from_bytes_value, from_str_value, ErrorKind, InvalidUtf8, Map, ParseMode, QsConfig, Value,
+};
+
+macro_rules! s {
+    ($value:expr) => {
+        Value::String(String::from($value))
+    };
+}
+
+macro_rules! map {
+    ($($key:expr => $value:expr),* $(,)?) => {
+        Value::Map(
+            vec![$((String::from($key), $value)),*]
+                .into_iter()
+                .collect::<Map>(),
+        )
+    };
+}
+
+#[test]
+fn value_flat_modes() {
+    let slice = b"a=1&b=x+y&a=2&c";
+
+    assert_eq!(
+        from_bytes_value(slice, ParseMode::UrlEncoded).unwrap(),
+        map! {"a" => s!("2"), "b" => s!("x y"), "c" => s!("")}
+    );
+    assert_eq!(
+        from_bytes_value(slice, ParseMode::Duplicate).unwrap(),
+        map! {
+            "a" => Value::Sequence(vec![s!("1"), s!("2")]),
+            "b" => s!("x y"),
+            "c" => s!(""),
+        }
+    );
+    assert_eq!(
+        from_bytes_value(b"a=1|2&b=3", ParseMode::Delimiter(b'|')).unwrap(),
+        map! {"a" => Value::Sequence(vec![s!("1"), s!("2")]), "b" => s!("3")}
+    );
+}
+
+#[test]
+fn value_brackets() {
+    let value = from_str_value(
+        "user[name]=John&user[langs][1]=rust&user[langs][0]=go&tags[]=a&tags[]=b&id=3",
+        ParseMode::Brackets,
+    )
+    .unwrap();
+
+    assert_eq!(
+        value,
+        map! {
+            "user" => map! {
+                "name" => s!("John"),
+                "langs" => Value::Sequence(vec![s!("go"), s!("rust")]),
+            },
+            "tags" => Value::Sequence(vec![s!("a"), s!("b")]),
+            "id" => s!("3"),
+        }
+    );
+
+    assert_eq!(value.get("id").and_then(Value::as_str), Some("3"));
+    assert_eq!(value.get("missing"), None);
+
+    // Sequences of maps
+    assert_eq!(
+        from_str_value("v[0][a]=1&v[1][a]=2", ParseMode::Brackets).unwrap(),
+        map! {
+            "v" => Value::Sequence(vec![map! {"a" => s!("1")}, map! {"a" => s!("2")}])
+        }
+    );
+
+    // Sparse indexes are kept as a map
+    assert_eq!(
+        from_str_value("v[3]=1&v[7]=2", ParseMode::Brackets).unwrap(),
+        map! {
+            "v" => map! {"3" => s!("1"), "7" => s!("2")}
+        }
+    );
+
+    // Invalid utf-8
+    assert_eq!(
+        from_str_value("v=%FF", ParseMode::Brackets)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidEncoding
+    );
+    assert_eq!(
+        from_str_value(
+            "v=%FF&%FF=1",
+            QsConfig::new(ParseMode::Brackets).invalid_utf8(InvalidUtf8::Replace)
+        )
+        .unwrap(),
+        map! {
+            "v" => s!("\u{FFFD}"),
+            "\u{FFFD}" => s!("1")
+        }
+    );
+}
+
+#[test]
+fn value_deserialize_into() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    enum Side {
+        Left,
+        Right(u8),
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct User {
+        name: String,
+        age: u8,
+        admin: bool,
+        langs: Vec<String>,
+        single: Vec<u32>,
+        nick: Option<String>,
+        last: u32,
+        side: Side,
+        other: Side,
+    }
+
+    let value = from_str_value(
+        "name=John&age=42&admin=on&langs[]=go&langs[]=rust&single=1&nick=&last[]=1&last[]=2\
+         &side=Left&other[Right]=3",
+        ParseMode::Brackets,
+    )
+    .unwrap();
+
+    assert_eq!(
+        value.deserialize_into(),
+        Ok(User {
+            name: String::from("John"),
+            age: 42,
+            admin: true,
+            langs: vec![String::from("go"), String::from("rust")],
+            single: vec![1],
+            nick: None,
+            last: 2,
+            side: Side::Left,
+            other: Side::Right(3),
+        })
+    );
+
+    assert_eq!(
+        s!("x").deserialize_into::<u32>().unwrap_err().kind,
+        ErrorKind::InvalidNumber
+    );
+    assert_eq!(
+        map! {}.deserialize_into::<u32>().unwrap_err().kind,
+        ErrorKind::InvalidType
+    );
+}
+
+#[test]
+fn value_flatten() {
+    use std::collections::HashMap;
+
+    use serde_querystring::from_bytes;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Rest {
+        name: String,
+        #[serde(flatten)]
+        rest: BTreeMap<String, Value>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Strings {
+        name: String,
+        #[serde(flatten)]
+        rest: HashMap<String, String>,
+    }
+
+    let slice = b"name=foo&a=1&b=x+y&b=2&c&f[x]=1&f[y]=2&g[]=3|4";
+
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+        ParseMode::DotNotation,
+        ParseMode::BracketsDelimiter(b'|'),
+    ] {
+        // The rest has the same shape as the value of the whole query string
+        let mut expected = match from_bytes_value(slice, mode).unwrap() {
+            Value::Map(map) => map,
+            _ => unreachable!(),
+        };
+        expected.remove("name");
+
+        let query: Rest = from_bytes(slice, mode).unwrap();
+        assert_eq!(query.name, "foo");
+        assert_eq!(query.rest.into_iter().collect::<Map>(), expected);
+
+        let query: Strings = from_bytes(b"name=foo&a=1&b=x+y&c", mode).unwrap();
+        assert_eq!(query.name, "foo");
+        assert_eq!(query.rest.len(), 3);
+        assert_eq!(query.rest["b"], "x y");
+        assert_eq!(query.rest["c"], "");
+    }
+}
+
+#[test]
+fn value_integer_keys() {
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Scores {
+        scores: HashMap<u32, u32>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Sequence {
+        scores: Vec<u32>,
+    }
+
+    let value = from_str_value("scores[7]=20&scores[3]=10", ParseMode::Brackets).unwrap();
+    assert_eq!(
+        value.clone().deserialize_into::<Scores>().unwrap().scores,
+        vec![(3, 10), (7, 20)].into_iter().collect()
+    );
+    // Sparse indexes are still sorted into sequences
+    assert_eq!(
+        value.deserialize_into::<Sequence>().unwrap().scores,
+        vec![10, 20]
+    );
+
+    let value = from_str_value("scores[1]=20&scores[0]=10", ParseMode::Brackets).unwrap();
+    assert_eq!(
+        value.deserialize_into::<Scores>().unwrap().scores,
+        vec![(0, 10), (1, 20)].into_iter().collect()
+    );
+}